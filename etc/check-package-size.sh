@@ -16,6 +16,9 @@ function indent () {
 
 echo "in root: gitoxide CLI"
 (enter gix-fsck && indent cargo diet -n --package-size-limit 10KB)
+(enter gix-bundle && indent cargo diet -n --package-size-limit 15KB)
+(enter gix-blame && indent cargo diet -n --package-size-limit 15KB)
+(enter gix-apply && indent cargo diet -n --package-size-limit 25KB)
 (enter gix-actor && indent cargo diet -n --package-size-limit 10KB)
 (enter gix-archive && indent cargo diet -n --package-size-limit 10KB)
 (enter gix-worktree-stream && indent cargo diet -n --package-size-limit 40KB)
@@ -37,6 +37,12 @@ struct Args {
     /// Commits are sorted by their commit time in descending order.
     #[clap(short, long)]
     newest_first: bool,
+    /// Commits are never shown before their children, and commits of a branch are shown together.
+    #[clap(long)]
+    topo_order: bool,
+    /// Commits are never shown before their children, but otherwise sorted by their commit time in descending order.
+    #[clap(long)]
+    date_order: bool,
     /// Show commits with the specified minimum number of parents
     #[clap(long)]
     min_parents: Option<usize>,
@@ -77,6 +83,10 @@ fn run(args: Args) -> anyhow::Result<()> {
 
     let sorting = if args.breadth_first {
         Sorting::BreadthFirst
+    } else if args.topo_order {
+        Sorting::TopoOrder
+    } else if args.date_order {
+        Sorting::DateOrder
     } else {
         // else if args.newest_first {
         Sorting::ByCommitTimeNewestFirst
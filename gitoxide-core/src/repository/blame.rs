@@ -0,0 +1,126 @@
+use std::{ffi::OsStr, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use gix::bstr::BStr;
+
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// The one-based, inclusive range of lines to blame, like `git blame -L <start>,<end>`.
+    pub range: Option<(u32, u32)>,
+    pub ignore_whitespace: bool,
+    pub ignore_revs: Vec<String>,
+    pub ignore_revs_file: Option<PathBuf>,
+    pub statistics: bool,
+}
+
+/// Print each line of `file` as of `rev_spec`, or `HEAD`, along with the commit that introduced it.
+pub fn blame_file(
+    mut repo: gix::Repository,
+    file: &OsStr,
+    rev_spec: Option<&str>,
+    mut out: impl std::io::Write,
+    mut err: impl std::io::Write,
+    Options {
+        format,
+        range,
+        ignore_whitespace,
+        ignore_revs,
+        ignore_revs_file,
+        statistics,
+    }: Options,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    repo.object_cache_size_if_unset(4 * 1024 * 1024);
+
+    let commit_id = repo
+        .rev_parse_single(rev_spec.unwrap_or("HEAD"))?
+        .object()?
+        .peel_to_kind(gix::object::Kind::Commit)?
+        .id;
+    let path = match repo.prefix()? {
+        Some(prefix) => prefix.join(file),
+        None => PathBuf::from(file),
+    };
+    let path = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(path)).into_owned();
+
+    let mut ignored_revs = match ignore_revs_file {
+        Some(ignore_revs_file) => gix_blame::ignore_revs::parse(
+            &std::fs::read(&ignore_revs_file)
+                .with_context(|| format!("Could not read ignore-revs file at '{}'", ignore_revs_file.display()))?,
+        )?,
+        None => Default::default(),
+    };
+    for spec in ignore_revs {
+        ignored_revs.insert(
+            repo.rev_parse_single(spec.as_str())?
+                .object()?
+                .peel_to_kind(gix::object::Kind::Commit)?
+                .id,
+        );
+    }
+
+    let range = range
+        .map(|(start, end)| {
+            if start == 0 || end < start {
+                Err(anyhow!(
+                    "Line ranges are one-based and must not end before they start, got {start},{end}"
+                ))
+            } else {
+                Ok(start - 1..end)
+            }
+        })
+        .transpose()?;
+    let rewrites = gix::diff::new_rewrites(&repo.config_snapshot(), true)?.unwrap_or_default();
+    let mut resource_cache = repo.diff_resource_cache(gix::diff::blob::pipeline::Mode::ToGit, Default::default())?;
+    let traverse = repo
+        .rev_walk(Some(commit_id))
+        .sorting(gix::traverse::commit::Sorting::TopoOrder)
+        .all()?
+        .map(|info| info.map(|info| info.detach()));
+    let outcome = gix_blame::file(
+        &repo.objects,
+        traverse,
+        &mut resource_cache,
+        path.as_ref(),
+        gix_blame::Options {
+            range,
+            ignore_whitespace,
+            ignored_revs,
+            rewrites: Some(rewrites),
+        },
+    )?;
+
+    let show_paths = outcome.entries.iter().any(|entry| entry.source_path != path);
+    let num_lines = outcome
+        .entries
+        .last()
+        .map_or(0, |entry| entry.range_in_blamed_file().end);
+    let line_width = num_lines.to_string().len();
+    for (entry, lines) in outcome.entries_with_lines() {
+        for (line_number, line) in entry.range_in_blamed_file().zip(lines) {
+            write!(out, "{}", entry.commit_id.to_hex_with_len(8))?;
+            if show_paths {
+                write!(out, " {}", entry.source_path)?;
+            }
+            write!(out, " {:>line_width$}) ", line_number + 1)?;
+            write_line(&mut out, line)?;
+        }
+    }
+
+    if statistics {
+        writeln!(err, "{:#?}", outcome.statistics)?;
+    }
+    Ok(())
+}
+
+fn write_line(out: &mut impl std::io::Write, line: &BStr) -> std::io::Result<()> {
+    out.write_all(line)?;
+    if !line.ends_with(b"\n") {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
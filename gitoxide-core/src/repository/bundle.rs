@@ -0,0 +1,155 @@
+use std::{io::BufReader, path::Path, sync::atomic::AtomicBool};
+
+use anyhow::{bail, Context};
+use gix::{bstr::BString, NestedProgress};
+
+use crate::OutputFormat;
+
+pub use gix_bundle::Version;
+
+pub struct Options {
+    /// Include all references, along with `HEAD`.
+    pub all: bool,
+    /// The bundle version to write, or `None` to let it be chosen automatically.
+    pub version: Option<Version>,
+    pub thread_limit: Option<usize>,
+}
+
+/// Write a bundle to `destination` with the references named in `specs` and all objects reachable from them,
+/// except for the objects reachable by excluded revisions like `^a` or `a` in `a..b`.
+pub fn create(
+    repo: gix::Repository,
+    destination: &Path,
+    specs: Vec<BString>,
+    progress: impl NestedProgress + 'static,
+    should_interrupt: &AtomicBool,
+    Options {
+        all,
+        version,
+        thread_limit,
+    }: Options,
+) -> anyhow::Result<()> {
+    let mut refs = Vec::new();
+    let mut basis = Vec::new();
+    if all {
+        if let Some(head) = repo.head()?.id() {
+            refs.push(gix_bundle::Ref {
+                id: head.detach(),
+                name: "HEAD".into(),
+            });
+        }
+        for reference in repo.references()?.all()? {
+            let reference = reference.map_err(|err| anyhow::anyhow!("{err}"))?;
+            if let Some(id) = reference.target().try_id() {
+                refs.push(gix_bundle::Ref {
+                    id: id.to_owned(),
+                    name: reference.name().as_bstr().to_owned(),
+                });
+            }
+        }
+    }
+    for spec in specs {
+        let parsed = repo
+            .rev_parse(spec.as_slice())
+            .with_context(|| format!("Could not parse revision '{spec}'"))?;
+        let (first, second) = (
+            parsed.first_reference().map(|r| r.name.clone()),
+            parsed.second_reference().map(|r| r.name.clone()),
+        );
+        let (tip, name) = match parsed.detach() {
+            gix::revision::plumbing::Spec::Include(id) => (id, first),
+            gix::revision::plumbing::Spec::Exclude(id) => {
+                basis.push(id);
+                continue;
+            }
+            gix::revision::plumbing::Spec::Range { from, to } => {
+                basis.push(from);
+                (to, second)
+            }
+            _ => bail!(
+                "Revision '{spec}' must be a single revision, an excluded revision like `^a`, or a range like `a..b`"
+            ),
+        };
+        let Some(name) = name else {
+            bail!("Revision '{spec}' must name a reference to be put into the bundle")
+        };
+        refs.push(gix_bundle::Ref {
+            id: tip,
+            name: name.as_bstr().to_owned(),
+        });
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    refs.retain(|r| seen.insert(r.name.clone()));
+
+    let mut objects = repo.objects.clone().into_arc()?;
+    objects.prevent_pack_unload();
+    let out = std::fs::File::create(destination)
+        .with_context(|| format!("Could not create bundle at '{}'", destination.display()))?;
+    let outcome = gix_bundle::create(
+        refs,
+        &basis,
+        objects,
+        out,
+        Box::new(progress),
+        should_interrupt,
+        gix_bundle::create::Options {
+            version,
+            object_hash: repo.object_hash(),
+            thread_limit,
+        },
+    )?;
+    gix::trace::info!(
+        num_objects = outcome.num_objects,
+        num_refs = outcome.header.refs.len(),
+        num_prerequisites = outcome.header.prerequisites.len(),
+        "wrote bundle"
+    );
+    Ok(())
+}
+
+/// Check that the bundle at `path` can be applied to `repo` and that its pack is valid.
+pub fn verify(
+    repo: gix::Repository,
+    path: &Path,
+    mut out: impl std::io::Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let (header, pack) = read_header(path)?;
+    let outcome = gix_bundle::verify(&header, pack, &repo.objects, should_interrupt)?;
+    writeln!(
+        out,
+        "The bundle contains {} ref(s) and {} object(s)",
+        header.refs.len(),
+        outcome.num_objects
+    )?;
+    if header.prerequisites.is_empty() {
+        writeln!(out, "The bundle records a complete history.")?;
+    } else {
+        writeln!(out, "The bundle requires these {} ref(s):", header.prerequisites.len())?;
+        for prerequisite in &header.prerequisites {
+            writeln!(out, "{} {}", prerequisite.id, prerequisite.comment)?;
+        }
+    }
+    writeln!(out, "{} is okay", path.display())?;
+    Ok(())
+}
+
+/// List the references in the bundle at `path`.
+pub fn list_heads(path: &Path, mut out: impl std::io::Write, format: OutputFormat) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is currently supported");
+    }
+    let (header, _) = read_header(path)?;
+    for gix_bundle::Ref { id, name } in header.refs {
+        writeln!(out, "{id} {name}")?;
+    }
+    Ok(())
+}
+
+fn read_header(path: &Path) -> anyhow::Result<(gix_bundle::Header, BufReader<std::fs::File>)> {
+    let mut file = BufReader::new(
+        std::fs::File::open(path).with_context(|| format!("Could not open bundle at '{}'", path.display()))?,
+    );
+    let header = gix_bundle::Header::from_read(&mut file)?;
+    Ok((header, file))
+}
@@ -24,6 +24,8 @@ pub mod config;
 mod credential;
 pub use credential::function as credential;
 pub mod attributes;
+pub mod blame;
+pub mod bundle;
 #[cfg(feature = "clean")]
 pub mod clean;
 #[cfg(feature = "clean")]
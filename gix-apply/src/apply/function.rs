@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::{oid, ObjectId};
+use gix_object::{tree::EntryKind, FindExt};
+
+use crate::{
+    apply::{text, Conflict, Error, Merge, Options, Outcome},
+    patch::{self, BinaryHunk, Content},
+};
+
+/// Apply all `patches` to `tree`, with `objects` to read its subtrees and blobs, and to write new blobs and trees, and return the
+/// id of the resulting tree.
+///
+/// Either all `patches` apply, or an error is returned. If a text patch doesn't apply and `merge` is set, it's used to merge the
+/// changes into the current version of the file, and conflicts are written as merged file with conflict markers and returned.
+pub fn to_tree(
+    patches: &[patch::File],
+    tree: &oid,
+    objects: &(impl gix_object::Find + gix_odb::Write),
+    options: Options,
+    merge: Option<&mut dyn Merge>,
+) -> Result<(ObjectId, Outcome), Error> {
+    let mut buf = Vec::new();
+    let (changes, outcome) = apply(
+        patches,
+        |path| tree_entry(tree, path, objects, &mut buf),
+        objects,
+        options,
+        merge,
+    )?;
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|(path, change)| {
+            let entry = match change {
+                Change::Remove => None,
+                Change::Set { id, mode } => Some((*id, *mode)),
+                Change::Conflict { conflict, mode } => Some((conflict.merged, *mode)),
+            };
+            (path.as_bstr(), entry)
+        })
+        .collect();
+    let tree = match edit_tree(Some(tree), &changes, objects)? {
+        Some(tree) => tree,
+        None => objects.write(&gix_object::Tree::empty())?,
+    };
+    Ok((tree, outcome))
+}
+
+/// Apply all `patches` to the entries of `index`, with `objects` to read and write blobs, like `git apply --cached` does.
+///
+/// Either all `patches` apply, or an error is returned. If a text patch doesn't apply and `merge` is set, it's used to merge the
+/// changes into the current version of the file, and conflicts are added as entries in stages 1 to 3 and returned.
+///
+/// Note that the `tree` extension is removed from `index` as it would be outdated.
+pub fn to_index(
+    patches: &[patch::File],
+    index: &mut gix_index::State,
+    objects: &(impl gix_object::Find + gix_odb::Write),
+    options: Options,
+    merge: Option<&mut dyn Merge>,
+) -> Result<Outcome, Error> {
+    let (changes, outcome) = apply(
+        patches,
+        |path| {
+            Ok(index
+                .entry_by_path_and_stage(path, 0)
+                .and_then(|entry| Some((entry.id, entry.mode.to_tree_entry_mode()?.kind()))))
+        },
+        objects,
+        options,
+        merge,
+    )?;
+
+    index.remove_entries(|_, path, _| changes.contains_key(path));
+    for (path, change) in &changes {
+        match change {
+            Change::Remove => {}
+            Change::Set { id, mode } => index.dangerously_push_entry(
+                Default::default(),
+                *id,
+                gix_index::entry::Flags::empty(),
+                index_mode(*mode),
+                path.as_ref(),
+            ),
+            Change::Conflict { conflict, mode } => {
+                for (stage, id) in [(1, conflict.base), (2, conflict.ours), (3, conflict.theirs)] {
+                    index.dangerously_push_entry(
+                        Default::default(),
+                        id,
+                        gix_index::entry::Flags::from_bits_retain(stage << 12),
+                        index_mode(*mode),
+                        path.as_ref(),
+                    );
+                }
+            }
+        }
+    }
+    index.sort_entries();
+    index.remove_tree();
+    Ok(outcome)
+}
+
+/// The change of a single path after applying all patches.
+enum Change {
+    Remove,
+    Set { id: ObjectId, mode: EntryKind },
+    Conflict { conflict: Conflict, mode: EntryKind },
+}
+
+/// Apply `patches` to files obtained with `lookup`, and return the change of each path that is affected.
+fn apply(
+    patches: &[patch::File],
+    mut lookup: impl FnMut(&BStr) -> Result<Option<(ObjectId, EntryKind)>, Error>,
+    objects: &(impl gix_object::Find + gix_odb::Write),
+    options: Options,
+    mut merge: Option<&mut dyn Merge>,
+) -> Result<(BTreeMap<BString, Change>, Outcome), Error> {
+    let mut changes = BTreeMap::<BString, Change>::new();
+    let mut outcome = Outcome::default();
+    let mut current = |path: &BStr, changes: &BTreeMap<BString, Change>| match changes.get(path) {
+        Some(Change::Remove) => Ok(None),
+        Some(Change::Set { id, mode }) => Ok(Some((*id, *mode))),
+        Some(Change::Conflict { conflict, mode }) => Ok(Some((conflict.merged, *mode))),
+        None => lookup(path),
+    };
+    let (mut old_buf, mut buf) = (Vec::new(), Vec::new());
+    for file in patches {
+        let path = file.path();
+        if file.old_mode == Some(EntryKind::Commit) || file.new_mode == Some(EntryKind::Commit) {
+            return Err(Error::Submodule { path: path.to_owned() });
+        }
+        let previous = match &file.old_path {
+            Some(old_path) => {
+                Some(current(old_path.as_ref(), &changes)?.ok_or_else(|| Error::NotFound { path: old_path.clone() })?)
+            }
+            None => None,
+        };
+        if let Some(new_path) = file
+            .new_path
+            .as_ref()
+            .filter(|new_path| file.old_path.as_ref() != Some(new_path))
+        {
+            if current(new_path.as_ref(), &changes)?.is_some() {
+                return Err(Error::AlreadyExists { path: new_path.clone() });
+            }
+        }
+        let old: &[u8] = match previous {
+            Some((id, _)) => objects.find_blob(&id, &mut old_buf)?.data,
+            None => &[],
+        };
+
+        let mut conflict = None;
+        let id = match &file.content {
+            Content::Text(hunks) if hunks.is_empty() => previous.map(|(id, _)| id),
+            Content::Text(hunks) => {
+                let new = match text::apply(old, hunks, options.min_context) {
+                    Ok(new) => new,
+                    Err(hunk) => {
+                        let mismatch = || Error::HunkMismatch {
+                            path: path.to_owned(),
+                            hunk: hunk + 1,
+                            line: hunks[hunk].old_start,
+                        };
+                        let Some(merge) = merge.as_mut() else {
+                            return Err(mismatch());
+                        };
+                        let Some(base_id) = full_id(file.old_id.as_ref()) else {
+                            return Err(mismatch());
+                        };
+                        let Some(base) = objects
+                            .try_find(&base_id, &mut buf)?
+                            .filter(|data| data.kind == gix_object::Kind::Blob)
+                        else {
+                            return Err(mismatch());
+                        };
+                        let theirs = text::apply(base.data, hunks, options.min_context).map_err(|_| mismatch())?;
+                        let mut merged = Vec::new();
+                        let has_conflicts =
+                            merge
+                                .merge(path, base.data, old, &theirs, &mut merged)
+                                .map_err(|source| Error::Merge {
+                                    path: path.to_owned(),
+                                    source,
+                                })?;
+                        if has_conflicts {
+                            conflict = Some(Conflict {
+                                path: path.to_owned(),
+                                base: base_id,
+                                ours: previous.map_or_else(|| base_id.kind().null(), |(id, _)| id),
+                                theirs: objects.write_buf(gix_object::Kind::Blob, &theirs)?,
+                                merged: base_id.kind().null(),
+                            });
+                        }
+                        merged
+                    }
+                };
+                Some(objects.write_buf(gix_object::Kind::Blob, &new)?)
+            }
+            Content::Binary { forward, .. } => {
+                check_binary_preimage(file, previous.map(|(id, _)| id))?;
+                let new = match forward {
+                    BinaryHunk::Literal(data) => data.clone(),
+                    BinaryHunk::Delta(delta) => {
+                        apply_delta(old, delta).ok_or_else(|| Error::InvalidDelta { path: path.to_owned() })?
+                    }
+                };
+                let id = objects.write_buf(gix_object::Kind::Blob, &new)?;
+                if let Some(expected) =
+                    full_id(file.new_id.as_ref()).filter(|expected| !expected.is_null() && *expected != id)
+                {
+                    return Err(Error::BinaryResultMismatch {
+                        path: path.to_owned(),
+                        expected,
+                        actual: id,
+                    });
+                }
+                Some(id)
+            }
+            Content::BinaryWithoutData => {
+                check_binary_preimage(file, previous.map(|(id, _)| id))?;
+                let id =
+                    full_id(file.new_id.as_ref()).ok_or_else(|| Error::MissingBinaryData { path: path.to_owned() })?;
+                if !id.is_null() && objects.try_find(&id, &mut buf)?.is_none() {
+                    return Err(Error::MissingBinaryData { path: path.to_owned() });
+                }
+                Some(id)
+            }
+        };
+
+        match &file.new_path {
+            None => {
+                if id.map_or(false, |id| !id.is_null() && id != ObjectId::empty_blob(id.kind())) {
+                    return Err(Error::RemovalLeavesContent { path: path.to_owned() });
+                }
+                changes.insert(path.to_owned(), Change::Remove);
+            }
+            Some(new_path) => {
+                let mode = file
+                    .new_mode
+                    .or(previous.map(|(_, mode)| mode))
+                    .unwrap_or(EntryKind::Blob);
+                let id = match id {
+                    Some(id) => id,
+                    None => objects.write_buf(gix_object::Kind::Blob, &[])?,
+                };
+                if let Some(old_path) = file.old_path.as_ref().filter(|old_path| *old_path != new_path) {
+                    if !file.is_copy {
+                        changes.insert(old_path.clone(), Change::Remove);
+                    }
+                }
+                let change = match conflict {
+                    Some(mut conflict) => {
+                        conflict.merged = id;
+                        outcome.conflicts.push(conflict.clone());
+                        Change::Conflict { conflict, mode }
+                    }
+                    None => Change::Set { id, mode },
+                };
+                changes.insert(new_path.clone(), change);
+            }
+        }
+    }
+    Ok((changes, outcome))
+}
+
+/// Return the id of `prefix` if it isn't abbreviated.
+fn full_id(prefix: Option<&gix_hash::Prefix>) -> Option<ObjectId> {
+    prefix
+        .filter(|prefix| prefix.hex_len() == prefix.as_oid().kind().len_in_hex())
+        .map(|prefix| prefix.as_oid().to_owned())
+}
+
+/// Binary patches must note the full id of the version they apply to, and it must match the `current` one.
+fn check_binary_preimage(file: &patch::File, current: Option<ObjectId>) -> Result<(), Error> {
+    let Some(actual) = current else { return Ok(()) };
+    let expected = full_id(file.old_id.as_ref()).ok_or_else(|| Error::BinaryWithoutFullIndex {
+        path: file.path().to_owned(),
+    })?;
+    if expected != actual {
+        return Err(Error::BinaryPreimageMismatch {
+            path: file.path().to_owned(),
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Apply `delta` in the format also used in packs to `base`, or return `None` if it's invalid.
+fn apply_delta(base: &[u8], mut delta: &[u8]) -> Option<Vec<u8>> {
+    fn next(delta: &mut &[u8]) -> Option<u8> {
+        let (byte, rest) = delta.split_first()?;
+        *delta = rest;
+        Some(*byte)
+    }
+    fn size(delta: &mut &[u8]) -> Option<usize> {
+        let mut size = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = next(delta)?;
+            size |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(size);
+            }
+        }
+        None
+    }
+    if size(&mut delta)? != base.len() {
+        return None;
+    }
+    let target_size = size(&mut delta)?;
+    let mut out = Vec::new();
+    while let Some(cmd) = next(&mut delta) {
+        if cmd & 0x80 != 0 {
+            let (mut offset, mut len) = (0, 0);
+            for idx in 0..4 {
+                if cmd & (1 << idx) != 0 {
+                    offset |= usize::from(next(&mut delta)?) << (idx * 8);
+                }
+            }
+            for idx in 0..3 {
+                if cmd & (0x10 << idx) != 0 {
+                    len |= usize::from(next(&mut delta)?) << (idx * 8);
+                }
+            }
+            if len == 0 {
+                len = 0x10000;
+            }
+            out.extend_from_slice(base.get(offset..offset.checked_add(len)?)?);
+        } else if cmd != 0 {
+            let (data, rest) = (delta.get(..usize::from(cmd))?, &delta[usize::from(cmd)..]);
+            out.extend_from_slice(data);
+            delta = rest;
+        } else {
+            return None;
+        }
+    }
+    (out.len() == target_size).then_some(out)
+}
+
+/// Return the id and kind of the entry at `path` in `tree`, or `None` if there is no such file.
+fn tree_entry(
+    tree: &oid,
+    path: &BStr,
+    objects: &impl gix_object::Find,
+    buf: &mut Vec<u8>,
+) -> Result<Option<(ObjectId, EntryKind)>, Error> {
+    let mut tree = tree.to_owned();
+    let mut components = path.split_str(b"/").peekable();
+    while let Some(component) = components.next() {
+        let Some(entry) = objects
+            .find_tree(&tree, buf)?
+            .entries
+            .into_iter()
+            .find(|entry| entry.filename == component)
+        else {
+            return Ok(None);
+        };
+        match (components.peek().is_none(), entry.mode.is_tree()) {
+            (true, false) => return Ok(Some((entry.oid.to_owned(), entry.mode.kind()))),
+            (false, true) => tree = entry.oid.to_owned(),
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Apply `changes`, sorted by path, to the tree with id `tree`, or to an empty tree, and return the id of the resulting tree,
+/// or `None` if it is empty.
+fn edit_tree(
+    tree: Option<&oid>,
+    changes: &[(&BStr, Option<(ObjectId, EntryKind)>)],
+    objects: &(impl gix_object::Find + gix_odb::Write),
+) -> Result<Option<ObjectId>, Error> {
+    let mut tree: gix_object::Tree = match tree {
+        Some(tree) => objects.find_tree(tree, &mut Vec::new())?.into(),
+        None => gix_object::Tree::empty(),
+    };
+    let mut idx = 0;
+    while let Some((path, change)) = changes.get(idx) {
+        match path.find_byte(b'/') {
+            None => {
+                tree.entries.retain(|entry| entry.filename != *path);
+                if let Some((id, kind)) = change {
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: (*kind).into(),
+                        filename: (*path).to_owned(),
+                        oid: *id,
+                    });
+                }
+                idx += 1;
+            }
+            Some(pos) => {
+                let (name, prefix) = (&path[..pos], &path[..=pos]);
+                let end = idx
+                    + changes[idx..]
+                        .iter()
+                        .take_while(|(path, _)| path.starts_with(prefix))
+                        .count();
+                let nested: Vec<_> = changes[idx..end]
+                    .iter()
+                    .map(|(path, change)| (path[pos + 1..].as_bstr(), *change))
+                    .collect();
+                let existing = tree.entries.iter().position(|entry| entry.filename == name);
+                let subtree = existing
+                    .map(|pos| &tree.entries[pos])
+                    .filter(|entry| entry.mode.is_tree())
+                    .map(|entry| entry.oid);
+                let new_subtree = edit_tree(subtree.as_deref(), &nested, objects)?;
+                if let Some(pos) = existing.filter(|_| subtree.is_some() || new_subtree.is_some()) {
+                    tree.entries.remove(pos);
+                }
+                if let Some(id) = new_subtree {
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: EntryKind::Tree.into(),
+                        filename: name.into(),
+                        oid: id,
+                    });
+                }
+                idx = end;
+            }
+        }
+    }
+    if tree.entries.is_empty() {
+        return Ok(None);
+    }
+    tree.entries.sort();
+    Ok(Some(objects.write(&tree)?))
+}
+
+fn index_mode(kind: EntryKind) -> gix_index::entry::Mode {
+    match kind {
+        EntryKind::Tree => gix_index::entry::Mode::DIR,
+        EntryKind::Blob => gix_index::entry::Mode::FILE,
+        EntryKind::BlobExecutable => gix_index::entry::Mode::FILE_EXECUTABLE,
+        EntryKind::Link => gix_index::entry::Mode::SYMLINK,
+        EntryKind::Commit => gix_index::entry::Mode::COMMIT,
+    }
+}
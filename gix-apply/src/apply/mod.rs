@@ -0,0 +1,100 @@
+use bstr::{BStr, BString};
+use gix_hash::ObjectId;
+
+/// Options for use in [`to_tree()`](crate::to_tree()) and [`to_index()`](crate::to_index()).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The least amount of context lines before and after each change that have to match, or `None` if all of them have to match.
+    ///
+    /// If a hunk can't be found with all of its context, its leading and trailing context lines are ignored one by one until
+    /// it is found or only this many are left, like `git apply -C<n>` does.
+    pub min_context: Option<u32>,
+}
+
+/// A three-way merge of the content of files, used as fallback for patches that don't apply to the current version of a file,
+/// like `git apply --3way` does.
+///
+/// This is only possible if the patch notes the full id of the version it was created against, and if it is present in the
+/// object database. The patch is applied to that version, and the result is merged into the current version.
+pub trait Merge {
+    /// Merge the changes from `base` to `ours` and from `base` to `theirs` of the file at `path` into `out`, and return `true`
+    /// if there were conflicts, which are then marked in `out`.
+    fn merge(
+        &mut self,
+        path: &BStr,
+        base: &[u8],
+        ours: &[u8],
+        theirs: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// A file whose patch didn't apply, and that had conflicts when merging it with [`Merge`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The location of the file after the change.
+    pub path: BString,
+    /// The id of the blob the patch was created against.
+    pub base: ObjectId,
+    /// The id of the blob that was present before applying the patch.
+    pub ours: ObjectId,
+    /// The id of the blob that resulted from applying the patch to `base`.
+    pub theirs: ObjectId,
+    /// The id of the blob with the merged content along with conflict markers.
+    pub merged: ObjectId,
+}
+
+/// The outcome of [`to_tree()`](crate::to_tree()) and [`to_index()`](crate::to_index()).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// All files that had conflicts when merging, which can only happen if a [`Merge`] implementation was provided.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The error returned by [`to_tree()`](crate::to_tree()) and [`to_index()`](crate::to_index()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("'{path}' does not exist")]
+    NotFound { path: BString },
+    #[error("'{path}' already exists")]
+    AlreadyExists { path: BString },
+    #[error("Patch of '{path}' does not apply at hunk {hunk}, which is expected at line {line}")]
+    HunkMismatch { path: BString, hunk: usize, line: u32 },
+    #[error("Removal patch leaves contents in '{path}'")]
+    RemovalLeavesContent { path: BString },
+    #[error("Cannot apply binary patch to '{path}' without full index line")]
+    BinaryWithoutFullIndex { path: BString },
+    #[error(
+        "The binary patch applies to '{path}' at {expected}, which does not match its current version at {actual}"
+    )]
+    BinaryPreimageMismatch {
+        path: BString,
+        expected: ObjectId,
+        actual: ObjectId,
+    },
+    #[error("Binary patch to '{path}' creates incorrect result, expecting {expected}, got {actual}")]
+    BinaryResultMismatch {
+        path: BString,
+        expected: ObjectId,
+        actual: ObjectId,
+    },
+    #[error("The delta of the binary patch to '{path}' is invalid")]
+    InvalidDelta { path: BString },
+    #[error("The patch of binary file '{path}' has no data, and its new version isn't available")]
+    MissingBinaryData { path: BString },
+    #[error("Changes to submodule '{path}' can't be applied")]
+    Submodule { path: BString },
+    #[error("Could not merge changes to '{path}'")]
+    Merge {
+        path: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not read or write an object")]
+    Object(#[from] gix_object::find::Error),
+}
+
+pub(crate) mod function;
+mod text;
@@ -0,0 +1,88 @@
+use bstr::ByteSlice;
+
+use crate::patch::{Hunk, LineKind};
+
+/// Apply `hunks` to `data` and return the result, or the index of the first hunk that didn't apply.
+///
+/// Each hunk is searched for at the line it names in the new version of the file, and if it doesn't match there, at the closest
+/// lines after or before it. If it doesn't match anywhere, context lines are dropped until only `min_context` are left.
+pub(super) fn apply<'a>(data: &'a [u8], hunks: &'a [Hunk], min_context: Option<u32>) -> Result<Vec<u8>, usize> {
+    let min_context = min_context.map_or(usize::MAX, |lines| lines as usize);
+    let mut image: Vec<&[u8]> = data.lines_with_terminator().collect();
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let side = |skip: LineKind| -> Vec<&[u8]> {
+            hunk.lines
+                .iter()
+                .filter(|line| line.kind != skip)
+                .map(|line| line.content.as_slice())
+                .collect()
+        };
+        let mut preimage = side(LineKind::Addition);
+        let mut postimage = side(LineKind::Removal);
+        let is_context = |kind: &LineKind| *kind == LineKind::Context;
+        let mut leading = hunk.lines.iter().take_while(|line| is_context(&line.kind)).count();
+        let mut trailing = if leading == hunk.lines.len() {
+            0
+        } else {
+            hunk.lines
+                .iter()
+                .rev()
+                .take_while(|line| is_context(&line.kind))
+                .count()
+        };
+
+        // Hunks at the beginning of a file and those without trailing context, which are at its end, must match there.
+        let mut match_beginning = hunk.old_start <= 1;
+        let mut match_end = trailing == 0;
+        let mut pos = (hunk.new_start as usize).saturating_sub(1);
+        let at = loop {
+            if let Some(at) = find(&image, &preimage, pos, match_beginning, match_end) {
+                break at;
+            }
+            if leading <= min_context && trailing <= min_context {
+                return Err(idx);
+            }
+            if match_beginning || match_end {
+                match_beginning = false;
+                match_end = false;
+                continue;
+            }
+            // Reduce the larger context, or both if they are the same.
+            if leading >= trailing {
+                preimage.remove(0);
+                postimage.remove(0);
+                pos = pos.saturating_sub(1);
+                leading -= 1;
+            }
+            if trailing > leading {
+                preimage.pop();
+                postimage.pop();
+                trailing -= 1;
+            }
+        };
+        image.splice(at..at + preimage.len(), postimage);
+    }
+    Ok(image.concat())
+}
+
+/// Return the line in `image` at which `preimage` starts, searching from `pos` and alternating between lines after and before it.
+fn find(image: &[&[u8]], preimage: &[&[u8]], pos: usize, match_beginning: bool, match_end: bool) -> Option<usize> {
+    let last = image.len().checked_sub(preimage.len())?;
+    let start = if match_beginning {
+        0
+    } else if match_end {
+        last
+    } else {
+        pos.min(last)
+    };
+    let matches = |at: usize| {
+        (!match_beginning || at == 0) && (!match_end || at == last) && image[at..][..preimage.len()] == *preimage
+    };
+    if matches(start) {
+        return Some(start);
+    }
+    (1..=last.max(start)).find_map(|distance| {
+        let after = Some(start + distance).filter(|at| *at <= last && matches(*at));
+        after.or_else(|| start.checked_sub(distance).filter(|at| matches(*at)))
+    })
+}
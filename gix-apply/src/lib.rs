@@ -0,0 +1,29 @@
+//! Parse patches as produced by `git diff` or `git format-patch` and apply them to trees or indices, like `git apply` does.
+//!
+//! [Parsing](parse()) turns the textual representation of a patch into a [`patch::File`] per changed file, skipping all text
+//! that isn't part of a patch, like the commit message and signature of a patch created with `git format-patch`.
+//! These are then applied to [a tree](to_tree()) or [an index](to_index()), where all patches have to apply or nothing is changed.
+//!
+//! Each hunk is expected at the lines it names first, and if its context doesn't match there, the closest position where it does
+//! is used instead. If it can't be found anywhere, context lines can be ignored as configured in the
+//! [options](apply::Options::min_context), and as last resort, the change may be merged into the current version of the file
+//! with a [three-way merge](apply::Merge) implementation, similar to `git apply --3way`.
+//!
+//! ## Deviation
+//!
+//! * Whitespace isn't ignored or fixed, so options like `--ignore-whitespace` and `--whitespace=fix` aren't available.
+//! * Changes to submodules can't be applied.
+//! * There is no way to apply patches in reverse, or to reject only the hunks that don't apply like `--reject` does.
+#![deny(rust_2018_idioms, missing_docs)]
+#![forbid(unsafe_code)]
+
+///
+pub mod patch;
+
+///
+pub mod parse;
+pub use parse::function::parse;
+
+///
+pub mod apply;
+pub use apply::function::{to_index, to_tree};
@@ -0,0 +1,439 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_object::tree::EntryKind;
+
+use crate::{
+    parse::Error,
+    patch::{self, BinaryHunk, Content, Hunk, Line, LineKind},
+};
+
+/// The characters used by git's base85 encoding.
+const BASE85: &[u8; 85] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Parse `input` into the patch of each file it contains, in order of appearance.
+///
+/// Patches start with a `diff --git` line as written by `git diff`, or with `---` and `+++` lines as written by `diff -u`,
+/// and everything that isn't part of a patch is skipped. Paths are expected to have one leading directory, like `a/` and `b/`,
+/// which is removed, similar to `git apply -p1`.
+pub fn parse(input: &[u8]) -> Result<Vec<patch::File>, Error> {
+    let mut lines = Lines {
+        lines: input.lines_with_terminator().collect(),
+        pos: 0,
+    };
+    let mut out = Vec::new();
+    while let Some(line) = lines.peek(0) {
+        if let Some(names) = line.strip_prefix(b"diff --git ") {
+            let line_number = lines.line_number();
+            lines.pos += 1;
+            out.push(git_file(names.as_bstr(), line_number, &mut lines)?);
+        } else if line.starts_with(b"--- ")
+            && lines.peek(1).map_or(false, |line| line.starts_with(b"+++ "))
+            && lines.peek(2).map_or(false, |line| line.starts_with(b"@@ -"))
+        {
+            let (old_path, new_path) = names(&mut lines)?;
+            out.push(patch::File {
+                old_path,
+                new_path,
+                old_mode: None,
+                new_mode: None,
+                old_id: None,
+                new_id: None,
+                is_copy: false,
+                similarity: None,
+                content: Content::Text(hunks(&mut lines)?),
+            });
+        } else {
+            lines.pos += 1;
+        }
+    }
+    Ok(out)
+}
+
+struct Lines<'a> {
+    lines: Vec<&'a [u8]>,
+    pos: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn peek(&self, offset: usize) -> Option<&'a [u8]> {
+        self.lines.get(self.pos + offset).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let line = self.peek(0)?;
+        self.pos += 1;
+        Some(line)
+    }
+
+    /// The one-based number of the line to be returned next.
+    fn line_number(&self) -> usize {
+        self.pos + 1
+    }
+}
+
+/// Parse the extended header lines after `diff --git <header_names>` at `line_number` and the hunks or binary data that follow.
+fn git_file(header_names: &BStr, line_number: usize, lines: &mut Lines<'_>) -> Result<patch::File, Error> {
+    let header_path = header_path(header_names).map_err(|source| Error::Unquote { line_number, source })?;
+    let mut file = patch::File {
+        old_path: None,
+        new_path: None,
+        old_mode: None,
+        new_mode: None,
+        old_id: None,
+        new_id: None,
+        is_copy: false,
+        similarity: None,
+        content: Content::Text(Vec::new()),
+    };
+    let (mut is_creation, mut is_deletion) = (false, false);
+    while let Some(line) = lines.peek(0) {
+        let line_number = lines.line_number();
+        let unquote = |path: &[u8]| {
+            gix_quote::ansi_c::undo(path.trim_end().as_bstr())
+                .map(|(path, _)| path.into_owned())
+                .map_err(|source| Error::Unquote { line_number, source })
+        };
+        let mode = |mode: &[u8]| {
+            parse_mode(mode.trim_end()).ok_or_else(|| Error::InvalidMode {
+                line_number,
+                line: line.into(),
+            })
+        };
+        if let Some(value) = line.strip_prefix(b"old mode ") {
+            file.old_mode = Some(mode(value)?);
+        } else if let Some(value) = line.strip_prefix(b"new mode ") {
+            file.new_mode = Some(mode(value)?);
+        } else if let Some(value) = line.strip_prefix(b"deleted file mode ") {
+            file.old_mode = Some(mode(value)?);
+            is_deletion = true;
+        } else if let Some(value) = line.strip_prefix(b"new file mode ") {
+            file.new_mode = Some(mode(value)?);
+            is_creation = true;
+        } else if let Some(value) = line.strip_prefix(b"copy from ") {
+            file.old_path = Some(unquote(value)?);
+            file.is_copy = true;
+        } else if let Some(value) = line.strip_prefix(b"copy to ") {
+            file.new_path = Some(unquote(value)?);
+            file.is_copy = true;
+        } else if let Some(value) = line
+            .strip_prefix(b"rename from ")
+            .or_else(|| line.strip_prefix(b"rename old "))
+        {
+            file.old_path = Some(unquote(value)?);
+        } else if let Some(value) = line
+            .strip_prefix(b"rename to ")
+            .or_else(|| line.strip_prefix(b"rename new "))
+        {
+            file.new_path = Some(unquote(value)?);
+        } else if let Some(value) = line.strip_prefix(b"similarity index ") {
+            file.similarity = value
+                .trim_end()
+                .strip_suffix(b"%")
+                .and_then(|value| value.to_str().ok()?.parse().ok());
+        } else if let Some(value) = line.strip_prefix(b"index ") {
+            let mut tokens = value.trim_end().splitn_str(2, b" ");
+            let ids = tokens.next().unwrap_or_default();
+            if let Some((old, new)) = ids.split_once_str(b"..") {
+                let id = |hex: &[u8]| gix_hash::Prefix::from_hex(hex.to_str().ok()?).ok();
+                file.old_id = id(old);
+                file.new_id = id(new);
+            }
+            if let Some(value) = tokens.next() {
+                let mode = mode(value)?;
+                file.old_mode.get_or_insert(mode);
+                file.new_mode.get_or_insert(mode);
+            }
+        } else if !line.starts_with(b"dissimilarity index ") {
+            break;
+        }
+        lines.pos += 1;
+    }
+
+    let (mut old_path, mut new_path) = (file.old_path.take(), file.new_path.take());
+    match lines.peek(0) {
+        Some(line) if line.starts_with(b"--- ") && lines.peek(1).map_or(false, |line| line.starts_with(b"+++ ")) => {
+            let (old, new) = names(lines)?;
+            is_creation |= old.is_none();
+            is_deletion |= new.is_none();
+            old_path = old_path.or(old);
+            new_path = new_path.or(new);
+            file.content = Content::Text(hunks(lines)?);
+        }
+        Some(line) if line.starts_with(b"GIT binary patch") => {
+            lines.pos += 1;
+            file.content = binary(lines)?;
+        }
+        Some(line) if line.starts_with(b"Binary files ") => {
+            lines.pos += 1;
+            file.content = Content::BinaryWithoutData;
+        }
+        _ => {}
+    }
+
+    file.old_path = if is_creation {
+        None
+    } else {
+        old_path.or_else(|| header_path.clone())
+    };
+    file.new_path = if is_deletion { None } else { new_path.or(header_path) };
+    if file.old_path.is_none() && file.new_path.is_none() {
+        return Err(Error::MissingPath {
+            line_number,
+            line: header_names.trim_end().into(),
+        });
+    }
+    Ok(file)
+}
+
+/// Return the path of `a/<path> b/<path>` as seen on a `diff --git` line, if both paths are the same.
+/// Otherwise, the paths are expected to be noted in extended headers.
+fn header_path(names: &BStr) -> Result<Option<BString>, gix_quote::ansi_c::undo::Error> {
+    let names = names.trim_end().as_bstr();
+    if names.starts_with(b"\"") {
+        let (a, consumed) = gix_quote::ansi_c::undo(names)?;
+        let (b, _) = gix_quote::ansi_c::undo(names[consumed..].trim_start().as_bstr())?;
+        return Ok(same_path(a.as_ref(), b.as_ref()));
+    }
+    for pos in names.find_iter(b" ") {
+        let (a, b) = (names[..pos].as_bstr(), names[pos + 1..].as_bstr());
+        let (b, _) = gix_quote::ansi_c::undo(b)?;
+        if let Some(path) = same_path(a, b.as_ref()) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+fn same_path(a: &BStr, b: &BStr) -> Option<BString> {
+    let a = strip_component(a)?;
+    (a == strip_component(b)?).then(|| a.to_owned())
+}
+
+/// Remove the first path component of `path`, or return `None` if there is only one.
+fn strip_component(path: &BStr) -> Option<&BStr> {
+    let pos = path.find_byte(b'/')?;
+    Some(path[pos + 1..].as_bstr()).filter(|path| !path.is_empty())
+}
+
+/// Parse the paths of the `---` and `+++` lines, which are `None` for `/dev/null`.
+fn names(lines: &mut Lines<'_>) -> Result<(Option<BString>, Option<BString>), Error> {
+    let mut name = || -> Result<Option<BString>, Error> {
+        let line_number = lines.line_number();
+        let line = lines.next().expect("checked by caller").as_bstr();
+        let name = line[4..].trim_end_with(|c| c == '\n' || c == '\r').as_bstr();
+        let name = if name.starts_with(b"\"") {
+            gix_quote::ansi_c::undo(name)
+                .map_err(|source| Error::Unquote { line_number, source })?
+                .0
+                .into_owned()
+        } else {
+            name.split_str(b"\t")
+                .next()
+                .unwrap_or_default()
+                .trim_end()
+                .as_bstr()
+                .to_owned()
+        };
+        if name == "/dev/null" {
+            return Ok(None);
+        }
+        strip_component(name.as_ref())
+            .map(|name| Some(name.to_owned()))
+            .ok_or_else(|| Error::MissingPath {
+                line_number,
+                line: line.trim_end().into(),
+            })
+    };
+    let old = name()?;
+    let new = name()?;
+    Ok((old, new))
+}
+
+fn parse_mode(mode: &[u8]) -> Option<EntryKind> {
+    Some(match u32::from_str_radix(mode.to_str().ok()?, 8).ok()? {
+        0o100644 => EntryKind::Blob,
+        0o100755 => EntryKind::BlobExecutable,
+        0o120000 => EntryKind::Link,
+        0o160000 => EntryKind::Commit,
+        0o040000 => EntryKind::Tree,
+        _ => return None,
+    })
+}
+
+/// Parse all hunks that follow, each starting with a `@@ -` header.
+fn hunks(lines: &mut Lines<'_>) -> Result<Vec<Hunk>, Error> {
+    let mut out = Vec::new();
+    while let Some(header) = lines.peek(0).filter(|line| line.starts_with(b"@@ -")) {
+        let mut hunk = hunk_header(header).ok_or_else(|| Error::InvalidHunkHeader {
+            line_number: lines.line_number(),
+            line: header.trim_end().into(),
+        })?;
+        lines.pos += 1;
+        let (mut old, mut new) = (hunk.old_lines, hunk.new_lines);
+        while old != 0 || new != 0 {
+            let corrupt = Error::CorruptHunk {
+                line_number: lines.line_number(),
+                old_lines: old,
+                new_lines: new,
+            };
+            let Some(line) = lines.next() else { return Err(corrupt) };
+            let (kind, content) = match line.first() {
+                Some(b' ') if old != 0 && new != 0 => (LineKind::Context, &line[1..]),
+                // An empty context line, possibly with its trailing whitespace removed by a mail program.
+                Some(b'\n' | b'\r') if old != 0 && new != 0 && line.trim_end().is_empty() => (LineKind::Context, line),
+                Some(b'-') if old != 0 => (LineKind::Removal, &line[1..]),
+                Some(b'+') if new != 0 => (LineKind::Addition, &line[1..]),
+                Some(b'\\') => {
+                    remove_trailing_newline(&mut hunk);
+                    continue;
+                }
+                _ => return Err(corrupt),
+            };
+            match kind {
+                LineKind::Context => {
+                    old -= 1;
+                    new -= 1;
+                }
+                LineKind::Removal => old -= 1,
+                LineKind::Addition => new -= 1,
+            }
+            hunk.lines.push(Line {
+                kind,
+                content: content.into(),
+            });
+        }
+        if lines.peek(0).map_or(false, |line| line.starts_with(b"\\")) {
+            lines.pos += 1;
+            remove_trailing_newline(&mut hunk);
+        }
+        out.push(hunk);
+    }
+    Ok(out)
+}
+
+/// Handle `\ No newline at end of file`, which applies to the previous line.
+fn remove_trailing_newline(hunk: &mut Hunk) {
+    if let Some(line) = hunk.lines.last_mut() {
+        if line.content.last() == Some(&b'\n') {
+            line.content.pop();
+        }
+    }
+}
+
+/// Parse `@@ -<start>[,<lines>] +<start>[,<lines>] @@[ <function name>]`.
+fn hunk_header(line: &[u8]) -> Option<Hunk> {
+    fn number(input: &[u8]) -> Option<(u32, &[u8])> {
+        let end = input.iter().position(|b| !b.is_ascii_digit()).unwrap_or(input.len());
+        Some((input[..end].to_str().ok()?.parse().ok()?, &input[end..]))
+    }
+    fn range(input: &[u8]) -> Option<(u32, u32, &[u8])> {
+        let (start, input) = number(input)?;
+        match input.strip_prefix(b",") {
+            Some(input) => {
+                let (lines, input) = number(input)?;
+                Some((start, lines, input))
+            }
+            None => Some((start, 1, input)),
+        }
+    }
+    let (old_start, old_lines, rest) = range(line.strip_prefix(b"@@ -")?)?;
+    let (new_start, new_lines, rest) = range(rest.strip_prefix(b" +")?)?;
+    let rest = rest.strip_prefix(b" @@")?;
+    let function_name = rest.strip_prefix(b" ").unwrap_or(rest);
+    Some(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        function_name: function_name.trim_end_with(|c| c == '\n' || c == '\r').into(),
+        lines: Vec::new(),
+    })
+}
+
+/// Parse the data following `GIT binary patch`, which is the forward hunk along with an optional reverse hunk.
+fn binary(lines: &mut Lines<'_>) -> Result<Content, Error> {
+    let line_number = lines.line_number();
+    let forward = binary_hunk(lines)?.ok_or_else(|| Error::InvalidBinaryData {
+        line_number,
+        line: lines.peek(0).unwrap_or_default().trim_end().into(),
+    })?;
+    let reverse = binary_hunk(lines)?;
+    Ok(Content::Binary { forward, reverse })
+}
+
+/// Parse `literal <size>` or `delta <size>` followed by lines of encoded data up to an empty line.
+fn binary_hunk(lines: &mut Lines<'_>) -> Result<Option<BinaryHunk>, Error> {
+    let Some(header) = lines.peek(0) else { return Ok(None) };
+    let header_line_number = lines.line_number();
+    let (is_delta, size) = match (header.strip_prefix(b"literal "), header.strip_prefix(b"delta ")) {
+        (Some(size), _) => (false, size),
+        (_, Some(size)) => (true, size),
+        (None, None) => return Ok(None),
+    };
+    let size: usize = size
+        .trim_end()
+        .to_str()
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .ok_or_else(|| Error::InvalidBinaryData {
+            line_number: header_line_number,
+            line: header.trim_end().into(),
+        })?;
+    lines.pos += 1;
+
+    let mut deflated = Vec::new();
+    loop {
+        let line_number = lines.line_number();
+        let Some(line) = lines.next().map(ByteSlice::trim_end).filter(|line| !line.is_empty()) else {
+            break;
+        };
+        decode_base85_line(line, &mut deflated).ok_or_else(|| Error::InvalidBinaryData {
+            line_number,
+            line: line.into(),
+        })?;
+    }
+    let data = inflate(&deflated, size).ok_or(Error::Inflate {
+        line_number: header_line_number,
+        expected: size,
+    })?;
+    Ok(Some(if is_delta {
+        BinaryHunk::Delta(data)
+    } else {
+        BinaryHunk::Literal(data)
+    }))
+}
+
+/// Decode a line of a binary patch into `out`, which starts with a character encoding the amount of bytes on the line,
+/// followed by groups of 5 characters each encoding 4 bytes.
+fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let (&len, encoded) = line.split_first()?;
+    let mut remaining = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if encoded.len() != (remaining + 3) / 4 * 5 {
+        return None;
+    }
+    for group in encoded.chunks(5) {
+        let mut acc = 0u32;
+        for char in group {
+            let value = BASE85.iter().position(|c| c == char)? as u32;
+            acc = acc.checked_mul(85)?.checked_add(value)?;
+        }
+        let len = remaining.min(4);
+        out.extend_from_slice(&acc.to_be_bytes()[..len]);
+        remaining -= len;
+    }
+    Some(())
+}
+
+/// Decompress `data` and return it if it's exactly `size` bytes.
+fn inflate(data: &[u8], size: usize) -> Option<Vec<u8>> {
+    // One more byte to be able to detect if there is more data than expected.
+    let mut out = vec![0; size + 1];
+    let (status, _consumed, written) = gix_features::zlib::Inflate::default().once(data, &mut out).ok()?;
+    (status == gix_features::zlib::Status::StreamEnd && written == size).then(|| {
+        out.truncate(size);
+        out
+    })
+}
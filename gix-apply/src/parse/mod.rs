@@ -0,0 +1,30 @@
+use bstr::BString;
+
+/// The error returned by [`parse()`](crate::parse()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number}: could not find the file name in git diff header '{line}'")]
+    MissingPath { line_number: usize, line: BString },
+    #[error("Line {line_number}: could not unquote path")]
+    Unquote {
+        line_number: usize,
+        source: gix_quote::ansi_c::undo::Error,
+    },
+    #[error("Line {line_number}: invalid file mode in '{line}'")]
+    InvalidMode { line_number: usize, line: BString },
+    #[error("Line {line_number}: invalid hunk header '{line}'")]
+    InvalidHunkHeader { line_number: usize, line: BString },
+    #[error("Line {line_number}: corrupt hunk, expected {old_lines} more old and {new_lines} more new lines")]
+    CorruptHunk {
+        line_number: usize,
+        old_lines: u32,
+        new_lines: u32,
+    },
+    #[error("Line {line_number}: invalid binary patch data in '{line}'")]
+    InvalidBinaryData { line_number: usize, line: BString },
+    #[error("Line {line_number}: binary patch data didn't decompress into the expected {expected} bytes")]
+    Inflate { line_number: usize, expected: usize },
+}
+
+pub(crate) mod function;
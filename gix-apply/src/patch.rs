@@ -0,0 +1,102 @@
+use bstr::{BStr, BString};
+use gix_object::tree::EntryKind;
+
+/// The patch of a single file, as parsed from a section that starts with `diff --git`, or with `---` and `+++` lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The repository-relative location of the file before the change, or `None` if it is created.
+    pub old_path: Option<BString>,
+    /// The repository-relative location of the file after the change, or `None` if it is deleted.
+    ///
+    /// If it differs from `old_path`, the file was renamed or copied.
+    pub new_path: Option<BString>,
+    /// The kind of file before the change, if known.
+    pub old_mode: Option<EntryKind>,
+    /// The kind of file after the change, if known.
+    pub new_mode: Option<EntryKind>,
+    /// The possibly abbreviated id of the blob before the change, as noted on the `index` line.
+    pub old_id: Option<gix_hash::Prefix>,
+    /// The possibly abbreviated id of the blob after the change, as noted on the `index` line.
+    pub new_id: Option<gix_hash::Prefix>,
+    /// If `true`, the file at `old_path` was copied to `new_path`, and it was renamed otherwise, if the paths differ.
+    pub is_copy: bool,
+    /// The similarity of the files at `old_path` and `new_path` in percent, if noted for a rename or copy.
+    pub similarity: Option<u8>,
+    /// The changes to the content of the file.
+    pub content: Content,
+}
+
+impl File {
+    /// Return the location to use when referring to this file, which is its path after the change, or before it if it was deleted.
+    pub fn path(&self) -> &BStr {
+        self.new_path
+            .as_ref()
+            .or(self.old_path.as_ref())
+            .expect("parsing assures one of the paths is set")
+            .as_ref()
+    }
+}
+
+/// The changes to the content of a [`File`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    /// The lines of a text file changed, or nothing changed if there are no hunks, like when only the mode changed.
+    Text(Vec<Hunk>),
+    /// A binary file changed as described by a `GIT binary patch`.
+    Binary {
+        /// The change from the old version to the new one.
+        forward: BinaryHunk,
+        /// The change from the new version to the old one, if present.
+        reverse: Option<BinaryHunk>,
+    },
+    /// A binary file changed, but the patch only notes that `Binary files differ`.
+    ///
+    /// These can only be applied if the new version can be found in the object database by its full id.
+    BinaryWithoutData,
+}
+
+/// A section of consecutive changed lines along with the unchanged lines around them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The one-based line number at which the hunk starts in the old version of the file, or 0 if the old version is empty.
+    pub old_start: u32,
+    /// The amount of lines the hunk spans in the old version of the file.
+    pub old_lines: u32,
+    /// The one-based line number at which the hunk starts in the new version of the file, or 0 if the new version is empty.
+    pub new_start: u32,
+    /// The amount of lines the hunk spans in the new version of the file.
+    pub new_lines: u32,
+    /// The text after the line numbers of the hunk header, typically the name of the function the hunk is in.
+    pub function_name: BString,
+    /// All lines of the hunk.
+    pub lines: Vec<Line>,
+}
+
+/// A line of a [`Hunk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Whether the line was added, removed or is unchanged context.
+    pub kind: LineKind,
+    /// The line itself, including its line separator unless it's the last line of a file without a trailing newline.
+    pub content: BString,
+}
+
+/// The kind of [`Line`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    /// The line is unchanged and present in both versions, prefixed with a space.
+    Context,
+    /// The line was removed, prefixed with `-`.
+    Removal,
+    /// The line was added, prefixed with `+`.
+    Addition,
+}
+
+/// One direction of a binary patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryHunk {
+    /// The complete content of the resulting file.
+    Literal(Vec<u8>),
+    /// A delta to apply to the content of the original file to obtain the resulting file, in the same format as used in packs.
+    Delta(Vec<u8>),
+}
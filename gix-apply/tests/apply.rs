@@ -0,0 +1,399 @@
+use std::path::Path;
+
+use bstr::{BStr, ByteSlice};
+use gix_apply::{
+    apply::{Error, Merge, Options},
+    patch::{BinaryHunk, Content, LineKind},
+};
+use gix_hash::ObjectId;
+use gix_object::{tree::EntryKind, FindExt};
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+mod parse {
+    use super::*;
+
+    #[test]
+    fn git_diff_with_renames_copies_and_binary_files() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_apply_repo.sh")?;
+        let files = gix_apply::parse(&std::fs::read(dir.join("all.patch"))?)?;
+        let paths: Vec<_> = files
+            .iter()
+            .map(|file| file.path().to_str_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "binary.bin",
+                "code.rs",
+                "copied.txt",
+                "dir/file",
+                "exec.sh",
+                "link",
+                "new.bin",
+                "no-newline.txt",
+                "numbers.txt",
+                "other/new.txt",
+                "renamed.txt"
+            ]
+        );
+        let file = |path: &str| files.iter().find(|file| file.path() == path).expect("present");
+
+        let copy = file("copied.txt");
+        assert_eq!(copy.old_path.as_ref().expect("set"), "to-copy.txt");
+        assert!(copy.is_copy, "copies are detected");
+        assert!(copy.similarity.is_some());
+
+        let rename = file("renamed.txt");
+        assert_eq!(rename.old_path.as_ref().expect("set"), "to-rename.txt");
+        assert!(!rename.is_copy);
+
+        let deletion = file("dir/file");
+        assert_eq!(deletion.new_path, None);
+        assert_eq!(deletion.old_mode, Some(EntryKind::Blob));
+
+        let mode_change = file("exec.sh");
+        assert_eq!(mode_change.old_mode, Some(EntryKind::Blob));
+        assert_eq!(mode_change.new_mode, Some(EntryKind::BlobExecutable));
+
+        let link = file("link");
+        assert_eq!(link.old_path, None);
+        assert_eq!(link.new_mode, Some(EntryKind::Link));
+
+        let new_binary = file("new.bin");
+        assert_eq!(
+            new_binary.content,
+            Content::Binary {
+                forward: BinaryHunk::Literal(b"\0\x01\x02".to_vec()),
+                reverse: Some(BinaryHunk::Literal(Vec::new())),
+            }
+        );
+        assert_eq!(
+            new_binary.new_id.expect("set").hex_len(),
+            40,
+            "full ids are needed to apply binary patches"
+        );
+
+        let Content::Text(hunks) = &file("no-newline.txt").content else {
+            unreachable!("text changes")
+        };
+        let last = hunks[0].lines.last().expect("present");
+        assert_eq!(last.kind, LineKind::Addition);
+        assert_eq!(last.content, "three", "the missing newline is retained");
+
+        let Content::Text(hunks) = &file("numbers.txt").content else {
+            unreachable!("text changes")
+        };
+        assert_eq!(
+            hunks
+                .iter()
+                .map(|hunk| (hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines))
+                .collect::<Vec<_>>(),
+            [(2, 7, 2, 7), (12, 7, 12, 7)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mbox_skips_message_and_diffstat() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_apply_repo.sh")?;
+        let from_mbox = gix_apply::parse(&std::fs::read(dir.join("target.mbox"))?)?;
+        let from_diff = gix_apply::parse(&std::fs::read(dir.join("all.patch"))?)?;
+        assert_eq!(from_mbox.len(), from_diff.len());
+        assert!(from_mbox
+            .iter()
+            .all(|file| !matches!(file.content, Content::BinaryWithoutData)));
+        Ok(())
+    }
+
+    #[test]
+    fn traditional_unified_diff() -> crate::Result {
+        let files = gix_apply::parse(
+            b"some text before\n--- a/one\t2024-01-01 00:00:00\n+++ \"b/t\\303\\244st\"\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n",
+        )?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_path.as_ref().expect("set"), "one");
+        assert_eq!(files[0].new_path.as_ref().expect("set"), "täst");
+        let Content::Text(hunks) = &files[0].content else {
+            unreachable!("text")
+        };
+        assert_eq!(
+            hunks[0].lines.iter().map(|line| line.kind).collect::<Vec<_>>(),
+            [LineKind::Context, LineKind::Removal, LineKind::Addition]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_hunk() {
+        let err = gix_apply::parse(b"--- a/one\n+++ b/one\n@@ -1,3 +1,3 @@\n a\n-b\n+c\n").unwrap_err();
+        assert!(
+            matches!(err, gix_apply::parse::Error::CorruptHunk { line_number: 7, .. }),
+            "like git, the line after the end of input is reported: {err:?}"
+        );
+    }
+}
+
+mod to_tree {
+    use super::*;
+
+    #[test]
+    fn all_changes_of_diff_and_mbox() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        for name in ["all.patch", "target.mbox"] {
+            let files = gix_apply::parse(&std::fs::read(dir.path().join(name))?)?;
+            let (tree, outcome) = gix_apply::to_tree(
+                &files,
+                &tree_of(&dir, &odb, "tags/base")?,
+                &odb,
+                Options::default(),
+                None,
+            )?;
+            assert_eq!(tree, tree_of(&dir, &odb, "tags/target")?, "{name}");
+            assert!(outcome.conflicts.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn hunks_at_an_offset() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let (tree, _) = gix_apply::to_tree(
+            &files,
+            &tree_of(&dir, &odb, "heads/shifted")?,
+            &odb,
+            Options::default(),
+            None,
+        )?;
+        assert_eq!(tree, read_id(dir.path(), "shifted-applied-tree")?);
+        Ok(())
+    }
+
+    #[test]
+    fn fuzz_is_opt_in() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let tree = tree_of(&dir, &odb, "heads/fuzzy")?;
+        let err = gix_apply::to_tree(&files, &tree, &odb, Options::default(), None).unwrap_err();
+        assert!(
+            matches!(&err, Error::HunkMismatch { path, hunk: 1, line: 1 } if path == "code.rs"),
+            "{err:?}"
+        );
+
+        let (tree, _) = gix_apply::to_tree(&files, &tree, &odb, Options { min_context: Some(1) }, None)?;
+        assert_eq!(tree, read_id(dir.path(), "fuzzy-applied-tree")?);
+        Ok(())
+    }
+
+    #[test]
+    fn already_applied_patches_fail() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let err = gix_apply::to_tree(
+            &files,
+            &tree_of(&dir, &odb, "tags/target")?,
+            &odb,
+            Options::default(),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::BinaryPreimageMismatch { .. }), "{err:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_is_used_if_hunks_do_not_apply() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let tree = tree_of(&dir, &odb, "heads/conflicting")?;
+        let err = gix_apply::to_tree(&files, &tree, &odb, Options::default(), None).unwrap_err();
+        assert!(matches!(err, Error::HunkMismatch { .. }), "{err:?}");
+
+        let mut merge = ConflictMarkers::default();
+        let (tree, outcome) = gix_apply::to_tree(&files, &tree, &odb, Options::default(), Some(&mut merge))?;
+        assert_eq!(merge.calls, 1, "only the file with mismatching hunks is merged");
+        assert_eq!(outcome.conflicts.len(), 1);
+        let conflict = &outcome.conflicts[0];
+        assert_eq!(conflict.path, "code.rs");
+        assert_eq!(
+            blob(&odb, conflict.merged)?,
+            b"<<<<<<<\n    let b = 3;\n=======\n    let b = 20;\n>>>>>>>\n"
+        );
+        assert!(blob(&odb, conflict.ours)?.contains_str("let b = 3;"));
+        assert!(blob(&odb, conflict.theirs)?.contains_str("let b = 20;"));
+        assert!(blob(&odb, conflict.base)?.contains_str("let b = 2;"));
+
+        let mut buf = Vec::new();
+        let tree = odb.find_tree(&tree, &mut buf)?;
+        let entry = tree
+            .entries
+            .iter()
+            .find(|entry| entry.filename == "code.rs")
+            .expect("present");
+        assert_eq!(entry.oid, conflict.merged, "the merged file is stored in the tree");
+        Ok(())
+    }
+
+    #[test]
+    fn binary_patches_written_by_gix_diff() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let tree = tree_of(&dir, &odb, "tags/base")?;
+        let old = blob(&odb, entry_id(&odb, tree, "binary.bin")?)?;
+        for new in [
+            old.repeat(20),
+            b"something else entirely".to_vec(),
+            Vec::new(),
+            [old.as_slice(), b"\0appended"].concat(),
+        ] {
+            let (old_id, new_id) = (
+                gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Blob, &old),
+                gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Blob, &new),
+            );
+            let mut patch =
+                format!("diff --git a/binary.bin b/binary.bin\nindex {old_id}..{new_id} 100644\n").into_bytes();
+            gix_diff::patch::binary::write(&old, &new, &mut patch)?;
+            let files = gix_apply::parse(&patch)?;
+            let (new_tree, _) = gix_apply::to_tree(&files, &tree, &odb, Options::default(), None)?;
+            assert_eq!(blob(&odb, entry_id(&odb, new_tree, "binary.bin")?)?, new);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn new_empty_files_are_written_as_empty_blob() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(b"diff --git a/empty b/empty\nnew file mode 100644\nindex 0000000..e69de29\n")?;
+        let (tree, _) = gix_apply::to_tree(
+            &files,
+            &tree_of(&dir, &odb, "tags/base")?,
+            &odb,
+            Options::default(),
+            None,
+        )?;
+        let id = entry_id(&odb, tree, "empty")?;
+        assert_eq!(id, ObjectId::empty_blob(gix_hash::Kind::Sha1));
+        assert!(
+            blob(&odb, id)?.is_empty(),
+            "the blob is available in the object database"
+        );
+        Ok(())
+    }
+}
+
+mod to_index {
+    use super::*;
+
+    #[test]
+    fn all_changes() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let mut index = gix_index::State::from_tree(&tree_of(&dir, &odb, "tags/base")?, &odb)?;
+        let outcome = gix_apply::to_index(&files, &mut index, &odb, Options::default(), None)?;
+        assert!(outcome.conflicts.is_empty());
+        let expected = gix_index::State::from_tree(&tree_of(&dir, &odb, "tags/target")?, &odb)?;
+        assert_eq!(entries(&index), entries(&expected));
+        Ok(())
+    }
+
+    #[test]
+    fn conflicts_are_added_as_stages() -> crate::Result {
+        let (dir, odb) = fixture()?;
+        let files = gix_apply::parse(&std::fs::read(dir.path().join("all.patch"))?)?;
+        let mut index = gix_index::State::from_tree(&tree_of(&dir, &odb, "heads/conflicting")?, &odb)?;
+        let outcome = gix_apply::to_index(
+            &files,
+            &mut index,
+            &odb,
+            Options::default(),
+            Some(&mut ConflictMarkers::default()),
+        )?;
+        let conflict = &outcome.conflicts[0];
+        let stages: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|entry| entry.path(&index) == "code.rs")
+            .map(|entry| (entry.stage(), entry.id))
+            .collect();
+        assert_eq!(
+            stages,
+            [(1, conflict.base), (2, conflict.ours), (3, conflict.theirs)],
+            "like `git apply --3way --cached`, the merged file isn't added"
+        );
+        Ok(())
+    }
+}
+
+/// A merge that fails whenever all sides differ, and places `ours` and `theirs` between conflict markers.
+#[derive(Default)]
+struct ConflictMarkers {
+    calls: usize,
+}
+
+impl Merge for ConflictMarkers {
+    fn merge(
+        &mut self,
+        _path: &BStr,
+        base: &[u8],
+        ours: &[u8],
+        theirs: &[u8],
+        out: &mut Vec<u8>,
+    ) -> std::result::Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.calls += 1;
+        let (ours, theirs): (Vec<_>, Vec<_>) = ours
+            .lines_with_terminator()
+            .zip(theirs.lines_with_terminator())
+            .zip(base.lines_with_terminator())
+            .filter(|((ours, theirs), base)| ours != base && theirs != base)
+            .map(|((ours, theirs), _)| (ours, theirs))
+            .unzip();
+        out.extend_from_slice(b"<<<<<<<\n");
+        out.extend(ours.concat());
+        out.extend_from_slice(b"=======\n");
+        out.extend(theirs.concat());
+        out.extend_from_slice(b">>>>>>>\n");
+        Ok(true)
+    }
+}
+
+fn fixture() -> Result<(gix_testtools::tempfile::TempDir, gix_odb::Handle)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_apply_repo.sh")?;
+    let odb = gix_odb::at(dir.path().join(".git/objects"))?;
+    Ok((dir, odb))
+}
+
+fn read_id(root: &Path, name: &str) -> Result<ObjectId> {
+    Ok(ObjectId::from_hex(std::fs::read(root.join(name))?.trim())?)
+}
+
+fn tree_of(dir: &gix_testtools::tempfile::TempDir, odb: &gix_odb::Handle, name: &str) -> Result<ObjectId> {
+    let commit = read_id(&dir.path().join(".git/refs"), name)?;
+    let mut buf = Vec::new();
+    let tree = odb.find_commit(&commit, &mut buf)?.tree();
+    Ok(tree)
+}
+
+fn entry_id(odb: &gix_odb::Handle, tree: ObjectId, name: &str) -> Result<ObjectId> {
+    let mut buf = Vec::new();
+    let tree = odb.find_tree(&tree, &mut buf)?;
+    Ok(tree
+        .entries
+        .iter()
+        .find(|entry| entry.filename == name)
+        .expect("present")
+        .oid
+        .to_owned())
+}
+
+fn blob(odb: &gix_odb::Handle, id: ObjectId) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    Ok(odb.find_blob(&id, &mut buf)?.data.to_owned())
+}
+
+fn entries(index: &gix_index::State) -> Vec<(String, ObjectId, gix_index::entry::Mode, gix_index::entry::Stage)> {
+    index
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(index).to_string(), entry.id, entry.mode, entry.stage()))
+        .collect()
+}
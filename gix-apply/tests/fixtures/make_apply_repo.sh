@@ -0,0 +1,60 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+seq 1 20 >numbers.txt
+cat <<'EOF2' >code.rs
+fn main() {
+    let a = 1;
+    let b = 2;
+    let c = 3;
+    println!("{a} {b} {c}");
+}
+EOF2
+seq 1 10 >to-rename.txt
+seq 30 50 >to-copy.txt
+seq 1 3 >to-delete.txt
+seq 1 3 >exec.sh
+printf 'one\ntwo' >no-newline.txt
+printf 'a\0b\0c' >binary.bin
+mkdir dir && seq 1 5 >dir/file
+git add . && git commit -qm "base"
+git tag base
+
+sed -i 's/^5$/five/; s/^15$/fifteen/' numbers.txt
+sed -i 's/let b = 2;/let b = 20;/' code.rs
+git mv to-rename.txt renamed.txt && echo 11 >>renamed.txt
+cp to-copy.txt copied.txt && echo 51 >>copied.txt
+git rm -q to-delete.txt dir/file
+chmod +x exec.sh
+printf 'one\ntwo\nthree' >no-newline.txt
+printf 'a\0b\0c\0d' >binary.bin
+printf '\0\1\2' >new.bin
+mkdir other && seq 1 3 >other/new.txt
+ln -s numbers.txt link
+git add . && git commit -qm "changes" -m "with a body that is skipped when parsing"
+git tag target
+
+git diff --binary --full-index -M -C --find-copies-harder base target >all.patch
+git format-patch --stdout -1 target >target.mbox
+
+git checkout -q -b shifted base
+{ seq 1 3 | sed 's/^/head /'; seq 1 20; } >numbers.txt
+git commit -qam "shift numbers"
+git apply --cached all.patch
+git write-tree >shifted-applied-tree
+git reset -q --hard
+
+git checkout -q -b fuzzy base
+sed -i 's/println!("{a} {b} {c}");/println!("{a}, {b}, {c}");/' code.rs
+git commit -qam "change context"
+git apply --cached -C1 all.patch
+git write-tree >fuzzy-applied-tree
+git reset -q --hard
+
+git checkout -q -b conflicting base
+sed -i 's/let b = 2;/let b = 3;/' code.rs
+git commit -qam "change the same line"
+
+git checkout -q target
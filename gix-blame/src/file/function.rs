@@ -0,0 +1,452 @@
+use std::{borrow::Cow, num::NonZeroU32};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_diff::blob::{intern::TokenSource, platform::prepare_diff::Operation, ResourceKind};
+use gix_hash::ObjectId;
+use gix_object::{tree::EntryKind, FindExt};
+
+use super::{split_hunks, Error, Mapping, Suspect, UnblamedHunk};
+use crate::{BlameEntry, Options, Outcome, Statistics};
+
+/// Attribute each line of the file at `file_path` to the commit that introduced it, starting at the first commit yielded by `traverse`.
+///
+/// `traverse` must yield each commit after all of its children, which is what [`Sorting::TopoOrder`](gix_traverse::commit::Sorting::TopoOrder)
+/// guarantees, and it is stopped as soon as all lines are blamed. Lines that are still unblamed once `traverse` is exhausted,
+/// for instance as its commits were limited, are attributed to the last commit that they were passed to.
+///
+/// `odb` is used to read commits, trees and blobs, while `resource_cache` is used to obtain versions of the file that are suitable
+/// for diffing, which is why it should be configured to [convert to git](gix_diff::blob::pipeline::Mode::ToGit).
+/// Use `options` to limit the range of lines to blame and to configure how the versions of the file are compared.
+pub fn file<E>(
+    odb: impl gix_object::Find + gix_object::FindHeader,
+    traverse: impl IntoIterator<Item = Result<gix_traverse::commit::Info, E>>,
+    resource_cache: &mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = gix_trace::coarse!("gix_blame::file()", ?file_path);
+    let mut traverse = traverse.into_iter();
+    let mut stats = Statistics::default();
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+
+    let first = traverse
+        .next()
+        .ok_or(Error::EmptyTraversal)?
+        .map_err(|err| Error::Traverse(err.into()))?;
+    stats.commits_traversed += 1;
+    let (id, kind) =
+        find_path_entry(&odb, &first.id, file_path, &mut buf, &mut buf2)?.ok_or_else(|| Error::FileMissing {
+            file_path: file_path.to_owned(),
+            commit_id: first.id,
+        })?;
+    let blob = file_data(&odb, resource_cache, id, kind, file_path, &mut buf)?;
+    let num_lines = gix_diff::blob::sources::byte_lines_with_terminator(&blob)
+        .tokenize()
+        .count() as u32;
+    let range = options.range.clone().unwrap_or(0..num_lines);
+    if range.start > range.end || range.end > num_lines {
+        return Err(Error::InvalidRange { range, num_lines });
+    }
+
+    let mut entries = Vec::new();
+    let mut suspects = gix_hashtable::HashMap::<ObjectId, Vec<Suspect>>::default();
+    if !range.is_empty() {
+        suspects.insert(
+            first.id,
+            vec![Suspect {
+                path: file_path.to_owned(),
+                id,
+                kind,
+                hunks: vec![UnblamedHunk {
+                    start_in_suspect: range.start,
+                    range_in_blamed_file: range,
+                }],
+            }],
+        );
+    }
+
+    let mut info = Some(first);
+    while let Some(commit) = info.take() {
+        if let Some(commit_suspects) = suspects.remove(&commit.id) {
+            for suspect in commit_suspects {
+                process_suspect(
+                    &odb,
+                    resource_cache,
+                    &options,
+                    &commit,
+                    suspect,
+                    &mut suspects,
+                    &mut entries,
+                    &mut stats,
+                )?;
+            }
+        }
+        if suspects.is_empty() {
+            break;
+        }
+        info = traverse.next().transpose().map_err(|err| Error::Traverse(err.into()))?;
+        stats.commits_traversed += usize::from(info.is_some());
+    }
+    for (commit_id, commit_suspects) in suspects {
+        for suspect in commit_suspects {
+            blame(&mut entries, commit_id, suspect.path.as_ref(), suspect.hunks);
+        }
+    }
+
+    Ok(Outcome {
+        entries: coalesce(entries),
+        blob,
+        statistics: stats,
+    })
+}
+
+/// Pass the lines of `suspect` that didn't change in the parents of `commit` on to these parents, and blame the remaining lines on `commit`.
+#[allow(clippy::too_many_arguments)]
+fn process_suspect(
+    odb: &(impl gix_object::Find + gix_object::FindHeader),
+    resource_cache: &mut gix_diff::blob::Platform,
+    options: &Options,
+    commit: &gix_traverse::commit::Info,
+    suspect: Suspect,
+    suspects: &mut gix_hashtable::HashMap<ObjectId, Vec<Suspect>>,
+    entries: &mut Vec<BlameEntry>,
+    stats: &mut Statistics,
+) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut parents = Vec::with_capacity(commit.parent_ids.len());
+    for parent_id in &commit.parent_ids {
+        let entry = find_path_entry(odb, parent_id, suspect.path.as_ref(), &mut buf, &mut buf2)?;
+        if let Some((id, _)) = entry {
+            if id == suspect.id {
+                let (id, kind) = entry.expect("just checked");
+                pass(suspects, *parent_id, suspect.path, id, kind, suspect.hunks);
+                return Ok(());
+            }
+        }
+        parents.push((*parent_id, entry.map(|(id, kind)| (suspect.path.clone(), id, kind))));
+    }
+
+    if let Some(rewrites) = options.rewrites {
+        for (parent_id, entry) in parents.iter_mut().filter(|(_, entry)| entry.is_none()) {
+            stats.trees_diffed += 1;
+            *entry = rename_source(
+                odb,
+                resource_cache,
+                rewrites,
+                *parent_id,
+                commit.id,
+                suspect.path.as_ref(),
+            )?;
+        }
+    }
+
+    let mut hunks = suspect.hunks;
+    let mut first_parent_changes = None;
+    for (parent_index, (parent_id, entry)) in parents.into_iter().enumerate() {
+        let Some((path, id, kind)) = entry else { continue };
+        if hunks.is_empty() {
+            break;
+        }
+        stats.blobs_diffed += 1;
+        let changes = diff(
+            odb,
+            resource_cache,
+            (path.as_ref(), id, kind),
+            (suspect.path.as_ref(), suspect.id, suspect.kind),
+            options.ignore_whitespace,
+        )?;
+        let (passed, remaining) = split_hunks(hunks, &unchanged(&changes));
+        if parent_index == 0 {
+            first_parent_changes = Some((parent_id, path.clone(), id, kind, changes));
+        }
+        pass(suspects, parent_id, path, id, kind, passed);
+        hunks = remaining;
+    }
+
+    if options.ignored_revs.contains(&commit.id) {
+        if let Some((parent_id, path, id, kind, changes)) = first_parent_changes {
+            let (passed, remaining) = split_hunks(hunks, &same_offset_in_changes(&changes));
+            pass(suspects, parent_id, path, id, kind, passed);
+            hunks = remaining;
+        }
+    }
+    blame(entries, commit.id, suspect.path.as_ref(), hunks);
+    Ok(())
+}
+
+fn pass(
+    suspects: &mut gix_hashtable::HashMap<ObjectId, Vec<Suspect>>,
+    commit_id: ObjectId,
+    path: BString,
+    id: ObjectId,
+    kind: EntryKind,
+    hunks: Vec<UnblamedHunk>,
+) {
+    if hunks.is_empty() {
+        return;
+    }
+    let commit_suspects = suspects.entry(commit_id).or_default();
+    match commit_suspects.iter_mut().find(|suspect| suspect.path == path) {
+        Some(suspect) => suspect.hunks.extend(hunks),
+        None => commit_suspects.push(Suspect { path, id, kind, hunks }),
+    }
+}
+
+fn blame(entries: &mut Vec<BlameEntry>, commit_id: ObjectId, path: &BStr, hunks: Vec<UnblamedHunk>) {
+    entries.extend(hunks.into_iter().filter_map(|hunk| {
+        Some(BlameEntry {
+            start_in_blamed_file: hunk.range_in_blamed_file.start,
+            start_in_source_file: hunk.start_in_suspect,
+            len: NonZeroU32::new(hunk.range_in_blamed_file.len() as u32)?,
+            commit_id,
+            source_path: path.to_owned(),
+        })
+    }));
+}
+
+/// Sort `entries` by their position in the blamed file and merge consecutive entries of the same commit and path.
+fn coalesce(mut entries: Vec<BlameEntry>) -> Vec<BlameEntry> {
+    entries.sort_by_key(|entry| entry.start_in_blamed_file);
+    let mut out: Vec<BlameEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if let Some(previous) = out.last_mut() {
+            if previous.commit_id == entry.commit_id
+                && previous.source_path == entry.source_path
+                && previous.range_in_blamed_file().end == entry.start_in_blamed_file
+                && previous.range_in_source_file().end == entry.start_in_source_file
+            {
+                previous.len = previous.len.saturating_add(entry.len.get());
+                continue;
+            }
+        }
+        out.push(entry);
+    }
+    out
+}
+
+/// A change of lines `before` in a parent to lines `after` in the suspect.
+struct Change {
+    before: std::ops::Range<u32>,
+    after: std::ops::Range<u32>,
+}
+
+/// Return the mappings of all lines between `changes` in the suspect, which has `num_lines`, to their lines in the parent,
+/// or nothing if the versions couldn't be diffed.
+fn unchanged(changes: &Option<(Vec<Change>, u32)>) -> Vec<Mapping> {
+    let Some((changes, num_lines)) = changes else {
+        return Vec::new();
+    };
+    let mut out = Vec::with_capacity(changes.len() + 1);
+    let (mut before, mut after) = (0, 0);
+    for change in changes {
+        if change.after.start > after {
+            out.push(Mapping {
+                range_in_suspect: after..change.after.start,
+                start_in_parent: before,
+            });
+        }
+        before = change.before.end;
+        after = change.after.end;
+    }
+    if *num_lines > after {
+        out.push(Mapping {
+            range_in_suspect: after..*num_lines,
+            start_in_parent: before,
+        });
+    }
+    out
+}
+
+/// Map the lines of the suspect within `changes` to the lines of the parent at the same offset in the change, for use with
+/// ignored revisions.
+fn same_offset_in_changes(changes: &Option<(Vec<Change>, u32)>) -> Vec<Mapping> {
+    let Some((changes, _)) = changes else {
+        return Vec::new();
+    };
+    changes
+        .iter()
+        .filter_map(|change| {
+            let len = change.after.len().min(change.before.len()) as u32;
+            (len > 0).then(|| Mapping {
+                range_in_suspect: change.after.start..change.after.start + len,
+                start_in_parent: change.before.start,
+            })
+        })
+        .collect()
+}
+
+/// Diff the lines of the file in the parent, `old`, with the ones in the suspect, `new`, and return all changes along with
+/// the amount of lines in `new`, or `None` if they can't be diffed.
+fn diff(
+    odb: &(impl gix_object::Find + gix_object::FindHeader),
+    resource_cache: &mut gix_diff::blob::Platform,
+    old: (&BStr, ObjectId, EntryKind),
+    new: (&BStr, ObjectId, EntryKind),
+    ignore_whitespace: bool,
+) -> Result<Option<(Vec<Change>, u32)>, Error> {
+    resource_cache.set_resource(old.1, old.2, old.0, ResourceKind::OldOrSource, odb)?;
+    resource_cache.set_resource(new.1, new.2, new.0, ResourceKind::NewOrDestination, odb)?;
+    let outcome = resource_cache.prepare_diff()?;
+    let Operation::InternalDiff { algorithm } = outcome.operation else {
+        return Ok(None);
+    };
+    let (Some(old), Some(new)) = (outcome.old.data.as_slice(), outcome.new.data.as_slice()) else {
+        return Ok(None);
+    };
+
+    fn tokens(data: &[u8], ignore_whitespace: bool) -> impl Iterator<Item = Cow<'_, [u8]>> {
+        gix_diff::blob::sources::byte_lines_with_terminator(data)
+            .tokenize()
+            .map(move |line| {
+                if ignore_whitespace {
+                    Cow::Owned(line.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect())
+                } else {
+                    Cow::Borrowed(line)
+                }
+            })
+    }
+    let mut input = gix_diff::blob::intern::InternedInput::default();
+    input.update_before(tokens(old, ignore_whitespace));
+    input.update_after(tokens(new, ignore_whitespace));
+
+    let mut changes = Vec::new();
+    gix_diff::blob::diff(algorithm, &input, |before, after| {
+        changes.push(Change { before, after })
+    });
+    Ok(Some((changes, input.after.len() as u32)))
+}
+
+/// Obtain the data of the blamed file as seen by the diff, or its data in `odb` if it is binary.
+fn file_data(
+    odb: &(impl gix_object::Find + gix_object::FindHeader),
+    resource_cache: &mut gix_diff::blob::Platform,
+    id: ObjectId,
+    kind: EntryKind,
+    path: &BStr,
+    buf: &mut Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    resource_cache.set_resource(id, kind, path, ResourceKind::NewOrDestination, odb)?;
+    if let Some(data) = resource_cache
+        .resource(ResourceKind::NewOrDestination)
+        .and_then(|resource| resource.data.as_slice())
+    {
+        return Ok(data.to_owned());
+    }
+    Ok(odb.find_blob(&id, buf)?.data.to_owned())
+}
+
+/// Find the blob or symlink at `path` in the tree of `commit_id`.
+fn find_path_entry(
+    odb: &impl gix_object::Find,
+    commit_id: &gix_hash::oid,
+    path: &BStr,
+    buf: &mut Vec<u8>,
+    buf2: &mut Vec<u8>,
+) -> Result<Option<(ObjectId, EntryKind)>, Error> {
+    let mut tree_id = odb.find_commit(commit_id, buf)?.tree();
+    let mut components = path.split_str("/").peekable();
+    while let Some(component) = components.next() {
+        let Some(entry) = odb
+            .find_tree_iter(&tree_id, buf2)?
+            .filter_map(Result::ok)
+            .find(|entry| entry.filename == component)
+        else {
+            return Ok(None);
+        };
+        if components.peek().is_none() {
+            return Ok(entry
+                .mode
+                .is_blob_or_symlink()
+                .then(|| (entry.oid.to_owned(), entry.mode.kind())));
+        }
+        if !entry.mode.is_tree() {
+            return Ok(None);
+        }
+        tree_id = entry.oid.to_owned();
+    }
+    Ok(None)
+}
+
+/// Find the path and entry of the file that was renamed to `path` when going from the tree of `parent_id` to the one of `commit_id`.
+fn rename_source(
+    odb: &(impl gix_object::Find + gix_object::FindHeader),
+    resource_cache: &mut gix_diff::blob::Platform,
+    rewrites: gix_diff::Rewrites,
+    parent_id: ObjectId,
+    commit_id: ObjectId,
+    path: &BStr,
+) -> Result<Option<(BString, ObjectId, EntryKind)>, Error> {
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let parent_tree = odb.find_commit(&parent_id, &mut buf)?.tree();
+    let tree = odb.find_commit(&commit_id, &mut buf)?.tree();
+    let mut recorder = gix_diff::tree::Recorder::default();
+    gix_diff::tree::Changes::from(odb.find_tree_iter(&parent_tree, &mut buf)?).needed_to_obtain(
+        odb.find_tree_iter(&tree, &mut buf2)?,
+        gix_diff::tree::State::default(),
+        odb,
+        &mut recorder,
+    )?;
+
+    use gix_diff::tree::{recorder, visit};
+    let mut tracker = gix_diff::rewrites::Tracker::new(rewrites);
+    for change in recorder.records {
+        let (change, location) = match change {
+            recorder::Change::Addition {
+                entry_mode,
+                oid,
+                path: location,
+            } => {
+                if location != path {
+                    continue;
+                }
+                (visit::Change::Addition { entry_mode, oid }, location)
+            }
+            recorder::Change::Deletion {
+                entry_mode,
+                oid,
+                path: location,
+            } => (visit::Change::Deletion { entry_mode, oid }, location),
+            recorder::Change::Modification {
+                previous_entry_mode,
+                previous_oid,
+                entry_mode,
+                oid,
+                path: location,
+            } => (
+                visit::Change::Modification {
+                    previous_entry_mode,
+                    previous_oid,
+                    entry_mode,
+                    oid,
+                },
+                location,
+            ),
+        };
+        tracker.try_push_change(change, location.as_ref());
+    }
+
+    let mut source = None;
+    tracker.emit(
+        |destination, rewrite_source| match rewrite_source {
+            Some(rewrite_source) if destination.location == path => {
+                source = Some((
+                    rewrite_source.location.to_owned(),
+                    rewrite_source.id,
+                    rewrite_source.entry_mode.kind(),
+                ));
+                visit::Action::Cancel
+            }
+            _ => visit::Action::Continue,
+        },
+        resource_cache,
+        odb,
+        |_push| Ok::<_, std::convert::Infallible>(()),
+    )?;
+    Ok(source)
+}
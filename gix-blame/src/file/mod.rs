@@ -0,0 +1,143 @@
+use std::ops::Range;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The error returned by [`file()`](crate::file()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The commit traversal didn't yield a commit to start blaming at")]
+    EmptyTraversal,
+    #[error(transparent)]
+    Traverse(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("The file to blame at '{file_path}' doesn't exist in commit {commit_id}")]
+    FileMissing { file_path: BString, commit_id: ObjectId },
+    #[error("The range {}..{} is invalid for a file with {num_lines} lines", range.start, range.end)]
+    InvalidRange { range: Range<u32>, num_lines: u32 },
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    FindTree(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    DiffTree(#[from] gix_diff::tree::changes::Error),
+    #[error("Could not find the source of a rename")]
+    Rewrites(#[from] gix_diff::rewrites::tracker::emit::Error),
+    #[error(transparent)]
+    SetResource(#[from] gix_diff::blob::platform::set_resource::Error),
+    #[error(transparent)]
+    PrepareDiff(#[from] gix_diff::blob::platform::prepare_diff::Error),
+}
+
+pub(crate) mod function;
+
+/// A range of lines of the blamed file that isn't attributed to a commit yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UnblamedHunk {
+    /// The lines in the blamed file.
+    range_in_blamed_file: Range<u32>,
+    /// The index of the line corresponding to the first line of `range_in_blamed_file` in the version of the suspect.
+    start_in_suspect: u32,
+}
+
+impl UnblamedHunk {
+    fn range_in_suspect(&self) -> Range<u32> {
+        self.start_in_suspect..self.start_in_suspect + self.range_in_blamed_file.len() as u32
+    }
+
+    /// Return the part of this hunk whose lines in the suspect are in `range`, which must be contained in
+    /// [`range_in_suspect()`](Self::range_in_suspect()).
+    fn slice(&self, range: Range<u32>) -> Self {
+        let offset = range.start - self.start_in_suspect;
+        let start = self.range_in_blamed_file.start + offset;
+        UnblamedHunk {
+            range_in_blamed_file: start..start + range.len() as u32,
+            start_in_suspect: range.start,
+        }
+    }
+}
+
+/// A version of the file in a commit, with lines that may have been introduced by it.
+#[derive(Debug, Clone)]
+struct Suspect {
+    /// The path of the file in the commit.
+    path: BString,
+    /// The id of the file's blob.
+    id: ObjectId,
+    /// The kind of the file's entry.
+    kind: gix_object::tree::EntryKind,
+    /// The lines of the blamed file that are yet to be attributed to this commit or one of its ancestors.
+    hunks: Vec<UnblamedHunk>,
+}
+
+/// A range of lines in the suspect that map to lines in a parent, starting at `start_in_parent`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mapping {
+    range_in_suspect: Range<u32>,
+    start_in_parent: u32,
+}
+
+/// Split `hunks` into the parts whose lines are covered by `mappings`, translated to the lines of the parent, and the remaining parts.
+/// `mappings` must be ordered by their range and not overlap.
+fn split_hunks(hunks: Vec<UnblamedHunk>, mappings: &[Mapping]) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    let mut passed = Vec::new();
+    let mut remaining = Vec::new();
+    for hunk in hunks {
+        let range = hunk.range_in_suspect();
+        let mut cursor = range.start;
+        for mapping in mappings {
+            let start = range.start.max(mapping.range_in_suspect.start);
+            let end = range.end.min(mapping.range_in_suspect.end);
+            if start >= end {
+                continue;
+            }
+            if start > cursor {
+                remaining.push(hunk.slice(cursor..start));
+            }
+            let mut part = hunk.slice(start..end);
+            part.start_in_suspect = mapping.start_in_parent + (start - mapping.range_in_suspect.start);
+            passed.push(part);
+            cursor = end;
+        }
+        if cursor < range.end {
+            remaining.push(hunk.slice(cursor..range.end));
+        }
+    }
+    (passed, remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_hunks, Mapping, UnblamedHunk};
+
+    fn hunk(range_in_blamed_file: std::ops::Range<u32>, start_in_suspect: u32) -> UnblamedHunk {
+        UnblamedHunk {
+            range_in_blamed_file,
+            start_in_suspect,
+        }
+    }
+
+    #[test]
+    fn split_hunks_passes_mapped_lines_and_keeps_the_rest() {
+        let mappings = [
+            Mapping {
+                range_in_suspect: 0..2,
+                start_in_parent: 0,
+            },
+            Mapping {
+                range_in_suspect: 4..6,
+                start_in_parent: 3,
+            },
+        ];
+        let (passed, remaining) = split_hunks(vec![hunk(10..17, 0)], &mappings);
+        assert_eq!(passed, [hunk(10..12, 0), hunk(14..16, 3)]);
+        assert_eq!(remaining, [hunk(12..14, 2), hunk(16..17, 6)]);
+    }
+
+    #[test]
+    fn split_hunks_without_mappings_keeps_everything() {
+        let (passed, remaining) = split_hunks(vec![hunk(0..3, 5)], &[]);
+        assert!(passed.is_empty());
+        assert_eq!(remaining, [hunk(0..3, 5)]);
+    }
+}
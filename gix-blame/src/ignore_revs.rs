@@ -0,0 +1,33 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+/// The error returned by [`parse()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number} doesn't contain a full hexadecimal object id: '{line}'")]
+    InvalidObjectId {
+        line_number: usize,
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+}
+
+/// Parse `input` as the content of a file passed to `git blame --ignore-revs-file`, which contains one full hexadecimal object id
+/// per line. Empty lines and everything following `#` are ignored.
+pub fn parse(input: &[u8]) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+    let mut out = gix_hashtable::HashSet::default();
+    for (line_number, line) in input.lines().enumerate() {
+        let hex = line.find_byte(b'#').map_or(line, |pos| &line[..pos]).trim();
+        if hex.is_empty() {
+            continue;
+        }
+        let id = ObjectId::from_hex(hex).map_err(|source| Error::InvalidObjectId {
+            line_number: line_number + 1,
+            line: line.into(),
+            source,
+        })?;
+        out.insert(id);
+    }
+    Ok(out)
+}
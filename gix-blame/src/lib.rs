@@ -0,0 +1,110 @@
+//! Attribute each line of a file to the commit that introduced it, like `git blame` does.
+//!
+//! Blaming starts at the first commit of a commit traversal, where all lines of the file are unblamed. For each commit, its version
+//! of the file is diffed against the version in each of its parents, and all lines that remained unchanged are passed on to the
+//! parent. All lines that are left are the ones the commit introduced, and they are blamed on it. Renames of the file are followed
+//! by looking for the source of a rename whenever the file doesn't exist in a parent.
+//!
+//! ## Deviation
+//!
+//! * Lines of ignored revisions are passed to the parent line at the same offset within the changed region, whereas `git` tries
+//!   to find the most similar line nearby. Lines that can't be passed that way are blamed on the ignored revision.
+//! * Lines moved or copied within a file or from other files, as detected by `git blame -M` and `-C`, aren't tracked.
+#![deny(rust_2018_idioms, missing_docs)]
+#![forbid(unsafe_code)]
+
+use std::{num::NonZeroU32, ops::Range};
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+///
+pub mod file;
+pub use file::function::file;
+
+///
+pub mod ignore_revs;
+
+/// Options for use in [`file()`].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// The zero-based range of lines to blame, or `None` to blame all lines of the file.
+    ///
+    /// This is similar to `git blame -L`, but unlike the latter the first line is `0` and the end of the range is exclusive.
+    pub range: Option<Range<u32>>,
+    /// If `true`, lines that only differ in whitespace are considered equal, like `git blame -w`.
+    pub ignore_whitespace: bool,
+    /// The commits whose changes should be ignored, like `git blame --ignore-rev`.
+    ///
+    /// Lines changed by these commits are attributed to the commit that changed them before, if possible.
+    /// Use [`ignore_revs::parse()`] to read these from a file as passed to `git blame --ignore-revs-file`.
+    pub ignored_revs: gix_hashtable::HashSet<ObjectId>,
+    /// The way to find the source of a rename of the file, or `None` to not follow renames.
+    ///
+    /// Note that `git blame` always follows renames.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// A range of consecutive lines in the blamed file that were introduced by the same commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameEntry {
+    /// The zero-based index of the first line in the blamed file.
+    pub start_in_blamed_file: u32,
+    /// The zero-based index of the first line in the version of the file as it was introduced in `commit_id`.
+    pub start_in_source_file: u32,
+    /// The amount of lines in this entry.
+    pub len: NonZeroU32,
+    /// The commit that introduced the lines.
+    pub commit_id: ObjectId,
+    /// The path of the file in `commit_id`, which differs from the path of the blamed file if it was renamed since.
+    pub source_path: BString,
+}
+
+impl BlameEntry {
+    /// Return the range of lines of this entry in the blamed file.
+    pub fn range_in_blamed_file(&self) -> Range<u32> {
+        self.start_in_blamed_file..self.start_in_blamed_file + self.len.get()
+    }
+
+    /// Return the range of lines of this entry in the version of the file as it was introduced in `commit_id`.
+    pub fn range_in_source_file(&self) -> Range<u32> {
+        self.start_in_source_file..self.start_in_source_file + self.len.get()
+    }
+}
+
+/// Information about the work performed by [`file()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Statistics {
+    /// The amount of commits obtained from the traversal.
+    pub commits_traversed: usize,
+    /// The amount of trees that were diffed to find the source of renames.
+    pub trees_diffed: usize,
+    /// The amount of versions of the file that were diffed.
+    pub blobs_diffed: usize,
+}
+
+/// The result of [`file()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// One entry for each range of consecutive lines introduced by the same commit, ordered by their position in the blamed file.
+    pub entries: Vec<BlameEntry>,
+    /// The content of the blamed file, as seen by the diff.
+    pub blob: Vec<u8>,
+    /// Information about the work performed.
+    pub statistics: Statistics,
+}
+
+impl Outcome {
+    /// Return an iterator over all entries along with their lines, which include the line terminator.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&BlameEntry, Vec<&bstr::BStr>)> {
+        use gix_diff::blob::intern::TokenSource;
+        let lines: Vec<&bstr::BStr> = gix_diff::blob::sources::byte_lines_with_terminator(&self.blob)
+            .tokenize()
+            .map(Into::into)
+            .collect();
+        self.entries.iter().map(move |entry| {
+            let range = entry.range_in_blamed_file();
+            (entry, lines[range.start as usize..range.end as usize].to_vec())
+        })
+    }
+}
@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn fixture() -> Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")
+}
+
+fn read_id(root: &Path, name: &str) -> Result<ObjectId> {
+    Ok(ObjectId::from_hex(std::fs::read(root.join(name))?.trim())?)
+}
+
+fn resource_cache(root: &Path) -> gix_diff::blob::Platform {
+    let attributes = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let filter = gix_diff::blob::Pipeline::new(
+        Default::default(),
+        gix_filter::Pipeline::default(),
+        Vec::new(),
+        gix_diff::blob::pipeline::Options {
+            large_file_threshold_bytes: 0,
+            fs: gix_fs::Capabilities::probe(root),
+        },
+    );
+    gix_diff::blob::Platform::new(
+        Default::default(),
+        filter,
+        gix_diff::blob::pipeline::Mode::ToGit,
+        attributes,
+    )
+}
+
+/// Blame `path` at the commit whose id is stored in the file named `tip`.
+fn blame(tip: &str, path: &str, options: gix_blame::Options) -> Result<gix_blame::Outcome> {
+    let root = fixture()?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let traverse = gix_traverse::commit::Ancestors::new(
+        Some(read_id(&root, tip)?),
+        gix_traverse::commit::ancestors::State::default(),
+        &odb,
+    )
+    .sorting(gix_traverse::commit::Sorting::TopoOrder)?;
+    Ok(gix_blame::file(
+        &odb,
+        traverse,
+        &mut resource_cache(&root),
+        path.into(),
+        options,
+    )?)
+}
+
+fn follow_renames() -> gix_blame::Options {
+    gix_blame::Options {
+        rewrites: Some(Default::default()),
+        ..Default::default()
+    }
+}
+
+/// A line of a blamed file along with the commit it was attributed to, the zero-based index of the line in the version of the
+/// file in that commit, and the path of that file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlamedLine {
+    commit_id: ObjectId,
+    line_in_source: u32,
+    source_path: BString,
+}
+
+/// Parse the output of `git blame --porcelain` into one item per line.
+fn baseline(name: &str) -> Result<Vec<BlamedLine>> {
+    let data = std::fs::read(fixture()?.join(name))?;
+    let mut out = Vec::<BlamedLine>::new();
+    let mut last_path_by_commit = std::collections::HashMap::<ObjectId, BString>::new();
+    for line in data.lines() {
+        if line.starts_with(b"\t") {
+            continue;
+        }
+        if let Some(path) = line.strip_prefix(b"filename ") {
+            let last = out.last_mut().expect("filename follows a header");
+            last.source_path = path.into();
+            last_path_by_commit.insert(last.commit_id, path.into());
+            continue;
+        }
+        let mut tokens = line.split_str(" ");
+        let Some(commit_id) = tokens.next().and_then(|hex| ObjectId::from_hex(hex).ok()) else {
+            continue;
+        };
+        let line_in_source: u32 = tokens.next().expect("source line").to_str()?.parse()?;
+        out.push(BlamedLine {
+            commit_id,
+            line_in_source: line_in_source - 1,
+            source_path: last_path_by_commit.get(&commit_id).cloned().unwrap_or_default(),
+        });
+    }
+    Ok(out)
+}
+
+fn lines(outcome: &gix_blame::Outcome) -> Vec<BlamedLine> {
+    outcome
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            entry.range_in_source_file().map(move |line_in_source| BlamedLine {
+                commit_id: entry.commit_id,
+                line_in_source,
+                source_path: entry.source_path.clone(),
+            })
+        })
+        .collect()
+}
+
+mod file {
+    use gix_blame::Options;
+
+    use crate::{baseline, blame, follow_renames, lines};
+
+    #[test]
+    fn merges_pass_lines_to_the_parent_they_came_from() -> crate::Result {
+        let outcome = blame("before-rename-id", "simple.txt", Options::default())?;
+        assert_eq!(lines(&outcome), baseline("simple.baseline")?);
+        assert_eq!(
+            outcome
+                .entries
+                .iter()
+                .map(|e| e.start_in_blamed_file)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5],
+            "entries are ordered and only consecutive lines of the same commit are merged"
+        );
+        assert_eq!(outcome.statistics.trees_diffed, 0, "renames aren't followed");
+        Ok(())
+    }
+
+    #[test]
+    fn renames_are_followed() -> crate::Result {
+        let outcome = blame("head-id", "renamed.txt", follow_renames())?;
+        assert_eq!(lines(&outcome), baseline("renamed.baseline")?);
+        assert!(outcome.statistics.trees_diffed > 0);
+        assert_eq!(
+            outcome.entries_with_lines().last().map(|(_, lines)| lines),
+            Some(vec!["line 7\n".into()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn without_following_renames_the_commit_adding_the_file_is_blamed() -> crate::Result {
+        let outcome = blame("head-id", "renamed.txt", Options::default())?;
+        let expected = baseline("renamed.baseline")?;
+        let actual = lines(&outcome);
+        assert_eq!(actual.len(), expected.len());
+        assert!(actual
+            .iter()
+            .take(expected.len() - 1)
+            .all(|line| line.commit_id == expected[5].commit_id && line.source_path == "renamed.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn line_range() -> crate::Result {
+        let outcome = blame(
+            "head-id",
+            "renamed.txt",
+            Options {
+                range: Some(1..4),
+                ..follow_renames()
+            },
+        )?;
+        assert_eq!(lines(&outcome), baseline("range.baseline")?);
+        assert_eq!(outcome.entries[0].start_in_blamed_file, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_line_range() -> crate::Result {
+        let err = blame(
+            "head-id",
+            "renamed.txt",
+            Options {
+                range: Some(5..8),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "The range 5..8 is invalid for a file with 7 lines");
+        Ok(())
+    }
+
+    #[test]
+    fn whitespace_changes() -> crate::Result {
+        let outcome = blame("head-id", "whitespace.txt", Options::default())?;
+        assert_eq!(lines(&outcome), baseline("whitespace.baseline")?);
+
+        let outcome = blame(
+            "head-id",
+            "whitespace.txt",
+            Options {
+                ignore_whitespace: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(lines(&outcome), baseline("whitespace-ignored.baseline")?);
+        Ok(())
+    }
+
+    #[test]
+    fn ignored_revisions() -> crate::Result {
+        let root = crate::fixture()?;
+        let ignored_revs = gix_blame::ignore_revs::parse(&std::fs::read(root.join(".git-blame-ignore-revs"))?)?;
+        assert_eq!(ignored_revs.len(), 1);
+        let outcome = blame(
+            "head-id",
+            "ignore.txt",
+            Options {
+                ignored_revs,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(lines(&outcome), baseline("ignore-revs.baseline")?);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file() -> crate::Result {
+        let err = blame("before-rename-id", "renamed.txt", Options::default()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("The file to blame at 'renamed.txt' doesn't exist in commit"));
+        Ok(())
+    }
+}
+
+mod ignore_revs {
+    #[test]
+    fn comments_and_empty_lines_are_skipped() -> crate::Result {
+        let ids = gix_blame::ignore_revs::parse(
+            b"# a comment\n\n0c65fffd74c1280aa5818ed04b9514afe1c5d218 # trailing comment\n  019460cfaf6eb51a25c2af057e297afd2e2a9bf4\n",
+        )?;
+        assert_eq!(ids.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn abbreviated_ids_are_rejected() {
+        let err = gix_blame::ignore_revs::parse(b"\n0c65fff\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 2 doesn't contain a full hexadecimal object id: '0c65fff'"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config merge.ff false
+
+function tick () {
+  if test -z "${tick+set}"
+  then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function commit() {
+  local message=${1:?first argument is the commit message}
+  tick
+  git add -A
+  git commit -q -m "$message"
+}
+
+git checkout -q -b main
+printf 'line 1\nline 2\nline 3\nline 4\nline 5\n' > simple.txt
+commit "c1"
+printf 'line 1\nline 2\nline 3 changed\nline 4\nline 5\nline 6\n' > simple.txt
+commit "c2"
+printf 'line 1\nline 1.5\nline 2\nline 3 changed\nline 4\nline 5\nline 6\n' > simple.txt
+commit "c3"
+
+git checkout -q -b side
+printf 'line 1\nline 1.5\nline 2\nline 3 changed\nline 4\nline 5 on side\nline 6\n' > simple.txt
+commit "s1"
+
+git checkout -q main
+printf 'line 1 on main\nline 1.5\nline 2\nline 3 changed\nline 4\nline 5\nline 6\n' > simple.txt
+commit "c4"
+tick
+git merge -q side -m "m1"
+
+printf 'line 1 on main\nline 1.5\nline 3 changed\nline 4\nline 5 on side\nline 6\n' > simple.txt
+commit "c5"
+git tag before-rename
+
+git mv simple.txt renamed.txt
+printf 'line 1 on main\nline 1.5\nline 3 changed\nline 4\nline 5 on side\nline 6 renamed\n' > renamed.txt
+commit "c6 rename"
+printf 'line 1 on main\nline 1.5\nline 3 changed\nline 4\nline 5 on side\nline 6 renamed\nline 7\n' > renamed.txt
+commit "c7"
+
+printf 'fn main() {\nlet a = 1;\nlet b = 2;\nprintln!("{a}");\n}\n' > whitespace.txt
+commit "w1"
+printf 'fn main() {\n    let a = 1;\n    let b = 2;\n    println!("{b}");\n}\n' > whitespace.txt
+commit "w2 indent"
+
+printf 'first line\nsecond line\nthird line\nfourth line\n' > ignore.txt
+commit "i1"
+printf 'first line\nsecond  line\nthird line\nfourth  line\n' > ignore.txt
+commit "i2 reformat"
+printf 'first line changed\nsecond  line\nthird line\nfourth  line\n' > ignore.txt
+commit "i3"
+{ echo "# reformatting"; git rev-parse HEAD~1; echo; } > .git-blame-ignore-revs
+git rev-parse HEAD > head-id
+git rev-parse before-rename > before-rename-id
+
+git blame --porcelain simple.txt before-rename > simple.baseline
+git blame --porcelain renamed.txt > renamed.baseline
+git blame --porcelain -L 2,4 renamed.txt > range.baseline
+git blame --porcelain whitespace.txt > whitespace.baseline
+git blame --porcelain -w whitespace.txt > whitespace-ignored.baseline
+git blame --porcelain --ignore-revs-file .git-blame-ignore-revs ignore.txt > ignore-revs.baseline
@@ -0,0 +1,300 @@
+use crate::{Header, Version};
+
+/// The error returned by [`create()`](crate::create()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Refusing to create a bundle without references")]
+    NoRefs,
+    #[error("Version {version:?} bundles can't hold objects of kind {object_hash}")]
+    UnsupportedVersion {
+        version: Version,
+        object_hash: gix_hash::Kind,
+    },
+    #[error(transparent)]
+    Find(#[from] gix_object::find::existing::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::commit::ancestors::Error),
+    #[error("Could not write the bundle")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Options for use in [`create()`](crate::create()).
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// The version of the bundle to write, or `None` to use the lowest version that can represent it.
+    pub version: Option<Version>,
+    /// The kind of hash used by the objects to put into the bundle.
+    pub object_hash: gix_hash::Kind,
+    /// The amount of threads to use when producing the pack, or `None` to use as many threads as there are cores.
+    pub thread_limit: Option<usize>,
+}
+
+/// The result of [`create()`](crate::create()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The header that was written.
+    pub header: Header,
+    /// The amount of objects in the pack that followed the header.
+    pub num_objects: u32,
+}
+
+pub(crate) mod function {
+    use std::{
+        io::Write,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use gix_features::progress::DynNestedProgress;
+    use gix_hash::ObjectId;
+    use gix_hashtable::HashSet;
+    use gix_object::{CommitRefIter, FindExt, Kind, TagRefIter, TreeRefIter};
+    use gix_pack::data::output;
+    use gix_traverse::commit::{ancestors, Ancestors, Sorting};
+
+    use super::{Error, Options, Outcome};
+    use crate::{Header, Prerequisite, Ref, Version};
+
+    /// Write a bundle with `refs` to `out`, along with all objects in `objects` reachable from them but not from the
+    /// commits in `basis`, which the receiver is expected to have.
+    ///
+    /// `basis` are commits or tags pointing to commits, like `main` in `main..feature`, and the commits at the boundary
+    /// between what's in the bundle and what is reachable from them become the [prerequisites](Header::prerequisites).
+    /// `progress` is used while producing the pack, and the operation stops with an error if `should_interrupt` is set.
+    ///
+    /// Note that `objects` must be configured to keep pack locations stable, as with `gix_odb::Handle::prevent_pack_unload()`.
+    pub fn create<O>(
+        refs: Vec<Ref>,
+        basis: &[ObjectId],
+        objects: O,
+        out: impl Write,
+        progress: Box<dyn DynNestedProgress + 'static>,
+        should_interrupt: &AtomicBool,
+        Options {
+            version,
+            object_hash,
+            thread_limit,
+        }: Options,
+    ) -> Result<Outcome, Error>
+    where
+        O: gix_object::Find + gix_pack::Find + Send + Clone + 'static,
+    {
+        if refs.is_empty() {
+            return Err(Error::NoRefs);
+        }
+        let version = match version {
+            Some(version) => version,
+            None if object_hash == gix_hash::Kind::Sha1 => Version::V2,
+            None => Version::V3,
+        };
+        if version == Version::V2 && object_hash != gix_hash::Kind::Sha1 {
+            return Err(Error::UnsupportedVersion { version, object_hash });
+        }
+
+        let mut walk = Walk {
+            objects: &objects,
+            buf: Vec::new(),
+            should_interrupt,
+        };
+        let (ids, prerequisites) = walk.objects_to_write(&refs, basis)?;
+        let header = Header {
+            version,
+            object_hash,
+            capabilities: Vec::new(),
+            prerequisites,
+            refs,
+        };
+
+        let mut out = std::io::BufWriter::new(out);
+        header.write_to(&mut out)?;
+        let num_objects = ids.len() as u32;
+        let counts = ids
+            .into_iter()
+            .map(|id| output::Count {
+                id,
+                entry_pack_location: output::count::PackLocation::NotLookedUp,
+            })
+            .collect();
+        let entries = output::entry::iter_from_counts(
+            counts,
+            objects,
+            progress,
+            output::entry::iter_from_counts::Options {
+                thread_limit,
+                allow_thin_pack: false,
+                ..Default::default()
+            },
+        );
+        for written in output::bytes::FromEntriesIter::new(
+            gix_features::parallel::InOrderIter::from(entries),
+            &mut out,
+            num_objects,
+            gix_pack::data::Version::V2,
+            object_hash,
+        ) {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            written?;
+        }
+        out.flush()?;
+        Ok(Outcome { header, num_objects })
+    }
+
+    struct Walk<'a, O> {
+        objects: &'a O,
+        buf: Vec<u8>,
+        should_interrupt: &'a AtomicBool,
+    }
+
+    impl<'a, O> Walk<'a, O>
+    where
+        O: gix_object::Find,
+    {
+        /// Return the ids of all objects to write, commits first, along with the prerequisites.
+        fn objects_to_write(
+            &mut self,
+            refs: &[Ref],
+            basis: &[ObjectId],
+        ) -> Result<(Vec<ObjectId>, Vec<Prerequisite>), Error> {
+            let mut tags = Vec::new();
+            let mut want_commits = Vec::new();
+            let mut want_others = Vec::new();
+            for tip in refs {
+                let (kind, id) = self.peel(tip.id, &mut tags)?;
+                match kind {
+                    Kind::Commit => want_commits.push(id),
+                    _ => want_others.push(id),
+                }
+            }
+            let mut basis_commits = Vec::new();
+            for id in basis {
+                let (kind, id) = self.peel(*id, &mut Vec::new())?;
+                if kind == Kind::Commit {
+                    basis_commits.push(id);
+                }
+            }
+
+            let (commits, boundary) = self.limit_commits(&want_commits, &basis_commits)?;
+
+            let mut prerequisites = Vec::with_capacity(boundary.len());
+            let mut excluded = HashSet::default();
+            for id in boundary {
+                let data = self.objects.find(&id, &mut self.buf)?;
+                let (comment, tree) = {
+                    let commit = gix_object::CommitRef::from_bytes(data.data)?;
+                    (commit.message_summary().into_owned(), commit.tree())
+                };
+                prerequisites.push(Prerequisite { id, comment });
+                self.add_tree(tree, &mut excluded, &mut Vec::new())?;
+            }
+
+            let mut out = commits;
+            let mut trees_and_blobs = Vec::new();
+            for id in out.clone() {
+                let tree = self.commit(&id)?.tree_id()?;
+                self.add_tree(tree, &mut excluded, &mut trees_and_blobs)?;
+            }
+            for id in want_others {
+                if self.objects.find(&id, &mut self.buf)?.kind == Kind::Tree {
+                    self.add_tree(id, &mut excluded, &mut trees_and_blobs)?;
+                } else if excluded.insert(id) {
+                    trees_and_blobs.push(id);
+                }
+            }
+            out.append(&mut trees_and_blobs);
+            for tag in tags {
+                if excluded.insert(tag) {
+                    out.push(tag);
+                }
+            }
+            Ok((out, prerequisites))
+        }
+
+        /// Follow `id` through all tags, which are added to `tags`, and return the kind and id of the object at the end.
+        fn peel(&mut self, mut id: ObjectId, tags: &mut Vec<ObjectId>) -> Result<(Kind, ObjectId), Error> {
+            loop {
+                let data = self.objects.find(&id, &mut self.buf)?;
+                if data.kind != Kind::Tag {
+                    return Ok((data.kind, id));
+                }
+                tags.push(id);
+                id = TagRefIter::from_bytes(data.data).target_id()?;
+            }
+        }
+
+        fn commit(&mut self, id: &ObjectId) -> Result<CommitRefIter<'_>, Error> {
+            if self.should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            Ok(CommitRefIter::from_bytes(self.objects.find(id, &mut self.buf)?.data))
+        }
+
+        /// Return the commits reachable from `wants` but not from `basis`, from newest to oldest, along with the
+        /// commits reachable from `basis` which are `wants` or parents of these.
+        ///
+        /// Like `git rev-list --boundary`, the walk stops once only commits reachable from `basis` are left instead of
+        /// traversing all of their history.
+        fn limit_commits(
+            &mut self,
+            wants: &[ObjectId],
+            basis: &[ObjectId],
+        ) -> Result<(Vec<ObjectId>, Vec<ObjectId>), Error> {
+            let mut commits = Vec::new();
+            for info in Ancestors::new(wants.iter().copied(), ancestors::State::default(), self.objects)
+                .sorting(Sorting::ByCommitTimeNewestFirst)?
+                .hide(basis.iter().copied())
+            {
+                if self.should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                commits.push(info?);
+            }
+
+            let written: HashSet<_> = commits.iter().map(|info| info.id).collect();
+            let mut boundary = Vec::new();
+            let mut seen = HashSet::default();
+            let candidates = wants
+                .iter()
+                .chain(commits.iter().flat_map(|info| info.parent_ids.iter()));
+            for id in candidates {
+                if !written.contains(id) && seen.insert(*id) {
+                    boundary.push(*id);
+                }
+            }
+            Ok((commits.into_iter().map(|info| info.id).collect(), boundary))
+        }
+
+        /// Add the tree with `id` and everything it contains to `out` unless it's in `seen`, which is updated as well.
+        fn add_tree(
+            &mut self,
+            id: ObjectId,
+            seen: &mut HashSet<ObjectId>,
+            out: &mut Vec<ObjectId>,
+        ) -> Result<(), Error> {
+            let mut stack = vec![id];
+            while let Some(id) = stack.pop() {
+                if !seen.insert(id) {
+                    continue;
+                }
+                out.push(id);
+                let data = self.objects.find(&id, &mut self.buf)?;
+                for entry in TreeRefIter::from_bytes(data.data) {
+                    let entry = entry?;
+                    if entry.mode.is_tree() {
+                        stack.push(entry.oid.to_owned());
+                    } else if !entry.mode.is_commit() && seen.insert(entry.oid.to_owned()) {
+                        out.push(entry.oid.to_owned());
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::{BufRead, Write};
+
+use bstr::{BString, ByteSlice};
+
+use crate::{Capability, Header, Prerequisite, Ref, Version};
+
+impl Version {
+    /// The first line of bundles of this version, including the trailing newline.
+    pub fn signature(&self) -> &'static [u8] {
+        match self {
+            Version::V2 => b"# v2 git bundle\n",
+            Version::V3 => b"# v3 git bundle\n",
+        }
+    }
+
+    /// Return the version whose [signature](Version::signature()) is `line`, or `None` if `line` doesn't start a bundle.
+    pub fn from_signature(line: &[u8]) -> Option<Self> {
+        [Version::V2, Version::V3]
+            .into_iter()
+            .find(|version| version.signature() == line)
+    }
+}
+
+///
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [`Header::from_read()`](crate::Header::from_read()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read bundle header")]
+        Io(#[from] std::io::Error),
+        #[error("The signature line {line:?} does not belong to a supported bundle version")]
+        Signature { line: BString },
+        #[error("The header ended before the empty line that separates it from the pack")]
+        UnexpectedEof,
+        #[error(
+            "Capabilities like {line:?} are only supported in v3 bundles, or they came after prerequisites or refs"
+        )]
+        UnexpectedCapability { line: BString },
+        #[error("The object format {name:?} is not supported")]
+        UnsupportedObjectFormat { name: BString },
+        #[error("Could not parse header line {line:?}")]
+        InvalidLine { line: BString },
+    }
+}
+
+/// Decoding
+impl Header {
+    /// Read the header of a bundle from `read`, leaving it positioned at the start of the pack that follows it.
+    pub fn from_read(read: &mut impl BufRead) -> Result<Self, decode::Error> {
+        use decode::Error;
+        let mut line = Vec::new();
+        read.read_until(b'\n', &mut line)?;
+        let version = Version::from_signature(&line).ok_or_else(|| Error::Signature {
+            line: line.as_bstr().trim_end_with(|c| c == '\n').into(),
+        })?;
+
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            capabilities: Vec::new(),
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        loop {
+            line.clear();
+            if read.read_until(b'\n', &mut line)? == 0 || line.last() != Some(&b'\n') {
+                return Err(Error::UnexpectedEof);
+            }
+            let line = &line[..line.len() - 1];
+            if line.is_empty() {
+                break;
+            }
+            let invalid = || Error::InvalidLine { line: line.into() };
+            if let Some(capability) = line.strip_prefix(b"@") {
+                if header.version == Version::V2 || !header.prerequisites.is_empty() || !header.refs.is_empty() {
+                    return Err(Error::UnexpectedCapability { line: line.into() });
+                }
+                let (name, value) = match capability.find_byte(b'=') {
+                    Some(pos) => (&capability[..pos], Some(&capability[pos + 1..])),
+                    None => (capability, None),
+                };
+                if name == b"object-format" {
+                    let value = value.ok_or_else(invalid)?;
+                    header.object_hash = match value {
+                        b"sha1" => gix_hash::Kind::Sha1,
+                        _ => return Err(Error::UnsupportedObjectFormat { name: value.into() }),
+                    };
+                } else {
+                    header.capabilities.push(Capability {
+                        name: name.into(),
+                        value: value.map(Into::into),
+                    });
+                }
+            } else if let Some(prerequisite) = line.strip_prefix(b"-") {
+                let (id, comment) = split_id(prerequisite, header.object_hash).ok_or_else(invalid)?;
+                header.prerequisites.push(Prerequisite {
+                    id,
+                    comment: comment.unwrap_or_default().into(),
+                });
+            } else {
+                let (id, name) = split_id(line, header.object_hash).ok_or_else(invalid)?;
+                header.refs.push(Ref {
+                    id,
+                    name: name.filter(|name| !name.is_empty()).ok_or_else(invalid)?.into(),
+                });
+            }
+        }
+        Ok(header)
+    }
+}
+
+/// Parse an object id of kind `object_hash` at the start of `line`, and return it with everything after the space that follows it.
+fn split_id(line: &[u8], object_hash: gix_hash::Kind) -> Option<(gix_hash::ObjectId, Option<&[u8]>)> {
+    let hex_len = object_hash.len_in_hex();
+    let id = gix_hash::ObjectId::from_hex(line.get(..hex_len)?).ok()?;
+    let rest = match &line[hex_len..] {
+        [] => None,
+        [b' ', rest @ ..] => Some(rest),
+        _ => return None,
+    };
+    Some((id, rest))
+}
+
+/// Encoding
+impl Header {
+    /// Write this header to `out`, including the empty line after which the pack is expected.
+    ///
+    /// Note that [capabilities](Header::capabilities) and the object format are only written for [`Version::V3`].
+    pub fn write_to(&self, mut out: impl Write) -> std::io::Result<()> {
+        out.write_all(self.version.signature())?;
+        if self.version == Version::V3 {
+            writeln!(
+                out,
+                "@object-format={}",
+                self.object_hash.to_string().to_ascii_lowercase()
+            )?;
+            for Capability { name, value } in &self.capabilities {
+                out.write_all(b"@")?;
+                out.write_all(name)?;
+                if let Some(value) = value {
+                    out.write_all(b"=")?;
+                    out.write_all(value)?;
+                }
+                out.write_all(b"\n")?;
+            }
+        }
+        for Prerequisite { id, comment } in &self.prerequisites {
+            write!(out, "-{id}")?;
+            if !comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for Ref { id, name } in &self.refs {
+            write!(out, "{id} ")?;
+            out.write_all(name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")
+    }
+
+    /// Return the header as it would be written by [`write_to()`](Header::write_to()).
+    pub fn to_bstring(&self) -> BString {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf).expect("writing to a vector never fails");
+        buf.into()
+    }
+}
@@ -0,0 +1,93 @@
+//! Read, write and verify git bundles, which are files holding a pack along with the references it provides.
+//!
+//! A bundle starts with a header that names the commits the receiver must already have, the *prerequisites*,
+//! and the references available in the bundle, followed by a pack with all objects needed to get from the prerequisites to
+//! the references.
+//!
+//! ## Deviation
+//!
+//! Bundles created here always contain complete packs, whereas `git` creates thin packs whose deltas may refer to
+//! objects reachable from the prerequisites.
+//!
+//! ## Feature Flags
+#![cfg_attr(
+    all(doc, feature = "document-features"),
+    doc = ::document_features::document_features!()
+)]
+#![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
+#![deny(rust_2018_idioms, missing_docs)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+///
+pub mod header;
+
+///
+pub mod list;
+pub use list::List;
+
+///
+#[cfg(feature = "pack")]
+pub mod create;
+#[cfg(feature = "pack")]
+pub use create::function::create;
+
+///
+#[cfg(feature = "pack")]
+pub mod verify;
+#[cfg(feature = "pack")]
+pub use verify::function::verify;
+
+/// The version of the bundle format.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Version {
+    /// The original format, which only supports SHA1 and has no capabilities.
+    #[default]
+    V2,
+    /// The format that adds capabilities, which are needed for other hash kinds than SHA1 or for filtered packs.
+    V3,
+}
+
+/// The header of a bundle, which is followed by a pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The version of the bundle.
+    pub version: Version,
+    /// The kind of hash used for all object ids, as set by the `object-format` capability.
+    pub object_hash: gix_hash::Kind,
+    /// All capabilities other than `object-format`, like `filter=blob:none`, which are only supported by [`Version::V3`].
+    pub capabilities: Vec<Capability>,
+    /// The commits the receiver must have to be able to use the bundle.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references the bundle provides.
+    pub refs: Vec<Ref>,
+}
+
+/// A capability of a bundle, like `filter=blob:none`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    /// The name of the capability, like `filter`.
+    pub name: BString,
+    /// The value of the capability, if present.
+    pub value: Option<BString>,
+}
+
+/// A commit the receiver of a bundle must already have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prerequisite {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// A comment that is typically the subject of the commit, or empty if there is none.
+    pub comment: BString,
+}
+
+/// A reference provided by a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ref {
+    /// The object the reference points to.
+    pub id: ObjectId,
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+}
@@ -0,0 +1,195 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::list::parse::Error;
+
+/// How the bundles in a [`List`] relate to each other.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// All bundles are needed to obtain the complete data.
+    #[default]
+    All,
+    /// Any bundle can be used on its own, for instance because they are copies of each other on different servers.
+    Any,
+}
+
+/// A heuristic advertised by a [`List`] to help deciding which bundles to download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heuristic {
+    /// Each bundle has a creation token, with newer bundles having larger tokens, and each bundle only depending on bundles
+    /// with smaller tokens.
+    CreationToken,
+}
+
+/// A bundle as listed in a [`List`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The identifier of the bundle, unique within its list.
+    pub id: BString,
+    /// The location of the bundle, which is either absolute or relative to the list, until it was
+    /// [resolved](List::resolve_relative_uris()).
+    pub uri: BString,
+    /// A number used with the [creation token heuristic](Heuristic::CreationToken), or `None` if unset.
+    pub creation_token: Option<u64>,
+}
+
+/// A list of bundles, as advertised by a server with the `bundle-uri` command or read from a file in the `git-config` format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct List {
+    /// The version of the list format, which currently is always `1`.
+    pub version: u8,
+    /// How the bundles relate to each other.
+    pub mode: Mode,
+    /// A hint on which bundles to download, or `None` if unset or not understood.
+    pub heuristic: Option<Heuristic>,
+    /// The bundles in the order in which they were listed.
+    pub entries: Vec<Entry>,
+}
+
+///
+pub mod parse {
+    use bstr::BString;
+
+    /// The error returned by [`List::from_key_value_pairs()`](crate::List::from_key_value_pairs()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The bundle list has no version")]
+        MissingVersion,
+        #[error("The bundle list version {version:?} is not supported")]
+        UnsupportedVersion { version: BString },
+        #[error("The bundle list has no mode")]
+        MissingMode,
+        #[error("The bundle list mode {mode:?} is not supported")]
+        UnsupportedMode { mode: BString },
+        #[error("The creation token {value:?} of bundle {id:?} is not a number")]
+        InvalidCreationToken { id: BString, value: BString },
+        #[error("The bundle {id:?} has no uri")]
+        MissingUri { id: BString },
+    }
+}
+
+/// Initialization
+impl List {
+    /// Parse a list from `pairs` of keys like `bundle.version` or `bundle.<id>.uri` along with their values, as they are sent
+    /// by the `bundle-uri` command or found in bundle list files.
+    ///
+    /// Keys that aren't in the `bundle` section or that aren't known are ignored.
+    pub fn from_key_value_pairs<'a>(pairs: impl IntoIterator<Item = (&'a BStr, &'a BStr)>) -> Result<Self, Error> {
+        let mut version = None;
+        let mut mode = None;
+        let mut heuristic = None;
+        let mut entries = Vec::<Entry>::new();
+        let mut ids_without_uri = Vec::new();
+        for (key, value) in pairs {
+            let Some(key) = key
+                .get(.."bundle.".len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(b"bundle."))
+                .map(|_| &key["bundle.".len()..])
+            else {
+                continue;
+            };
+            match key.rfind_byte(b'.') {
+                None => {
+                    let key = key.to_ascii_lowercase();
+                    match key.as_slice() {
+                        b"version" => version = Some(value),
+                        b"mode" => mode = Some(value),
+                        b"heuristic" => heuristic = (value == "creationToken").then_some(Heuristic::CreationToken),
+                        _ => {}
+                    }
+                }
+                Some(pos) => {
+                    let (id, key) = (key[..pos].as_bstr(), key[pos + 1..].to_ascii_lowercase());
+                    let entry = match entries.iter().position(|e| e.id == id) {
+                        Some(idx) => &mut entries[idx],
+                        None => {
+                            entries.push(Entry {
+                                id: id.to_owned(),
+                                uri: BString::default(),
+                                creation_token: None,
+                            });
+                            ids_without_uri.push(id.to_owned());
+                            entries.last_mut().expect("just pushed")
+                        }
+                    };
+                    match key.as_slice() {
+                        b"uri" => {
+                            entry.uri = value.to_owned();
+                            ids_without_uri.retain(|id| *id != entry.id);
+                        }
+                        b"creationtoken" => {
+                            entry.creation_token =
+                                Some(value.to_str().ok().and_then(|v| v.parse().ok()).ok_or_else(|| {
+                                    Error::InvalidCreationToken {
+                                        id: entry.id.clone(),
+                                        value: value.to_owned(),
+                                    }
+                                })?);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let version = version.ok_or(Error::MissingVersion)?;
+        if version != "1" {
+            return Err(Error::UnsupportedVersion {
+                version: version.to_owned(),
+            });
+        }
+        let mode = match mode.ok_or(Error::MissingMode)? {
+            mode if mode == "all" => Mode::All,
+            mode if mode == "any" => Mode::Any,
+            mode => return Err(Error::UnsupportedMode { mode: mode.to_owned() }),
+        };
+        if let Some(id) = ids_without_uri.into_iter().next() {
+            return Err(Error::MissingUri { id });
+        }
+        Ok(List {
+            version: 1,
+            mode,
+            heuristic,
+            entries,
+        })
+    }
+}
+
+/// Access
+impl List {
+    /// Return all entries in the order in which they should be applied.
+    ///
+    /// With the [creation token heuristic](Heuristic::CreationToken), that's the order of increasing creation tokens,
+    /// so that bundles are applied after the ones they depend on. Otherwise it's the order in which they were listed.
+    pub fn entries_in_application_order(&self) -> Vec<&Entry> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        if self.heuristic == Some(Heuristic::CreationToken) {
+            entries.sort_by_key(|e| e.creation_token.unwrap_or_default());
+        }
+        entries
+    }
+
+    /// Return the largest creation token of all entries, or `None` if there is none.
+    pub fn max_creation_token(&self) -> Option<u64> {
+        self.entries.iter().filter_map(|e| e.creation_token).max()
+    }
+}
+
+/// Mutation
+impl List {
+    /// Turn the uris of all entries that are relative into absolute ones, by interpreting them relative to `list_uri`,
+    /// the location from which this list was obtained.
+    ///
+    /// Uris are considered absolute if they have a scheme like `https://` or are absolute paths.
+    pub fn resolve_relative_uris(&mut self, list_uri: &BStr) {
+        let base = list_uri.rfind_byte(b'/').map_or(&b""[..], |pos| &list_uri[..=pos]);
+        for entry in &mut self.entries {
+            if entry.uri.find(b"://").is_some() || entry.uri.starts_with(b"/") {
+                continue;
+            }
+            let mut uri = BString::from(base);
+            uri.push_str(&entry.uri);
+            entry.uri = uri;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use gix_hash::ObjectId;
+
+/// The error returned by [`verify()`](crate::verify()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The repository lacks {} prerequisite commit(s) of the bundle: {}", ids.len(), ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    MissingPrerequisites { ids: Vec<ObjectId> },
+    #[error("The pack of the bundle is invalid")]
+    Pack(#[from] gix_pack::data::input::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// The result of [`verify()`](crate::verify()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of objects in the pack.
+    pub num_objects: u32,
+    /// The checksum of the pack as found in its trailer, or `None` if the pack had no objects.
+    pub pack_checksum: Option<ObjectId>,
+}
+
+pub(crate) mod function {
+    use std::{
+        io::BufRead,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use gix_pack::data::input;
+
+    use super::{Error, Outcome};
+    use crate::Header;
+
+    /// Check that all [prerequisites](Header::prerequisites) of `header` exist in `objects`, and read the `pack` that
+    /// followed `header` to the end to validate its entries and its checksum.
+    ///
+    /// The operation stops with an error if `should_interrupt` is set.
+    pub fn verify(
+        header: &Header,
+        pack: impl BufRead,
+        objects: &impl gix_object::Exists,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let missing: Vec<_> = header
+            .prerequisites
+            .iter()
+            .filter(|prerequisite| !objects.exists(&prerequisite.id))
+            .map(|prerequisite| prerequisite.id)
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::MissingPrerequisites { ids: missing });
+        }
+
+        let mut num_objects = 0;
+        let mut pack_checksum = None;
+        for entry in input::BytesToEntriesIter::new_from_header(
+            pack,
+            input::Mode::Verify,
+            input::EntryDataMode::Ignore,
+            header.object_hash,
+        )? {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            pack_checksum = entry?.trailer;
+            num_objects += 1;
+        }
+        Ok(Outcome {
+            num_objects,
+            pack_checksum,
+        })
+    }
+}
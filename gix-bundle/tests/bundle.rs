@@ -0,0 +1,443 @@
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn fixture() -> Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_bundles.sh")
+}
+
+fn read_header(path: &Path) -> Result<(gix_bundle::Header, std::io::BufReader<std::fs::File>)> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let header = gix_bundle::Header::from_read(&mut file)?;
+    Ok((header, file))
+}
+
+fn hex_to_id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.trim().as_bytes()).expect("valid hex")
+}
+
+mod header {
+    use bstr::ByteSlice;
+    use gix_bundle::{header::decode, Header, Version};
+
+    use crate::{fixture, hex_to_id, read_header};
+
+    #[test]
+    fn all_refs() -> crate::Result {
+        let (header, _) = read_header(&fixture()?.join("all.bundle"))?;
+        assert_eq!(header.version, Version::V2);
+        assert_eq!(header.object_hash, gix_hash::Kind::Sha1);
+        assert!(header.prerequisites.is_empty());
+        assert!(header.capabilities.is_empty());
+        assert_eq!(
+            header.refs.iter().map(|r| r.name.to_str_lossy()).collect::<Vec<_>>(),
+            ["refs/heads/feature", "refs/heads/main", "refs/tags/v1", "HEAD"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn prerequisites() -> crate::Result {
+        let dir = fixture()?;
+        let (header, _) = read_header(&dir.join("incremental.bundle"))?;
+        assert_eq!(header.prerequisites.len(), 1);
+        assert_eq!(
+            header.prerequisites[0].id,
+            hex_to_id(&std::fs::read_to_string(dir.join("incremental.prerequisite"))?)
+        );
+        assert_eq!(header.prerequisites[0].comment, "commit 1");
+        assert_eq!(header.refs.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn v3() -> crate::Result {
+        let (header, _) = read_header(&fixture()?.join("v3.bundle"))?;
+        assert_eq!(header.version, Version::V3);
+        assert_eq!(header.object_hash, gix_hash::Kind::Sha1);
+        assert!(header.capabilities.is_empty(), "object-format isn't a capability");
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips_exactly() -> crate::Result {
+        for name in ["all.bundle", "incremental.bundle", "v3.bundle"] {
+            let data = std::fs::read(fixture()?.join(name))?;
+            let mut read = data.as_slice();
+            let header = Header::from_read(&mut read)?;
+            assert!(read.starts_with(b"PACK"), "the reader is positioned at the pack");
+            let header_len = data.len() - read.len();
+            assert_eq!(header.to_bstring(), data[..header_len].as_bstr(), "{name}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn capabilities() -> crate::Result {
+        let input = "# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n@flag\n\n";
+        let header = Header::from_read(&mut input.as_bytes())?;
+        assert_eq!(header.capabilities.len(), 2);
+        assert_eq!(header.capabilities[0].name, "filter");
+        assert_eq!(header.capabilities[0].value.as_ref().expect("set"), "blob:none");
+        assert_eq!(header.capabilities[1].name, "flag");
+        assert_eq!(header.capabilities[1].value, None);
+        assert_eq!(header.to_bstring(), input);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for (input, expected) in [
+            ("# v4 git bundle\n\n", "Signature"),
+            ("# v2 git bundle\n@object-format=sha1\n\n", "UnexpectedCapability"),
+            ("# v3 git bundle\n@object-format=sha256\n\n", "UnsupportedObjectFormat"),
+            ("# v2 git bundle\n", "UnexpectedEof"),
+            (
+                "# v2 git bundle\nc7b5fb3e3a4044278698d921c6261cff13342493 refs/heads/main",
+                "UnexpectedEof",
+            ),
+            (
+                "# v2 git bundle\nc7b5fb3e3a4044278698d921c6261cff13342493\n\n",
+                "InvalidLine",
+            ),
+            ("# v2 git bundle\n-c7b5fb3\n\n", "InvalidLine"),
+        ] {
+            let err = Header::from_read(&mut input.as_bytes()).expect_err(input);
+            let actual = match err {
+                decode::Error::Signature { .. } => "Signature",
+                decode::Error::UnexpectedCapability { .. } => "UnexpectedCapability",
+                decode::Error::UnsupportedObjectFormat { .. } => "UnsupportedObjectFormat",
+                decode::Error::UnexpectedEof => "UnexpectedEof",
+                decode::Error::InvalidLine { .. } => "InvalidLine",
+                decode::Error::Io(_) => "Io",
+            };
+            assert_eq!(actual, expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn version_from_signature() {
+        assert_eq!(Version::from_signature(b"# v2 git bundle\n"), Some(Version::V2));
+        assert_eq!(Version::from_signature(b"# v3 git bundle\n"), Some(Version::V3));
+        assert_eq!(Version::from_signature(b"# v3 git bundle"), None);
+    }
+}
+
+mod list {
+    use bstr::{BStr, ByteSlice};
+    use gix_bundle::list::{parse::Error, Entry, Heuristic, Mode};
+    use gix_bundle::List;
+
+    fn parse(input: &str) -> Result<List, Error> {
+        List::from_key_value_pairs(input.lines().map(|line| {
+            let (key, value) = line.trim().split_once('=').expect("key=value");
+            (BStr::new(key), BStr::new(value))
+        }))
+    }
+
+    #[test]
+    fn all_with_creation_token_heuristic() -> crate::Result {
+        let list = parse(
+            "bundle.version=1
+             bundle.mode=all
+             bundle.heuristic=creationToken
+             bundle.new.unknown=ignored
+             bundle.new.uri=https://example.com/new.bundle
+             bundle.new.creationToken=2
+             bundle.old.uri=old.bundle
+             bundle.old.CREATIONTOKEN=1
+             core.bare=true",
+        )?;
+        assert_eq!(list.version, 1);
+        assert_eq!(list.mode, Mode::All);
+        assert_eq!(list.heuristic, Some(Heuristic::CreationToken));
+        assert_eq!(
+            list.entries.iter().map(|e| e.id.to_str_lossy()).collect::<Vec<_>>(),
+            ["new", "old"],
+            "unknown keys and sections are ignored"
+        );
+        assert_eq!(list.entries[1].creation_token, Some(1), "keys are case-insensitive");
+        Ok(())
+    }
+
+    #[test]
+    fn application_order_follows_creation_tokens() -> crate::Result {
+        let list = parse(
+            "bundle.version=1
+             bundle.mode=all
+             bundle.heuristic=creationToken
+             bundle.new.uri=new.bundle
+             bundle.new.creationToken=20
+             bundle.old.uri=old.bundle
+             bundle.old.creationToken=10",
+        )?;
+        assert_eq!(
+            list.entries_in_application_order()
+                .iter()
+                .map(|e| e.id.to_str_lossy())
+                .collect::<Vec<_>>(),
+            ["old", "new"]
+        );
+        assert_eq!(list.max_creation_token(), Some(20));
+
+        let mut list = list;
+        list.heuristic = None;
+        assert_eq!(
+            list.entries_in_application_order()
+                .iter()
+                .map(|e| e.id.to_str_lossy())
+                .collect::<Vec<_>>(),
+            ["new", "old"],
+            "without heuristic, the order of the list is used"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn any_with_unknown_heuristic() -> crate::Result {
+        let list = parse(
+            "bundle.version=1
+             bundle.mode=any
+             bundle.heuristic=something-new
+             bundle.a.uri=/absolute/a.bundle",
+        )?;
+        assert_eq!(list.mode, Mode::Any);
+        assert_eq!(list.heuristic, None, "unknown heuristics are ignored");
+        assert_eq!(
+            list.entries,
+            [Entry {
+                id: "a".into(),
+                uri: "/absolute/a.bundle".into(),
+                creation_token: None
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_relative_uris() -> crate::Result {
+        let mut list = parse(
+            "bundle.version=1
+             bundle.mode=all
+             bundle.relative.uri=sub/relative.bundle
+             bundle.absolute.uri=/absolute.bundle
+             bundle.remote.uri=https://example.com/remote.bundle",
+        )?;
+        list.resolve_relative_uris("file:///path/to/list".into());
+        assert_eq!(
+            list.entries.iter().map(|e| e.uri.to_str_lossy()).collect::<Vec<_>>(),
+            [
+                "file:///path/to/sub/relative.bundle",
+                "/absolute.bundle",
+                "https://example.com/remote.bundle"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(parse("bundle.mode=all"), Err(Error::MissingVersion)));
+        assert!(matches!(
+            parse("bundle.version=2\nbundle.mode=all"),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(parse("bundle.version=1"), Err(Error::MissingMode)));
+        assert!(matches!(
+            parse("bundle.version=1\nbundle.mode=some"),
+            Err(Error::UnsupportedMode { .. })
+        ));
+        assert!(matches!(
+            parse("bundle.version=1\nbundle.mode=all\nbundle.a.creationToken=-1\nbundle.a.uri=a"),
+            Err(Error::InvalidCreationToken { .. })
+        ));
+        assert!(matches!(
+            parse("bundle.version=1\nbundle.mode=all\nbundle.a.creationToken=1"),
+            Err(Error::MissingUri { .. })
+        ));
+    }
+}
+
+mod verify {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_bundle::verify;
+
+    use crate::{fixture, read_header};
+
+    #[test]
+    fn complete_and_incremental_bundles() -> crate::Result {
+        let dir = fixture()?;
+        let odb = gix_odb::at(dir.join("repo/.git/objects"))?;
+        let (header, pack) = read_header(&dir.join("all.bundle"))?;
+        let outcome = gix_bundle::verify(&header, pack, &odb, &AtomicBool::default())?;
+        assert_eq!(outcome.num_objects, 14);
+        assert!(outcome.pack_checksum.is_some());
+
+        let (header, pack) = read_header(&dir.join("incremental.bundle"))?;
+        let outcome = gix_bundle::verify(&header, pack, &odb, &AtomicBool::default())?;
+        assert_eq!(outcome.num_objects, 6, "two commits with their trees and blobs");
+        Ok(())
+    }
+
+    #[test]
+    fn missing_prerequisites() -> crate::Result {
+        let dir = fixture()?;
+        let odb = gix_odb::at(dir.join("empty/.git/objects"))?;
+        let (header, pack) = read_header(&dir.join("incremental.bundle"))?;
+        let err = gix_bundle::verify(&header, pack, &odb, &AtomicBool::default()).unwrap_err();
+        assert!(
+            matches!(err, verify::Error::MissingPrerequisites { ref ids } if ids == &[header.prerequisites[0].id]),
+            "{err:?}"
+        );
+
+        let (header, pack) = read_header(&dir.join("all.bundle"))?;
+        gix_bundle::verify(&header, pack, &odb, &AtomicBool::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_pack() -> crate::Result {
+        let mut data = std::fs::read(fixture()?.join("all.bundle"))?;
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        let mut read = data.as_slice();
+        let header = gix_bundle::Header::from_read(&mut read)?;
+        let err = gix_bundle::verify(&header, read, &gix_object::find::Never, &AtomicBool::default()).unwrap_err();
+        assert!(matches!(err, verify::Error::Pack(_)), "{err:?}");
+        Ok(())
+    }
+}
+
+mod create {
+    use std::sync::atomic::AtomicBool;
+
+    use bstr::BString;
+    use gix_bundle::{create, Ref, Version};
+
+    use crate::{fixture, hex_to_id, read_header};
+
+    fn create_bundle(
+        refs: Vec<Ref>,
+        basis: &[gix_hash::ObjectId],
+        options: create::Options,
+    ) -> crate::Result<(gix_testtools::tempfile::TempDir, create::Outcome)> {
+        create_bundle_in("repo", refs, basis, options)
+    }
+
+    fn create_bundle_in(
+        repo: &str,
+        refs: Vec<Ref>,
+        basis: &[gix_hash::ObjectId],
+        options: create::Options,
+    ) -> crate::Result<(gix_testtools::tempfile::TempDir, create::Outcome)> {
+        let mut odb = gix_odb::at(fixture()?.join(repo).join(".git/objects"))?.into_arc()?;
+        odb.prevent_pack_unload();
+        let tmp = gix_testtools::tempfile::tempdir()?;
+        let outcome = gix_bundle::create(
+            refs,
+            basis,
+            odb,
+            std::fs::File::create(tmp.path().join("new.bundle"))?,
+            Box::new(gix_features::progress::Discard),
+            &AtomicBool::default(),
+            options,
+        )?;
+        Ok((tmp, outcome))
+    }
+
+    #[test]
+    fn complete_bundles_have_the_same_objects_as_the_ones_created_by_git() -> crate::Result {
+        let dir = fixture()?;
+        let (expected, _) = read_header(&dir.join("all.bundle"))?;
+        let (tmp, outcome) = create_bundle(expected.refs.clone(), &[], Default::default())?;
+
+        let bundle = tmp.path().join("new.bundle");
+        let (actual, pack) = read_header(&bundle)?;
+        assert_eq!(actual, expected, "headers are exactly the same");
+        assert_eq!(outcome.header, actual);
+        assert_eq!(outcome.num_objects, 14, "the annotated tag is included");
+        let odb = gix_odb::at(dir.join("empty/.git/objects"))?;
+        let verified = gix_bundle::verify(&actual, pack, &odb, &AtomicBool::default())?;
+        assert_eq!(verified.num_objects, outcome.num_objects);
+
+        git_can_read(&dir.join("repo"), &bundle)
+    }
+
+    #[test]
+    fn incremental_bundles_have_prerequisites_like_the_ones_created_by_git() -> crate::Result {
+        let dir = fixture()?;
+        let (expected, _) = read_header(&dir.join("incremental.bundle"))?;
+        let (tmp, outcome) = create_bundle(
+            expected.refs.clone(),
+            &[expected.prerequisites[0].id],
+            create::Options {
+                version: Some(Version::V3),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(outcome.header.version, Version::V3);
+        assert_eq!(outcome.header.prerequisites, expected.prerequisites);
+        assert_eq!(outcome.header.refs, expected.refs);
+        assert_eq!(outcome.num_objects, 6);
+
+        git_can_read(&dir.join("repo"), &tmp.path().join("new.bundle"))
+    }
+
+    #[test]
+    fn history_of_the_basis_is_not_traversed_in_full() -> crate::Result {
+        let dir = fixture()?;
+        let tip = hex_to_id(&std::fs::read_to_string(dir.join("long-history.tip"))?);
+        let basis = hex_to_id(&std::fs::read_to_string(dir.join("long-history.basis"))?);
+        let (_tmp, outcome) = create_bundle_in(
+            "long-history",
+            vec![Ref {
+                id: tip,
+                name: "refs/heads/main".into(),
+            }],
+            &[basis],
+            Default::default(),
+        )?;
+        assert_eq!(
+            outcome.header.prerequisites.iter().map(|p| p.id).collect::<Vec<_>>(),
+            [basis],
+            "the root commit is missing, which is fine as the walk stops shortly after the basis"
+        );
+        assert_eq!(outcome.num_objects, 3 * 3, "three commits with their tree and blob");
+        Ok(())
+    }
+
+    #[test]
+    fn basis_of_tips_makes_for_empty_pack() -> crate::Result {
+        let (header, _) = read_header(&fixture()?.join("v3.bundle"))?;
+        let tip = header.refs[0].id;
+        let (_tmp, outcome) = create_bundle(
+            vec![Ref {
+                id: tip,
+                name: "refs/heads/main".into(),
+            }],
+            &[tip],
+            Default::default(),
+        )?;
+        assert_eq!(outcome.num_objects, 0);
+        assert_eq!(outcome.header.prerequisites.len(), 1);
+        assert_eq!(outcome.header.prerequisites[0].id, tip);
+        Ok(())
+    }
+
+    #[test]
+    fn no_refs() -> crate::Result {
+        let err = create_bundle(Vec::new(), &[], Default::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Refusing to create a bundle without references");
+        Ok(())
+    }
+
+    fn git_can_read(repo: &std::path::Path, bundle: &std::path::Path) -> crate::Result {
+        let bundle: BString = gix_path::into_bstr(bundle).into_owned();
+        let status = gix_testtools::run_git(repo, &["bundle", "verify", "-q", &bundle.to_string()])?;
+        assert!(status.success(), "git can verify our bundle");
+        Ok(())
+    }
+}
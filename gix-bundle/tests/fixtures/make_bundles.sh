@@ -0,0 +1,40 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  for n in 1 2 3; do
+    echo $n > file
+    git add file
+    git commit -q -m "commit $n"
+  done
+  git tag -a -m "annotated" v1 main~1
+  git checkout -q -b feature main~1
+  mkdir dir && echo f > dir/feature
+  git add dir && git commit -q -m "feature"
+  git checkout -q main
+
+  git bundle create -q ../all.bundle --all
+  git bundle create -q ../incremental.bundle main~2..main
+  git bundle create -q --version=3 ../v3.bundle main
+  git rev-parse main~2 > ../incremental.prerequisite
+)
+
+git init -q empty
+
+git init -q long-history
+(cd long-history
+  git checkout -q -b main
+  for n in $(seq 1 20); do
+    echo $n > file
+    git add file
+    git commit -q -m "commit $n"
+  done
+  git rev-parse main > ../long-history.tip
+  git rev-parse main~3 > ../long-history.basis
+
+  # The history of the basis isn't needed, so it is made unavailable.
+  root=$(git rev-parse main~19)
+  rm ".git/objects/${root:0:2}/${root:2}"
+)
@@ -41,6 +41,13 @@ impl Graph {
         Some(self.lookup_by_id(id.as_ref())?.graph_pos)
     }
 
+    /// Return `true` if all files of this graph store corrected commit dates, which are only usable if that's the case.
+    ///
+    /// See [`Commit::corrected_commit_date()`] for details.
+    pub fn has_generation_data(&self) -> bool {
+        self.files.iter().all(File::has_generation_data)
+    }
+
     /// Returns the number of commits stored in this file.
     pub fn num_commits(&self) -> u32 {
         self.files.iter().map(File::num_commits).sum()
@@ -0,0 +1,183 @@
+//! Changed-path bloom filters as stored in the `BIDX` and `BDAT` chunks of a commit-graph file.
+//!
+//! Each commit may have a bloom filter which records all paths that changed compared to its first parent,
+//! along with all of their leading directories. It can be used to quickly determine that a path definitely
+//! didn't change in a commit, without having to diff any trees.
+//!
+//! See the [commit-graph format](https://git-scm.com/docs/commit-graph-format#_chunk_data) for details.
+use bstr::{BStr, ByteSlice};
+
+/// The settings used when creating the bloom filters of a single commit-graph file, as stored in the header of its `BDAT` chunk.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Settings {
+    /// The version of the murmur3 hash implementation, which is `1` or `2`.
+    ///
+    /// Version 1 sign-extends bytes larger than 127 as the C implementation of git did on most platforms,
+    /// while version 2 treats all bytes as unsigned.
+    pub hash_version: u32,
+    /// The number of hashes computed per path, which is also the number of bits set in the filter for each of them.
+    pub num_hashes: u32,
+    /// The minimum number of bits used per changed path in a filter.
+    pub bits_per_entry: u32,
+}
+
+impl Settings {
+    /// Return `true` if we know how to compute keys for the filters created with these settings.
+    pub fn is_supported(&self) -> bool {
+        matches!(self.hash_version, 1 | 2) && self.num_hashes > 0
+    }
+}
+
+/// The hashes of a single path, ready to be checked for in any [`Filter`] created with the same hash version.
+///
+/// As creating a key is comparatively costly, it should be created once and reused for testing many filters.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Key {
+    hash_version: u32,
+    hash0: u32,
+    hash1: u32,
+}
+
+impl Key {
+    /// Create a new key for `path`, which is a slash-separated path relative to the root of the repository,
+    /// using the murmur3 implementation of `hash_version`.
+    pub fn new(path: &BStr, hash_version: u32) -> Self {
+        const SEED0: u32 = 0x293a_e76f;
+        const SEED1: u32 = 0x7e64_6e2c;
+        Key {
+            hash_version,
+            hash0: murmur3(SEED0, path, hash_version),
+            hash1: murmur3(SEED1, path, hash_version),
+        }
+    }
+
+    /// Create keys for `path` and each of its leading directories, which is what filters need to contain
+    /// if they are to report `path` as possibly changed.
+    ///
+    /// Trailing slashes are ignored, and the keys are ordered from the longest to the shortest path.
+    pub fn new_with_leading_directories(path: &BStr, hash_version: u32) -> Vec<Self> {
+        let mut path = path.as_bytes();
+        while let Some(stripped) = path.strip_suffix(b"/") {
+            path = stripped;
+        }
+        let mut keys = Vec::new();
+        if path.is_empty() {
+            return keys;
+        }
+        keys.push(Key::new(path.as_bstr(), hash_version));
+        keys.extend(
+            path.iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, b)| **b == b'/')
+                .map(|(idx, _)| Key::new(path[..idx].as_bstr(), hash_version)),
+        );
+        keys
+    }
+
+    /// The hash version this key was created with.
+    pub fn hash_version(&self) -> u32 {
+        self.hash_version
+    }
+
+    /// Return an iterator over the first `num_hashes` hashes of this key.
+    pub fn hashes(&self, num_hashes: u32) -> impl Iterator<Item = u32> + '_ {
+        (0..num_hashes).map(move |i| self.hash0.wrapping_add(i.wrapping_mul(self.hash1)))
+    }
+}
+
+/// The changed-path bloom filter of a single commit.
+#[derive(Debug, Clone, Copy)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+    settings: Settings,
+}
+
+impl<'a> Filter<'a> {
+    pub(crate) fn new(data: &'a [u8], settings: Settings) -> Self {
+        Filter { data, settings }
+    }
+
+    /// The settings this filter was created with.
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    /// The raw bytes of the filter.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return `false` if the path that `key` was created from definitely wasn't changed, or `true` if it may have been.
+    ///
+    /// Note that keys created with a hash version that differs from the one of this filter will always be
+    /// considered as possibly contained.
+    pub fn contains(&self, key: &Key) -> bool {
+        const BITS_PER_WORD: u64 = 8;
+        let modulo = self.data.len() as u64 * BITS_PER_WORD;
+        if modulo == 0 || key.hash_version != self.settings.hash_version {
+            return true;
+        }
+        key.hashes(self.settings.num_hashes).all(|hash| {
+            let bit = u64::from(hash) % modulo;
+            let byte = self.data[(bit / BITS_PER_WORD) as usize];
+            byte & (1 << (bit % BITS_PER_WORD)) != 0
+        })
+    }
+
+    /// Return `false` if `path` definitely wasn't changed, or `true` if it may have been.
+    ///
+    /// This is a convenience method which creates keys for `path` and its leading directories on each call, and requires
+    /// all of them to be contained in this filter.
+    /// Prefer creating keys once with [`Key::new_with_leading_directories()`] when checking many filters.
+    pub fn maybe_contains_path(&self, path: &BStr) -> bool {
+        Key::new_with_leading_directories(path, self.settings.hash_version)
+            .iter()
+            .all(|key| self.contains(key))
+    }
+}
+
+/// An implementation of the 32-bit murmur3 hash as used by git, with `version` 1 reproducing git's original
+/// sign-extension of bytes larger than 127.
+fn murmur3(mut seed: u32, data: &[u8], version: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    const R1: u32 = 15;
+    const R2: u32 = 13;
+    const M: u32 = 5;
+    const N: u32 = 0xe654_6b64;
+
+    let byte = |b: u8| -> u32 {
+        if version == 1 {
+            b as i8 as u32
+        } else {
+            u32::from(b)
+        }
+    };
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k = byte(chunk[0]) | (byte(chunk[1]) << 8) | (byte(chunk[2]) << 16) | (byte(chunk[3]) << 24);
+        let k = k.wrapping_mul(C1).rotate_left(R1).wrapping_mul(C2);
+        seed ^= k;
+        seed = seed.rotate_left(R2).wrapping_mul(M).wrapping_add(N);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (idx, b) in tail.iter().enumerate().rev() {
+            k1 ^= byte(*b) << (8 * idx);
+        }
+        seed ^= k1.wrapping_mul(C1).rotate_left(R1).wrapping_mul(C2);
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
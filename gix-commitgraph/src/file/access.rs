@@ -5,7 +5,8 @@ use std::{
 };
 
 use crate::{
-    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH},
+    bloom,
+    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK},
     File,
 };
 
@@ -16,6 +17,12 @@ impl File {
         self.base_graph_count
     }
 
+    /// The settings used to create the changed-path bloom filters of this file, or `None` if there are
+    /// no bloom filters or if they can't be used.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.bloom_filter_data.as_ref().map(|(_, settings)| *settings)
+    }
+
     /// Returns the commit data for the commit located at the given lexicographical position.
     ///
     /// `pos` must range from 0 to `self.num_commits()`.
@@ -27,6 +34,11 @@ impl File {
         Commit::new(self, pos)
     }
 
+    /// Return `true` if this file stores corrected commit dates, also known as generation number v2.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// The kind of hash used in this File.
     ///
     /// Note that it is always conforming to the hash used in the owning repository.
@@ -132,6 +144,42 @@ impl File {
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
     }
+
+    /// Returns the corrected commit date offset for the given commit from the Generation Data (GDA2) chunk,
+    /// resolving it through the Generation Data Overflow (GDO2) chunk if needed.
+    ///
+    /// Returns `None` if there is no such chunk, or if the overflow entry can't be found.
+    pub(crate) fn corrected_commit_date_offset(&self, pos: file::Position) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().unwrap());
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(offset.into());
+        }
+        let overflow_data = &self.data[self.generation_data_overflow_range.clone()?];
+        let overflow_pos = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize * 8;
+        overflow_data
+            .get(overflow_pos..)
+            .and_then(|tail| tail.get(..8))
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Returns the bloom filter of the given commit from the Bloom Filter Data (BDAT) chunk, using the Bloom Filter
+    /// Index (BIDX) chunk to find it.
+    ///
+    /// Returns `None` if there are no bloom filters, or if the index points outside of the filter data.
+    pub(crate) fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::Filter<'_>> {
+        let index_offset = self.bloom_filter_index_offset?;
+        let (data_range, settings) = self.bloom_filter_data.as_ref()?;
+        let read_end = |pos: usize| -> usize {
+            let start = index_offset + pos * 4;
+            u32::from_be_bytes(self.data[start..][..4].try_into().unwrap()) as usize
+        };
+        let pos = pos.0 as usize;
+        let start = if pos == 0 { 0 } else { read_end(pos - 1) };
+        let end = read_end(pos);
+        let filter_data = self.data[data_range.clone()].get(start..end)?;
+        Some(bloom::Filter::new(filter_data, *settings))
+    }
 }
 
 impl Debug for File {
@@ -5,7 +5,10 @@ use std::{
     slice::Chunks,
 };
 
+use bstr::BStr;
+
 use crate::{
+    bloom,
     file::{self, EXTENDED_EDGES_MASK, LAST_EXTENDED_EDGE_MASK, NO_PARENT},
     File, Position,
 };
@@ -51,9 +54,6 @@ impl<'a> Commit<'a> {
             root_tree_id: gix_hash::oid::from_bytes_unchecked(&bytes[..file.hash_len]),
             parent1: ParentEdge::from_raw(read_u32(&bytes[file.hash_len..][..4])),
             parent2: ParentEdge::from_raw(read_u32(&bytes[file.hash_len + 4..][..4])),
-            // TODO: Add support for corrected commit date offset overflow.
-            //      See https://github.com/git/git/commit/e8b63005c48696a26f976f5f9b0ccaf1983e439d and
-            //          https://github.com/git/git/commit/f90fca638e99a031dce8e3aca72427b2f9b4bb38 for more details and hints at a test.
             generation: read_u32(&bytes[file.hash_len + 8..][..4]) >> 2,
             commit_timestamp: u64::from_be_bytes(bytes[file.hash_len + 8..][..8].try_into().unwrap())
                 & 0x0003_ffff_ffff,
@@ -67,6 +67,40 @@ impl<'a> Commit<'a> {
         self.commit_timestamp
     }
 
+    /// Returns the corrected commit date of this commit, also known as generation number v2, or `None` if the file
+    /// containing this commit doesn't store generation data.
+    ///
+    /// The corrected commit date is the commit's timestamp, or one more than the largest corrected commit date
+    /// of its parents, whichever is larger. Thus, like the [generation](Self::generation()), it's always larger than
+    /// the one of all of its parents, but it's much closer to the actual commit date which allows faster traversals
+    /// when sorting by commit date.
+    ///
+    /// Note that for graphs spanning multiple files, these are only meaningful if [all files](crate::Graph::has_generation_data())
+    /// contain generation data.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file
+            .corrected_commit_date_offset(self.pos)
+            .map(|offset| self.commit_timestamp.saturating_add(offset))
+    }
+
+    /// Returns the changed-path bloom filter of this commit, or `None` if there is none.
+    ///
+    /// The filter records all paths that changed compared to the first parent of this commit, or compared to
+    /// the empty tree if there is no parent.
+    pub fn bloom_filter(&self) -> Option<bloom::Filter<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
+
+    /// Return `Some(false)` if `path` definitely wasn't changed by this commit compared to its first parent,
+    /// `Some(true)` if it might have been, or `None` if there is no [bloom filter](Self::bloom_filter()) to tell.
+    ///
+    /// `path` is slash-separated and relative to the root of the repository.
+    /// Note that when checking many commits, it's more efficient to create [keys](bloom::Key) once and check them
+    /// against the filter directly.
+    pub fn maybe_changed(&self, path: &BStr) -> Option<bool> {
+        self.bloom_filter().map(|filter| filter.maybe_contains_path(path))
+    }
+
     /// Returns the generation number of this commit.
     ///
     /// Commits without parents have generation number 1. Commits with parents have a generation
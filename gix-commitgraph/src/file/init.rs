@@ -8,9 +8,12 @@ use bstr::ByteSlice;
 use memmap2::Mmap;
 
 use crate::{
+    bloom,
     file::{
-        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-        EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_DATA_HEADER_LEN,
+        BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
+        EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN,
+        OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
     },
     File,
 };
@@ -189,6 +192,67 @@ impl File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let generation_data_offset = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size % 4 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_CHUNK_ID,
+                        msg: format!("chunk size {chunk_size} is not a multiple of 4"),
+                    });
+                }
+                let count: u32 = (chunk_size / 4)
+                    .try_into()
+                    .expect("number of commits in GDA2 chunk to fit in 32 bits");
+                Ok((chunk_range.start, count))
+            })
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks.usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID).ok();
+
+        let bloom_filter_index = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size % 4 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                        msg: format!("chunk size {chunk_size} is not a multiple of 4"),
+                    });
+                }
+                let count: u32 = (chunk_size / 4)
+                    .try_into()
+                    .expect("number of commits in BIDX chunk to fit in 32 bits");
+                Ok((chunk_range.start, count))
+            })
+            .ok()
+            .transpose()?;
+        let bloom_filter_data = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() < BLOOM_FILTER_DATA_HEADER_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!(
+                            "chunk size {} is smaller than the header size of {BLOOM_FILTER_DATA_HEADER_LEN}",
+                            chunk_range.len()
+                        ),
+                    });
+                }
+                let header = &data[chunk_range.start..][..BLOOM_FILTER_DATA_HEADER_LEN];
+                let settings = bloom::Settings {
+                    hash_version: u32::from_be_bytes(header[..4].try_into().unwrap()),
+                    num_hashes: u32::from_be_bytes(header[4..8].try_into().unwrap()),
+                    bits_per_entry: u32::from_be_bytes(header[8..].try_into().unwrap()),
+                };
+                Ok((
+                    chunk_range.start + BLOOM_FILTER_DATA_HEADER_LEN..chunk_range.end,
+                    settings,
+                ))
+            })
+            .ok()
+            .transpose()?
+            // Like git, ignore filters we can't interpret instead of failing.
+            .filter(|(_range, settings)| settings.is_supported());
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -222,12 +286,41 @@ impl File {
                 chunk2_commits: commit_data_count,
             });
         }
+        if let Some((_, generation_data_count)) = generation_data_offset {
+            if generation_data_count != fan[255] {
+                return Err(Error::CommitCountMismatch {
+                    chunk1_id: OID_FAN_CHUNK_ID,
+                    chunk1_commits: fan[255],
+                    chunk2_id: GENERATION_DATA_CHUNK_ID,
+                    chunk2_commits: generation_data_count,
+                });
+            }
+        }
+        if let Some((_, bloom_filter_index_count)) = bloom_filter_index {
+            if bloom_filter_index_count != fan[255] {
+                return Err(Error::CommitCountMismatch {
+                    chunk1_id: OID_FAN_CHUNK_ID,
+                    chunk1_commits: fan[255],
+                    chunk2_id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                    chunk2_commits: bloom_filter_index_count,
+                });
+            }
+        }
+        let (bloom_filter_index_offset, bloom_filter_data) = match (bloom_filter_index, bloom_filter_data) {
+            (Some((index_offset, _)), Some(data)) => (Some(index_offset), Some(data)),
+            _ => (None, None),
+        };
+
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
             commit_data_offset,
             data,
             extra_edges_list_range,
+            generation_data_offset: generation_data_offset.map(|(offset, _)| offset),
+            generation_data_overflow_range,
+            bloom_filter_index_offset,
+            bloom_filter_data,
             fan,
             oid_lookup_offset,
             path,
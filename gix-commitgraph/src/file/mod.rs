@@ -17,8 +17,14 @@ const SIGNATURE: &[u8] = b"CGPH";
 
 type ChunkId = gix_chunk::Id;
 const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
+const BLOOM_FILTER_DATA_CHUNK_ID: ChunkId = *b"BDAT";
+const BLOOM_FILTER_INDEX_CHUNK_ID: ChunkId = *b"BIDX";
 const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
 const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
+// Note that the original `GDAT` and `GDOV` chunks are ignored just like git does, as some git versions wrote them incorrectly.
+// Their replacements, `GDA2` and `GDO2`, have the same format.
+const GENERATION_DATA_CHUNK_ID: ChunkId = *b"GDA2";
+const GENERATION_DATA_OVERFLOW_CHUNK_ID: ChunkId = *b"GDO2";
 const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
 const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
 
@@ -27,6 +33,9 @@ const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
 const NO_PARENT: u32 = 0x7000_0000;
 const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
 const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+const GENERATION_DATA_OVERFLOW_MASK: u32 = 0x8000_0000;
+/// The size of the header of the `BDAT` chunk, consisting of the hash version, the number of hashes and the bits per entry.
+const BLOOM_FILTER_DATA_HEADER_LEN: usize = 12;
 
 /// The position of a given commit within a graph file, starting at 0.
 ///
@@ -27,6 +27,10 @@ pub struct File {
     commit_data_offset: usize,
     data: memmap2::Mmap,
     extra_edges_list_range: Option<std::ops::Range<usize>>,
+    generation_data_offset: Option<usize>,
+    generation_data_overflow_range: Option<std::ops::Range<usize>>,
+    bloom_filter_index_offset: Option<usize>,
+    bloom_filter_data: Option<(std::ops::Range<usize>, bloom::Settings)>,
     fan: [u32; file::FAN_LEN],
     oid_lookup_offset: usize,
     path: std::path::PathBuf,
@@ -49,6 +53,7 @@ pub fn at(path: impl AsRef<Path>) -> Result<Graph, init::Error> {
 }
 
 mod access;
+pub mod bloom;
 pub mod file;
 ///
 pub mod init;
@@ -111,3 +111,86 @@ fn two_parents() {
     assert_eq!(cg.commit_at(refs["parent2"].pos()).generation(), 1);
     assert_eq!(cg.commit_at(refs["child"].pos()).generation(), 2);
 }
+
+#[test]
+fn corrected_commit_dates_with_overflow_in_chained_graph() {
+    let names = ["future-1", "old-1", "future-2", "old-2", "extra"];
+    let (cg, refs) = graph_and_expected("generation_number_overflow.sh", &names);
+    assert!(cg.has_generation_data(), "all files in the chain have generation data");
+
+    let future_date = 4147483646;
+    for (name, expected) in names.iter().zip(future_date..) {
+        let commit = cg.commit_by_id(refs[*name].id()).expect("present");
+        assert_eq!(
+            commit.corrected_commit_date(),
+            Some(expected),
+            "{name}: each commit must be at least one second newer than its parent, even if its own date is older"
+        );
+    }
+}
+
+#[test]
+fn corrected_commit_dates_are_unavailable_without_generation_data() {
+    let (cg, refs) = graph_and_expected_named("single_commit_huge_dates.sh", "v1", &["HEAD"]);
+    assert!(!cg.has_generation_data());
+    assert_eq!(
+        cg.commit_by_id(refs["HEAD"].id())
+            .expect("present")
+            .corrected_commit_date(),
+        None
+    );
+}
+
+#[test]
+fn bloom_filters_of_changed_paths() {
+    let (cg, refs) = graph_and_expected(
+        "changed_paths.sh",
+        &["root", "changes-a", "changes-c", "changes-nothing"],
+    );
+    check_common(&cg, &refs);
+
+    let settings = cg
+        .commit_at(refs["root"].pos())
+        .bloom_filter()
+        .expect("filter present")
+        .settings();
+    assert_eq!(settings.num_hashes, 7, "git defaults");
+    assert_eq!(settings.bits_per_entry, 10);
+
+    let changed = |name: &str, path: &str| cg.commit_at(refs[name].pos()).maybe_changed(path.into());
+    for path in ["a", "dir", "dir/b", "dir/sub", "dir/sub/c", "dir/sub/"] {
+        assert_eq!(
+            changed("root", path),
+            Some(true),
+            "{path}: everything changed in the root commit"
+        );
+    }
+
+    assert_eq!(changed("changes-a", "a"), Some(true));
+    for path in ["dir", "dir/b", "dir/sub/c"] {
+        assert_eq!(changed("changes-a", path), Some(false), "{path}");
+    }
+
+    for path in ["dir", "dir/sub", "dir/sub/c"] {
+        assert_eq!(
+            changed("changes-c", path),
+            Some(true),
+            "{path}: leading directories are recorded as well"
+        );
+    }
+    for path in ["a", "dir/b", "dir/sub/d", "dir/b/sub/c"] {
+        assert_eq!(changed("changes-c", path), Some(false), "{path}");
+    }
+
+    for path in ["a", "dir", "dir/b", "dir/sub/c"] {
+        assert_eq!(changed("changes-nothing", path), Some(false), "{path}");
+    }
+}
+
+#[test]
+fn bloom_filters_are_unavailable_if_not_written() {
+    let (cg, refs) = graph_and_expected("single_parent.sh", &["parent", "child"]);
+    let commit = cg.commit_at(refs["child"].pos());
+    assert!(commit.bloom_filter().is_none());
+    assert_eq!(commit.maybe_changed("any".into()), None);
+}
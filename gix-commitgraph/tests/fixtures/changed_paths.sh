@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commitGraph.generationVersion 2
+
+git checkout -q -b root
+mkdir -p dir/sub
+echo a > a
+echo b > dir/b
+echo c > dir/sub/c
+git add . && git commit -q -m root
+
+git checkout -q -b changes-a
+echo a2 > a
+git commit -q -am changes-a
+
+git checkout -q -b changes-c
+echo c2 > dir/sub/c
+git commit -q -am changes-c
+
+git checkout -q -b changes-nothing
+git commit -q --allow-empty -m changes-nothing
+
+git commit-graph write --no-progress --reachable --changed-paths
+git repack -adq
@@ -0,0 +1,142 @@
+use std::{borrow::Cow, convert::TryFrom};
+
+use bstr::BStr;
+
+use crate::{file, file::MetadataFilter, url_match, value, File};
+
+/// Access values of keys that may be specialized for URLs, like `http.<url>.proxy`.
+///
+/// All sections with the name of the key apply, as long as they have no subsection or their subsection is a URL pattern
+/// that [matches](url_match::Url::matches()) `url`. Like `git`, we see values in order, and ignore those that are in sections
+/// matching less specifically than the ones we have seen before. Thus the value that is seen last wins among those
+/// that match most specifically.
+impl<'event> File<'event> {
+    /// Like [`string_filter_by_key()`][File::string_filter_by_key()], but with `key` like `http.proxy` also looked up in sections
+    /// like `[http "https://example.com"]` that apply to `url`.
+    pub fn string_filter_by_url<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<Cow<'_, BStr>> {
+        let key = crate::parse::key(key.into())?;
+        self.sections_by_key_and_url(key.section_name, key.value_name, url, filter)
+            .last()
+            .and_then(|section| section.value(key.value_name))
+    }
+
+    /// Like [`strings_filter_by_key()`][File::strings_filter_by_key()], but with `key` like `http.extraHeader` also looked up
+    /// in sections like `[http "https://example.com"]` that apply to `url`.
+    pub fn strings_filter_by_url<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<Vec<Cow<'_, BStr>>> {
+        let key = crate::parse::key(key.into())?;
+        let values: Vec<_> = self
+            .sections_by_key_and_url(key.section_name, key.value_name, url, filter)
+            .into_iter()
+            .flat_map(|section| section.values(key.value_name))
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+
+    /// Like [`path_filter_by_key()`][File::path_filter_by_key()], but with `key` like `http.sslCAInfo` also looked up in sections
+    /// like `[http "https://example.com"]` that apply to `url`.
+    pub fn path_filter_by_url<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<crate::Path<'_>> {
+        self.string_filter_by_url(key, url, filter).map(crate::Path::from)
+    }
+
+    /// Like [`boolean_filter_by_key()`][File::boolean_filter_by_key()], but with `key` like `http.sslVerify` also looked up
+    /// in sections like `[http "https://example.com"]` that apply to `url`.
+    pub fn boolean_filter_by_url<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<Result<bool, value::Error>> {
+        let key = crate::parse::key(key.into())?;
+        match self
+            .sections_by_key_and_url(key.section_name, key.value_name, url, filter)
+            .last()?
+            .value_implicit(key.value_name)?
+        {
+            Some(v) => Some(crate::Boolean::try_from(v).map(Into::into)),
+            None => Some(Ok(true)),
+        }
+    }
+
+    /// Like [`integer_filter_by_key()`][File::integer_filter_by_key()], but with `key` like `http.lowSpeedLimit` also looked up
+    /// in sections like `[http "https://example.com"]` that apply to `url`.
+    pub fn integer_filter_by_url<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<Result<i64, value::Error>> {
+        let int = self.string_filter_by_url(key, url, filter)?;
+        Some(crate::Integer::try_from(int.as_ref()).and_then(|b| {
+            b.to_decimal()
+                .ok_or_else(|| value::Error::new("Integer overflow", int.into_owned()))
+        }))
+    }
+
+    /// Return all sections named `name` that pass `filter` and apply to `url`, along with how specifically they match, in order
+    /// of appearance.
+    ///
+    /// Sections without subsection apply to all URLs, and have the least specific match.
+    pub fn sections_by_url_filter(
+        &self,
+        name: &str,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Vec<(&file::Section<'event>, url_match::Match)> {
+        let Ok(ids) = self.section_ids_by_name(name) else {
+            return Vec::new();
+        };
+        ids.filter_map(|id| {
+            let section = self
+                .sections
+                .get(&id)
+                .expect("section doesn't have id from from lookup");
+            if !filter(section.meta()) {
+                return None;
+            }
+            match section.header().subsection_name() {
+                None => Some((section, url_match::Match::default())),
+                Some(pattern) => url_match::Url::parse(pattern)
+                    .and_then(|pattern| url.matches(&pattern))
+                    .map(|m| (section, m)),
+            }
+        })
+        .collect()
+    }
+
+    /// Return the sections that contain `key` and whose values should be applied in order, according to `git`'s rules.
+    fn sections_by_key_and_url(
+        &self,
+        section_name: &str,
+        key: &str,
+        url: &url_match::Url,
+        filter: &mut MetadataFilter,
+    ) -> Vec<&file::Section<'event>> {
+        let mut best = url_match::Match::default();
+        self.sections_by_url_filter(section_name, url, filter)
+            .into_iter()
+            .filter(|(section, m)| {
+                if *m < best || section.value_implicit(key).is_none() {
+                    return false;
+                }
+                best = *m;
+                true
+            })
+            .map(|(section, _)| section)
+            .collect()
+    }
+}
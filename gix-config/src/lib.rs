@@ -50,3 +50,5 @@ mod types;
 pub use types::{File, Source};
 ///
 pub mod source;
+///
+pub mod url_match;
@@ -0,0 +1,198 @@
+//! Match URLs against the URL patterns in subsections like `http.<url>.*`, following the rules of `git`.
+//!
+//! Both URLs and patterns are normalized before matching, and patterns match if scheme, host and port are the same,
+//! with `*` matching any single label of the host. The pattern path must be a prefix of the URL path, ending at a `/`,
+//! and a user in the pattern must be the one of the URL.
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// A URL normalized the way `git` does it before matching it against URL patterns.
+///
+/// Normalization lower-cases scheme and host, removes the default port of `http` and `https` URLs, resolves
+/// `.` and `..` path components and unifies percent-encodings.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    /// The lower-case scheme, like `https`.
+    pub scheme: BString,
+    /// The name of the user, if the URL has user information, without password.
+    pub user: Option<BString>,
+    /// The lower-case host, like `example.com`, which is only empty for `file` URLs.
+    pub host: BString,
+    /// The port, if it was specified and isn't the default port for the scheme.
+    pub port: Option<u16>,
+    /// The path, which always starts with `/`, along with the query and fragment, if present.
+    pub path: BString,
+}
+
+/// Describes how specifically a URL pattern matched a URL, with more specific matches comparing greater.
+///
+/// The host is most important, followed by the path and finally by the user.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Match {
+    /// The length of the host in the pattern, wildcards included.
+    pub host_len: usize,
+    /// The length of the path in the pattern that matched, or `0` if this isn't a match with a URL pattern.
+    pub path_len: usize,
+    /// `true` if the pattern had a user name that matched the URL's user.
+    pub user_matched: bool,
+}
+
+impl Url {
+    /// Parse and normalize `url`, or return `None` if it isn't a URL with scheme, like `https://example.com`.
+    pub fn parse(url: &BStr) -> Option<Self> {
+        let scheme_end = url.find(b"://")?;
+        let scheme = &url[..scheme_end];
+        if !scheme.first()?.is_ascii_alphabetic()
+            || !scheme
+                .iter()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+        {
+            return None;
+        }
+        let scheme: BString = scheme.to_ascii_lowercase().into();
+
+        let rest = &url[scheme_end + 3..];
+        let authority_end = rest.find_byteset(b"/?#").unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(authority_end);
+        let (user, host_and_port) = match authority.find_byte(b'@') {
+            Some(at) => {
+                let user_info = &authority[..at];
+                let user = user_info.find_byte(b':').map_or(user_info, |colon| &user_info[..colon]);
+                (Some(normalize_escapes(user)), &authority[at + 1..])
+            }
+            None => (None, authority),
+        };
+
+        let port_start = host_and_port
+            .rfind_byte(b':')
+            .filter(|colon| !host_and_port[*colon..].contains(&b']'));
+        let (host, port) = match port_start {
+            Some(colon) => (&host_and_port[..colon], &host_and_port[colon + 1..]),
+            None => (host_and_port, &b""[..]),
+        };
+        let host: BString = normalize_escapes(host).to_ascii_lowercase().into();
+        if host.is_empty() && scheme != "file" {
+            return None;
+        }
+        let port = if port.is_empty() {
+            None
+        } else {
+            if !port.iter().all(u8::is_ascii_digit) {
+                return None;
+            }
+            let port: u16 = port.to_str().ok()?.parse().ok().filter(|port| *port != 0)?;
+            match (scheme.as_slice(), port) {
+                (b"http", 80) | (b"https", 443) => None,
+                _ => Some(port),
+            }
+        };
+
+        Some(Url {
+            scheme,
+            user,
+            host,
+            port,
+            path: normalize_path(path)?,
+        })
+    }
+
+    /// Return how specifically `pattern` matches this URL, or `None` if it doesn't match.
+    pub fn matches(&self, pattern: &Url) -> Option<Match> {
+        if self.scheme != pattern.scheme || self.port != pattern.port || !host_matches(&self.host, &pattern.host) {
+            return None;
+        }
+        let user_matched = match &pattern.user {
+            Some(user) if self.user.as_ref() != Some(user) => return None,
+            Some(_) => true,
+            None => false,
+        };
+        Some(Match {
+            host_len: pattern.host.len(),
+            path_len: path_match_len(&self.path, &pattern.path)?,
+            user_matched,
+        })
+    }
+}
+
+/// Return `true` if all labels of `host` are the same as the ones of `pattern`, with `*` matching any label.
+fn host_matches(host: &[u8], pattern: &[u8]) -> bool {
+    let mut labels = host.split_str(".");
+    let mut pattern_labels = pattern.split_str(".");
+    loop {
+        match (labels.next(), pattern_labels.next()) {
+            (Some(label), Some(pattern)) if pattern == b"*" || label == pattern => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Return the length of `pattern` plus one if it's a prefix of `path` that ends at a path separator.
+fn path_match_len(path: &[u8], pattern: &[u8]) -> Option<usize> {
+    if pattern.is_empty() || pattern == b"/" {
+        return Some(1);
+    }
+    let pattern = pattern.strip_suffix(b"/").unwrap_or(pattern);
+    let rest = path.strip_prefix(pattern)?;
+    (rest.is_empty() || rest[0] == b'/').then_some(pattern.len() + 1)
+}
+
+/// Resolve `.` and `..` components of `path`, which may be empty, and normalize escapes.
+/// Return `None` if `..` would leave the root.
+fn normalize_path(path: &[u8]) -> Option<BString> {
+    let path_end = path.find_byteset(b"?#").unwrap_or(path.len());
+    let (path, query) = path.split_at(path_end);
+    let path = normalize_escapes(path.strip_prefix(b"/").unwrap_or(path));
+
+    let mut components = Vec::new();
+    let mut has_trailing_slash = false;
+    for component in path.split_str("/") {
+        has_trailing_slash = true;
+        match component {
+            b"." => {}
+            b".." => {
+                components.pop()?;
+            }
+            _ => {
+                components.push(component);
+                has_trailing_slash = false;
+            }
+        }
+    }
+    let mut out = BString::from("/");
+    out.push_str(components.join(&b'/'));
+    if has_trailing_slash && !out.ends_with(b"/") {
+        out.push(b'/');
+    }
+    out.push_str(normalize_escapes(query));
+    Some(out)
+}
+
+/// Decode escapes of characters that don't need them, and escape characters that need it, using upper-case hex digits.
+fn normalize_escapes(input: &[u8]) -> BString {
+    const UNSAFE: &[u8] = b" <>\"%{}|\\^`";
+    const RESERVED: &[u8] = b":/?#[]@!$&'()*+,;=";
+    let mut out = BString::default();
+    let mut idx = 0;
+    while idx < input.len() {
+        let mut byte = input[idx];
+        let mut was_escaped = false;
+        if byte == b'%' {
+            if let Some(decoded) = input
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| hex.to_str().ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                byte = decoded;
+                was_escaped = true;
+                idx += 2;
+            }
+        }
+        if !(0x20..0x7f).contains(&byte) || UNSAFE.contains(&byte) || (was_escaped && RESERVED.contains(&byte)) {
+            out.push_str(format!("%{byte:02X}"));
+        } else {
+            out.push(byte);
+        }
+        idx += 1;
+    }
+    out
+}
@@ -4,4 +4,5 @@ mod file;
 mod mem;
 mod parse;
 mod source;
+mod url_match;
 mod value;
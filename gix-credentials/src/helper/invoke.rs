@@ -40,7 +40,13 @@ pub fn invoke(helper: &mut crate::Program, action: &Action) -> Result {
 }
 
 pub(crate) fn raw(helper: &mut crate::Program, action: &Action) -> std::result::Result<Option<Vec<u8>>, Error> {
-    let (mut stdin, stdout) = helper.start(action)?;
+    let (mut stdin, stdout) = match (helper.start(action), &helper.kind) {
+        (Err(err), crate::program::Kind::Native(native)) if err.kind() == std::io::ErrorKind::NotFound => {
+            // Without `git`, the helper runs in this process instead.
+            return native.invoke(action);
+        }
+        (res, _) => res?,
+    };
     if let (Action::Get(_), None) = (&action, &stdout) {
         panic!("BUG: `Helper` impls must return an output handle to read output from if Action::Get is provided")
     }
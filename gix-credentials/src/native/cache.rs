@@ -0,0 +1,247 @@
+//! A helper to hold credentials in memory for a limited time, compatible with `git credential-cache`.
+use std::{path::PathBuf, time::Duration};
+
+use crate::{native, program::main::Action, protocol::Context};
+
+/// A credential helper that sends credentials to a daemon listening on a Unix domain socket, which forgets them after a timeout.
+///
+/// The daemon speaks the same protocol as the one launched by `git credential-cache`, so either can serve the other's clients.
+/// If no daemon is running when credentials are stored, [one is started](Daemon::bind()) on a thread of the current process,
+/// keeping credentials for as long as the process is alive or until they expire, whichever happens first.
+/// Applications that want credentials to outlive them can [serve](Daemon::serve()) the cache from a process of their own.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cache {
+    /// The path to the socket to connect to, or `None` to use [the default location](Cache::default_socket()).
+    pub socket: Option<PathBuf>,
+    /// How long the daemon should keep stored credentials.
+    pub timeout: Duration,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache {
+            socket: None,
+            timeout: Duration::from_secs(900),
+        }
+    }
+}
+
+/// The error returned by [`Cache::handle()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    UrlParse(#[from] gix_url::parse::Error),
+    #[error("Could not communicate with the credential cache daemon")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Context(#[from] crate::protocol::context::decode::Error),
+    #[error("The home directory could not be determined to find the socket of the credential cache daemon")]
+    NoDefaultSocket,
+    #[error("The credential cache requires Unix domain sockets, which aren't available on this platform")]
+    Unsupported,
+}
+
+impl Cache {
+    /// Return the socket `git` would use by default, which is `~/.git-credential-cache/socket` if its directory exists,
+    /// or `$XDG_CACHE_HOME/git/credential/socket` otherwise.
+    pub fn default_socket() -> Option<PathBuf> {
+        let legacy_dir = gix_path::env::home_dir().map(|home| home.join(".git-credential-cache"));
+        if let Some(dir) = legacy_dir.filter(|dir| dir.is_dir()) {
+            return Some(dir.join("socket"));
+        }
+        gix_path::env::var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| gix_path::env::home_dir().map(|home| home.join(".cache")))
+            .map(|cache| cache.join("git").join("credential").join("socket"))
+    }
+
+    /// Perform `action` with the credentials in `ctx` by talking to the cache daemon, and return the cached credentials
+    /// on [`Get`](Action::Get).
+    ///
+    /// If the daemon isn't running, nothing is cached, and it's started only to store credentials.
+    #[cfg(unix)]
+    pub fn handle(&self, action: Action, ctx: Context) -> Result<Option<Context>, Error> {
+        let ctx = native::destructure_url(ctx)?;
+        let socket = match &self.socket {
+            Some(socket) => socket.clone(),
+            None => Self::default_socket().ok_or(Error::NoDefaultSocket)?,
+        };
+        let mut request = format!("action={}\ntimeout={}\n", action.as_str(), self.timeout.as_secs()).into_bytes();
+        ctx.write_to(&mut request)?;
+
+        let response = match send(&socket, &request) {
+            Ok(response) => response,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+                ) =>
+            {
+                match action {
+                    Action::Get | Action::Erase => return Ok(None),
+                    Action::Store => {
+                        let daemon = Daemon::bind(&socket)?;
+                        std::thread::Builder::new()
+                            .name("gix-credentials::cache::Daemon".into())
+                            .spawn(move || daemon.serve())?;
+                        send(&socket, &request)?
+                    }
+                }
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if !matches!(action, Action::Get) {
+            return Ok(None);
+        }
+        let found = Context::from_bytes(&response)?;
+        Ok((found.username.is_some() || found.password.is_some()).then_some(found))
+    }
+
+    /// Fail as there is no Unix domain socket support on this platform.
+    #[cfg(not(unix))]
+    pub fn handle(&self, _action: Action, _ctx: Context) -> Result<Option<Context>, Error> {
+        Err(Error::Unsupported)
+    }
+}
+
+#[cfg(unix)]
+fn send(socket: &std::path::Path, request: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::{Read, Write};
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    stream.write_all(request)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+#[cfg(unix)]
+pub use daemon::Daemon;
+
+#[cfg(unix)]
+mod daemon {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    };
+
+    use bstr::ByteSlice;
+
+    use crate::{native, protocol::Context};
+
+    /// How long to wait for the first credentials to be stored before shutting down.
+    const WAIT_FOR_FIRST_ENTRY: Duration = Duration::from_secs(30);
+    /// How long to sleep between checking for new connections.
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+    /// How long to wait for a client to send its request.
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// A daemon to hold credentials in memory, serving clients like [`Cache`](super::Cache) or `git credential-cache`.
+    pub struct Daemon {
+        listener: UnixListener,
+        socket: PathBuf,
+    }
+
+    impl Daemon {
+        /// Listen on `socket`, creating its missing leading directories so that only the current user can access them,
+        /// and replacing a stale socket.
+        pub fn bind(socket: &Path) -> std::io::Result<Self> {
+            if let Some(dir) = socket.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                use std::os::unix::fs::DirBuilderExt;
+                std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+            }
+            match std::fs::remove_file(socket) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+            Ok(Daemon {
+                listener: UnixListener::bind(socket)?,
+                socket: socket.to_owned(),
+            })
+        }
+
+        /// Serve requests until all stored credentials have expired, or until a client asks us to exit.
+        ///
+        /// Errors caused by individual clients are ignored.
+        pub fn serve(self) -> std::io::Result<()> {
+            self.listener.set_nonblocking(true)?;
+            let started = Instant::now();
+            let mut entries = Vec::<(Context, Instant)>::new();
+            loop {
+                let now = Instant::now();
+                entries.retain(|(_, expires_at)| *expires_at > now);
+                if entries.is_empty() && now.duration_since(started) > WAIT_FOR_FIRST_ENTRY {
+                    break;
+                }
+                match self.listener.accept() {
+                    Ok((stream, _addr)) => {
+                        if let Ok(Request::Exit) = serve_client(stream, &mut entries) {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+                    Err(err) => return Err(err),
+                }
+            }
+            std::fs::remove_file(&self.socket).ok();
+            Ok(())
+        }
+    }
+
+    enum Request {
+        Exit,
+        Other,
+    }
+
+    fn serve_client(mut stream: UnixStream, entries: &mut Vec<(Context, Instant)>) -> std::io::Result<Request> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf)?;
+
+        let mut lines = buf.splitn(3, |b| *b == b'\n');
+        let protocol_error = || std::io::Error::new(std::io::ErrorKind::InvalidData, "cache daemon protocol error");
+        let action = lines
+            .next()
+            .and_then(|line| line.strip_prefix(b"action="))
+            .ok_or_else(protocol_error)?;
+        let timeout = lines
+            .next()
+            .and_then(|line| line.strip_prefix(b"timeout="))
+            .and_then(|timeout| timeout.to_str().ok()?.parse().ok())
+            .map(Duration::from_secs)
+            .ok_or_else(protocol_error)?;
+        let ctx = Context::from_bytes(lines.next().unwrap_or_default())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+
+        match action {
+            b"get" => {
+                if let Some((entry, _)) = entries.iter().find(|(entry, _)| native::matches(&ctx, entry, false)) {
+                    Context {
+                        username: entry.username.clone(),
+                        password: entry.password.clone(),
+                        ..Default::default()
+                    }
+                    .write_to(&mut stream)?;
+                }
+            }
+            b"store" => {
+                if ctx.username.is_some() && ctx.password.is_some() {
+                    entries.retain(|(entry, _)| !native::matches(&ctx, entry, false));
+                    entries.push((ctx, Instant::now() + timeout));
+                }
+            }
+            b"erase" => {
+                let match_password = ctx.password.is_some();
+                entries.retain(|(entry, _)| !native::matches(&ctx, entry, match_password));
+            }
+            b"exit" => return Ok(Request::Exit),
+            _ => return Err(protocol_error()),
+        }
+        stream.flush()?;
+        Ok(Request::Other)
+    }
+}
@@ -0,0 +1,161 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::{helper, program::main::Action, protocol::Context};
+
+///
+pub mod store;
+
+///
+pub mod cache;
+
+/// A credential helper implemented in Rust, which works even if no `git` installation is present.
+///
+/// These are used in place of `git credential-<name>` when they are configured by name, like `store` or `cache --timeout 300`,
+/// but `git` isn't installed, and they remain compatible with their `git` counterparts as they use the same storage formats and protocols.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Helper {
+    /// Store credentials in plain text on disk, like `git credential-store`.
+    Store(store::Store),
+    /// Hold credentials in memory of a daemon for a limited amount of time, like `git credential-cache`.
+    Cache(cache::Cache),
+}
+
+/// The error returned by [`Helper::handle()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Store(#[from] store::Error),
+    #[error(transparent)]
+    Cache(#[from] cache::Error),
+}
+
+/// Initialization
+impl Helper {
+    /// Parse `name_and_args` as it would be given to `credential.helper`, like `store --file ~/creds`, and return a
+    /// native helper if the name and all arguments are understood, or `None` if the helper should be launched as program.
+    ///
+    /// Arguments are split by whitespace, and anything that would need a shell to be interpreted leads to `None`.
+    pub fn from_name_and_args(name_and_args: &BStr) -> Option<Self> {
+        if name_and_args.iter().any(|b| br#""'\$`|&;<>()*?[]{}"#.contains(b)) {
+            return None;
+        }
+        let mut tokens = name_and_args.fields_with(|c| c.is_ascii_whitespace());
+        let name = tokens.next()?;
+        let mut args = Vec::new();
+        while let Some(token) = tokens.next() {
+            let token = token.to_str().ok()?;
+            let (flag, value) = match token.split_once('=') {
+                Some((flag, value)) => (flag, value.to_owned()),
+                None => (token, tokens.next()?.to_str().ok()?.to_owned()),
+            };
+            args.push((flag, value));
+        }
+        match name {
+            b"store" => {
+                let mut file = None;
+                for (flag, value) in args {
+                    match flag {
+                        "--file" => file = Some(expand_home(value)?),
+                        _ => return None,
+                    }
+                }
+                Some(Helper::Store(store::Store { file }))
+            }
+            b"cache" => {
+                let mut helper = cache::Cache::default();
+                for (flag, value) in args {
+                    match flag {
+                        "--timeout" => helper.timeout = std::time::Duration::from_secs(value.parse().ok()?),
+                        "--socket" => helper.socket = Some(expand_home(value)?),
+                        _ => return None,
+                    }
+                }
+                Some(Helper::Cache(helper))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Access
+impl Helper {
+    /// The name of the helper as it would be configured in `credential.helper`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Helper::Store(_) => "store",
+            Helper::Cache(_) => "cache",
+        }
+    }
+
+    /// Perform `action` for the credentials in `ctx` and return the credentials that were found if `action` is
+    /// [`Get`](Action::Get).
+    ///
+    /// The signature is compatible with [`program::main()`](crate::program::main()) to allow serving these helpers
+    /// from a program of their own.
+    pub fn handle(&self, action: Action, ctx: Context) -> Result<Option<Context>, Error> {
+        Ok(match self {
+            Helper::Store(store) => store.handle(action, ctx)?,
+            Helper::Cache(cache) => cache.handle(action, ctx)?,
+        })
+    }
+
+    /// Perform `action` and return what a credential helper program would have written to `stdout`.
+    pub(crate) fn invoke(&self, action: &helper::Action) -> Result<Option<Vec<u8>>, helper::Error> {
+        gix_trace::debug!(
+            helper = self.name(),
+            action = action.as_arg(true),
+            "invoking native credential helper"
+        );
+        let (main_action, ctx) = match action {
+            helper::Action::Get(ctx) => (Action::Get, ctx.clone()),
+            helper::Action::Store(payload) => (Action::Store, Context::from_bytes(payload)?),
+            helper::Action::Erase(payload) => (Action::Erase, Context::from_bytes(payload)?),
+        };
+        let ctx = self
+            .handle(main_action, ctx)
+            .map_err(|err| helper::Error::CredentialsHelperFailed {
+                source: std::io::Error::new(std::io::ErrorKind::Other, err),
+            })?;
+        Ok(action
+            .expects_output()
+            .then(|| ctx.map(|ctx| ctx.to_bstring().into()).unwrap_or_default()))
+    }
+}
+
+fn expand_home(path: String) -> Option<std::path::PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(relative) => Some(gix_path::env::home_dir()?.join(relative)),
+        None => Some(path.into()),
+    }
+}
+
+/// Fill in the `protocol`, `host` and `path` of `ctx` from its `url` if these weren't provided, which is
+/// when helpers are invoked directly instead of through a [`Cascade`](crate::helper::Cascade).
+fn destructure_url(mut ctx: Context) -> Result<Context, gix_url::parse::Error> {
+    if ctx.protocol.is_none() && ctx.url.is_some() {
+        let username = ctx.username.take();
+        match ctx.destructure_url_in_place(true) {
+            Ok(_) => {}
+            Err(crate::protocol::Error::UrlParse(err)) => return Err(err),
+            Err(_) => unreachable!("the url is present"),
+        }
+        if ctx.username.is_none() {
+            ctx.username = username;
+        }
+    }
+    Ok(ctx)
+}
+
+/// Return `true` if all fields that are set in `query` are the same in `entry`, with the password only being
+/// considered if `match_password` is `true`.
+fn matches(query: &Context, entry: &Context, match_password: bool) -> bool {
+    fn check<T: PartialEq>(want: &Option<T>, have: &Option<T>) -> bool {
+        want.as_ref().map_or(true, |want| have.as_ref() == Some(want))
+    }
+    check(&query.protocol, &entry.protocol)
+        && check(&query.host, &entry.host)
+        && check(&query.path, &entry.path)
+        && check(&query.username, &entry.username)
+        && (!match_password || check(&query.password, &entry.password))
+}
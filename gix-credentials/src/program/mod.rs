@@ -24,6 +24,9 @@ pub enum Kind {
     },
     /// A script to execute with `sh`.
     ExternalShellScript(BString),
+    /// A helper implemented in Rust for `git credential-<name>` if the `name` and all of its arguments are supported,
+    /// which is invoked as `git credential-<name>` and only used in its place if `git` isn't installed.
+    Native(crate::native::Helper),
 }
 
 /// Initialization
@@ -39,6 +42,9 @@ impl Program {
 
     /// Parse the given input as per the custom helper definition, supporting `!<script>`, `name` and `/absolute/name`, the latter two
     /// also support arguments which are ignored here.
+    ///
+    /// The names `store` and `cache` are [implemented natively](crate::native::Helper) if their arguments are understood,
+    /// to be used if `git` isn't installed.
     pub fn from_custom_definition(input: impl Into<BString>) -> Self {
         fn from_custom_definition_inner(mut input: BString) -> Program {
             let kind = if input.starts_with(b"!") {
@@ -53,6 +59,8 @@ impl Program {
                 );
                 if gix_path::is_absolute(path) {
                     Kind::ExternalPath { path_and_args: input }
+                } else if let Some(helper) = crate::native::Helper::from_name_and_args(input.as_bstr()) {
+                    Kind::Native(helper)
                 } else {
                     Kind::ExternalName { name_and_args: input }
                 }
@@ -67,6 +75,8 @@ impl Program {
     }
 
     /// Convert the program into the respective command, suitable to invoke `action`.
+    ///
+    /// [Native](Kind::Native) helpers are converted into an invocation of their `git` counterpart.
     pub fn to_command(&self, action: &helper::Action) -> std::process::Command {
         let git_program = cfg!(windows).then(|| "git.exe").unwrap_or("git");
         let mut cmd = match &self.kind {
@@ -85,6 +95,25 @@ impl Program {
                     .with_shell_allow_argument_splitting()
                     .into()
             }
+            Kind::Native(helper) => {
+                let mut cmd = Command::new(git_program);
+                cmd.arg(format!("credential-{}", helper.name()));
+                match helper {
+                    crate::native::Helper::Store(store) => {
+                        if let Some(file) = &store.file {
+                            cmd.arg("--file").arg(file);
+                        }
+                    }
+                    crate::native::Helper::Cache(cache) => {
+                        cmd.arg("--timeout").arg(cache.timeout.as_secs().to_string());
+                        if let Some(socket) = &cache.socket {
+                            cmd.arg("--socket").arg(socket);
+                        }
+                    }
+                }
+                cmd.arg(action.as_arg(true));
+                cmd
+            }
             Kind::ExternalShellScript(for_shell)
             | Kind::ExternalPath {
                 path_and_args: for_shell,
@@ -0,0 +1,49 @@
+#[cfg(unix)]
+mod unix {
+    use std::time::Duration;
+
+    use gix_credentials::{
+        native::{cache::Cache, Helper},
+        program::main::Action,
+    };
+    use gix_testtools::tempfile;
+
+    use crate::native::{ctx, ctx_with_identity, identity};
+
+    fn cache_in(dir: &tempfile::TempDir, timeout: Duration) -> Helper {
+        Helper::Cache(Cache {
+            socket: Some(dir.path().join("cache-dir").join("socket")),
+            timeout,
+        })
+    }
+
+    #[test]
+    fn store_get_and_erase() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        crate::native::store_then_get_erase_roundtrip(&cache_in(&dir, Duration::from_secs(60)))
+    }
+
+    #[test]
+    fn credentials_expire() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let helper = cache_in(&dir, Duration::from_secs(1));
+        helper.handle(Action::Store, ctx_with_identity("https://example.com", "user", "pass"))?;
+        assert_eq!(
+            identity(helper.handle(Action::Get, ctx("https://example.com"))?),
+            Some(("user".into(), "pass".into()))
+        );
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(helper.handle(Action::Get, ctx("https://example.com"))?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn get_and_erase_do_not_start_a_daemon() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let helper = cache_in(&dir, Duration::from_secs(60));
+        assert_eq!(helper.handle(Action::Get, ctx("https://example.com"))?, None);
+        assert_eq!(helper.handle(Action::Erase, ctx("https://example.com"))?, None);
+        assert!(!dir.path().join("cache-dir").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+mod cache;
+mod store;
+
+use gix_credentials::{program::main::Action, protocol::Context};
+
+fn ctx(url: &str) -> Context {
+    Context {
+        url: Some(url.into()),
+        ..Default::default()
+    }
+}
+
+fn ctx_with_identity(url: &str, username: &str, password: &str) -> Context {
+    Context {
+        username: Some(username.into()),
+        password: Some(password.into()),
+        ..ctx(url)
+    }
+}
+
+fn identity(ctx: Option<Context>) -> Option<(String, String)> {
+    ctx.map(|ctx| (ctx.username.expect("username"), ctx.password.expect("password")))
+}
+
+fn store_then_get_erase_roundtrip(helper: &gix_credentials::native::Helper) -> crate::Result {
+    assert_eq!(helper.handle(Action::Get, ctx("https://example.com/repo"))?, None);
+    assert_eq!(
+        helper.handle(
+            Action::Store,
+            ctx_with_identity("https://example.com/repo", "user", "pass")
+        )?,
+        None
+    );
+    assert_eq!(
+        identity(helper.handle(Action::Get, ctx("https://example.com/repo"))?),
+        Some(("user".into(), "pass".into()))
+    );
+    assert_eq!(
+        helper.handle(Action::Get, ctx("https://other.example.com/repo"))?,
+        None,
+        "the host must match"
+    );
+    assert_eq!(
+        helper.handle(Action::Get, ctx("https://someone-else@example.com/repo"))?,
+        None,
+        "the username must match if it is known"
+    );
+
+    helper.handle(
+        Action::Store,
+        ctx_with_identity("https://example.com/repo", "user", "new-pass"),
+    )?;
+    assert_eq!(
+        identity(helper.handle(Action::Get, ctx("https://user@example.com/repo"))?),
+        Some(("user".into(), "new-pass".into())),
+        "storing credentials for the same user replaces them"
+    );
+
+    helper.handle(
+        Action::Erase,
+        ctx_with_identity("https://example.com/repo", "user", "outdated"),
+    )?;
+    assert!(
+        helper.handle(Action::Get, ctx("https://example.com/repo"))?.is_some(),
+        "the password must match for erasure if it is given"
+    );
+    helper.handle(Action::Erase, ctx("https://user@example.com/repo"))?;
+    assert_eq!(helper.handle(Action::Get, ctx("https://example.com/repo"))?, None);
+    Ok(())
+}
@@ -108,3 +108,45 @@ fn path_with_simple_args() {
         "a shell is used as there are arguments, and it's generally more flexible, but on windows we split ourselves"
     );
 }
+
+#[test]
+fn store_and_cache_are_native() {
+    use gix_credentials::native::{cache::Cache, store::Store, Helper};
+    let prog = Program::from_custom_definition("store");
+    assert!(matches!(&prog.kind, Kind::Native(Helper::Store(Store { file: None }))));
+
+    let prog = Program::from_custom_definition("store --file=/path/to/creds");
+    assert!(
+        matches!(&prog.kind, Kind::Native(Helper::Store(Store { file: Some(file) })) if file == std::path::Path::new("/path/to/creds"))
+    );
+    assert_eq!(
+        format!("{:?}", prog.to_command(&helper::Action::Store("egal".into()))),
+        format!(r#""{GIT}" "credential-store" "--file" "/path/to/creds" "store""#),
+        "the command is what git would run for the same configuration"
+    );
+
+    let prog = Program::from_custom_definition("cache --timeout 300 --socket /path/to/socket");
+    match &prog.kind {
+        Kind::Native(Helper::Cache(Cache { socket, timeout })) => {
+            assert_eq!(socket.as_deref(), Some(std::path::Path::new("/path/to/socket")));
+            assert_eq!(timeout.as_secs(), 300);
+        }
+        other => unreachable!("unexpected kind: {other:?}"),
+    }
+}
+
+#[test]
+fn store_and_cache_with_unknown_arguments_are_external() {
+    for input in [
+        "store --unknown",
+        "store --file",
+        "cache --timeout=soon",
+        "store --file \"/path with spaces\"",
+    ] {
+        let prog = Program::from_custom_definition(input);
+        assert!(
+            matches!(&prog.kind, Kind::ExternalName{name_and_args} if name_and_args == input),
+            "{input}: it's up to git to understand the arguments"
+        );
+    }
+}
@@ -93,7 +93,15 @@ mod relative {
     use crate::parse::Error;
 
     fn parse_inner(input: &str) -> Option<Duration> {
-        let mut split = input.split_whitespace();
+        match input {
+            "now" => return Some(Duration::ZERO),
+            "yesterday" => return Some(Duration::days(1)),
+            _ => {}
+        }
+        // Like git, allow dots as separators, as in `1.day.ago`.
+        let mut split = input
+            .split(|c: char| c.is_whitespace() || c == '.')
+            .filter(|token| !token.is_empty());
         let multiplier = i64::from_str(split.next()?).ok()?;
         let period = split.next()?;
         if split.next()? != "ago" {
@@ -139,5 +147,12 @@ mod relative {
         fn two_weeks_ago() {
             assert_eq!(parse_inner("2 weeks ago"), Some(Duration::weeks(2)));
         }
+
+        #[test]
+        fn named_and_dot_separated() {
+            assert_eq!(parse_inner("now"), Some(Duration::ZERO));
+            assert_eq!(parse_inner("yesterday"), Some(Duration::days(1)));
+            assert_eq!(parse_inner("1.day.ago"), Some(Duration::days(1)));
+        }
     }
 }
@@ -146,6 +146,18 @@ mod relative {
         assert_eq!(date.seconds, -1);
     }
 
+    #[test]
+    fn yesterday_and_now() {
+        let now = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60 * 60 * 24 * 2);
+        assert_eq!(gix_date::parse("now", Some(now)).unwrap().seconds, 60 * 60 * 24 * 2);
+        assert_eq!(gix_date::parse("yesterday", Some(now)).unwrap().seconds, 60 * 60 * 24);
+        assert_eq!(
+            gix_date::parse("2.days.ago", Some(now)).unwrap().seconds,
+            0,
+            "dots can separate words"
+        );
+    }
+
     #[test]
     fn various() {
         let now = SystemTime::now();
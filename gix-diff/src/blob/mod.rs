@@ -11,6 +11,9 @@ pub mod pipeline;
 ///
 pub mod platform;
 
+pub mod unified_diff;
+pub use unified_diff::UnifiedDiff;
+
 /// Information about the diff performed to detect similarity.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct DiffLineStats {
@@ -62,6 +65,12 @@ pub struct Driver {
     /// If `Some(false)`, it won't be considered binary, and the its data will not be sampled for the null-byte either.
     /// Leaving it to `None` means binary detection is automatic, and is based on the presence of the `0` byte in the first 8kB of the buffer.
     pub is_binary: Option<bool>,
+    /// The newline-separated regular expressions to find the line shown in hunk headers, typically the function that
+    /// contains the hunk, as configured with `diff.<driver>.xfuncname`.
+    /// See [`unified_diff::FunctionName::from_xfuncname()`] for details.
+    ///
+    /// Note that unlike `git`, we don't provide built-in patterns for well-known languages.
+    pub function_name_patterns: Option<BString>,
 }
 
 /// A conversion pipeline to take an object or path from what's stored in `git` to what can be diffed, while
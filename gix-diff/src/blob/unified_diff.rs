@@ -0,0 +1,350 @@
+//! Render the changes between two blobs as hunks in the unified diff format, like `git diff` does.
+use std::ops::Range;
+
+use bstr::BString;
+use imara_diff::{intern::InternedInput, Sink};
+
+/// The maximum length of the function name shown in hunk headers, which is what `git` uses as well.
+const MAX_FUNCTION_NAME_LEN: usize = 80;
+
+/// Options for use in [`UnifiedDiff::new()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of unchanged lines to show before and after each change, similar to `git diff -U<n>`. Defaults to 3.
+    ///
+    /// Changes that are separated by no more than twice this amount of lines are shown in the same hunk.
+    pub context_lines: u32,
+    /// If `true`, changed lines are diffed once more to show which words changed, as `[-removed-]{+added+}`,
+    /// similar to `git diff --word-diff=plain`. Unchanged lines are shown without prefix.
+    pub word_diff: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            word_diff: false,
+        }
+    }
+}
+
+/// A way to find the line that best describes the context of a hunk, shown after its `@@ … @@` header.
+///
+/// It's what `git` calls the *function name*, which is configured per diff driver with `diff.<driver>.xfuncname`.
+#[derive(Debug, Clone, Default)]
+pub enum FunctionName {
+    /// Use lines that start with an alphabetic character, `_` or `$`, which is what `git` does if no pattern is configured.
+    #[default]
+    Default,
+    /// Use lines matching one of the given patterns, tried in order until the first one matches.
+    Patterns(Vec<function_name::Pattern>),
+}
+
+///
+pub mod function_name {
+    use bstr::BStr;
+
+    use super::{FunctionName, MAX_FUNCTION_NAME_LEN};
+
+    /// A single pattern of a [`FunctionName::Patterns`] list.
+    #[derive(Debug, Clone)]
+    pub struct Pattern {
+        /// The regular expression to match lines against.
+        pub regex: regex::bytes::Regex,
+        /// If `true`, a matching line is not a function name and matching stops, as indicated by a leading `!` in the pattern.
+        pub negated: bool,
+    }
+
+    impl FunctionName {
+        /// Parse `patterns` the way `git` parses the value of `diff.<driver>.xfuncname`, i.e. as newline-separated extended
+        /// regular expressions, where those starting with `!` negate the match.
+        ///
+        /// If a pattern has a capture group, its first group is used as function name, otherwise it's the whole match.
+        pub fn from_xfuncname(patterns: &BStr) -> Result<Self, regex::Error> {
+            Ok(FunctionName::Patterns(
+                patterns
+                    .split(|b| *b == b'\n')
+                    .map(|pattern| {
+                        let (pattern, negated) = match pattern.strip_prefix(b"!") {
+                            Some(pattern) => (pattern, true),
+                            None => (pattern, false),
+                        };
+                        Ok(Pattern {
+                            regex: regex::bytes::Regex::new(&String::from_utf8_lossy(pattern))?,
+                            negated,
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+
+        /// Return the function name contained in `line`, which may end in a newline, or `None` if it doesn't define a function.
+        pub fn find<'a>(&self, line: &'a [u8]) -> Option<&'a BStr> {
+            let name = match self {
+                FunctionName::Default => {
+                    let first = *line.first()?;
+                    if !(first.is_ascii_alphabetic() || first == b'_' || first == b'$') {
+                        return None;
+                    }
+                    line
+                }
+                FunctionName::Patterns(patterns) => {
+                    let line = line
+                        .strip_suffix(b"\n")
+                        .map_or(line, |line| line.strip_suffix(b"\r").unwrap_or(line));
+                    let pattern = patterns.iter().find(|p| p.regex.is_match(line))?;
+                    if pattern.negated {
+                        return None;
+                    }
+                    let captures = pattern.regex.captures(line)?;
+                    captures.get(1).or_else(|| captures.get(0))?.as_bytes()
+                }
+            };
+            let name = &name[..name.len().min(MAX_FUNCTION_NAME_LEN)];
+            let end = name.iter().rposition(|b| !is_space(*b)).map_or(0, |pos| pos + 1);
+            Some(name[..end].into())
+        }
+    }
+
+    /// Return `true` if `b` is whitespace as `git` sees it.
+    pub(super) fn is_space(b: u8) -> bool {
+        matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+    }
+}
+
+/// A [`Sink`] to render all changes between two blobs in the unified diff format, as `git diff` does after the file header,
+/// so each hunk starts with `@@ -<old-start>,<old-count> +<new-start>,<new-count> @@ <function-name>`.
+///
+/// It's meant to be used with an [`InternedInput`] made from lines including their line terminators, like the one
+/// returned by [`prepare_diff::Outcome::interned_input()`](crate::blob::platform::prepare_diff::Outcome::interned_input()).
+pub struct UnifiedDiff<'a> {
+    input: &'a InternedInput<&'a [u8]>,
+    options: Options,
+    function_name: Option<&'a FunctionName>,
+    /// The changes of the hunk that is currently assembled, as `(before, after)` ranges.
+    changes: Vec<(Range<u32>, Range<u32>)>,
+    /// The function name of the last hunk, reused if no new one is found.
+    last_function_name: BString,
+    /// The lines in the old version that were already searched for function names, from the beginning of the file.
+    searched_lines: u32,
+    out: BString,
+}
+
+/// Lifecycle
+impl<'a> UnifiedDiff<'a> {
+    /// Create a new instance to render the changes between the lines in `input`, configured by `options`.
+    /// If `function_name` is set, it's used to find the line to show after the header of each hunk by searching
+    /// backwards through the lines of the old version, starting right before the first line of the hunk.
+    pub fn new(input: &'a InternedInput<&'a [u8]>, options: Options, function_name: Option<&'a FunctionName>) -> Self {
+        UnifiedDiff {
+            input,
+            options,
+            function_name,
+            changes: Vec::new(),
+            last_function_name: BString::default(),
+            searched_lines: 0,
+            out: BString::default(),
+        }
+    }
+}
+
+impl Sink for UnifiedDiff<'_> {
+    type Out = BString;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        if let Some((last_before, _)) = self.changes.last() {
+            if before.start - last_before.end > 2 * self.options.context_lines {
+                self.write_hunk();
+            }
+        }
+        self.changes.push((before, after));
+    }
+
+    fn finish(mut self) -> Self::Out {
+        self.write_hunk();
+        self.out
+    }
+}
+
+impl UnifiedDiff<'_> {
+    fn line(&self, token: imara_diff::intern::Token) -> &[u8] {
+        self.input.interner[token]
+    }
+
+    fn write_hunk(&mut self) {
+        let (Some((first_before, first_after)), Some((last_before, last_after))) =
+            (self.changes.first().cloned(), self.changes.last().cloned())
+        else {
+            return;
+        };
+        let context = self.options.context_lines;
+        let before =
+            first_before.start.saturating_sub(context)..(last_before.end + context).min(self.input.before.len() as u32);
+        let after =
+            first_after.start.saturating_sub(context)..(last_after.end + context).min(self.input.after.len() as u32);
+
+        if let Some(function_name) = self.function_name {
+            let input = self.input;
+            if let Some(name) = (self.searched_lines..before.start)
+                .rev()
+                .find_map(|line| function_name.find(input.interner[input.before[line as usize]]))
+            {
+                self.last_function_name = name.to_owned();
+            }
+            self.searched_lines = before.start;
+        }
+
+        self.out.extend_from_slice(b"@@ -");
+        write_range(&mut self.out, &before);
+        self.out.extend_from_slice(b" +");
+        write_range(&mut self.out, &after);
+        self.out.extend_from_slice(b" @@");
+        if self.function_name.is_some() && !self.last_function_name.is_empty() {
+            self.out.push(b' ');
+            self.out.extend_from_slice(&self.last_function_name);
+        }
+        self.out.push(b'\n');
+
+        let mut line_in_after = after.start;
+        let changes = std::mem::take(&mut self.changes);
+        for (change_before, change_after) in &changes {
+            self.write_context(line_in_after..change_after.start);
+            if self.options.word_diff {
+                let removed: Vec<u8> = self.lines(&self.input.before, change_before.clone()).concat();
+                let added: Vec<u8> = self.lines(&self.input.after, change_after.clone()).concat();
+                write_word_diff(&mut self.out, &removed, &added);
+            } else {
+                for line in change_before.clone() {
+                    self.write_line(b'-', self.input.before[line as usize]);
+                }
+                for line in change_after.clone() {
+                    self.write_line(b'+', self.input.after[line as usize]);
+                }
+            }
+            line_in_after = change_after.end;
+        }
+        self.write_context(line_in_after..after.end);
+    }
+
+    fn lines(&self, tokens: &[imara_diff::intern::Token], range: Range<u32>) -> Vec<&[u8]> {
+        tokens[range.start as usize..range.end as usize]
+            .iter()
+            .map(|token| self.line(*token))
+            .collect()
+    }
+
+    fn write_context(&mut self, lines: Range<u32>) {
+        for line in lines {
+            let token = self.input.after[line as usize];
+            if self.options.word_diff {
+                let line = self.input.interner[token];
+                self.out.extend_from_slice(line);
+                if !line.ends_with(b"\n") {
+                    self.out.push(b'\n');
+                }
+            } else {
+                self.write_line(b' ', token);
+            }
+        }
+    }
+
+    fn write_line(&mut self, prefix: u8, token: imara_diff::intern::Token) {
+        let line = self.input.interner[token];
+        self.out.push(prefix);
+        self.out.extend_from_slice(line);
+        if !line.ends_with(b"\n") {
+            self.out.extend_from_slice(b"\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Write `range` as `<start>,<count>` as used in hunk headers, where the count is omitted if it's 1, and where
+/// the start refers to the line before the hunk if it is empty.
+fn write_range(out: &mut BString, range: &Range<u32>) {
+    use std::io::Write;
+    let count = range.len();
+    let start = if count == 0 { range.start } else { range.start + 1 };
+    write!(out, "{start}").expect("writing to memory never fails");
+    if count != 1 {
+        write!(out, ",{count}").expect("writing to memory never fails");
+    }
+}
+
+/// Write the words that changed between the `removed` and `added` lines as `[-removed-]` and `{+added+}`, with unchanged
+/// text taken from `added`. Words are separated by whitespace.
+fn write_word_diff(out: &mut BString, removed: &[u8], added: &[u8]) {
+    let start = out.len();
+    if added.is_empty() {
+        write_words(out, removed, "[-", "-]");
+    } else {
+        let removed_words = words(removed);
+        let added_words = words(added);
+        let mut input = InternedInput::default();
+        input.update_before(removed_words.iter().map(|range| &removed[range.clone()]));
+        input.update_after(added_words.iter().map(|range| &added[range.clone()]));
+
+        let bounds = |words: &[Range<usize>], tokens: Range<u32>| {
+            if tokens.is_empty() {
+                let end = tokens
+                    .start
+                    .checked_sub(1)
+                    .map_or(0, |previous| words[previous as usize].end);
+                end..end
+            } else {
+                words[tokens.start as usize].start..words[tokens.end as usize - 1].end
+            }
+        };
+        let mut unchanged_start = 0;
+        imara_diff::diff(
+            imara_diff::Algorithm::Myers,
+            &input,
+            |before: Range<u32>, after: Range<u32>| {
+                let removed_range = bounds(&removed_words, before);
+                let added_range = bounds(&added_words, after);
+                write_words(out, &added[unchanged_start..added_range.start], "", "");
+                write_words(out, &removed[removed_range], "[-", "-]");
+                write_words(out, &added[added_range.clone()], "{+", "+}");
+                unchanged_start = added_range.end;
+            },
+        );
+        write_words(out, &added[unchanged_start..], "", "");
+    }
+    if out.len() > start && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+}
+
+/// Write each line of `text` to `out`, surrounded by `prefix` and `suffix` unless it is empty.
+fn write_words(out: &mut BString, text: &[u8], prefix: &str, suffix: &str) {
+    let mut lines = text.split(|b| *b == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        if !line.is_empty() {
+            out.extend_from_slice(prefix.as_bytes());
+            out.extend_from_slice(line);
+            out.extend_from_slice(suffix.as_bytes());
+        }
+        if lines.peek().is_some() {
+            out.push(b'\n');
+        }
+    }
+}
+
+/// Return the ranges of all words in `text`, which are runs of non-whitespace.
+fn words(text: &[u8]) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (pos, b) in text.iter().enumerate() {
+        match (function_name::is_space(*b), start) {
+            (true, Some(word_start)) => {
+                out.push(word_start..pos);
+                start = None;
+            }
+            (false, None) => start = Some(pos),
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        out.push(word_start..text.len());
+    }
+    out
+}
@@ -49,3 +49,7 @@ pub mod tree;
 ///
 #[cfg(feature = "blob")]
 pub mod blob;
+
+/// Render changes as patches like `git diff` does, or summarize them.
+#[cfg(feature = "blob")]
+pub mod patch;
@@ -0,0 +1,149 @@
+use std::{collections::HashMap, io::Write};
+
+/// The most bytes encoded on a single line of a binary patch.
+const MAX_BYTES_PER_LINE: usize = 52;
+/// The characters used by git's base85 encoding.
+const BASE85: &[u8; 85] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Write a binary patch that turns `old` into `new` to `out`, followed by the reverse patch that turns `new` into `old`,
+/// as `git diff --binary` does.
+///
+/// Each direction is written as the zlib-compressed, base85-encoded content of its result, or as delta against its base,
+/// whichever is smaller.
+///
+/// ### Deviation
+///
+/// Deltas are computed with a simpler algorithm than the one `git` uses, and compression levels may differ as well,
+/// so the encoded data isn't the same as the one produced by `git`, even though both apply in the same way.
+pub fn write(old: &[u8], new: &[u8], mut out: impl Write) -> std::io::Result<()> {
+    out.write_all(b"GIT binary patch\n")?;
+    write_hunk(old, new, &mut out)?;
+    write_hunk(new, old, &mut out)
+}
+
+/// Write the data to obtain `new` from `old`, either as literal or as delta.
+fn write_hunk(old: &[u8], new: &[u8], out: &mut dyn Write) -> std::io::Result<()> {
+    let literal = deflate(new)?;
+    let delta = if old.is_empty() || new.is_empty() {
+        None
+    } else {
+        let delta = delta(old, new);
+        Some((delta.len(), deflate(&delta)?))
+    };
+    let (kind, size, data) = match delta {
+        Some((size, data)) if data.len() < literal.len() => ("delta", size, data),
+        _ => ("literal", new.len(), literal),
+    };
+
+    writeln!(out, "{kind} {size}")?;
+    let mut line = Vec::with_capacity(1 + MAX_BYTES_PER_LINE / 4 * 5);
+    for chunk in data.chunks(MAX_BYTES_PER_LINE) {
+        line.clear();
+        let len = chunk.len() as u8;
+        line.push(if len <= 26 { b'A' + len - 1 } else { b'a' + len - 27 });
+        encode_base85(chunk, &mut line);
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    out.write_all(b"\n")
+}
+
+fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = gix_features::zlib::stream::deflate::Write::new(Vec::new());
+    out.write_all(data)?;
+    out.flush()?;
+    Ok(out.into_inner())
+}
+
+/// Append the base85-encoding of `data` to `out`, where each group of 4 bytes, padded with zeroes, yields 5 characters.
+fn encode_base85(data: &[u8], out: &mut Vec<u8>) {
+    for group in data.chunks(4) {
+        let mut acc = group
+            .iter()
+            .chain(std::iter::repeat(&0))
+            .take(4)
+            .fold(0u32, |acc, byte| acc << 8 | u32::from(*byte));
+        let mut encoded = [0; 5];
+        for char in encoded.iter_mut().rev() {
+            *char = BASE85[(acc % 85) as usize];
+            acc /= 85;
+        }
+        out.extend_from_slice(&encoded);
+    }
+}
+
+/// Return a delta in the format also used in packs, which turns `base` into `target` by copying ranges of `base` and
+/// inserting the bytes that can't be found in it.
+fn delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 16;
+    let mut out = Vec::new();
+    encode_size(base.len(), &mut out);
+    encode_size(target.len(), &mut out);
+
+    let mut offset_by_block = HashMap::new();
+    for (idx, block) in base.chunks_exact(BLOCK).enumerate() {
+        offset_by_block.entry(block).or_insert(idx * BLOCK);
+    }
+
+    let (mut pos, mut unmatched_start) = (0, 0);
+    while pos + BLOCK <= target.len() {
+        let Some(&offset) = offset_by_block.get(&target[pos..][..BLOCK]) else {
+            pos += 1;
+            continue;
+        };
+        let (mut start, mut offset) = (pos, offset);
+        while start > unmatched_start && offset > 0 && base[offset - 1] == target[start - 1] {
+            start -= 1;
+            offset -= 1;
+        }
+        let mut end = pos + BLOCK;
+        while end < target.len() && offset + (end - start) < base.len() && base[offset + (end - start)] == target[end] {
+            end += 1;
+        }
+        insert(&target[unmatched_start..start], &mut out);
+        copy(offset, end - start, &mut out);
+        pos = end;
+        unmatched_start = end;
+    }
+    insert(&target[unmatched_start..], &mut out);
+    out
+}
+
+fn encode_size(mut size: usize, out: &mut Vec<u8>) {
+    while size >= 0x80 {
+        out.push(size as u8 | 0x80);
+        size >>= 7;
+    }
+    out.push(size as u8);
+}
+
+fn insert(data: &[u8], out: &mut Vec<u8>) {
+    for chunk in data.chunks(0x7f) {
+        out.push(chunk.len() as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+fn copy(mut offset: usize, mut len: usize, out: &mut Vec<u8>) {
+    while len != 0 {
+        let size = len.min(0x10000);
+        let cmd_pos = out.len();
+        let mut cmd = 0x80;
+        out.push(cmd);
+        for (idx, byte) in (offset as u32).to_le_bytes().into_iter().enumerate() {
+            if byte != 0 {
+                cmd |= 1 << idx;
+                out.push(byte);
+            }
+        }
+        for (idx, byte) in (size as u32).to_le_bytes().into_iter().take(3).enumerate() {
+            if byte != 0 {
+                cmd |= 0x10 << idx;
+                out.push(byte);
+            }
+        }
+        out[cmd_pos] = cmd;
+        offset += size;
+        len -= size;
+    }
+}
@@ -0,0 +1,257 @@
+use std::io::Write;
+
+use bstr::BStr;
+use gix_object::{tree::EntryMode, FindExt};
+
+use crate::{
+    blob::{platform::prepare_diff::Operation, unified_diff, Platform, ResourceKind, UnifiedDiff},
+    patch::{self, Change, Error, Options, Resource},
+};
+
+/// Write `change` to `out` as `git diff` would, with a `diff --git` header, extended headers for mode changes, renames and copies,
+/// and the changed lines as rendered by [`UnifiedDiff`] according to `options`, or a note or [binary patch](patch::binary::write())
+/// if the file is binary.
+///
+/// Use `resource_cache` to obtain the data to diff, with `objects` providing blobs, and return the statistics of the change to be used
+/// in summaries.
+///
+/// ### Deviation
+///
+/// Paths are written verbatim, even if `git` would quote them.
+pub fn write(
+    change: Change<'_>,
+    resource_cache: &mut Platform,
+    objects: &impl gix_object::FindObjectOrHeader,
+    options: Options,
+    mut out: impl Write,
+) -> Result<patch::stat::File, Error> {
+    let previous = change.previous();
+    let current = change.current();
+    let (a, b) = match (previous, current) {
+        (Some(previous), Some(current)) => (previous.location, current.location),
+        (Some(resource), None) | (None, Some(resource)) => (resource.location, resource.location),
+        (None, None) => unreachable!("changes always have at least one side"),
+    };
+    writeln!(out, "diff --git a/{a} b/{b}")?;
+    match change {
+        Change::Addition(current) => writeln!(out, "new file mode {}", mode(current.mode))?,
+        Change::Deletion(previous) => writeln!(out, "deleted file mode {}", mode(previous.mode))?,
+        Change::Modification { previous, current }
+        | Change::Rewrite {
+            source: previous,
+            destination: current,
+            ..
+        } => {
+            if previous.mode.kind() != current.mode.kind() {
+                writeln!(out, "old mode {}", mode(previous.mode))?;
+                writeln!(out, "new mode {}", mode(current.mode))?;
+            }
+        }
+    }
+    if let Change::Rewrite {
+        source,
+        destination,
+        copy,
+        similarity,
+    } = change
+    {
+        let kind = if copy { "copy" } else { "rename" };
+        writeln!(out, "similarity index {}%", (similarity * 100.0) as u32)?;
+        writeln!(out, "{kind} from {}", source.location)?;
+        writeln!(out, "{kind} to {}", destination.location)?;
+    }
+
+    let null = previous
+        .or(current)
+        .expect("changes always have at least one side")
+        .id
+        .kind()
+        .null();
+    let previous_id = previous.map_or(null.as_ref(), |r| r.id);
+    let current_id = current.map_or(null.as_ref(), |r| r.id);
+    if previous_id == current_id {
+        return Ok(patch::stat::File {
+            name: change.display_name(),
+            counts: patch::stat::Counts::Lines {
+                insertions: 0,
+                deletions: 0,
+            },
+        });
+    }
+    let (counts, hunks) = diff(change, resource_cache, objects, Some(options.diff))?;
+    let binary_patch = options.binary && matches!(counts, patch::stat::Counts::Binary { .. });
+    let hex_len = if binary_patch {
+        previous_id.kind().len_in_hex()
+    } else {
+        options.hex_len
+    };
+    write!(
+        out,
+        "index {}..{}",
+        previous_id.to_hex_with_len(hex_len),
+        current_id.to_hex_with_len(hex_len)
+    )?;
+    match (previous, current) {
+        (Some(previous), Some(current)) if previous.mode.kind() == current.mode.kind() => {
+            writeln!(out, " {}", mode(current.mode))?
+        }
+        _ => writeln!(out)?,
+    }
+
+    let a = previous.map_or_else(|| "/dev/null".into(), |_| format!("a/{a}"));
+    let b = current.map_or_else(|| "/dev/null".into(), |_| format!("b/{b}"));
+    match counts {
+        patch::stat::Counts::Binary { .. } if binary_patch => {
+            let (mut old, mut new) = (Vec::new(), Vec::new());
+            let old = blob_data(previous, objects, &mut old)?;
+            let new = blob_data(current, objects, &mut new)?;
+            patch::binary::write(old, new, &mut out)?;
+        }
+        patch::stat::Counts::Binary { .. } => writeln!(out, "Binary files {a} and {b} differ")?,
+        patch::stat::Counts::Lines { .. } => {
+            if !hunks.is_empty() {
+                writeln!(out, "--- {a}")?;
+                writeln!(out, "+++ {b}")?;
+                out.write_all(&hunks)?;
+            }
+        }
+    }
+    Ok(patch::stat::File {
+        name: change.display_name(),
+        counts,
+    })
+}
+
+/// Compute the statistics of `change` for use in summaries, using `resource_cache` to obtain the data to diff, with `objects`
+/// providing blobs.
+pub fn stat(
+    change: Change<'_>,
+    resource_cache: &mut Platform,
+    objects: &impl gix_object::FindObjectOrHeader,
+) -> Result<patch::stat::File, Error> {
+    let counts = match (change.previous(), change.current()) {
+        (Some(previous), Some(current)) if previous.id == current.id => patch::stat::Counts::Lines {
+            insertions: 0,
+            deletions: 0,
+        },
+        _ => diff(change, resource_cache, objects, None)?.0,
+    };
+    Ok(patch::stat::File {
+        name: change.display_name(),
+        counts,
+    })
+}
+
+/// Diff both sides of `change` and return the statistics, along with the rendered hunks if `options` are set.
+fn diff(
+    change: Change<'_>,
+    resource_cache: &mut Platform,
+    objects: &impl gix_object::FindObjectOrHeader,
+    options: Option<unified_diff::Options>,
+) -> Result<(patch::stat::Counts, Vec<u8>), Error> {
+    let previous = change.previous();
+    let current = change.current();
+    let either = previous.or(current).expect("changes always have at least one side");
+    let null = either.id.kind().null();
+    let set = |resource_cache: &mut Platform, resource: Option<Resource<'_>>, kind| {
+        let resource = resource.unwrap_or(Resource {
+            id: null.as_ref(),
+            ..either
+        });
+        resource_cache.set_resource(
+            resource.id.to_owned(),
+            resource.mode.kind(),
+            resource.location,
+            kind,
+            objects,
+        )
+    };
+    set(resource_cache, previous, ResourceKind::OldOrSource)?;
+    set(resource_cache, current, ResourceKind::NewOrDestination)?;
+
+    let function_name = match options {
+        Some(_) => function_name(resource_cache)?,
+        None => None,
+    };
+    let outcome = resource_cache.prepare_diff()?;
+    match outcome.operation {
+        Operation::InternalDiff { algorithm } => {
+            let input = outcome.interned_input();
+            let (insertions, deletions, hunks) = match options {
+                Some(options) => {
+                    let function_name = function_name.unwrap_or_default();
+                    let counter = crate::blob::diff(
+                        algorithm,
+                        &input,
+                        crate::blob::sink::Counter::new(UnifiedDiff::new(&input, options, Some(&function_name))),
+                    );
+                    (counter.insertions, counter.removals, counter.wrapped.into())
+                }
+                None => {
+                    let counter = crate::blob::diff(algorithm, &input, crate::blob::sink::Counter::new(()));
+                    (counter.insertions, counter.removals, Vec::new())
+                }
+            };
+            Ok((patch::stat::Counts::Lines { insertions, deletions }, hunks))
+        }
+        Operation::SourceOrDestinationIsBinary => {
+            let size = |data: crate::blob::platform::resource::Data<'_>| match data {
+                crate::blob::platform::resource::Data::Missing => 0,
+                crate::blob::platform::resource::Data::Buffer(buf) => buf.len() as u64,
+                crate::blob::platform::resource::Data::Binary { size } => size,
+            };
+            Ok((
+                patch::stat::Counts::Binary {
+                    old_size: size(outcome.old.data),
+                    new_size: size(outcome.new.data),
+                },
+                Vec::new(),
+            ))
+        }
+        Operation::ExternalCommand { command } => Err(Error::ExternalCommand {
+            location: either.location.to_owned(),
+            command: command.to_owned(),
+        }),
+    }
+}
+
+/// Return the function name matcher configured in the driver of the old resource, or of the new one, or `None` if there is none.
+fn function_name(resource_cache: &Platform) -> Result<Option<unified_diff::FunctionName>, Error> {
+    let Some((old, new)) = resource_cache.resources() else {
+        return Ok(None);
+    };
+    let drivers = resource_cache.filter.drivers();
+    [old.driver_index, new.driver_index]
+        .into_iter()
+        .flatten()
+        .map(|idx| &drivers[idx])
+        .find_map(|driver| {
+            driver
+                .function_name_patterns
+                .as_ref()
+                .map(|patterns| (driver.name.as_ref(), patterns.as_ref()))
+        })
+        .map(|(driver, patterns): (&BStr, &BStr)| {
+            unified_diff::FunctionName::from_xfuncname(patterns).map_err(|source| Error::FunctionNamePatterns {
+                driver: driver.to_owned(),
+                source,
+            })
+        })
+        .transpose()
+}
+
+/// Return the unfiltered content of the blob of `resource`, or nothing if there is none.
+fn blob_data<'b>(
+    resource: Option<Resource<'_>>,
+    objects: &impl gix_object::FindObjectOrHeader,
+    buf: &'b mut Vec<u8>,
+) -> Result<&'b [u8], Error> {
+    Ok(match resource {
+        Some(resource) => objects.find_blob(resource.id, buf)?.data,
+        None => &[],
+    })
+}
+
+fn mode(mode: EntryMode) -> String {
+    format!("{:06o}", mode.kind() as u16)
+}
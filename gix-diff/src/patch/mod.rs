@@ -0,0 +1,126 @@
+use bstr::{BStr, BString};
+use gix_object::tree::EntryMode;
+
+use crate::blob::unified_diff;
+
+/// One side of a [`Change`], identifying a version of a file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Resource<'a> {
+    /// The repository-relative location of the file.
+    pub location: &'a BStr,
+    /// The id of the blob.
+    pub id: &'a gix_hash::oid,
+    /// The kind of entry in the tree.
+    pub mode: EntryMode,
+}
+
+/// A change of a file between two trees, for rendering it as patch or for summarizing it.
+///
+/// It's typically created from changes seen when diffing trees with [`tree::Changes`](crate::tree::Changes), possibly with
+/// rewrites found by a [`Tracker`](crate::rewrites::Tracker).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Change<'a> {
+    /// The file was added.
+    Addition(Resource<'a>),
+    /// The file was deleted.
+    Deletion(Resource<'a>),
+    /// The file's content or mode changed, or both.
+    Modification {
+        /// The file before the change.
+        previous: Resource<'a>,
+        /// The file after the change, at the same location as `previous`.
+        current: Resource<'a>,
+    },
+    /// The file at `source` was renamed or copied to `destination`, possibly with changes.
+    Rewrite {
+        /// The file before it was renamed or copied.
+        source: Resource<'a>,
+        /// The file after it was renamed or copied.
+        destination: Resource<'a>,
+        /// If `true`, `source` was copied, and it was renamed otherwise.
+        copy: bool,
+        /// The similarity of `source` and `destination`, from 0.0 to 1.0, where 1.0 means they are identical.
+        similarity: f32,
+    },
+}
+
+impl<'a> Change<'a> {
+    /// Return the file before the change, or `None` if it was added.
+    pub fn previous(&self) -> Option<Resource<'a>> {
+        match self {
+            Change::Addition(_) => None,
+            Change::Deletion(previous) | Change::Modification { previous, .. } => Some(*previous),
+            Change::Rewrite { source, .. } => Some(*source),
+        }
+    }
+
+    /// Return the file after the change, or `None` if it was deleted.
+    pub fn current(&self) -> Option<Resource<'a>> {
+        match self {
+            Change::Deletion(_) => None,
+            Change::Addition(current) | Change::Modification { current, .. } => Some(*current),
+            Change::Rewrite { destination, .. } => Some(*destination),
+        }
+    }
+
+    /// Return the name to use for this change in summaries, which is its location, or `old => new` for rewrites.
+    /// Common leading and trailing path components are only shown once, like `dir/{old => new}/file`.
+    pub fn display_name(&self) -> BString {
+        match self {
+            Change::Rewrite {
+                source, destination, ..
+            } => stat::rewrite_name(source.location, destination.location),
+            Change::Addition(resource)
+            | Change::Deletion(resource)
+            | Change::Modification { current: resource, .. } => resource.location.to_owned(),
+        }
+    }
+}
+
+/// Options for use in [`write()`](crate::patch::write()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Control how the changed lines of each file are rendered.
+    pub diff: unified_diff::Options,
+    /// The amount of hexadecimal characters to use for the abbreviated object ids on `index` lines. Defaults to 7.
+    pub hex_len: usize,
+    /// If `true`, changes to binary files are written as [binary patch](binary::write()) that can be applied, instead of
+    /// just noting that they differ, with full object ids on their `index` lines, like `git diff --binary` does.
+    pub binary: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            diff: Default::default(),
+            hex_len: 7,
+            binary: false,
+        }
+    }
+}
+
+/// The error returned by [`write()`](crate::patch::write()) and [`stat()`](crate::patch::stat()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    SetResource(#[from] crate::blob::platform::set_resource::Error),
+    #[error(transparent)]
+    PrepareDiff(#[from] crate::blob::platform::prepare_diff::Error),
+    #[error("Changes of '{location}' should be shown by the external diff command '{command}'")]
+    ExternalCommand { location: BString, command: BString },
+    #[error("The function name patterns of diff driver '{driver}' are invalid")]
+    FunctionNamePatterns { driver: BString, source: regex::Error },
+    #[error("Could not read blob to write a binary patch")]
+    FindBlob(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not write patch")]
+    Io(#[from] std::io::Error),
+}
+
+pub(crate) mod function;
+pub use function::{stat, write};
+
+///
+pub mod binary;
+///
+pub mod stat;
@@ -0,0 +1,221 @@
+use std::io::Write;
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// The statistics of a single changed file, as returned by [`write()`](crate::patch::write()) and [`stat()`](crate::patch::stat()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The name to show for the file, see [`Change::display_name()`](super::Change::display_name()).
+    pub name: BString,
+    /// The amount of changes.
+    pub counts: Counts,
+}
+
+/// The amount of changes of a [`File`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Counts {
+    /// The file could be diffed line by line.
+    Lines {
+        /// The amount of added lines.
+        insertions: u32,
+        /// The amount of removed lines.
+        deletions: u32,
+    },
+    /// One version of the file was binary, so only the sizes of both versions are known.
+    Binary {
+        /// The size of the previous version in bytes, or 0 if there is none.
+        old_size: u64,
+        /// The size of the current version in bytes, or 0 if there is none.
+        new_size: u64,
+    },
+}
+
+/// Write one line per file in `files` to `out` as `git diff --numstat` does, i.e. as `<insertions>\t<deletions>\t<name>`,
+/// where binary files have `-` instead of counts.
+pub fn write_numstat(files: &[File], mut out: impl Write) -> std::io::Result<()> {
+    for file in files {
+        match file.counts {
+            Counts::Lines { insertions, deletions } => writeln!(out, "{insertions}\t{deletions}\t{}", file.name)?,
+            Counts::Binary { .. } => writeln!(out, "-\t-\t{}", file.name)?,
+        }
+    }
+    Ok(())
+}
+
+/// Write a line per file in `files` to `out` with the amount of changed lines and a graph of `+` and `-` scaled to fit in
+/// `width` columns, followed by a summary of all changes, as `git diff --stat` does.
+///
+/// Nothing is written if `files` is empty.
+pub fn write(files: &[File], width: usize, mut out: impl Write) -> std::io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let decimal_width = |n: u64| n.to_string().len();
+
+    let mut max_name_len = 0;
+    let mut max_change = 0;
+    let mut number_width = 0;
+    let mut bin_width = 0;
+    for file in files {
+        max_name_len = max_name_len.max(file.name.chars().count());
+        match file.counts {
+            Counts::Lines { insertions, deletions } => max_change = max_change.max(u64::from(insertions + deletions)),
+            Counts::Binary { old_size, new_size } => {
+                // "Bin XXX -> YYY bytes"
+                bin_width = bin_width.max(14 + decimal_width(old_size) + decimal_width(new_size));
+                // show the count of changes aligned with "Bin"
+                number_width = 3;
+            }
+        }
+    }
+
+    let number_width = number_width.max(decimal_width(max_change));
+    let width = width.max(16 + 6 + number_width);
+    let mut graph_width = if max_change as usize + 4 > bin_width {
+        max_change as usize
+    } else {
+        bin_width - 4
+    };
+    let mut name_width = max_name_len;
+    if name_width + number_width + 6 + graph_width > width {
+        if graph_width > (width * 3 / 8).saturating_sub(number_width + 6) {
+            graph_width = (width * 3 / 8).saturating_sub(number_width + 6).max(6);
+        }
+        if name_width > width - number_width - 6 - graph_width {
+            name_width = width - number_width - 6 - graph_width;
+        } else {
+            graph_width = width - number_width - 6 - name_width;
+        }
+    }
+
+    let (mut total_insertions, mut total_deletions) = (0, 0);
+    for file in files {
+        let (prefix, name) = shorten(file.name.as_ref(), name_width);
+        let padding = name_width.saturating_sub(prefix.len() + name.chars().count());
+        match file.counts {
+            Counts::Binary { old_size, new_size } => {
+                write!(out, " {prefix}{name}{:padding$} | {:>number_width$}", "", "Bin")?;
+                if old_size == 0 && new_size == 0 {
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, " {old_size} -> {new_size} bytes")?;
+                }
+            }
+            Counts::Lines { insertions, deletions } => {
+                total_insertions += insertions;
+                total_deletions += deletions;
+                let (mut add, mut del) = (u64::from(insertions), u64::from(deletions));
+                let changes = add + del;
+                if graph_width as u64 <= max_change {
+                    let mut total = scale_linear(add + del, graph_width as u64, max_change);
+                    if total < 2 && add != 0 && del != 0 {
+                        total = 2;
+                    }
+                    if add < del {
+                        add = scale_linear(add, graph_width as u64, max_change);
+                        del = total - add;
+                    } else {
+                        del = scale_linear(del, graph_width as u64, max_change);
+                        add = total - del;
+                    }
+                }
+                writeln!(
+                    out,
+                    " {prefix}{name}{:padding$} | {changes:>number_width$}{}{}{}",
+                    "",
+                    if changes != 0 { " " } else { "" },
+                    "+".repeat(add as usize),
+                    "-".repeat(del as usize),
+                )?;
+            }
+        }
+    }
+    write_summary(files.len(), total_insertions, total_deletions, out)
+}
+
+/// Write the summary line of [`write()`] like ` 2 files changed, 1 insertion(+), 3 deletions(-)`.
+fn write_summary(files: usize, insertions: u32, deletions: u32, mut out: impl Write) -> std::io::Result<()> {
+    write!(out, " {files} file{} changed", if files == 1 { "" } else { "s" })?;
+    if insertions != 0 || deletions == 0 {
+        write!(
+            out,
+            ", {insertions} insertion{}(+)",
+            if insertions == 1 { "" } else { "s" }
+        )?;
+    }
+    if deletions != 0 || insertions == 0 {
+        write!(
+            out,
+            ", {deletions} deletion{}(-)",
+            if deletions == 1 { "" } else { "s" }
+        )?;
+    }
+    writeln!(out)
+}
+
+/// Scale `n` to `width` relative to `max`, but make sure that at least one column is used if `n` isn't 0.
+fn scale_linear(n: u64, width: u64, max: u64) -> u64 {
+    if n == 0 {
+        0
+    } else {
+        1 + (n * (width - 1) / max)
+    }
+}
+
+/// Shorten `name` to fit into `width` columns, returning a prefix to indicate it was shortened, along with the remaining name,
+/// which starts at a path separator if possible.
+fn shorten(name: &BStr, width: usize) -> (&'static str, &BStr) {
+    let len = name.chars().count();
+    if len <= width {
+        return ("", name);
+    }
+    let keep = width.saturating_sub(3);
+    let skip = name
+        .char_indices()
+        .nth(len - keep)
+        .map_or(name.len(), |(start, _, _)| start);
+    let name = &name[skip..];
+    let name = name.find_byte(b'/').map_or(name, |pos| &name[pos..]);
+    ("...", name.as_bstr())
+}
+
+/// Return `source => destination`, or `common/{source => destination}/path` if both share leading or trailing components.
+pub(super) fn rewrite_name(source: &BStr, destination: &BStr) -> BString {
+    let (a, b) = (source.as_bytes(), destination.as_bytes());
+    let prefix_len = a
+        .iter()
+        .zip(b)
+        .take_while(|(a, b)| a == b)
+        .enumerate()
+        .filter(|(_, (c, _))| **c == b'/')
+        .last()
+        .map_or(0, |(pos, _)| pos + 1);
+
+    // If there is a common prefix, it ends in a slash which may also be the start of the common suffix.
+    let min_start = prefix_len.saturating_sub(1);
+    let mut suffix_len = 0;
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > min_start && j > min_start && a[i - 1] == b[j - 1] {
+        i -= 1;
+        j -= 1;
+        if a[i] == b'/' {
+            suffix_len = a.len() - i;
+        }
+    }
+
+    let a_mid = a.len().saturating_sub(prefix_len + suffix_len);
+    let b_mid = b.len().saturating_sub(prefix_len + suffix_len);
+    let mut out = BString::default();
+    if prefix_len + suffix_len != 0 {
+        out.extend_from_slice(&a[..prefix_len]);
+        out.push(b'{');
+    }
+    out.extend_from_slice(&a[prefix_len..][..a_mid]);
+    out.extend_from_slice(b" => ");
+    out.extend_from_slice(&b[prefix_len..][..b_mid]);
+    if prefix_len + suffix_len != 0 {
+        out.push(b'}');
+        out.extend_from_slice(&a[a.len() - suffix_len..]);
+    }
+    out
+}
@@ -2,11 +2,11 @@
 //!
 //! Note that the algorithm implemented here is in many ways different from what `git` does.
 //!
-//! - it's less sophisticated and doesn't use any ranking of candidates. Instead, it picks the first possible match.
+//! - similarity is computed by diffing lines instead of comparing hashes of chunks, so scores can differ slightly.
 //! - the set used for copy-detection is probably smaller by default.
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
-use bstr::BStr;
+use bstr::{BStr, ByteSlice};
 use gix_object::tree::{EntryKind, EntryMode};
 
 use crate::{
@@ -173,6 +173,10 @@ impl<T: Change> Tracker<T> {
 
     /// Can only be called once effectively as it alters its own state to assure each item is only emitted once.
     ///
+    /// Like in `git`, destinations are first matched with sources of identical content, preferring the ones with the same basename.
+    /// Then, when looking for renames only, destinations and sources with a unique and identical basename are matched if they are similar enough.
+    /// Finally, the most similar candidates of each destination are paired, starting with the best match among all of them.
+    ///
     /// `cb(destination, source)` is called for each item, either with `Some(source)` if it's
     /// the destination of a copy or rename, or with `None` for source if no relation to other
     /// items in the tracked set exist, which is like saying 'no rename or rewrite or copy' happened.
@@ -291,12 +295,19 @@ impl<T: Change> Tracker<T> {
     ) -> Result<(), emit::Error> {
         // we try to cheaply reduce the set of possibilities first, before possibly looking more exhaustively.
         let needs_second_pass = !needs_exact_match(percentage);
-        if self.match_pairs(cb, None /* by identity */, kind, out, diff_cache, objects)?
-            == crate::tree::visit::Action::Cancel
-        {
+        if self.match_pairs_by_identity(cb, kind) == crate::tree::visit::Action::Cancel {
             return Ok(());
         }
         if needs_second_pass {
+            let percentage = percentage.expect("it's set to something below 1.0 and we assured this");
+            // Like `git`, we don't match by basename when looking for copies as all candidates need to be seen for that.
+            if kind == visit::SourceKind::Rename
+                && self.rewrites.copies.is_none()
+                && self.match_pairs_by_basename(cb, percentage, out, diff_cache, objects)?
+                    == crate::tree::visit::Action::Cancel
+            {
+                return Ok(());
+            }
             let is_limited = if self.rewrites.limit == 0 {
                 false
             } else {
@@ -318,69 +329,228 @@ impl<T: Change> Tracker<T> {
                 }
             };
             if !is_limited {
-                self.match_pairs(cb, percentage, kind, out, diff_cache, objects)?;
+                self.match_pairs_by_similarity(cb, percentage, kind, out, diff_cache, objects)?;
             }
         }
         Ok(())
     }
 
-    fn match_pairs(
+    /// Pair each destination with a source of identical content, preferring sources with the same basename.
+    fn match_pairs_by_identity(
         &mut self,
         cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_>>) -> crate::tree::visit::Action,
-        percentage: Option<f32>,
         kind: visit::SourceKind,
+    ) -> crate::tree::visit::Action {
+        for dest_idx in 0..self.items.len() {
+            let dest = &self.items[dest_idx];
+            if dest.emitted || !matches!(dest.change.kind(), ChangeKind::Addition) {
+                continue;
+            }
+            let Some(src_idx) = find_identical_source(&self.items, dest_idx, kind, &self.path_backing) else {
+                continue;
+            };
+            if self.emit_pair(cb, kind, dest_idx, src_idx, None) == crate::tree::visit::Action::Cancel {
+                return crate::tree::visit::Action::Cancel;
+            }
+        }
+        crate::tree::visit::Action::Continue
+    }
+
+    /// Pair destinations with sources of renames if their basename is unique among all destinations and sources respectively,
+    /// and if they are similar enough. This is what `git` does to quickly find files that were moved into another directory.
+    fn match_pairs_by_basename(
+        &mut self,
+        cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_>>) -> crate::tree::visit::Action,
+        percentage: f32,
         stats: &mut Outcome,
         diff_cache: &mut crate::blob::Platform,
         objects: &impl gix_object::FindObjectOrHeader,
     ) -> Result<crate::tree::visit::Action, emit::Error> {
-        let mut dest_ofs = 0;
-        while let Some((mut dest_idx, dest)) = self.items[dest_ofs..].iter().enumerate().find_map(|(idx, item)| {
-            (!item.emitted && matches!(item.change.kind(), ChangeKind::Addition)).then_some((idx, item))
-        }) {
-            dest_idx += dest_ofs;
-            dest_ofs = dest_idx + 1;
-            let src = find_match(
+        let candidates = {
+            // basename -> index of the item, or `None` if the basename isn't unique.
+            let mut sources = HashMap::<&BStr, Option<usize>>::new();
+            let mut destinations = HashMap::<&BStr, Option<usize>>::new();
+            for (idx, item) in self.items.iter().enumerate() {
+                if item.emitted || !item.change.entry_mode().is_blob() {
+                    continue;
+                }
+                let by_basename = match item.change.kind() {
+                    ChangeKind::Deletion => &mut sources,
+                    ChangeKind::Addition => &mut destinations,
+                    ChangeKind::Modification => continue,
+                };
+                by_basename
+                    .entry(basename(item.location(&self.path_backing)))
+                    .and_modify(|idx| *idx = None)
+                    .or_insert(Some(idx));
+            }
+            let mut candidates: Vec<_> = destinations
+                .iter()
+                .filter_map(|(name, dest_idx)| Some(((*dest_idx)?, sources.get(name).copied().flatten()?)))
+                .collect();
+            candidates.sort_unstable();
+            candidates
+        };
+
+        let min_similarity = percentage + (1.0 - percentage) / 2.0;
+        let mut pairs = Vec::new();
+        for (dest_idx, src_idx) in candidates {
+            if !self.items[src_idx].entry_mode_compatible(self.items[dest_idx].change.entry_mode()) {
+                continue;
+            }
+            if let Some(diff) = similarity(
                 &self.items,
-                dest,
                 dest_idx,
-                percentage,
-                kind,
+                src_idx,
                 stats,
                 objects,
                 diff_cache,
                 &self.path_backing,
             )?
-            .map(|(src_idx, src, diff)| {
-                let (id, entry_mode) = src.change.id_and_entry_mode();
-                let id = id.to_owned();
-                let location = src.location(&self.path_backing);
-                (
-                    visit::Source {
-                        entry_mode,
-                        id,
-                        kind,
-                        location,
-                        diff,
-                    },
-                    src_idx,
-                )
-            });
-            if src.is_none() {
+            .filter(|diff| diff.similarity >= min_similarity)
+            {
+                pairs.push((dest_idx, src_idx, diff));
+            }
+        }
+
+        for (dest_idx, src_idx, diff) in pairs {
+            if self.emit_pair(cb, visit::SourceKind::Rename, dest_idx, src_idx, Some(diff))
+                == crate::tree::visit::Action::Cancel
+            {
+                return Ok(crate::tree::visit::Action::Cancel);
+            }
+        }
+        Ok(crate::tree::visit::Action::Continue)
+    }
+
+    /// Compare each destination with all possible sources and keep its best candidates. Then, like `git`, pair them
+    /// starting with the most similar ones, so each destination gets the best source that isn't taken yet.
+    fn match_pairs_by_similarity(
+        &mut self,
+        cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_>>) -> crate::tree::visit::Action,
+        percentage: f32,
+        kind: visit::SourceKind,
+        stats: &mut Outcome,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<crate::tree::visit::Action, emit::Error> {
+        let mut candidates = Vec::new();
+        for (dest_idx, dest) in self.items.iter().enumerate() {
+            let dest_mode = dest.change.entry_mode();
+            if dest.emitted || !matches!(dest.change.kind(), ChangeKind::Addition) || !dest_mode.is_blob() {
                 continue;
             }
-            let location = dest.location(&self.path_backing);
-            let change = dest.change.clone();
-            let dest = visit::Destination { change, location };
-            self.items[dest_idx].emitted = true;
-            if let Some(src_idx) = src.as_ref().map(|t| t.1) {
-                self.items[src_idx].emitted = true;
+            let dest_basename = basename(dest.location(&self.path_backing));
+            let first_candidate = candidates.len();
+            for (src_idx, src) in self.items.iter().enumerate() {
+                if src_idx == dest_idx || !src.is_source_for_destination_of(kind, dest_mode) {
+                    continue;
+                }
+                let diff = match similarity(
+                    &self.items,
+                    dest_idx,
+                    src_idx,
+                    stats,
+                    objects,
+                    diff_cache,
+                    &self.path_backing,
+                )? {
+                    Some(diff) if diff.similarity >= percentage => diff,
+                    _ => continue,
+                };
+                candidates.push(Candidate {
+                    dest_idx,
+                    src_idx,
+                    diff,
+                    same_basename: basename(src.location(&self.path_backing)) == dest_basename,
+                });
             }
-            if cb(dest, src.map(|t| t.0)) == crate::tree::visit::Action::Cancel {
+            candidates[first_candidate..].sort_by(Candidate::by_rank);
+            candidates.truncate(first_candidate + NUM_CANDIDATES_PER_DESTINATION);
+        }
+
+        candidates.sort_by(Candidate::by_rank);
+        let mut pairs = Vec::new();
+        for candidate in candidates {
+            let is_taken = self.items[candidate.dest_idx].emitted
+                || (kind == visit::SourceKind::Rename && self.items[candidate.src_idx].emitted);
+            if is_taken {
+                continue;
+            }
+            self.items[candidate.dest_idx].emitted = true;
+            self.items[candidate.src_idx].emitted = true;
+            pairs.push(candidate);
+        }
+
+        pairs.sort_by_key(|candidate| candidate.dest_idx);
+        for Candidate {
+            dest_idx,
+            src_idx,
+            diff,
+            ..
+        } in pairs
+        {
+            if self.emit_pair(cb, kind, dest_idx, src_idx, Some(diff)) == crate::tree::visit::Action::Cancel {
                 return Ok(crate::tree::visit::Action::Cancel);
             }
         }
         Ok(crate::tree::visit::Action::Continue)
     }
+
+    /// Mark the items at `dest_idx` and `src_idx` as emitted and pass them to `cb`.
+    fn emit_pair(
+        &mut self,
+        cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_>>) -> crate::tree::visit::Action,
+        kind: visit::SourceKind,
+        dest_idx: usize,
+        src_idx: usize,
+        diff: Option<DiffLineStats>,
+    ) -> crate::tree::visit::Action {
+        self.items[dest_idx].emitted = true;
+        self.items[src_idx].emitted = true;
+
+        let src = &self.items[src_idx];
+        let (id, entry_mode) = src.change.id_and_entry_mode();
+        let source = visit::Source {
+            entry_mode,
+            id: id.to_owned(),
+            kind,
+            location: src.location(&self.path_backing),
+            diff,
+        };
+        let dest = &self.items[dest_idx];
+        cb(
+            visit::Destination {
+                change: dest.change.clone(),
+                location: dest.location(&self.path_backing),
+            },
+            Some(source),
+        )
+    }
+}
+
+/// The amount of best candidates to keep for each destination when matching by similarity, just like `git` does.
+const NUM_CANDIDATES_PER_DESTINATION: usize = 4;
+
+/// A possible pairing of a destination with a source that is similar enough.
+struct Candidate {
+    dest_idx: usize,
+    src_idx: usize,
+    diff: DiffLineStats,
+    /// If `true`, source and destination have the same basename, which makes this pairing more likely.
+    same_basename: bool,
+}
+
+impl Candidate {
+    /// Order the best candidates first, by similarity and then by basename, with ties being broken by the order of items.
+    fn by_rank(a: &Self, b: &Self) -> std::cmp::Ordering {
+        b.diff
+            .similarity
+            .total_cmp(&a.diff.similarity)
+            .then_with(|| b.same_basename.cmp(&a.same_basename))
+            .then_with(|| a.dest_idx.cmp(&b.dest_idx))
+            .then_with(|| a.src_idx.cmp(&b.src_idx))
+    }
 }
 
 /// Returns the amount of viable sources and destinations for `items` as eligible for the given `kind` of operation.
@@ -420,125 +590,107 @@ fn needs_exact_match(percentage: Option<f32>) -> bool {
     percentage.map_or(true, |p| p >= 1.0)
 }
 
-/// <`src_idx`, src, possibly diff stat>
-type SourceTuple<'a, T> = (usize, &'a Item<T>, Option<DiffLineStats>);
-
-/// Find `item` in our set of items ignoring `item_idx` to avoid finding ourselves, by similarity indicated by `percentage`.
-/// The latter can be `None` or `Some(x)` where `x>=1` for identity, and anything else for similarity.
-/// We also ignore emitted items entirely.
-/// Use `kind` to indicate what kind of match we are looking for, which might be deletions matching an `item` addition, or
-/// any non-deletion otherwise.
-/// Note that we always try to find by identity first even if a percentage is given as it's much faster and may reduce the set
-/// of items to be searched.
-#[allow(clippy::too_many_arguments)]
-fn find_match<'a, T: Change>(
-    items: &'a [Item<T>],
-    item: &Item<T>,
-    item_idx: usize,
-    percentage: Option<f32>,
+/// Return the last component of `path`.
+fn basename(path: &BStr) -> &BStr {
+    path.rfind_byte(b'/').map_or(path, |pos| path[pos + 1..].as_bstr())
+}
+
+/// Find a source of the given `kind` with the same content as the destination at `dest_idx` in `items`, which
+/// are expected to be sorted by id.
+/// Like `git`, we prefer sources with the same basename, and otherwise use the first one we find.
+fn find_identical_source<T: Change>(
+    items: &[Item<T>],
+    dest_idx: usize,
     kind: visit::SourceKind,
+    path_backing: &[u8],
+) -> Option<usize> {
+    let dest = &items[dest_idx];
+    let (dest_id, dest_mode) = dest.change.id_and_entry_mode();
+    let dest_basename = basename(dest.location(path_backing));
+    let first_idx = items.partition_point(|a| a.change.id() < dest_id);
+
+    let mut first_match = None;
+    for (src_idx, src) in items
+        .iter()
+        .enumerate()
+        .skip(first_idx)
+        .take_while(|(_, src)| src.change.id() == dest_id)
+    {
+        if src_idx == dest_idx || !src.is_source_for_destination_of(kind, dest_mode) {
+            continue;
+        }
+        if basename(src.location(path_backing)) == dest_basename {
+            return Some(src_idx);
+        }
+        first_match.get_or_insert(src_idx);
+    }
+    first_match
+}
+
+/// Compute how similar the source at `src_idx` is to the destination at `dest_idx`, or return `None` if this can't
+/// be determined as one of them is binary.
+fn similarity<T: Change>(
+    items: &[Item<T>],
+    dest_idx: usize,
+    src_idx: usize,
     stats: &mut Outcome,
     objects: &impl gix_object::FindObjectOrHeader,
     diff_cache: &mut crate::blob::Platform,
     path_backing: &[u8],
-) -> Result<Option<SourceTuple<'a, T>>, emit::Error> {
-    let (item_id, item_mode) = item.change.id_and_entry_mode();
-    if needs_exact_match(percentage) || item_mode.is_link() {
-        let first_idx = items.partition_point(|a| a.change.id() < item_id);
-        let range = items.get(first_idx..).map(|items| {
-            let end = items
-                .iter()
-                .position(|a| a.change.id() != item_id)
-                .map_or(items.len(), |idx| first_idx + idx);
-            first_idx..end
-        });
-        let range = match range {
-            Some(range) => range,
-            None => return Ok(None),
-        };
-        if range.is_empty() {
-            return Ok(None);
+) -> Result<Option<DiffLineStats>, emit::Error> {
+    let (dest, src) = (&items[dest_idx], &items[src_idx]);
+    let (dest_id, dest_mode) = dest.change.id_and_entry_mode();
+    debug_assert!(
+        dest_mode.is_blob(),
+        "symlinks are matched exactly, and trees aren't used here"
+    );
+    diff_cache.set_resource(
+        dest_id.to_owned(),
+        dest_mode.kind(),
+        dest.location(path_backing),
+        ResourceKind::NewOrDestination,
+        objects,
+    )?;
+    let (src_id, src_mode) = src.change.id_and_entry_mode();
+    diff_cache.set_resource(
+        src_id.to_owned(),
+        src_mode.kind(),
+        src.location(path_backing),
+        ResourceKind::OldOrSource,
+        objects,
+    )?;
+    let prep = diff_cache.prepare_diff()?;
+    stats.num_similarity_checks += 1;
+    Ok(match prep.operation {
+        Operation::InternalDiff { algorithm } => {
+            let tokens = crate::blob::intern::InternedInput::new(prep.old.intern_source(), prep.new.intern_source());
+            let counts = crate::blob::diff(
+                algorithm,
+                &tokens,
+                crate::blob::sink::Counter::new(diff::Statistics {
+                    removed_bytes: 0,
+                    input: &tokens,
+                }),
+            );
+            let old_data_len = prep.old.data.as_slice().unwrap_or_default().len();
+            let new_data_len = prep.new.data.as_slice().unwrap_or_default().len();
+            let similarity = (old_data_len - counts.wrapped) as f32 / old_data_len.max(new_data_len) as f32;
+            Some(DiffLineStats {
+                removals: counts.removals,
+                insertions: counts.insertions,
+                before: tokens.before.len().try_into().expect("interner handles only u32"),
+                after: tokens.after.len().try_into().expect("interner handles only u32"),
+                similarity,
+            })
         }
-        let res = items[range.clone()].iter().enumerate().find_map(|(mut src_idx, src)| {
-            src_idx += range.start;
-            (src_idx != item_idx && src.is_source_for_destination_of(kind, item_mode)).then_some((src_idx, src, None))
-        });
-        if let Some(src) = res {
-            return Ok(Some(src));
+        Operation::ExternalCommand { .. } => {
+            unreachable!("we have disabled this possibility with an option")
         }
-    } else {
-        let mut has_new = false;
-        let percentage = percentage.expect("it's set to something below 1.0 and we assured this");
-        debug_assert!(
-            item_mode.is_blob(),
-            "symlinks are matched exactly, and trees aren't used here"
-        );
-
-        for (can_idx, src) in items
-            .iter()
-            .enumerate()
-            .filter(|(src_idx, src)| *src_idx != item_idx && src.is_source_for_destination_of(kind, item_mode))
-        {
-            if !has_new {
-                diff_cache.set_resource(
-                    item_id.to_owned(),
-                    item_mode.kind(),
-                    item.location(path_backing),
-                    ResourceKind::NewOrDestination,
-                    objects,
-                )?;
-                has_new = true;
-            }
-            let (src_id, src_mode) = src.change.id_and_entry_mode();
-            diff_cache.set_resource(
-                src_id.to_owned(),
-                src_mode.kind(),
-                src.location(path_backing),
-                ResourceKind::OldOrSource,
-                objects,
-            )?;
-            let prep = diff_cache.prepare_diff()?;
-            stats.num_similarity_checks += 1;
-            match prep.operation {
-                Operation::InternalDiff { algorithm } => {
-                    let tokens =
-                        crate::blob::intern::InternedInput::new(prep.old.intern_source(), prep.new.intern_source());
-                    let counts = crate::blob::diff(
-                        algorithm,
-                        &tokens,
-                        crate::blob::sink::Counter::new(diff::Statistics {
-                            removed_bytes: 0,
-                            input: &tokens,
-                        }),
-                    );
-                    let old_data_len = prep.old.data.as_slice().unwrap_or_default().len();
-                    let new_data_len = prep.new.data.as_slice().unwrap_or_default().len();
-                    let similarity = (old_data_len - counts.wrapped) as f32 / old_data_len.max(new_data_len) as f32;
-                    if similarity >= percentage {
-                        return Ok(Some((
-                            can_idx,
-                            src,
-                            DiffLineStats {
-                                removals: counts.removals,
-                                insertions: counts.insertions,
-                                before: tokens.before.len().try_into().expect("interner handles only u32"),
-                                after: tokens.after.len().try_into().expect("interner handles only u32"),
-                                similarity,
-                            }
-                            .into(),
-                        )));
-                    }
-                }
-                Operation::ExternalCommand { .. } => {
-                    unreachable!("we have disabled this possibility with an option")
-                }
-                Operation::SourceOrDestinationIsBinary => {
-                    // TODO: figure out if git does more here
-                }
-            };
+        Operation::SourceOrDestinationIsBinary => {
+            // TODO: figure out if git does more here
+            None
         }
-    }
-    Ok(None)
+    })
 }
 
 mod diff {
@@ -553,10 +705,10 @@ mod diff {
         type Out = usize;
 
         fn process_change(&mut self, before: Range<u32>, _after: Range<u32>) {
-            self.removed_bytes = self.input.before[before.start as usize..before.end as usize]
+            self.removed_bytes += self.input.before[before.start as usize..before.end as usize]
                 .iter()
                 .map(|token| self.input.interner[*token].len())
-                .sum();
+                .sum::<usize>();
         }
 
         fn finish(self) -> Self::Out {
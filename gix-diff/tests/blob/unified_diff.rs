@@ -0,0 +1,60 @@
+use gix_diff::blob::{intern::InternedInput, unified_diff, unified_diff::FunctionName, Algorithm, UnifiedDiff};
+
+#[test]
+fn default_function_names_are_truncated_and_trimmed() {
+    let long = format!("{}  \n", "x".repeat(100));
+    assert_eq!(
+        FunctionName::Default.find(long.as_bytes()).map(|name| name.len()),
+        Some(80)
+    );
+    assert_eq!(FunctionName::Default.find(b"fn a()  \r\n"), Some("fn a()".into()));
+    assert_eq!(FunctionName::Default.find(b"$var\n"), Some("$var".into()));
+    assert_eq!(FunctionName::Default.find(b"  indented\n"), None);
+    assert_eq!(FunctionName::Default.find(b"\n"), None);
+}
+
+#[test]
+fn function_name_patterns_use_the_first_matching_pattern() -> Result<(), Box<dyn std::error::Error>> {
+    let name = FunctionName::from_xfuncname("!^skip\n^(begin.*)$\n^[a-z]+".into())?;
+    assert_eq!(name.find(b"skip begin\n"), None, "negated patterns stop the search");
+    assert_eq!(
+        name.find(b"begin a\r\n"),
+        Some("begin a".into()),
+        "line endings aren't matched, and the first capture group is used"
+    );
+    assert_eq!(
+        name.find(b"other line\n"),
+        Some("other".into()),
+        "otherwise it's the whole match"
+    );
+    assert_eq!(name.find(b"42\n"), None);
+
+    assert!(FunctionName::from_xfuncname("(unclosed".into()).is_err());
+    Ok(())
+}
+
+#[test]
+fn hunks_without_function_names() {
+    let before = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+    let after = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj";
+    let input = InternedInput::new(
+        gix_diff::blob::sources::byte_lines_with_terminator(before.as_bytes()),
+        gix_diff::blob::sources::byte_lines_with_terminator(after.as_bytes()),
+    );
+    let actual = gix_diff::blob::diff(
+        Algorithm::Histogram,
+        &input,
+        UnifiedDiff::new(
+            &input,
+            unified_diff::Options {
+                context_lines: 1,
+                word_diff: false,
+            },
+            None,
+        ),
+    );
+    assert_eq!(
+        actual,
+        "@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n@@ -9 +9,2 @@\n i\n+j\n\\ No newline at end of file\n"
+    );
+}
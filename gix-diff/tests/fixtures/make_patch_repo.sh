@@ -0,0 +1,105 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config diff.custom.xfuncname '^\[(.*)\]$'
+git config diff.negated.xfuncname '!^skip
+^(begin.*)$'
+
+echo "*.cfg diff=custom" >.gitattributes
+echo "*.neg diff=negated" >>.gitattributes
+
+cat <<'EOF2' >code.rs
+use std::io;
+
+fn first() {
+    let a = 1;
+    let b = 2;
+    let c = 3;
+    let d = 4;
+    let e = 5;
+}
+
+fn second() {
+    let a = 1;
+    let b = 2;
+    let c = 3;
+    let d = 4;
+    let e = 5;
+    let f = 6;
+    let g = 7;
+    let h = 8;
+    let i = 9;
+}
+EOF2
+
+cat <<'EOF2' >settings.cfg
+[core]
+  one = 1
+  two = 2
+  three = 3
+  four = 4
+  five = 5
+  six = 6
+  seven = 7
+  eight = 8
+[user]
+  name = someone
+  mail = someone@example.com
+  id = 42
+  flag = true
+EOF2
+
+cat <<'EOF2' >functions.neg
+begin a
+  1
+skip this
+  2
+  3
+  4
+  5
+EOF2
+
+printf 'The quick brown fox\njumps over\nthe lazy dog.\nAnother line\n' >words.txt
+seq 1 10 >to-rename.txt
+seq 1 20 >to-rename-and-modify.txt
+mkdir -p dir/a
+seq 50 55 >dir/a/file
+seq 20 40 >to-copy.txt
+seq 1 3 >exec.sh
+seq 1 5 >deleted.txt
+printf 'one\ntwo\nthree' >no-newline.txt
+printf 'a\0b\0c' >binary.bin
+git add . && git commit -qm "base"
+
+sed -i 's/let b = 2;/let b = 20;/; s/let i = 9;/let i = 90;/' code.rs
+sed -i 's/two = 2/two = 22/; s/id = 42/id = 43/' settings.cfg
+sed -i 's/  5/  50/' functions.neg
+printf 'The quick red fox\njumps  over\nthe dog.\nAnother line\n' >words.txt
+git mv to-rename.txt renamed.txt
+git mv to-rename-and-modify.txt renamed-and-modified.txt
+sed -i 's/^10$/ten/' renamed-and-modified.txt
+chmod +x renamed-and-modified.txt
+mkdir dir/b && git mv dir/a/file dir/b/file
+cp to-copy.txt copied.txt
+echo 4 >>exec.sh && chmod +x exec.sh
+git rm -q deleted.txt
+seq 100 102 >new.txt
+touch empty
+ln -s code.rs link
+printf 'one\ntwo\nthree\nfour' >no-newline.txt
+printf 'a\0b\0c\0d' >binary.bin
+printf '\0\1\2' >new.bin
+git add . && git commit -qm "changes"
+
+git rev-parse @~1 >base-id
+git rev-parse @ >head-id
+
+git diff @~1 @ >all.patch
+git diff -U1 @~1 @ -- code.rs >code-u1.patch
+git diff -C --find-copies-harder @~1 @ -- to-copy.txt copied.txt >copy.patch
+git diff --word-diff @~1 @ >all.word-diff
+git diff --stat @~1 @ >all.stat
+git diff --stat=50 @~1 @ >narrow.stat
+git diff --numstat @~1 @ >all.numstat
+git diff --binary @~1 @ -- binary.bin new.bin >binary.patch
@@ -0,0 +1,375 @@
+use std::path::{Path, PathBuf};
+
+use gix_diff::{
+    blob::{pipeline, unified_diff, Platform},
+    patch::{self, Change, Resource},
+    tree::{recorder, visit},
+};
+use gix_hash::ObjectId;
+use gix_object::{
+    bstr::{BString, ByteSlice},
+    tree::EntryMode,
+    FindExt,
+};
+use gix_worktree::stack::state::attributes;
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only_standalone("make_patch_repo.sh")
+}
+
+/// A version of [`Change`] that owns its data.
+struct OwnedChange {
+    previous: Option<(BString, ObjectId, EntryMode)>,
+    current: Option<(BString, ObjectId, EntryMode)>,
+    /// If set, this is a rewrite along with `(copy, similarity)`.
+    rewrite: Option<(bool, f32)>,
+}
+
+impl OwnedChange {
+    fn as_change(&self) -> Change<'_> {
+        fn resource((location, id, mode): &(BString, ObjectId, EntryMode)) -> Resource<'_> {
+            Resource {
+                location: location.as_ref(),
+                id,
+                mode: *mode,
+            }
+        }
+        match (
+            self.previous.as_ref().map(resource),
+            self.current.as_ref().map(resource),
+        ) {
+            (Some(source), Some(destination)) => match self.rewrite {
+                Some((copy, similarity)) => Change::Rewrite {
+                    source,
+                    destination,
+                    copy,
+                    similarity,
+                },
+                None => Change::Modification {
+                    previous: source,
+                    current: destination,
+                },
+            },
+            (Some(previous), None) => Change::Deletion(previous),
+            (None, Some(current)) => Change::Addition(current),
+            (None, None) => unreachable!("at least one side is set"),
+        }
+    }
+
+    /// The location by which `git` sorts its changes.
+    fn sort_key(&self) -> &BString {
+        &self
+            .current
+            .as_ref()
+            .or(self.previous.as_ref())
+            .expect("at least one side")
+            .0
+    }
+}
+
+fn resource_cache(root: &Path) -> Platform {
+    let attributes = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            attributes::Source::WorktreeThenIdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let drivers = vec![
+        gix_diff::blob::Driver {
+            name: "custom".into(),
+            function_name_patterns: Some(r"^\[(.*)\]$".into()),
+            ..Default::default()
+        },
+        gix_diff::blob::Driver {
+            name: "negated".into(),
+            function_name_patterns: Some("!^skip\n^(begin.*)$".into()),
+            ..Default::default()
+        },
+    ];
+    let filter = gix_diff::blob::Pipeline::new(
+        Default::default(),
+        gix_filter::Pipeline::default(),
+        drivers,
+        pipeline::Options {
+            large_file_threshold_bytes: 0,
+            fs: gix_fs::Capabilities::probe(root),
+        },
+    );
+    Platform::new(Default::default(), filter, pipeline::Mode::ToGit, attributes)
+}
+
+/// Return all changes between the `base-id` and `head-id` commits of the fixture in the order `git` shows them, with
+/// renames found by similarity.
+fn changes(root: &Path, odb: &gix_odb::Handle, cache: &mut Platform) -> crate::Result<Vec<OwnedChange>> {
+    let tree_of = |name: &str, buf: &mut Vec<u8>| -> crate::Result<ObjectId> {
+        let id = ObjectId::from_hex(std::fs::read(root.join(name))?.trim())?;
+        Ok(odb.find_commit(&id, buf)?.tree())
+    };
+    let (mut buf, mut buf2) = (Vec::new(), Vec::new());
+    let base_tree = tree_of("base-id", &mut buf)?;
+    let head_tree = tree_of("head-id", &mut buf)?;
+    let mut recorder = gix_diff::tree::Recorder::default();
+    gix_diff::tree::Changes::from(odb.find_tree_iter(&base_tree, &mut buf)?).needed_to_obtain(
+        odb.find_tree_iter(&head_tree, &mut buf2)?,
+        gix_diff::tree::State::default(),
+        odb,
+        &mut recorder,
+    )?;
+
+    let mut out = Vec::new();
+    let mut tracker = gix_diff::rewrites::Tracker::new(Default::default());
+    for change in recorder.records {
+        let (change, location) = match change {
+            recorder::Change::Addition { entry_mode, oid, path } => (visit::Change::Addition { entry_mode, oid }, path),
+            recorder::Change::Deletion { entry_mode, oid, path } => (visit::Change::Deletion { entry_mode, oid }, path),
+            recorder::Change::Modification {
+                previous_entry_mode,
+                previous_oid,
+                entry_mode,
+                oid,
+                path,
+            } => (
+                visit::Change::Modification {
+                    previous_entry_mode,
+                    previous_oid,
+                    entry_mode,
+                    oid,
+                },
+                path,
+            ),
+        };
+        if let Some(change) = tracker.try_push_change(change, location.as_ref()) {
+            out.push(owned(change, location.as_ref()));
+        }
+    }
+    tracker.emit(
+        |destination, source| {
+            let mut change = owned(destination.change, destination.location);
+            if let Some(source) = source {
+                change.previous = Some((source.location.to_owned(), source.id, source.entry_mode));
+                change.rewrite = Some((
+                    source.kind == gix_diff::rewrites::tracker::visit::SourceKind::Copy,
+                    source.diff.map_or(1.0, |diff| diff.similarity),
+                ));
+            }
+            out.push(change);
+            visit::Action::Continue
+        },
+        cache,
+        odb,
+        |_push| Ok::<_, std::convert::Infallible>(()),
+    )?;
+    out.retain(|change| {
+        [change.previous.as_ref(), change.current.as_ref()]
+            .into_iter()
+            .flatten()
+            .all(|(_, _, mode)| mode.is_no_tree())
+    });
+    out.sort_by(|a, b| a.sort_key().cmp(b.sort_key()));
+    Ok(out)
+}
+
+fn owned(change: visit::Change, location: &gix_object::bstr::BStr) -> OwnedChange {
+    let location = location.to_owned();
+    match change {
+        visit::Change::Addition { entry_mode, oid } => OwnedChange {
+            previous: None,
+            current: Some((location, oid, entry_mode)),
+            rewrite: None,
+        },
+        visit::Change::Deletion { entry_mode, oid } => OwnedChange {
+            previous: Some((location, oid, entry_mode)),
+            current: None,
+            rewrite: None,
+        },
+        visit::Change::Modification {
+            previous_entry_mode,
+            previous_oid,
+            entry_mode,
+            oid,
+        } => OwnedChange {
+            previous: Some((location.clone(), previous_oid, previous_entry_mode)),
+            current: Some((location, oid, entry_mode)),
+            rewrite: None,
+        },
+    }
+}
+
+struct Setup {
+    root: PathBuf,
+    odb: gix_odb::Handle,
+    cache: Platform,
+    changes: Vec<OwnedChange>,
+}
+
+fn setup() -> crate::Result<Setup> {
+    let root = fixture()?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let mut cache = resource_cache(&root);
+    let changes = changes(&root, &odb, &mut cache)?;
+    Ok(Setup {
+        root,
+        odb,
+        cache,
+        changes,
+    })
+}
+
+fn baseline(root: &Path, name: &str) -> crate::Result<BString> {
+    Ok(std::fs::read(root.join(name))?.into())
+}
+
+/// Render all changes with `options`, returning the patch along with the statistics of each change.
+fn write_all(setup: &mut Setup, options: patch::Options) -> crate::Result<(BString, Vec<patch::stat::File>)> {
+    let mut out = Vec::new();
+    let mut files = Vec::new();
+    for change in &setup.changes {
+        files.push(patch::write(
+            change.as_change(),
+            &mut setup.cache,
+            &setup.odb,
+            options,
+            &mut out,
+        )?);
+    }
+    Ok((out.into(), files))
+}
+
+#[test]
+fn tree_changes_with_renames_modes_and_function_names() -> crate::Result {
+    let mut setup = setup()?;
+    let (actual, _) = write_all(&mut setup, Default::default())?;
+    assert_eq!(actual, baseline(&setup.root, "all.patch")?);
+    Ok(())
+}
+
+#[test]
+fn fewer_context_lines_split_hunks() -> crate::Result {
+    let mut setup = setup()?;
+    setup.changes.retain(|change| change.sort_key() == "code.rs");
+    let (actual, _) = write_all(
+        &mut setup,
+        patch::Options {
+            diff: unified_diff::Options {
+                context_lines: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        actual,
+        baseline(&setup.root, "code-u1.patch")?,
+        "the function name of the previous hunk is reused if there is no new one in between"
+    );
+    Ok(())
+}
+
+#[test]
+fn word_diff() -> crate::Result {
+    let mut setup = setup()?;
+    let (actual, _) = write_all(
+        &mut setup,
+        patch::Options {
+            diff: unified_diff::Options {
+                word_diff: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(actual, baseline(&setup.root, "all.word-diff")?);
+    Ok(())
+}
+
+#[test]
+fn copies() -> crate::Result {
+    let mut setup = setup()?;
+    let copy = setup
+        .changes
+        .iter()
+        .find(|change| change.sort_key() == "copied.txt")
+        .expect("copies aren't tracked, so it's an addition");
+    let (_, id, mode) = copy.current.clone().expect("added");
+    let copy = OwnedChange {
+        previous: Some(("to-copy.txt".into(), id, mode)),
+        current: copy.current.clone(),
+        rewrite: Some((true, 1.0)),
+    };
+    let mut out = Vec::new();
+    patch::write(
+        copy.as_change(),
+        &mut setup.cache,
+        &setup.odb,
+        Default::default(),
+        &mut out,
+    )?;
+    assert_eq!(out.as_bstr(), baseline(&setup.root, "copy.patch")?);
+    Ok(())
+}
+
+#[test]
+fn stat_and_numstat() -> crate::Result {
+    let mut setup = setup()?;
+    let (_, files) = write_all(&mut setup, Default::default())?;
+    let mut stats = Vec::new();
+    for change in &setup.changes {
+        stats.push(patch::stat(change.as_change(), &mut setup.cache, &setup.odb)?);
+    }
+    assert_eq!(files, stats, "writing a patch yields the same statistics");
+
+    let mut out = Vec::new();
+    patch::stat::write_numstat(&files, &mut out)?;
+    assert_eq!(out.as_bstr(), baseline(&setup.root, "all.numstat")?);
+
+    for (width, name) in [(80, "all.stat"), (50, "narrow.stat")] {
+        let mut out = Vec::new();
+        patch::stat::write(&files, width, &mut out)?;
+        assert_eq!(out.as_bstr(), baseline(&setup.root, name)?, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn binary_patches() -> crate::Result {
+    let mut setup = setup()?;
+    setup
+        .changes
+        .retain(|change| change.sort_key() == "binary.bin" || change.sort_key() == "new.bin");
+    let (actual, _) = write_all(
+        &mut setup,
+        patch::Options {
+            binary: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        without_binary_data(actual.as_ref()),
+        without_binary_data(baseline(&setup.root, "binary.patch")?.as_ref()),
+        "the encoded data differs from the one of `git`, but everything else is the same"
+    );
+    Ok(())
+}
+
+/// Replace the encoded lines of each hunk of a binary patch with a placeholder.
+fn without_binary_data(patch: &[u8]) -> Vec<&[u8]> {
+    let mut in_hunk = false;
+    let mut out = Vec::new();
+    for line in patch.lines() {
+        if in_hunk && !line.is_empty() {
+            if out.last() != Some(&b"<data>".as_slice()) {
+                out.push(b"<data>".as_slice());
+            }
+            continue;
+        }
+        in_hunk = line.starts_with(b"literal ") || line.starts_with(b"delta ");
+        out.push(line);
+    }
+    out
+}
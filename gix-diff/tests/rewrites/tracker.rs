@@ -492,11 +492,171 @@ fn rename_by_50_percent_similarity() -> crate::Result {
         out,
         rewrites::Outcome {
             options: rewrites,
-            num_similarity_checks: 1,
+            num_similarity_checks: 2,
             ..Default::default()
         },
-        "the first attempt already yields the one pair, so it doesn't participate anymore\
-         - we don't have best candidates yet, thus only one check"
+        "all candidates are checked to find the best one, even though the first one would already match"
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_by_id_prefers_source_with_same_basename() -> crate::Result {
+    let rewrites = Rewrites {
+        copies: None,
+        percentage: None,
+        limit: 0,
+    };
+    let mut track = util::new_tracker(rewrites);
+    let odb = util::add_retained_blobs(
+        &mut track,
+        [
+            (Change::deletion(), "a/other", "content"),
+            (Change::deletion(), "b/file", "content"),
+            (Change::addition(), "c/file", "content"),
+        ],
+    );
+
+    let mut actual = Vec::new();
+    util::assert_emit_with_objects(
+        &mut track,
+        |dst, src| {
+            actual.push((src.map(|src| src.location.to_owned()), dst.location.to_owned()));
+            Action::Continue
+        },
+        odb,
+    );
+    assert_eq!(
+        actual,
+        [(Some("b/file".into()), "c/file".into()), (None, "a/other".into())],
+        "even though `a/other` comes first, the source with the same basename is preferred"
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_by_similarity_picks_most_similar_source() -> crate::Result {
+    let rewrites = Rewrites {
+        copies: None,
+        percentage: Some(0.5),
+        limit: 0,
+    };
+    let mut track = util::new_tracker(rewrites);
+    let odb = util::add_retained_blobs(
+        &mut track,
+        [
+            (Change::deletion(), "far", "a\nb\ny\nz\n"),
+            (Change::deletion(), "near", "a\nb\nc\nx\n"),
+            (Change::addition(), "target", "a\nb\nc\nd\n"),
+        ],
+    );
+
+    let mut actual = Vec::new();
+    let out = util::assert_emit_with_objects(
+        &mut track,
+        |dst, src| {
+            actual.push((
+                src.map(|src| (src.location.to_owned(), src.diff.expect("similarity").similarity)),
+                dst.location.to_owned(),
+            ));
+            Action::Continue
+        },
+        odb,
+    );
+    assert_eq!(
+        actual,
+        [(Some(("near".into(), 0.75)), "target".into()), (None, "far".into())],
+        "both sources are similar enough, but the most similar one wins"
+    );
+    assert_eq!(out.num_similarity_checks, 2);
+    Ok(())
+}
+
+#[test]
+fn rename_by_similarity_pairs_best_candidates_first() -> crate::Result {
+    let rewrites = Rewrites {
+        copies: None,
+        percentage: Some(0.5),
+        limit: 0,
+    };
+    let mut track = util::new_tracker(rewrites);
+    let odb = util::add_retained_blobs(
+        &mut track,
+        [
+            (Change::deletion(), "s1", "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n"),
+            (Change::deletion(), "s2", "a\nb\nc\n3\n4\n5\n6\n7\n8\n9\n"),
+            (Change::addition(), "d1", "0\n1\n2\n3\n4\n5\n6\n7\nx\ny\n"),
+            (Change::addition(), "d2", "0\n1\n2\n3\n4\n5\n6\n7\n8\nz\n"),
+        ],
+    );
+
+    let mut actual = Vec::new();
+    let out = util::assert_emit_with_objects(
+        &mut track,
+        |dst, src| {
+            actual.push((
+                src.map(|src| (src.location.to_owned(), src.diff.expect("similarity").similarity)),
+                dst.location.to_owned(),
+            ));
+            Action::Continue
+        },
+        odb,
+    );
+    actual.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        actual,
+        [
+            (Some(("s2".into(), 0.5)), "d1".into()),
+            (Some(("s1".into(), 0.9)), "d2".into())
+        ],
+        "`s1` is most similar to `d1` and `d2`, but `d2` is the better match. \
+        `d1` then takes the next best candidate that isn't taken yet"
+    );
+    assert_eq!(out.num_similarity_checks, 4);
+    Ok(())
+}
+
+#[test]
+fn rename_by_similarity_prefers_unique_basenames() -> crate::Result {
+    let rewrites = Rewrites {
+        copies: None,
+        percentage: Some(0.5),
+        limit: 0,
+    };
+    let mut track = util::new_tracker(rewrites);
+    let odb = util::add_retained_blobs(
+        &mut track,
+        [
+            (Change::deletion(), "old/file", "0\n1\n2\n3\n4\n5\n6\n7\nx\ny\n"),
+            (Change::deletion(), "other", "0\n1\n2\n3\n4\n5\n6\n7\n8\nz\n"),
+            (Change::addition(), "new/file", "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n"),
+        ],
+    );
+
+    let mut actual = Vec::new();
+    let out = util::assert_emit_with_objects(
+        &mut track,
+        |dst, src| {
+            actual.push((
+                src.map(|src| (src.location.to_owned(), src.diff.expect("similarity").similarity)),
+                dst.location.to_owned(),
+            ));
+            Action::Continue
+        },
+        odb,
+    );
+    assert_eq!(
+        actual,
+        [
+            (Some(("old/file".into(), 0.8)), "new/file".into()),
+            (None, "other".into())
+        ],
+        "a file that was moved to another directory is detected by its basename if it's similar enough, \
+        even though `other` would be more similar"
+    );
+    assert_eq!(
+        out.num_similarity_checks, 1,
+        "only the files with the same basename were compared"
     );
     Ok(())
 }
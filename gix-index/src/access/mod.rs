@@ -533,6 +533,11 @@ impl State {
             res
         });
     }
+
+    /// Remove the `tree` extension and return it, which is needed after changing entries as it would be outdated otherwise.
+    pub fn remove_tree(&mut self) -> Option<extension::Tree> {
+        self.tree.take()
+    }
 }
 
 /// Extensions
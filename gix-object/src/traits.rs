@@ -297,6 +297,7 @@ mod find {
         }
 
         impl<T: super::Find + ?Sized> FindExt for T {}
+        impl<T: super::Header + ?Sized> HeaderExt for T {}
     }
     pub use ext::{FindExt, HeaderExt};
 }
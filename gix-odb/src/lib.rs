@@ -30,6 +30,11 @@ pub use store_impls::{dynamic as store, loose};
 
 pub mod alternate;
 
+pub mod promisor;
+
+pub mod quarantine;
+pub use quarantine::Quarantine;
+
 /// A way to access objects along with pre-configured thread-local caches for packed base objects as well as objects themselves.
 ///
 /// By default, no cache will be used.
@@ -137,6 +142,10 @@ pub struct Store {
     use_multi_pack_index: bool,
     /// The hash kind to use for some operations
     object_hash: gix_hash::Kind,
+    /// If set, objects that can't be found will be fetched from the promisor remote.
+    pub(crate) promisor: Option<Arc<dyn promisor::Fetch>>,
+    /// How to access the contents of packs and their indices.
+    pack_backing: gix_pack::Backing,
 }
 
 /// Create a new cached handle to the object store with support for additional options.
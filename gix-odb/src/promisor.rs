@@ -0,0 +1,28 @@
+//! Support for *partial clones*, where objects may be missing locally as they were omitted by a filter on the server side.
+//!
+//! Such objects are promised to be available on a *promisor remote*, from which they can be fetched on demand.
+//! Packs received from a promisor remote are marked with a `.promisor` file next to them.
+use std::path::Path;
+
+/// The error returned by [`Fetch::fetch()`].
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A way to obtain objects that are missing locally from a promisor remote.
+///
+/// If set via [`Options::promisor`](crate::store::init::Options::promisor), a [`Store`](crate::Store) will call it
+/// when objects can't be found while looking up their data or headers, and try again once after the call succeeded.
+/// Checks for existence will never trigger a fetch, nor will handles which are configured to
+/// [never refresh](crate::store::RefreshMode::Never) as they expect objects to be missing.
+pub trait Fetch: std::fmt::Debug + Send + Sync {
+    /// Fetch all objects identified by `ids` from the promisor remote and make them available in the object database,
+    /// typically by writing them into a new pack.
+    fn fetch(&self, ids: &[gix_hash::ObjectId]) -> Result<(), Error>;
+}
+
+/// The extension of files that mark a pack as received from a promisor remote, e.g. `pack-<hash>.promisor`.
+pub const MARKER_EXTENSION: &str = "promisor";
+
+/// Return `true` if the pack at `pack_path`, i.e. `pack-<hash>.pack` or `pack-<hash>.idx`, was received from a promisor remote.
+pub fn is_promisor_pack(pack_path: &Path) -> bool {
+    pack_path.with_extension(MARKER_EXTENSION).is_file()
+}
@@ -0,0 +1,183 @@
+//! A temporary object directory to receive objects into, before they are either migrated into the main object database or discarded.
+//!
+//! This is how servers receive packs: objects are kept in quarantine while checks, like `pre-receive` hooks, are run on them,
+//! and only migrated once all checks succeeded. This way, objects that are rejected never become visible to other readers
+//! of the object database.
+//!
+//! The quarantine directory uses the main object database as alternate, so that all objects are visible while reading from it.
+//! Note that while objects are in quarantine, no references must be changed to point to them. Reference stores can be configured
+//! to refuse transactions while in quarantine, as is the case if `GIT_QUARANTINE_PATH` is set.
+//!
+//! See the [`git receive-pack` documentation](https://git-scm.com/docs/git-receive-pack#_quarantine_environment) for details.
+use std::{
+    cmp::Ordering,
+    path::{Path, PathBuf},
+};
+
+/// The prefix of the directory name of quarantines within the main object directory, which matches the `tmp_objdir-incoming-*`
+/// directories `git receive-pack` creates.
+pub const DIRECTORY_PREFIX: &str = "tmp_objdir-incoming-";
+
+/// The environment variable that is set to the [quarantine path](Quarantine::path()) for processes that run while objects
+/// are in quarantine.
+pub const ENV_QUARANTINE_PATH: &str = "GIT_QUARANTINE_PATH";
+
+/// A temporary object directory within the main object directory, which is deleted when dropped unless its objects
+/// were [migrated](Quarantine::migrate()).
+#[derive(Debug)]
+pub struct Quarantine {
+    dir: tempfile::TempDir,
+    objects_dir: PathBuf,
+}
+
+///
+pub mod migrate {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Quarantine::migrate()`](super::Quarantine::migrate()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not migrate '{}' from the quarantine into the object database", path.display())]
+        Io { source: std::io::Error, path: PathBuf },
+    }
+}
+
+/// Lifecycle
+impl Quarantine {
+    /// Create a new and empty quarantine directory within `objects_dir`, the object directory of the main object database
+    /// which is configured as its only alternate.
+    ///
+    /// If `objects_dir` is relative, it is made absolute using the current working directory so the paths remain
+    /// valid for processes which run in a different directory.
+    pub fn new(objects_dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let objects_dir = objects_dir.into();
+        let objects_dir = if objects_dir.is_relative() {
+            gix_fs::current_dir(false)?.join(objects_dir)
+        } else {
+            objects_dir
+        };
+        let dir = tempfile::Builder::new()
+            .prefix(DIRECTORY_PREFIX)
+            .tempdir_in(&objects_dir)?;
+        std::fs::create_dir(dir.path().join("pack"))?;
+        let info_dir = dir.path().join("info");
+        std::fs::create_dir(&info_dir)?;
+        std::fs::write(info_dir.join("alternates"), b"..\n")?;
+        Ok(Quarantine { dir, objects_dir })
+    }
+
+    /// Move all objects in quarantine into the main object database, and remove the quarantine directory afterwards.
+    ///
+    /// Packs are moved before their indices, which makes them visible, and objects which already exist in the main
+    /// object database are skipped. This way, readers never see partially moved packs, while it's safe to migrate
+    /// even if some objects were received more than once.
+    /// If this fails, all objects that weren't migrated yet are deleted along with the quarantine directory.
+    pub fn migrate(self) -> Result<(), migrate::Error> {
+        migrate_directory(self.dir.path(), &self.objects_dir, true)?;
+        let path = self.dir.path().to_owned();
+        self.dir.close().map_err(|source| migrate::Error::Io { source, path })
+    }
+
+    /// Delete the quarantine directory along with all objects in it.
+    ///
+    /// This is the same as dropping this instance, but makes IO errors observable.
+    pub fn discard(self) -> std::io::Result<()> {
+        self.dir.close()
+    }
+}
+
+/// Access
+impl Quarantine {
+    /// The path to the quarantine directory, which is a complete objects directory with the main object database as alternate.
+    ///
+    /// Open an object database here to write objects into the quarantine while being able to read all objects.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The directory into which packs are to be written.
+    pub fn pack_dir(&self) -> PathBuf {
+        self.dir.path().join("pack")
+    }
+
+    /// The objects directory of the main object database into which objects will be migrated.
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
+    }
+
+    /// Open an object database handle on the quarantine directory, configured with `options`.
+    ///
+    /// All objects written through it end up in quarantine, and objects of the main object database are visible as well.
+    pub fn handle(&self, options: crate::store::init::Options) -> std::io::Result<crate::Handle> {
+        crate::at_opts(self.path(), Vec::new(), options)
+    }
+
+    /// Return the environment variables for processes that should have access to the quarantined objects, like
+    /// `pre-receive` hooks, as pairs of names and values.
+    pub fn environment(&self) -> [(&'static str, &Path); 3] {
+        [
+            ("GIT_OBJECT_DIRECTORY", self.path()),
+            ("GIT_ALTERNATE_OBJECT_DIRECTORIES", self.objects_dir()),
+            (ENV_QUARANTINE_PATH, self.path()),
+        ]
+    }
+}
+
+/// Move the contents of `src` into `dst` in the order git uses, skipping the `info` directory at the `top_level`.
+fn migrate_directory(src: &Path, dst: &Path, top_level: bool) -> Result<(), migrate::Error> {
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source| migrate::Error::Io { source, path }
+    };
+    let mut entries = std::fs::read_dir(src)
+        .map_err(io_err(src))?
+        .map(|entry| {
+            let entry = entry.map_err(io_err(src))?;
+            let is_dir = entry.file_type().map_err(io_err(&entry.path()))?.is_dir();
+            Ok((entry.file_name(), is_dir))
+        })
+        .collect::<Result<Vec<_>, migrate::Error>>()?;
+    entries.sort_by(|(a, _), (b, _)| {
+        let (a, b) = (a.to_string_lossy(), b.to_string_lossy());
+        match pack_copy_priority(&a).cmp(&pack_copy_priority(&b)) {
+            Ordering::Equal => a.cmp(&b),
+            other => other,
+        }
+    });
+
+    for (name, is_dir) in entries {
+        if top_level && name == "info" {
+            continue;
+        }
+        let (src, dst) = (src.join(&name), dst.join(&name));
+        if is_dir {
+            match std::fs::create_dir(&dst) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(io_err(&dst)(err)),
+            }
+            migrate_directory(&src, &dst, false)?;
+        } else if !dst.exists() {
+            std::fs::rename(&src, &dst).map_err(io_err(&dst))?;
+        }
+    }
+    Ok(())
+}
+
+/// Packs must be moved before their indices, as the presence of an index makes the pack visible.
+fn pack_copy_priority(name: &str) -> usize {
+    if !name.starts_with("pack") {
+        0
+    } else if name.ends_with(".keep") {
+        1
+    } else if name.ends_with(".pack") {
+        2
+    } else if name.ends_with(".rev") {
+        3
+    } else if name.ends_with(".idx") {
+        4
+    } else {
+        5
+    }
+}
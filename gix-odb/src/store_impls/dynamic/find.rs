@@ -42,6 +42,8 @@ pub(crate) mod error {
             /// The original object to lookup
             id: gix_hash::ObjectId,
         },
+        #[error("Could not fetch missing objects from the promisor remote")]
+        Promisor(#[source] crate::promisor::Error),
     }
 
     #[derive(Copy, Clone)]
@@ -109,6 +111,7 @@ where
             }
         }
 
+        let mut fetched_from_promisor = false;
         'outer: loop {
             {
                 let marker = snapshot.marker;
@@ -144,16 +147,19 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack
+                            .try_entry(pack_offset)
+                            .map_err(gix_pack::data::decode::Error::from)?;
                         let header_size = entry.header_size();
                         let res = match pack.decode_entry(
                             entry,
                             buffer,
                             inflate,
                             &|id, _out| {
-                                index_file.pack_offset_by_id(id).map(|pack_offset| {
-                                    gix_pack::data::decode::entry::ResolvedBase::InPack(pack.entry(pack_offset))
-                                })
+                                index_file
+                                    .pack_offset_by_id(id)
+                                    .and_then(|pack_offset| pack.try_entry(pack_offset).ok())
+                                    .map(gix_pack::data::decode::entry::ResolvedBase::InPack)
                             },
                             pack_cache,
                         ) {
@@ -230,7 +236,9 @@ where
                                 let pack = possibly_pack
                                     .as_ref()
                                     .expect("pack to still be available like just now");
-                                let entry = pack.entry(pack_offset);
+                                let entry = pack
+                                    .try_entry(pack_offset)
+                                    .map_err(gix_pack::data::decode::Error::from)?;
                                 let header_size = entry.header_size();
                                 pack.decode_entry(
                                     entry,
@@ -239,11 +247,8 @@ where
                                     &|id, out| {
                                         index_file
                                             .pack_offset_by_id(id)
-                                            .map(|pack_offset| {
-                                                gix_pack::data::decode::entry::ResolvedBase::InPack(
-                                                    pack.entry(pack_offset),
-                                                )
-                                            })
+                                            .and_then(|pack_offset| pack.try_entry(pack_offset).ok())
+                                            .map(gix_pack::data::decode::entry::ResolvedBase::InPack)
                                             .or_else(|| {
                                                 (id == base_id).then(|| {
                                                     out.resize(buf.len(), 0);
@@ -297,8 +302,29 @@ where
                     *snapshot = new_snapshot;
                     self.clear_cache();
                 }
-                None => return Ok(None),
+                None => {
+                    if recursion.is_none()
+                        && !fetched_from_promisor
+                        && self.fetch_missing_from_promisor(&[id.to_owned()])?
+                    {
+                        fetched_from_promisor = true;
+                        continue;
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Fetch the missing objects identified by `ids` from the promisor remote, if there is one and if this handle is
+    /// allowed to refresh, and return `true` if they should be looked up again.
+    pub(crate) fn fetch_missing_from_promisor(&self, ids: &[gix_hash::ObjectId]) -> Result<bool, Error> {
+        match (self.store.promisor.as_ref(), self.refresh) {
+            (Some(promisor), super::RefreshMode::AfterAllIndicesLoaded) if !ids.is_empty() => {
+                promisor.fetch(ids).map_err(Error::Promisor)?;
+                Ok(true)
             }
+            _ => Ok(false),
         }
     }
 
@@ -398,7 +424,7 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack.try_entry(pack_offset).ok()?;
 
                         buf.resize(entry.decompressed_size.try_into().expect("representable size"), 0);
                         assert_eq!(pack.id, pack_id.to_intrinsic_pack_id(), "both ids must always match");
@@ -481,7 +507,7 @@ where
                         return pack
                             .entry_slice(location.entry_range(location.pack_offset))
                             .map(|data| gix_pack::find::Entry {
-                                data: data.to_owned(),
+                                data: data.into_owned(),
                                 version: pack.version(),
                             });
                     }
@@ -519,7 +545,7 @@ where
     fn try_header(&self, id: &gix_hash::oid) -> Result<Option<gix_object::Header>, gix_object::find::Error> {
         let mut snapshot = self.snapshot.borrow_mut();
         let mut inflate = self.inflate.borrow_mut();
-        self.try_header_inner(id, &mut inflate, &mut snapshot, None)
+        self.try_header_or_fetch(id, &mut inflate, &mut snapshot)
             .map(|maybe_header| {
                 maybe_header.map(|hdr| gix_object::Header {
                     kind: hdr.kind(),
@@ -353,6 +353,8 @@ impl TryFrom<&super::Store> for super::Store {
                 object_hash: Default::default(),
                 use_multi_pack_index: false,
                 current_dir: s.current_dir.clone().into(),
+                promisor: s.promisor.clone(),
+                pack_backing: s.pack_backing,
             },
         )
     }
@@ -72,11 +72,14 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack
+                            .try_entry(pack_offset)
+                            .map_err(gix_pack::data::decode::Error::from)?;
                         let res = match pack.decode_header(entry, inflate, &|id| {
-                            index_file.pack_offset_by_id(id).map(|pack_offset| {
-                                gix_pack::data::decode::header::ResolvedBase::InPack(pack.entry(pack_offset))
-                            })
+                            index_file
+                                .pack_offset_by_id(id)
+                                .and_then(|pack_offset| pack.try_entry(pack_offset).ok())
+                                .map(gix_pack::data::decode::header::ResolvedBase::InPack)
                         }) {
                             Ok(header) => Ok(header.into()),
                             Err(gix_pack::data::decode::Error::DeltaBaseUnresolved(base_id)) => {
@@ -129,15 +132,14 @@ where
                                 let pack = possibly_pack
                                     .as_ref()
                                     .expect("pack to still be available like just now");
-                                let entry = pack.entry(pack_offset);
+                                let entry = pack
+                                    .try_entry(pack_offset)
+                                    .map_err(gix_pack::data::decode::Error::from)?;
                                 pack.decode_header(entry, inflate, &|id| {
                                     index_file
                                         .pack_offset_by_id(id)
-                                        .map(|pack_offset| {
-                                            gix_pack::data::decode::header::ResolvedBase::InPack(
-                                                pack.entry(pack_offset),
-                                            )
-                                        })
+                                        .and_then(|pack_offset| pack.try_entry(pack_offset).ok())
+                                        .map(gix_pack::data::decode::header::ResolvedBase::InPack)
                                         .or_else(|| {
                                             (id == base_id).then(|| {
                                                 gix_pack::data::decode::header::ResolvedBase::OutOfPack {
@@ -178,6 +180,62 @@ where
     }
 }
 
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    /// Like [`Self::try_header_inner()`], but fetches the object from the promisor remote if it is missing and tries again.
+    pub(crate) fn try_header_or_fetch(
+        &self,
+        id: &gix_hash::oid,
+        inflate: &mut zlib::Inflate,
+        snapshot: &mut load_index::Snapshot,
+    ) -> Result<Option<Header>, Error> {
+        match self.try_header_inner(id, inflate, snapshot, None)? {
+            None if self.fetch_missing_from_promisor(&[id.to_owned()])? => {
+                self.try_header_inner(id, inflate, snapshot, None)
+            }
+            res => Ok(res),
+        }
+    }
+}
+
+/// Batched access
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    /// Obtain the headers of all objects identified by `ids`, in order, with `None` for each object that doesn't exist.
+    ///
+    /// This is faster than calling [`try_header()`](crate::Header::try_header()) for each object as the
+    /// snapshot of the object database and the decompressor are obtained only once for all of them.
+    /// Just like with single lookups, delta chains are only followed to learn the kind of the base object,
+    /// and only the first bytes of the last delta in the chain are decompressed to learn the final size.
+    ///
+    /// If a [promisor](crate::promisor::Fetch) is configured, all missing objects are fetched with a single call.
+    pub fn try_headers<'a>(&self, ids: impl IntoIterator<Item = &'a oid>) -> Result<Vec<Option<Header>>, Error> {
+        let mut snapshot = self.snapshot.borrow_mut();
+        let mut inflate = self.inflate.borrow_mut();
+        let ids: Vec<_> = ids.into_iter().collect();
+        let mut out = ids
+            .iter()
+            .map(|id| self.try_header_inner(id, &mut inflate, &mut snapshot, None))
+            .collect::<Result<Vec<_>, _>>()?;
+        let missing: Vec<_> = ids
+            .iter()
+            .zip(out.iter())
+            .filter(|(_, hdr)| hdr.is_none())
+            .map(|(id, _)| (*id).to_owned())
+            .collect();
+        if self.fetch_missing_from_promisor(&missing)? {
+            for (id, hdr) in ids.iter().zip(out.iter_mut()).filter(|(_, hdr)| hdr.is_none()) {
+                *hdr = self.try_header_inner(id, &mut inflate, &mut snapshot, None)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
 impl<S> crate::Header for super::Handle<S>
 where
     S: Deref<Target = super::Store> + Clone,
@@ -185,7 +243,7 @@ where
     fn try_header(&self, id: &oid) -> Result<Option<Header>, gix_object::find::Error> {
         let mut snapshot = self.snapshot.borrow_mut();
         let mut inflate = self.inflate.borrow_mut();
-        self.try_header_inner(id, &mut inflate, &mut snapshot, None)
+        self.try_header_or_fetch(id, &mut inflate, &mut snapshot)
             .map_err(|err| Box::new(err) as _)
     }
 }
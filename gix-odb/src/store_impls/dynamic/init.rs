@@ -19,6 +19,17 @@ pub struct Options {
     /// The current directory of the process at the time of instantiation.
     /// If unset, it will be retrieved using `gix_fs::current_dir(false)`.
     pub current_dir: Option<std::path::PathBuf>,
+    /// If set, objects that can't be found when looking up their data or header will be fetched through it,
+    /// which is how missing objects are obtained in partial clones.
+    pub promisor: Option<Arc<dyn crate::promisor::Fetch>>,
+    /// Determines how the contents of pack data files and their indices are accessed.
+    ///
+    /// Use [`Backing::InMemory`](gix_pack::Backing::InMemory) to read these files fully into memory when they are loaded,
+    /// so that IO errors surface as errors instead of crashing the process, as may happen with memory maps if files
+    /// on network filesystems are truncated or become unavailable.
+    /// [`Backing::PositionedRead`](gix_pack::Backing::PositionedRead) reports these errors when objects are accessed, and
+    /// works with packs too large to be read into memory.
+    pub pack_backing: gix_pack::Backing,
 }
 
 impl Default for Options {
@@ -28,6 +39,8 @@ impl Default for Options {
             object_hash: Default::default(),
             use_multi_pack_index: true,
             current_dir: None,
+            promisor: None,
+            pack_backing: Default::default(),
         }
     }
 }
@@ -77,6 +90,8 @@ impl Store {
             object_hash,
             use_multi_pack_index,
             current_dir,
+            promisor,
+            pack_backing,
         }: Options,
     ) -> std::io::Result<Self> {
         let _span = gix_features::trace::detail!("gix_odb::Store::at()");
@@ -99,9 +114,10 @@ impl Store {
                 let mut db_paths = crate::alternate::resolve(objects_dir.clone(), &current_dir)
                     .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
                 db_paths.insert(0, objects_dir.clone());
-                let num_slots = super::Store::collect_indices_and_mtime_sorted_by_size(db_paths, None, None)
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
-                    .len();
+                let num_slots =
+                    super::Store::collect_indices_and_mtime_sorted_by_size(db_paths, None, None, pack_backing)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
+                        .len();
 
                 ((num_slots as f32 * multiplier) as usize).max(minimum)
             }
@@ -127,6 +143,8 @@ impl Store {
             num_handles_stable: Default::default(),
             num_handles_unstable: Default::default(),
             num_disk_state_consolidation: Default::default(),
+            promisor,
+            pack_backing,
         })
     }
 }
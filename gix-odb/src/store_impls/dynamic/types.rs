@@ -310,10 +310,14 @@ impl IndexAndPacks {
         }
     }
 
-    pub(crate) fn load_index(&mut self, object_hash: gix_hash::Kind) -> std::io::Result<()> {
+    pub(crate) fn load_index(
+        &mut self,
+        object_hash: gix_hash::Kind,
+        backing: gix_pack::Backing,
+    ) -> std::io::Result<()> {
         match self {
             IndexAndPacks::Index(bundle) => bundle.index.load_strict(|path| {
-                gix_pack::index::File::at(path, object_hash)
+                gix_pack::index::File::at_with_backing(path, object_hash, backing)
                     .map(Arc::new)
                     .map_err(|err| match err {
                         gix_pack::index::init::Error::Io { source, .. } => source,
@@ -322,7 +326,7 @@ impl IndexAndPacks {
             }),
             IndexAndPacks::MultiIndex(bundle) => {
                 bundle.multi_index.load_strict(|path| {
-                    gix_pack::multi_index::File::at(path)
+                    gix_pack::multi_index::File::at_with_backing(path, backing)
                         .map(Arc::new)
                         .map_err(|err| match err {
                             gix_pack::multi_index::init::Error::Io { source, .. } => source,
@@ -158,7 +158,11 @@ impl super::Store {
                         let index = match bundle.index.loaded() {
                             Some(index) => index.deref(),
                             None => {
-                                index = pack::index::File::at(bundle.index.path(), self.object_hash)?;
+                                index = pack::index::File::at_with_backing(
+                                    bundle.index.path(),
+                                    self.object_hash,
+                                    self.pack_backing,
+                                )?;
                                 &index
                             }
                         };
@@ -166,7 +170,11 @@ impl super::Store {
                         let data = match bundle.data.loaded() {
                             Some(pack) => pack.deref(),
                             None => {
-                                pack = pack::data::File::at(bundle.data.path(), self.object_hash)?;
+                                pack = pack::data::File::at_with_backing(
+                                    bundle.data.path(),
+                                    self.object_hash,
+                                    self.pack_backing,
+                                )?;
                                 &pack
                             }
                         };
@@ -197,7 +205,10 @@ impl super::Store {
                         let index = match bundle.multi_index.loaded() {
                             Some(index) => index.deref(),
                             None => {
-                                index = pack::multi_index::File::at(bundle.multi_index.path())?;
+                                index = pack::multi_index::File::at_with_backing(
+                                    bundle.multi_index.path(),
+                                    self.pack_backing,
+                                )?;
                                 &index
                             }
                         };
@@ -136,9 +136,13 @@ impl Store {
 
     /// Return only the decompressed size of the object and its kind without fully reading it into memory as tuple of `(size, kind)`.
     /// Returns `None` if `id` does not exist in the database.
+    ///
+    /// Only as many bytes are read and decompressed as needed to obtain the object header.
     pub fn try_header(&self, id: &gix_hash::oid) -> Result<Option<(u64, gix_object::Kind)>, Error> {
-        const BUF_SIZE: usize = 256;
-        let mut buf = [0_u8; BUF_SIZE];
+        const BUF_SIZE: usize = 64;
+        let mut compressed_buf = [0_u8; BUF_SIZE];
+        let mut header_buf = [0_u8; HEADER_MAX_SIZE];
+        let mut header_len = 0;
         let path = hash_path(id, self.path.clone());
 
         let mut inflate = zlib::Inflate::default();
@@ -154,28 +158,40 @@ impl Store {
             }
         };
 
-        let (compressed_buf, _) = buf.split_at_mut(BUF_SIZE - HEADER_MAX_SIZE);
-        let bytes_read = istream.read(compressed_buf).map_err(|e| Error::Io {
-            source: e,
-            action: "read",
-            path: path.to_owned(),
-        })?;
-        let (compressed_buf, header_buf) = buf.split_at_mut(bytes_read);
-        let (status, _consumed_in, consumed_out) =
-            inflate
-                .once(compressed_buf, header_buf)
-                .map_err(|e| Error::DecompressFile {
-                    source: e,
-                    path: path.to_owned(),
-                })?;
+        'read: loop {
+            let bytes_read = istream.read(&mut compressed_buf).map_err(|e| Error::Io {
+                source: e,
+                action: "read",
+                path: path.to_owned(),
+            })?;
+            let mut input = &compressed_buf[..bytes_read];
+            loop {
+                let (status, consumed_in, consumed_out) =
+                    inflate
+                        .once(input, &mut header_buf[header_len..])
+                        .map_err(|e| Error::DecompressFile {
+                            source: e,
+                            path: path.to_owned(),
+                        })?;
+                input = &input[consumed_in..];
+                header_len += consumed_out;
 
-        if status == zlib::Status::BufError {
-            return Err(Error::DecompressFile {
-                source: zlib::inflate::Error::Status(status),
-                path,
-            });
+                let header_complete = header_buf[..header_len].contains(&0);
+                if header_complete || header_len == header_buf.len() || status == zlib::Status::StreamEnd {
+                    break 'read;
+                }
+                if status == zlib::Status::BufError || (consumed_in == 0 && consumed_out == 0) {
+                    if bytes_read == 0 {
+                        return Err(Error::DecompressFile {
+                            source: zlib::inflate::Error::Status(status),
+                            path,
+                        });
+                    }
+                    continue 'read;
+                }
+            }
         }
-        let (kind, size, _header_size) = gix_object::decode::loose_header(&header_buf[..consumed_out])?;
+        let (kind, size, _header_size) = gix_object::decode::loose_header(&header_buf[..header_len])?;
         Ok(Some((size, kind)))
     }
 
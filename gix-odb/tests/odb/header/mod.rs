@@ -39,3 +39,25 @@ fn pack_object() {
         })
     );
 }
+
+#[test]
+fn batched_lookup_of_loose_and_packed_objects() -> crate::odb::Result {
+    let db = db();
+    let ids = [
+        "37d4e6c5c48ba0d245164c4e10d5f41140cab980",
+        "501b297447a8255d3533c6858bb692575cdefaa0",
+        "0000000000000000000000000000000000000000",
+        "dd25c539efbb0ab018caa4cda2d133285634e9b5",
+    ]
+    .map(hex_to_id);
+    let headers = db.try_headers(ids.iter().map(AsRef::as_ref))?;
+    assert_eq!(headers.len(), ids.len(), "one result per input id, in order");
+    for (id, header) in ids.iter().zip(headers) {
+        assert_eq!(
+            header,
+            gix_odb::Header::try_header(&db, id)?,
+            "batched lookups yield the same as single lookups"
+        );
+    }
+    Ok(())
+}
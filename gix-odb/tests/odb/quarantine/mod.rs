@@ -0,0 +1,115 @@
+use gix_object::Exists;
+use gix_odb::{Quarantine, Write};
+use gix_testtools::fixture_path_standalone;
+
+use crate::hex_to_id;
+
+const PACK: &str = "objects/pack/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1";
+
+fn main_objects_dir() -> crate::Result<gix_testtools::tempfile::TempDir> {
+    let dir = gix_testtools::tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("pack"))?;
+    Ok(dir)
+}
+
+fn receive_pack(quarantine: &Quarantine) -> crate::Result {
+    for ext in ["pack", "idx"] {
+        let src = fixture_path_standalone(format!("{PACK}.{ext}"));
+        std::fs::copy(&src, quarantine.pack_dir().join(src.file_name().expect("file")))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn objects_are_only_visible_in_quarantine_until_migrated() -> crate::Result {
+    let objects_dir = main_objects_dir()?;
+    let main = gix_odb::at(objects_dir.path())?;
+    let existing = main.write_buf(gix_object::Kind::Blob, b"existing")?;
+
+    let quarantine = Quarantine::new(objects_dir.path())?;
+    assert!(
+        quarantine
+            .path()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("valid UTF-8")
+            .starts_with("tmp_objdir-incoming-"),
+        "like the quarantine directories `git receive-pack` creates"
+    );
+    assert_eq!(quarantine.objects_dir(), objects_dir.path());
+    assert_eq!(quarantine.environment()[2], ("GIT_QUARANTINE_PATH", quarantine.path()));
+
+    receive_pack(&quarantine)?;
+    let odb = quarantine.handle(Default::default())?;
+    let loose = odb.write_buf(gix_object::Kind::Blob, b"new")?;
+    let packed = hex_to_id("f139391424a8c623adadf2388caec73e5e90865b");
+    for id in [existing, loose, packed] {
+        assert!(
+            odb.exists(&id),
+            "quarantined objects and those of the alternate are visible"
+        );
+    }
+    assert!(
+        !main.exists(&loose),
+        "the main object database doesn't see quarantined objects"
+    );
+    assert!(!main.exists(&packed));
+
+    let quarantine_path = quarantine.path().to_owned();
+    quarantine.migrate()?;
+    assert!(!quarantine_path.exists(), "the quarantine is removed after migration");
+    for id in [existing, loose, packed] {
+        assert!(main.exists(&id), "all objects are now in the main object database");
+    }
+    assert!(
+        !objects_dir.path().join("info").exists(),
+        "the alternates file of the quarantine isn't migrated"
+    );
+    Ok(())
+}
+
+#[test]
+fn objects_that_exist_already_are_skipped_during_migration() -> crate::Result {
+    let objects_dir = main_objects_dir()?;
+    let main = gix_odb::at(objects_dir.path())?;
+    let id = main.write_buf(gix_object::Kind::Blob, b"existing")?;
+
+    let quarantine = Quarantine::new(objects_dir.path())?;
+    let odb = quarantine.handle(Default::default())?;
+    assert_eq!(odb.write_buf(gix_object::Kind::Blob, b"existing")?, id);
+    receive_pack(&quarantine)?;
+    quarantine.migrate()?;
+
+    let quarantine = Quarantine::new(objects_dir.path())?;
+    receive_pack(&quarantine)?;
+    quarantine.migrate()?;
+
+    assert!(main.exists(&id));
+    assert!(main.exists(&hex_to_id("f139391424a8c623adadf2388caec73e5e90865b")));
+    Ok(())
+}
+
+#[test]
+fn discarded_objects_are_never_visible() -> crate::Result {
+    let objects_dir = main_objects_dir()?;
+    let main = gix_odb::at(objects_dir.path())?;
+
+    for explicit_discard in [false, true] {
+        let quarantine = Quarantine::new(objects_dir.path())?;
+        receive_pack(&quarantine)?;
+        let id = quarantine
+            .handle(Default::default())?
+            .write_buf(gix_object::Kind::Blob, b"rejected")?;
+        let quarantine_path = quarantine.path().to_owned();
+        if explicit_discard {
+            quarantine.discard()?;
+        } else {
+            drop(quarantine);
+        }
+
+        assert!(!quarantine_path.exists());
+        assert!(!main.exists(&id));
+        assert!(!main.exists(&hex_to_id("f139391424a8c623adadf2388caec73e5e90865b")));
+    }
+    Ok(())
+}
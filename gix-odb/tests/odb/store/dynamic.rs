@@ -144,6 +144,34 @@ fn multi_index_access() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn in_memory_and_positioned_read_pack_backing() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repo_multi_index.sh")?;
+    for backing in [gix_odb::pack::Backing::InMemory, gix_odb::pack::Backing::PositionedRead] {
+        let handle = gix_odb::at_opts(
+            dir.join(".git/objects"),
+            Vec::new(),
+            gix_odb::store::init::Options {
+                pack_backing: backing,
+                ..Default::default()
+            },
+        )?;
+
+        let mut count = 0;
+        let mut buf = Vec::new();
+        for oid in handle.iter()? {
+            let oid = oid?;
+            let obj = handle.find(&oid, &mut buf)?;
+            let hdr = handle.try_header(&oid)?.expect("exists");
+            assert_eq!(hdr.kind(), obj.kind);
+            assert_eq!(hdr.size(), obj.data.len() as u64);
+            count += 1;
+        }
+        assert_eq!(count, 1732, "{backing:?}: everything works just like with memory maps");
+    }
+    Ok(())
+}
+
 #[test]
 fn multi_index_keep_open() -> crate::Result {
     let dir = gix_testtools::scripted_fixture_writable_standalone("make_repo_multi_index.sh")?;
@@ -794,6 +822,166 @@ fn iterate_over_a_bunch_of_loose_and_packed_objects() -> crate::Result {
     Ok(())
 }
 
+mod promisor {
+    use std::{
+        path::PathBuf,
+        process::Command,
+        sync::{Arc, Mutex},
+    };
+
+    use gix_hash::ObjectId;
+    use gix_object::{Exists, Find, FindExt};
+    use gix_odb::{store, Header};
+    use gix_testtools::fixture_path_standalone;
+
+    use crate::hex_to_id;
+
+    /// Makes a hidden pack available when asked to fetch, just like a fetch from a promisor remote would add a new pack.
+    #[derive(Debug)]
+    struct UnhidePack {
+        stem: PathBuf,
+        calls: Mutex<Vec<Vec<ObjectId>>>,
+        fail: bool,
+    }
+
+    impl gix_odb::promisor::Fetch for UnhidePack {
+        fn fetch(&self, ids: &[ObjectId]) -> Result<(), gix_odb::promisor::Error> {
+            self.calls.lock().unwrap().push(ids.to_vec());
+            if self.fail {
+                return Err("the remote went away".into());
+            }
+            for ext in ["idx", "pack"] {
+                let hidden = self.stem.with_extension(format!("{ext}.bak"));
+                if hidden.is_file() {
+                    std::fs::rename(hidden, self.stem.with_extension(ext))?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn db_with_hidden_pack(
+        fail: bool,
+    ) -> crate::Result<(gix_odb::Handle, Arc<UnhidePack>, gix_testtools::tempfile::TempDir)> {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        assert!(
+            Command::new("git")
+                .arg("-C")
+                .arg(tmp.path())
+                .arg("init")
+                .arg("--bare")
+                .status()?
+                .success(),
+            "git should work"
+        );
+        gix_testtools::copy_recursively_into_existing_dir(
+            fixture_path_standalone("objects/pack"),
+            tmp.path().join("objects/pack"),
+        )?;
+        let stem = tmp
+            .path()
+            .join("objects/pack/pack-11fdfa9e156ab73caae3b6da867192221f2089c2");
+        for ext in ["idx", "pack"] {
+            std::fs::rename(stem.with_extension(ext), stem.with_extension(format!("{ext}.bak")))?;
+        }
+        let promisor = Arc::new(UnhidePack {
+            stem,
+            calls: Default::default(),
+            fail,
+        });
+        let handle = gix_odb::at_opts(
+            tmp.path().join("objects"),
+            None,
+            store::init::Options {
+                promisor: Some(promisor.clone()),
+                ..Default::default()
+            },
+        )?;
+        Ok((handle, promisor, tmp))
+    }
+
+    const IN_HIDDEN_PACK: &str = "501b297447a8255d3533c6858bb692575cdefaa0";
+
+    #[test]
+    fn missing_objects_are_fetched_once_when_looked_up() -> crate::Result {
+        let (handle, promisor, _tmp) = db_with_hidden_pack(false)?;
+        let id = hex_to_id(IN_HIDDEN_PACK);
+        assert!(!handle.exists(&id), "existence checks never fetch");
+        assert!(promisor.calls.lock().unwrap().is_empty());
+
+        let mut buf = Vec::new();
+        handle.find(&id, &mut buf)?;
+        assert_eq!(
+            *promisor.calls.lock().unwrap(),
+            vec![vec![id]],
+            "it was fetched and found afterwards"
+        );
+
+        handle.find(&id, &mut buf)?;
+        assert_eq!(
+            promisor.calls.lock().unwrap().len(),
+            1,
+            "no need to fetch what's present"
+        );
+
+        let missing = hex_to_id("ffffffffffffffffffffffffffffffffffffffff");
+        assert!(handle.try_find(&missing, &mut buf)?.is_none());
+        assert_eq!(
+            promisor.calls.lock().unwrap().len(),
+            2,
+            "objects that don't exist on the remote either are tried to be fetched only once"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_headers_are_fetched_in_one_batch() -> crate::Result {
+        let (handle, promisor, _tmp) = db_with_hidden_pack(false)?;
+        let id = hex_to_id(IN_HIDDEN_PACK);
+        let missing = hex_to_id("ffffffffffffffffffffffffffffffffffffffff");
+        let present = hex_to_id("dd25c539efbb0ab018caa4cda2d133285634e9b5");
+        let headers = handle.try_headers([id.as_ref(), present.as_ref(), missing.as_ref()])?;
+        assert!(headers[0].is_some(), "fetched on demand");
+        assert!(headers[1].is_some());
+        assert!(headers[2].is_none(), "not present on the remote either");
+        assert_eq!(*promisor.calls.lock().unwrap(), vec![vec![id, missing]]);
+        Ok(())
+    }
+
+    #[test]
+    fn single_header_lookups_fetch_as_well() -> crate::Result {
+        let (handle, promisor, _tmp) = db_with_hidden_pack(false)?;
+        let id = hex_to_id(IN_HIDDEN_PACK);
+        assert!(handle.try_header(&id)?.is_some());
+        assert_eq!(promisor.calls.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn handles_that_never_refresh_never_fetch() -> crate::Result {
+        let (mut handle, promisor, _tmp) = db_with_hidden_pack(false)?;
+        handle.refresh = store::RefreshMode::Never;
+        let id = hex_to_id(IN_HIDDEN_PACK);
+        assert!(handle.try_find(&id, &mut Vec::new())?.is_none());
+        assert!(handle.try_header(&id)?.is_none());
+        assert!(promisor.calls.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_errors_are_propagated() -> crate::Result {
+        let (handle, promisor, _tmp) = db_with_hidden_pack(true)?;
+        let id = hex_to_id(IN_HIDDEN_PACK);
+        let err = handle.try_find(&id, &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not fetch missing objects from the promisor remote"
+        );
+        assert_eq!(promisor.calls.lock().unwrap().len(), 1);
+        Ok(())
+    }
+}
+
 #[test]
 fn auto_refresh_with_and_without_id_stability() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
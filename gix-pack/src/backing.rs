@@ -0,0 +1,169 @@
+use std::{borrow::Cow, ops::Deref, ops::Range, path::Path};
+
+use gix_features::zlib;
+
+/// Determines how the contents of pack data files, pack indices and multi-pack indices are made available for reading.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backing {
+    /// Memory-map the file, which is the fastest option and only reads the portions of the file that are actually accessed.
+    ///
+    /// However, IO errors that occur when accessing the mapped memory can't be handled and typically abort the process,
+    /// for example with `SIGBUS` if the file is truncated after it was opened, or if it lives on a network filesystem that
+    /// becomes unavailable.
+    #[default]
+    MemoryMap,
+    /// Read the entire file into memory when opening it, so that all IO errors are returned when opening it.
+    ///
+    /// This trades memory usage and the time needed to read files in full for the guarantee that IO can't fail later.
+    InMemory,
+    /// Keep pack data files open and read only the portions that are accessed with positioned reads, i.e. `pread`,
+    /// so that IO errors are returned when accessing objects.
+    ///
+    /// This works with packs of any size without mapping them or reading them fully into memory, at the cost of a
+    /// system call and a copy for each access. Indices and multi-pack indices are comparatively small and accessed
+    /// randomly, so they are read into memory like with [`InMemory`](Backing::InMemory).
+    ///
+    /// On platforms without support for positioned reads, pack data files are read into memory as well.
+    PositionedRead,
+}
+
+/// The bytes of a file, as made available by a [`Backing`].
+pub(crate) enum Data {
+    Map(memmap2::Mmap),
+    Buffer(Vec<u8>),
+}
+
+impl Data {
+    pub(crate) fn read_only(path: &Path, backing: Backing) -> std::io::Result<Self> {
+        Ok(match backing {
+            Backing::MemoryMap => Data::Map(crate::mmap::read_only(path)?),
+            Backing::InMemory | Backing::PositionedRead => Data::Buffer(std::fs::read(path)?),
+        })
+    }
+}
+
+impl Deref for Data {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Data::Map(map) => map,
+            Data::Buffer(buf) => buf,
+        }
+    }
+}
+
+/// The bytes of a pack data file, which can also be read on demand as they can be too large to be held in memory.
+pub(crate) enum PackData {
+    Data(Data),
+    File { file: std::fs::File, len: usize },
+}
+
+impl PackData {
+    pub(crate) fn read_only(path: &Path, backing: Backing) -> std::io::Result<Self> {
+        Ok(match backing {
+            Backing::MemoryMap | Backing::InMemory => PackData::Data(Data::read_only(path, backing)?),
+            #[cfg(not(any(unix, windows)))]
+            Backing::PositionedRead => PackData::Data(Data::Buffer(std::fs::read(path)?)),
+            #[cfg(any(unix, windows))]
+            Backing::PositionedRead => {
+                let file = std::fs::File::open(path)?;
+                let len = file.metadata()?.len().try_into().map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::Other, "pack file too large for the address space")
+                })?;
+                PackData::File { file, len }
+            }
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            PackData::Data(data) => data.len(),
+            PackData::File { len, .. } => *len,
+        }
+    }
+
+    /// Return all bytes if they are available in memory.
+    pub(crate) fn as_slice(&self) -> Option<&[u8]> {
+        match self {
+            PackData::Data(data) => Some(data),
+            PackData::File { .. } => None,
+        }
+    }
+
+    /// Return the bytes in `range`, which must be contained in the file, borrowed if they are in memory.
+    pub(crate) fn read_at(&self, range: Range<usize>) -> std::io::Result<Cow<'_, [u8]>> {
+        match self {
+            PackData::Data(data) => data
+                .get(range)
+                .map(Cow::Borrowed)
+                .ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into()),
+            PackData::File { file, len } => {
+                if range.end > *len || range.start > range.end {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+                let mut buf = vec![0; range.len()];
+                read_exact_at(file, &mut buf, range.start as u64)?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+
+    /// Decompress the zlib stream starting at `offset` into `out` with `inflate` until `out` is full or the stream ends,
+    /// returning the status of the last decompression along with the amount of consumed and produced bytes.
+    pub(crate) fn inflate_at(
+        &self,
+        offset: usize,
+        inflate: &mut zlib::Inflate,
+        out: &mut [u8],
+    ) -> Result<(zlib::Status, usize, usize), crate::data::decode::Error> {
+        if let Some(data) = self.as_slice() {
+            return Ok(inflate.once(&data[offset..], out)?);
+        }
+        // Compressed data is rarely larger than its decompressed version, so one read typically suffices.
+        let chunk_len = (out.len() + out.len() / 1000 + 64).max(512);
+        let (mut consumed_in, mut consumed_out) = (0, 0);
+        loop {
+            let start = offset + consumed_in;
+            let input = self.read_at(start..(start + chunk_len).min(self.len()))?;
+            let (status, num_in, num_out) = inflate.once(&input, &mut out[consumed_out..])?;
+            consumed_in += num_in;
+            consumed_out += num_out;
+            let made_progress = num_in != 0 || num_out != 0;
+            if status == zlib::Status::StreamEnd
+                || consumed_out == out.len()
+                || offset + consumed_in == self.len()
+                || !made_progress
+            {
+                return Ok((status, consumed_in, consumed_out));
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &std::fs::File, mut buf: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        match std::os::windows::fs::FileExt::seek_read(file, buf, offset) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_exact_at(_file: &std::fs::File, _buf: &mut [u8], _offset: u64) -> std::io::Result<()> {
+    unreachable!("BUG: files aren't kept open without support for positioned reads")
+}
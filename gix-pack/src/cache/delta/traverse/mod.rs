@@ -1,4 +1,7 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use gix_features::{
     parallel::in_parallel_with_slice,
@@ -85,8 +88,8 @@ where
     /// Traverse this tree of delta objects with a function `inspect_object` to process each object at will.
     ///
     /// * `should_run_in_parallel() -> bool` returns true if the underlying pack is big enough to warrant parallel traversal at all.
-    /// * `resolve(EntrySlice, &mut Vec<u8>) -> Option<()>` resolves the bytes in the pack for the given `EntrySlice` and stores them in the
-    ///   output vector. It returns `Some(())` if the object existed in the pack, or `None` to indicate a resolution error, which would abort the
+    /// * `resolve(EntrySlice, &R) -> Option<Cow<'_, [u8]>>` resolves the bytes in the pack for the given `EntrySlice`, borrowed or owned.
+    ///   It returns `Some(bytes)` if the object existed in the pack, or `None` to indicate a resolution error, which would abort the
     ///   operation as well.
     /// * `pack_entries_end` marks one-past-the-last byte of the last entry in the pack, as the last entries size would otherwise
     ///   be unknown as it's not part of the index file.
@@ -114,7 +117,7 @@ where
         }: Options<'_, '_>,
     ) -> Result<Outcome<T>, Error>
     where
-        F: for<'r> Fn(EntryRange, &'r R) -> Option<&'r [u8]> + Send + Clone,
+        F: for<'r> Fn(EntryRange, &'r R) -> Option<Cow<'r, [u8]>> + Send + Clone,
         R: Send + Sync,
         MBFN: FnMut(&mut T, &dyn Progress, Context<'_>) -> Result<(), E> + Send + Clone,
         E: std::error::Error + Send + Sync + 'static,
@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     collections::BTreeMap,
     sync::atomic::{AtomicBool, AtomicIsize, Ordering},
 };
@@ -118,7 +119,7 @@ pub(super) unsafe fn deltas<T, F, MBFN, E, R>(
 where
     T: Send,
     R: Send + Sync,
-    F: for<'r> Fn(EntryRange, &'r R) -> Option<&'r [u8]> + Send + Clone,
+    F: for<'r> Fn(EntryRange, &'r R) -> Option<Cow<'r, [u8]>> + Send + Clone,
     MBFN: FnMut(&mut T, &dyn Progress, Context<'_>) -> Result<(), E> + Send + Clone,
     E: std::error::Error + Send + Sync + 'static,
 {
@@ -128,7 +129,7 @@ where
         let bytes = resolve(slice.clone(), resolve_data).ok_or(Error::ResolveFailed {
             pack_offset: slice.start,
         })?;
-        let entry = data::Entry::from_bytes(bytes, slice.start, hash_len);
+        let entry = data::Entry::from_bytes(&bytes, slice.start, hash_len);
         let compressed = &bytes[entry.header_size()..];
         let decompressed_len = entry.decompressed_size as usize;
         decompress_all_at_once_with(&mut inflate, compressed, decompressed_len, out)?;
@@ -271,7 +272,7 @@ fn deltas_mt<T, F, MBFN, E, R>(
 where
     T: Send,
     R: Send + Sync,
-    F: for<'r> Fn(EntryRange, &'r R) -> Option<&'r [u8]> + Send + Clone,
+    F: for<'r> Fn(EntryRange, &'r R) -> Option<Cow<'r, [u8]>> + Send + Clone,
     MBFN: FnMut(&mut T, &dyn Progress, Context<'_>) -> Result<(), E> + Send + Clone,
     E: std::error::Error + Send + Sync + 'static,
 {
@@ -304,7 +305,7 @@ where
                                     let bytes = resolve(slice.clone(), resolve_data).ok_or(Error::ResolveFailed {
                                         pack_offset: slice.start,
                                     })?;
-                                    let entry = data::Entry::from_bytes(bytes, slice.start, hash_len);
+                                    let entry = data::Entry::from_bytes(&bytes, slice.start, hash_len);
                                     let compressed = &bytes[entry.header_size()..];
                                     let decompressed_len = entry.decompressed_size as usize;
                                     decompress_all_at_once_with(&mut inflate, compressed, decompressed_len, out)?;
@@ -20,6 +20,10 @@ pub enum ResolvedBase {
     OutOfPack { kind: gix_object::Kind, end: usize },
 }
 
+/// The most bytes an entry header can take, with the size taking up to 10 bytes as variable length integer, followed by
+/// either the base offset, taking at most as many bytes, or the id of the base object.
+const MAX_ENTRY_HEADER_LEN: usize = 10 + gix_hash::Kind::longest().len_in_bytes();
+
 #[derive(Debug)]
 struct Delta {
     data: Range<usize>,
@@ -97,7 +101,6 @@ impl File {
         );
 
         self.decompress_entry_from_data_offset(entry.data_offset, inflate, out)
-            .map_err(Into::into)
     }
 
     fn assure_v2(&self) {
@@ -110,13 +113,24 @@ impl File {
     /// Obtain the [`Entry`][crate::data::Entry] at the given `offset` into the pack.
     ///
     /// The `offset` is typically obtained from the pack index file.
+    ///
+    /// # Panics
+    ///
+    /// If the entry couldn't be read with [`Backing::PositionedRead`](crate::Backing::PositionedRead),
+    /// use [`try_entry()`](Self::try_entry()) to handle this case.
     pub fn entry(&self, offset: data::Offset) -> data::Entry {
+        self.try_entry(offset).expect("pack data can be read")
+    }
+
+    /// Like [`entry()`](Self::entry()), but returns an error if the entry couldn't be read.
+    pub fn try_entry(&self, offset: data::Offset) -> Result<data::Entry, std::io::Error> {
         self.assure_v2();
         let pack_offset: usize = offset.try_into().expect("offset representable by machine");
         assert!(pack_offset <= self.data.len(), "offset out of bounds");
 
-        let object_data = &self.data[pack_offset..];
-        data::Entry::from_bytes(object_data, offset, self.hash_len)
+        let header_end = (pack_offset + MAX_ENTRY_HEADER_LEN).min(self.data.len());
+        let object_data = self.data.read_at(pack_offset..header_end)?;
+        Ok(data::Entry::from_bytes(&object_data, offset, self.hash_len))
     }
 
     /// Decompress the object expected at the given data offset, sans pack header. This information is only
@@ -129,13 +143,13 @@ impl File {
         data_offset: data::Offset,
         inflate: &mut zlib::Inflate,
         out: &mut [u8],
-    ) -> Result<usize, zlib::inflate::Error> {
+    ) -> Result<usize, Error> {
         let offset: usize = data_offset.try_into().expect("offset representable by machine");
         assert!(offset < self.data.len(), "entry offset out of bounds");
 
         inflate.reset();
-        inflate
-            .once(&self.data[offset..], out)
+        self.data
+            .inflate_at(offset, inflate, out)
             .map(|(_status, consumed_in, _consumed_out)| consumed_in)
     }
 
@@ -145,13 +159,13 @@ impl File {
         data_offset: data::Offset,
         inflate: &mut zlib::Inflate,
         out: &mut [u8],
-    ) -> Result<(usize, usize), zlib::inflate::Error> {
+    ) -> Result<(usize, usize), Error> {
         let offset: usize = data_offset.try_into().expect("offset representable by machine");
         assert!(offset < self.data.len(), "entry offset out of bounds");
 
         inflate.reset();
-        inflate
-            .once(&self.data[offset..], out)
+        self.data
+            .inflate_at(offset, inflate, out)
             .map(|(_status, consumed_in, consumed_out)| (consumed_in, consumed_out))
     }
 
@@ -246,7 +260,7 @@ impl File {
             });
             use crate::data::entry::Header;
             cursor = match cursor.header {
-                Header::OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance)),
+                Header::OfsDelta { base_distance } => self.try_entry(cursor.base_pack_offset(base_distance))?,
                 Header::RefDelta { base_id } => match resolve(base_id.as_ref(), out) {
                     Some(ResolvedBase::InPack(entry)) => entry,
                     Some(ResolvedBase::OutOfPack { end, kind }) => {
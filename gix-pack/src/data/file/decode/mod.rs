@@ -17,6 +17,8 @@ pub enum Error {
     DeltaBaseUnresolved(gix_hash::ObjectId),
     #[error("Entry too large to fit in memory")]
     OutOfMemory,
+    #[error("Could not read pack data")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<TryReserveError> for Error {
@@ -14,7 +14,7 @@ pub mod checksum {
 impl File {
     /// The checksum in the trailer of this pack data file
     pub fn checksum(&self) -> gix_hash::ObjectId {
-        gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - self.hash_len..])
+        self.checksum
     }
 
     /// Verifies that the checksum of the packfile over all bytes preceding it indeed matches the actual checksum,
@@ -30,9 +30,30 @@ impl File {
         progress: &mut dyn Progress,
         should_interrupt: &AtomicBool,
     ) -> Result<gix_hash::ObjectId, checksum::Error> {
+        let Some(data) = self.data.as_slice() else {
+            let actual = gix_features::hash::bytes_of_file(
+                self.path(),
+                self.pack_end() as u64,
+                self.object_hash,
+                progress,
+                should_interrupt,
+            )
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::Interrupted => checksum::Error::Interrupted,
+                _ => checksum::Error::Io(err),
+            })?;
+            return if actual == self.checksum() {
+                Ok(actual)
+            } else {
+                Err(checksum::Error::Mismatch {
+                    actual,
+                    expected: self.checksum(),
+                })
+            };
+        };
         crate::verify::checksum_on_disk_or_mmap(
             self.path(),
-            &self.data,
+            data,
             self.checksum(),
             self.object_hash,
             progress,
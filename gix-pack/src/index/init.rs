@@ -1,6 +1,9 @@
 use std::{mem::size_of, path::Path};
 
-use crate::index::{self, Version, FAN_LEN, V2_SIGNATURE};
+use crate::{
+    index::{self, Version, FAN_LEN, V2_SIGNATURE},
+    Backing,
+};
 
 /// Returned by [`index::File::at()`].
 #[derive(thiserror::Error, Debug)]
@@ -26,11 +29,20 @@ impl index::File {
     /// The `object_hash` is a way to read (and write) the same file format with different hashes, as the hash kind
     /// isn't stored within the file format itself.
     pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<index::File, Error> {
-        Self::at_inner(path.as_ref(), object_hash)
+        Self::at_inner(path.as_ref(), object_hash, Backing::default())
     }
 
-    fn at_inner(path: &Path, object_hash: gix_hash::Kind) -> Result<index::File, Error> {
-        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+    /// Like [`at()`](Self::at()), but uses `backing` to determine how the data of the file at `path` is made available.
+    pub fn at_with_backing(
+        path: impl AsRef<Path>,
+        object_hash: gix_hash::Kind,
+        backing: Backing,
+    ) -> Result<index::File, Error> {
+        Self::at_inner(path.as_ref(), object_hash, backing)
+    }
+
+    fn at_inner(path: &Path, object_hash: gix_hash::Kind, backing: Backing) -> Result<index::File, Error> {
+        let data = crate::backing::Data::read_only(path, backing).map_err(|source| Error::Io {
             source,
             path: path.to_owned(),
         })?;
@@ -73,8 +73,6 @@ macro_rules! izip {
     };
 }
 
-use memmap2::Mmap;
-
 /// The version of an index file
 #[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -105,7 +103,7 @@ const FAN_LEN: usize = 256;
 
 /// A representation of a pack index file
 pub struct File {
-    data: Mmap,
+    data: crate::backing::Data,
     path: std::path::PathBuf,
     version: Version,
     num_objects: u32,
@@ -159,7 +159,13 @@ impl index::File {
         C: crate::cache::DecodeEntry,
         E: std::error::Error + Send + Sync + 'static,
     {
-        let pack_entry = pack.entry(index_entry.pack_offset);
+        let pack_entry = pack
+            .try_entry(index_entry.pack_offset)
+            .map_err(|err| Error::PackDecode {
+                id: index_entry.oid,
+                offset: index_entry.pack_offset,
+                source: err.into(),
+            })?;
         let pack_entry_data_offset = pack_entry.data_offset;
         let entry_stats = pack
             .decode_entry(
@@ -167,9 +173,9 @@ impl index::File {
                 buf,
                 inflate,
                 &|id, _| {
-                    self.lookup(id).map(|index| {
-                        crate::data::decode::entry::ResolvedBase::InPack(pack.entry(self.pack_offset_at_index(index)))
-                    })
+                    self.lookup(id)
+                        .and_then(|index| pack.try_entry(self.pack_offset_at_index(index)).ok())
+                        .map(crate::data::decode::entry::ResolvedBase::InPack)
                 },
                 cache,
             )
@@ -201,7 +207,7 @@ fn process_entry<E>(
     object_kind: gix_object::Kind,
     decompressed: &[u8],
     index_entry: &index::Entry,
-    pack_entry_crc32: impl FnOnce() -> u32,
+    pack_entry_crc32: impl FnOnce() -> std::io::Result<u32>,
     progress: &dyn Progress,
     processor: &mut impl FnMut(gix_object::Kind, &[u8], &index::Entry, &dyn Progress) -> Result<(), E>,
 ) -> Result<(), Error<E>>
@@ -219,7 +225,11 @@ where
             });
         }
         if let Some(desired_crc32) = index_entry.crc32 {
-            let actual_crc32 = pack_entry_crc32();
+            let actual_crc32 = pack_entry_crc32().map_err(|err| Error::PackDecode {
+                id: index_entry.oid,
+                offset: index_entry.pack_offset,
+                source: err.into(),
+            })?;
             if actual_crc32 != desired_crc32 {
                 return Err(Error::Crc32Mismatch {
                     actual: actual_crc32,
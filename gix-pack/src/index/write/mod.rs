@@ -183,7 +183,7 @@ impl crate::index::File {
         let (resolver, pack) = make_resolver()?;
         let sorted_pack_offsets_by_oid = {
             let traverse::Outcome { roots, children } = tree.traverse(
-                resolver,
+                move |slice, pack| resolver(slice, pack).map(std::borrow::Cow::Borrowed),
                 &pack,
                 pack_entries_end,
                 |data,
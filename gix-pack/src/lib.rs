@@ -28,6 +28,9 @@ pub struct Bundle {
     pub index: index::File,
 }
 
+mod backing;
+pub use backing::Backing;
+
 ///
 pub mod find;
 
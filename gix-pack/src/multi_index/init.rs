@@ -1,6 +1,9 @@
 use std::{convert::TryFrom, path::Path};
 
-use crate::multi_index::{chunk, File, Version};
+use crate::{
+    multi_index::{chunk, File, Version},
+    Backing,
+};
 
 mod error {
     use crate::multi_index::chunk;
@@ -43,13 +46,24 @@ impl File {
     pub fn at(path: impl AsRef<Path>) -> Result<Self, Error> {
         Self::try_from(path.as_ref())
     }
+
+    /// Like [`at()`](Self::at()), but uses `backing` to determine how the data of the file at `path` is made available.
+    pub fn at_with_backing(path: impl AsRef<Path>, backing: Backing) -> Result<Self, Error> {
+        Self::at_inner(path.as_ref(), backing)
+    }
 }
 
 impl TryFrom<&Path> for File {
     type Error = Error;
 
     fn try_from(path: &Path) -> Result<Self, Self::Error> {
-        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+        Self::at_inner(path, Backing::default())
+    }
+}
+
+impl File {
+    fn at_inner(path: &Path, backing: Backing) -> Result<Self, Error> {
+        let data = crate::backing::Data::read_only(path, backing).map_err(|source| Error::Io {
             source,
             path: path.to_owned(),
         })?;
@@ -10,6 +10,8 @@ pub mod checksum {
     pub enum Error {
         #[error("Interrupted by user")]
         Interrupted,
+        #[error("Could not read the file to compute its checksum")]
+        Io(#[from] std::io::Error),
         #[error("index checksum mismatch: expected {expected}, got {actual}")]
         Mismatch {
             expected: gix_hash::ObjectId,
@@ -41,6 +41,108 @@ mod method {
     }
 }
 
+mod backing {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_features::progress;
+    use gix_pack::Backing;
+
+    use crate::{
+        fixture_path, hex_to_id,
+        pack::{SMALL_PACK, SMALL_PACK_INDEX},
+    };
+
+    #[test]
+    fn in_memory_files_are_unaffected_by_changes_on_disk() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = gix_testtools::tempfile::TempDir::new()?;
+        let pack_path = dir.path().join("pack.pack");
+        let index_path = dir.path().join("pack.idx");
+        std::fs::copy(fixture_path(SMALL_PACK), &pack_path)?;
+        std::fs::copy(fixture_path(SMALL_PACK_INDEX), &index_path)?;
+
+        let pack = gix_pack::data::File::at_with_backing(&pack_path, gix_hash::Kind::Sha1, Backing::InMemory)?;
+        let index = gix_pack::index::File::at_with_backing(&index_path, gix_hash::Kind::Sha1, Backing::InMemory)?;
+        for path in [&pack_path, &index_path] {
+            std::fs::File::create(path)?.set_len(0)?;
+        }
+
+        assert_eq!(pack.checksum(), hex_to_id("0f3ea84cd1bba10c2a03d736a460635082833e59"));
+        assert_eq!(
+            pack.verify_checksum(&mut progress::Discard, &AtomicBool::new(false))?,
+            pack.checksum(),
+            "all data was read when opening the file, truncating it has no effect"
+        );
+        let entry_index = index
+            .lookup(hex_to_id("f139391424a8c623adadf2388caec73e5e90865b"))
+            .expect("object present");
+        let entry = pack.entry(index.pack_offset_at_index(entry_index));
+        assert_eq!(
+            pack.decode_header(entry, &mut Default::default(), &|_| None)?
+                .object_size,
+            1163,
+            "the index can be used as well"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn positioned_reads_report_io_errors_on_access() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = gix_testtools::tempfile::TempDir::new()?;
+        let pack_path = dir.path().join("pack.pack");
+        std::fs::copy(fixture_path(SMALL_PACK), &pack_path)?;
+
+        let pack = gix_pack::data::File::at_with_backing(&pack_path, gix_hash::Kind::Sha1, Backing::PositionedRead)?;
+        let index = gix_pack::index::File::at(fixture_path(SMALL_PACK_INDEX), gix_hash::Kind::Sha1)?;
+        assert_eq!(pack.checksum(), hex_to_id("0f3ea84cd1bba10c2a03d736a460635082833e59"));
+        assert_eq!(
+            pack.verify_checksum(&mut progress::Discard, &AtomicBool::new(false))?,
+            pack.checksum()
+        );
+
+        let mapped = gix_pack::data::File::at(fixture_path(SMALL_PACK), gix_hash::Kind::Sha1)?;
+        let (mut buf, mut expected) = (Vec::new(), Vec::new());
+        for entry in index.iter() {
+            let outcome = pack.decode_entry(
+                pack.try_entry(entry.pack_offset)?,
+                &mut buf,
+                &mut Default::default(),
+                &|_, _| None,
+                &mut gix_pack::cache::Never,
+            )?;
+            let expected_outcome = mapped.decode_entry(
+                mapped.entry(entry.pack_offset),
+                &mut expected,
+                &mut Default::default(),
+                &|_, _| None,
+                &mut gix_pack::cache::Never,
+            )?;
+            assert_eq!(outcome.kind, expected_outcome.kind);
+            assert_eq!(buf, expected, "objects decode just like with memory maps");
+        }
+
+        std::fs::File::create(&pack_path)?.set_len(0)?;
+        let offset = index.pack_offset_at_index(0);
+        assert!(
+            pack.try_entry(offset).is_err(),
+            "data is read when accessed, so truncation is noticed"
+        );
+        assert!(pack
+            .verify_checksum(&mut progress::Discard, &AtomicBool::new(false))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn io_errors_are_reported_when_opening() {
+        let res = gix_pack::data::File::at_with_backing(
+            fixture_path("does-not-exist.pack"),
+            gix_hash::Kind::Sha1,
+            Backing::InMemory,
+        );
+        assert!(matches!(res, Err(gix_pack::data::header::decode::Error::Io { .. })));
+    }
+}
+
 /// All hardcoded offsets are obtained via `git pack-verify --verbose  tests/fixtures/packs/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.idx`
 mod decode_entry {
     use bstr::ByteSlice;
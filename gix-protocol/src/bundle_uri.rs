@@ -0,0 +1,93 @@
+mod error {
+    /// The error returned by [`bundle_uri()`][crate::bundle_uri()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Transport(#[from] gix_transport::client::Error),
+        #[error(transparent)]
+        PacketlineDecode(#[from] gix_transport::packetline::decode::Error),
+        #[error("The server does not support the 'bundle-uri' command")]
+        Unsupported,
+        #[error("The server sent an unexpected line: {line:?}")]
+        Parse { line: bstr::BString },
+    }
+
+    impl gix_transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                Error::Transport(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+pub use error::Error;
+
+pub(crate) mod function {
+    use std::borrow::Cow;
+
+    use bstr::{BString, ByteSlice};
+    use gix_transport::client::{Capabilities, Transport, TransportV2Ext};
+    use maybe_async::maybe_async;
+
+    use super::Error;
+    use crate::Command;
+
+    /// Invoke a bundle-uri V2 command on `transport` to obtain the bundle list advertised by the server as pairs of keys
+    /// like `bundle.<id>.uri` and their values, in the order in which they were sent.
+    /// It requires a prior handshake that yielded server `capabilities`, and `features` like `(agent, Some(name))` are sent
+    /// along with the command.
+    ///
+    /// The pairs can be parsed with `List::from_key_value_pairs()` of the `gix-bundle` crate, which isn't done here
+    /// so the list can be inspected even if it isn't understood.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    #[maybe_async]
+    pub async fn bundle_uri(
+        mut transport: impl Transport,
+        capabilities: &Capabilities,
+        features: Vec<(&'static str, Option<Cow<'static, str>>)>,
+        trace: bool,
+    ) -> Result<Vec<(BString, BString)>, Error> {
+        let _span = gix_features::trace::detail!("gix_protocol::bundle_uri()", capabilities = ?capabilities);
+        if !capabilities.contains(Command::BundleUri.as_str()) {
+            return Err(Error::Unsupported);
+        }
+        let bundle_uri = Command::BundleUri;
+        let arguments = bundle_uri.initial_arguments(&features);
+        bundle_uri.validate_argument_prefixes_or_panic(
+            gix_transport::Protocol::V2,
+            capabilities,
+            &arguments,
+            &features,
+        );
+
+        let mut lines = transport
+            .invoke(
+                bundle_uri.as_str(),
+                features.into_iter(),
+                Some(arguments.into_iter()),
+                trace,
+            )
+            .await?;
+
+        let mut out = Vec::new();
+        while let Some(line) = lines
+            .readline()
+            .await
+            .transpose()?
+            .transpose()?
+            .and_then(|l| l.as_bstr())
+        {
+            let line = line.trim_end();
+            let (key, value) = line
+                .split_once_str("=")
+                .ok_or_else(|| Error::Parse { line: line.into() })?;
+            out.push((key.into(), value.into()));
+        }
+        Ok(out)
+    }
+}
@@ -12,6 +12,8 @@ impl Command {
         match self {
             Command::LsRefs => "ls-refs",
             Command::Fetch => "fetch",
+            Command::ObjectInfo => "object-info",
+            Command::BundleUri => "bundle-uri",
         }
     }
 }
@@ -53,12 +55,14 @@ mod with_io {
                     // wait-for-done feature
                     "wait-for-done",
                 ],
+                Command::ObjectInfo => &["size", "oid "],
+                Command::BundleUri => &[],
             }
         }
 
         fn all_features(&self, version: gix_transport::Protocol) -> &'static [&'static str] {
             match self {
-                Command::LsRefs => &[],
+                Command::LsRefs | Command::ObjectInfo | Command::BundleUri => &[],
                 Command::Fetch => match version {
                     gix_transport::Protocol::V0 | gix_transport::Protocol::V1 => &[
                         "multi_ack",
@@ -108,6 +112,8 @@ mod with_io {
                     )
                     .collect(),
                 Command::LsRefs => vec![b"symrefs".as_bstr().to_owned(), b"peel".as_bstr().to_owned()],
+                Command::ObjectInfo => vec![b"size".as_bstr().to_owned()],
+                Command::BundleUri => vec![],
             }
         }
 
@@ -154,7 +160,7 @@ mod with_io {
                             .collect()
                     }
                 },
-                Command::LsRefs => vec![],
+                Command::LsRefs | Command::ObjectInfo | Command::BundleUri => vec![],
             }
         }
         /// Panics if the given arguments and features don't match what's statically known. It's considered a bug in the delegate.
@@ -19,6 +19,7 @@ pub struct Arguments {
     deepen_relative: bool,
     ref_in_want: bool,
     supports_include_tag: bool,
+    no_done: bool,
 
     features_for_first_want: Option<Vec<String>>,
     #[cfg(any(feature = "async-client", feature = "blocking-client"))]
@@ -80,6 +81,13 @@ impl Arguments {
     pub fn can_use_include_tag(&self) -> bool {
         self.supports_include_tag
     }
+    /// Return true if the 'no-done' capability is used in protocol V1, which allows the server to send the pack
+    /// as soon as it is ready, without waiting for the client to send `done`.
+    ///
+    /// This is needed to know when a response ends with [`Response::from_line_reader()`][crate::fetch::Response::from_line_reader()].
+    pub fn can_use_no_done(&self) -> bool {
+        self.no_done
+    }
     /// Return true if we will use a stateless mode of operation, which can be decided in conjunction with `transport_is_stateless`.
     ///
     /// * we are always stateless if the transport is stateless, i.e. doesn't support multiple interactions with a single connection.
@@ -154,7 +162,9 @@ impl Arguments {
             self.args.push(line);
         }
     }
-    /// Set the given filter `spec` when listing references.
+    /// Set the given filter `spec` to have the server omit objects, creating a partial clone.
+    ///
+    /// Use [`Filter`](crate::fetch::Filter) to produce a valid `spec` via its `Display` implementation.
     pub fn filter(&mut self, spec: &str) {
         debug_assert!(self.filter, "'filter' feature required");
         if self.filter {
@@ -204,6 +214,7 @@ impl Arguments {
         let filter = has("filter");
         let shallow = has("shallow");
         let ref_in_want = has("ref-in-want");
+        let no_done = has("no-done");
         let mut deepen_since = shallow;
         let mut deepen_not = shallow;
         let mut deepen_relative = shallow;
@@ -243,6 +254,7 @@ impl Arguments {
             deepen_not,
             deepen_relative,
             ref_in_want,
+            no_done,
             deepen_since,
             features_for_first_want,
             trace,
@@ -7,6 +7,8 @@ pub mod delegate;
 pub use delegate::Delegate;
 pub use delegate::{Action, DelegateBlocking};
 
+pub use crate::{filter, filter::Filter};
+
 mod error;
 pub use error::Error;
 ///
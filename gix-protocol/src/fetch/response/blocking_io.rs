@@ -33,69 +33,47 @@ fn parse_v2_section<'a, T>(
 impl Response {
     /// Parse a response of the given `version` of the protocol from `reader`.
     ///
-    /// `client_expects_pack` is only relevant for V1 stateful connections, and if `false`, causes us to stop parsing when seeing `NAK`,
-    /// and if `true` we will keep parsing until we get a pack as the client already signalled to the server that it's done.
-    /// This way of doing things allows us to exploit knowledge about more recent versions of the protocol, which keeps code easier
-    /// and more localized without having to support all the cruft that there is.
+    /// `client_expects_pack` and `no_done` are only relevant for V1, where the end of a response can't be told from the response
+    /// itself but depends on what the client sent. Without knowing it, parsing would have to peek past the end of the response,
+    /// which blocks forever on stateful connections as the server waits for the client to send more.
     ///
-    /// `wants_to_negotiate` should be `false` for clones which is when we don't have sent any haves. The reason for this flag to exist
-    /// is to predict how to parse V1 output only, and neither `client_expects_pack` nor `wants_to_negotiate` are relevant for V2.
-    /// This ugliness is in place to avoid having to resort to an [an even more complex ugliness](https://github.com/git/git/blob/9e49351c3060e1fa6e0d2de64505b7becf157f28/fetch-pack.c#L583-L594)
-    /// that `git` has to use to predict how many acks are supposed to be read. We also genuinely hope that this covers it all….
+    /// * `client_expects_pack` is `true` if the client sent `done`, and the response ends with the final `ACK` or `NAK`
+    ///   which is followed by the pack.
+    /// * Otherwise the response to a batch of `have` lines ends with `NAK`, unless the server is `ready` and the client
+    ///   negotiated the `no-done` capability, as indicated by `no_done`, which has the server send the final `ACK` and the pack
+    ///   right away.
+    ///
+    /// Both flags are ignored for V2, whose responses are self-describing.
     pub fn from_line_reader<'a>(
         version: Protocol,
         reader: &mut impl client::ExtendedBufRead<'a>,
         client_expects_pack: bool,
-        wants_to_negotiate: bool,
+        no_done: bool,
     ) -> Result<Response, response::Error> {
         match version {
             Protocol::V0 | Protocol::V1 => {
                 let mut line = String::new();
                 let mut acks = Vec::<Acknowledgement>::new();
                 let mut shallows = Vec::<ShallowUpdate>::new();
-                let mut saw_ready = false;
-                let has_pack = 'lines: loop {
+                let has_pack = loop {
                     line.clear();
-                    let peeked_line = match reader.peek_data_line() {
-                        Some(Ok(Ok(line))) => String::from_utf8_lossy(line),
-                        // This special case (hang/block forever) deals with a single NAK being a legitimate EOF sometimes
-                        // Note that this might block forever in stateful connections as there it's not really clear
-                        // if something will be following or not by just looking at the response. Instead you have to know
-                        // [a lot](https://github.com/git/git/blob/9e49351c3060e1fa6e0d2de64505b7becf157f28/fetch-pack.c#L583-L594)
-                        // to deal with this correctly.
-                        // For now this is acceptable, as V2 can be used as a workaround, which also is the default.
-                        Some(Err(err)) if err.kind() == io::ErrorKind::UnexpectedEof => break 'lines false,
-                        Some(Err(err)) => return Err(err.into()),
-                        Some(Ok(Err(err))) => return Err(err.into()),
-                        None => {
-                            // maybe we saw a shallow flush packet, let's reset and retry
-                            debug_assert_eq!(
-                                reader.stopped_at(),
-                                Some(client::MessageKind::Flush),
-                                "If this isn't a flush packet, we don't know what's going on"
-                            );
-                            reader.readline_str(&mut line)?;
-                            reader.reset(Protocol::V1);
-                            match reader.peek_data_line() {
-                                Some(Ok(Ok(line))) => String::from_utf8_lossy(line),
-                                Some(Err(err)) => return Err(err.into()),
-                                Some(Ok(Err(err))) => return Err(err.into()),
-                                None => break 'lines false, // EOF
+                    match reader.readline_str(&mut line) {
+                        Ok(0) => match reader.stopped_at() {
+                            // Shallow updates are terminated by a flush packet, and acknowledgements follow.
+                            Some(client::MessageKind::Flush) => {
+                                reader.reset(Protocol::V1);
+                                continue;
                             }
-                        }
-                    };
-
-                    if Response::parse_v1_ack_or_shallow_or_assume_pack(&mut acks, &mut shallows, &peeked_line) {
-                        break 'lines true;
+                            _ => break false,
+                        },
+                        Ok(_) => {}
+                        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break false,
+                        Err(err) => return Err(err.into()),
                     }
-                    assert_ne!(reader.readline_str(&mut line)?, 0, "consuming a peeked line works");
-                    // When the server sends ready, we know there is going to be a pack so no need to stop early.
-                    saw_ready |= matches!(acks.last(), Some(Acknowledgement::Ready));
-                    if let Some(Acknowledgement::Nak) = acks.last().filter(|_| !client_expects_pack || !saw_ready) {
-                        if !wants_to_negotiate {
-                            continue;
-                        }
-                        break 'lines false;
+                    if let Some(has_pack) =
+                        Response::parse_v1_line(&mut acks, &mut shallows, &line, client_expects_pack, no_done)?
+                    {
+                        break has_pack;
                     }
                 };
                 Ok(Response {
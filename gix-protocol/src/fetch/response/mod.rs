@@ -113,7 +113,7 @@ impl Acknowledgement {
                     };
                     if let Some(description) = description {
                         match description {
-                            "common" => {}
+                            "common" | "continue" => {}
                             "ready" => return Ok(Acknowledgement::Ready),
                             _ => return Err(Error::UnknownLineType { line: line.to_owned() }),
                         }
@@ -172,10 +172,10 @@ impl Response {
         match version {
             Protocol::V0 | Protocol::V1 => {
                 let has = |name: &str| features.iter().any(|f| f.0 == name);
-                // Let's focus on V2 standards, and simply not support old servers to keep our code simpler
-                if !has("multi_ack_detailed") {
+                // Without `multi_ack`, the server doesn't terminate each response with `NAK`, so we can't know when to stop reading.
+                if !has("multi_ack_detailed") && !has("multi_ack") {
                     return Err(Error::MissingServerCapability {
-                        feature: "multi_ack_detailed",
+                        feature: "multi_ack OR multi_ack_detailed",
                     });
                 }
                 // It's easy to NOT do sideband for us, but then again, everyone supports it.
@@ -211,14 +211,33 @@ impl Response {
 
 #[cfg(any(feature = "async-client", feature = "blocking-client"))]
 impl Response {
-    /// with a friendly server, we just assume that a non-ack line is a pack line
-    /// which is our hint to stop here.
-    fn parse_v1_ack_or_shallow_or_assume_pack(
+    /// Parse a V1 `line` into `acks` or `shallows`, and return `Some(has_pack)` if it is the last line of the response.
+    ///
+    /// See [`from_line_reader()`][Response::from_line_reader()] for the meaning of `client_expects_pack` and `no_done`.
+    fn parse_v1_line(
         acks: &mut Vec<Acknowledgement>,
         shallows: &mut Vec<ShallowUpdate>,
-        peeked_line: &str,
-    ) -> bool {
-        match Acknowledgement::from_line(peeked_line) {
+        line: &str,
+        client_expects_pack: bool,
+        no_done: bool,
+    ) -> Result<Option<bool>, Error> {
+        let line = line.trim_end();
+        if line == "NAK" {
+            let saw_ack = acks.iter().any(|ack| *ack != Acknowledgement::Nak);
+            let saw_ready = acks.contains(&Acknowledgement::Ready);
+            acks.push(Acknowledgement::Nak);
+            return Ok(if client_expects_pack {
+                // `done` is answered with `NAK` only if nothing is in common, otherwise this answers a flush
+                // and the final `ACK` is yet to come.
+                (!saw_ack).then_some(true)
+            } else if saw_ready && no_done {
+                None
+            } else {
+                Some(false)
+            });
+        }
+        let is_final_ack = line.strip_prefix("ACK ").map_or(false, |id| !id.contains(' '));
+        match Acknowledgement::from_line(line) {
             Ok(ack) => match ack.id() {
                 Some(id) => {
                     if !acks.iter().any(|a| a.id() == Some(id)) {
@@ -227,14 +246,10 @@ impl Response {
                 }
                 None => acks.push(ack),
             },
-            Err(_) => match ShallowUpdate::from_line(peeked_line) {
-                Ok(shallow) => {
-                    shallows.push(shallow);
-                }
-                Err(_) => return true,
-            },
+            Err(_) => shallows.push(ShallowUpdate::from_line(line)?),
         };
-        false
+        // The final `ACK` without status is only sent right before the pack.
+        Ok(is_final_ack.then_some(true))
     }
 }
 
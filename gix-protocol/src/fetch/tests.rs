@@ -393,6 +393,29 @@ mod arguments {
             }
         }
 
+        #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+        async fn filter() {
+            let mut out = Vec::new();
+            let mut t = transport(&mut out, false);
+            let mut arguments = arguments_v2(["filter"].iter().copied());
+            assert!(arguments.can_use_filter());
+
+            arguments.filter(&crate::fetch::Filter::BlobNone.to_string());
+            arguments.want(id("ff333369de1221f9bfbbe03a3a13e9a09bc1ffff"));
+            arguments.send(&mut t, true).await.expect("sending to buffer to work");
+            assert_eq!(
+                out.as_bstr(),
+                b"0012command=fetch
+0001000ethin-pack
+000eofs-delta
+0015filter blob:none
+0032want ff333369de1221f9bfbbe03a3a13e9a09bc1ffff
+0009done
+0000"
+                    .as_bstr()
+            )
+        }
+
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn ref_in_want() {
             let mut out = Vec::new();
@@ -414,3 +437,58 @@ mod arguments {
         }
     }
 }
+
+mod filter {
+    use crate::fetch::{filter, Filter};
+
+    fn parse(spec: &str) -> Result<Filter, filter::Error> {
+        spec.parse()
+    }
+
+    #[test]
+    fn round_trips() {
+        for (spec, expected) in [
+            ("blob:none", Filter::BlobNone),
+            ("blob:limit=0", Filter::BlobLimit(0)),
+            ("blob:limit=42", Filter::BlobLimit(42)),
+            ("tree:0", Filter::TreeDepth(0)),
+            ("tree:3", Filter::TreeDepth(3)),
+            ("sparse:oid=main:.sparse", Filter::SparseOid("main:.sparse".into())),
+        ] {
+            let filter = parse(spec).expect("valid");
+            assert_eq!(filter, expected);
+            assert_eq!(filter.to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn size_units_are_resolved() {
+        assert_eq!(parse("blob:limit=1k").unwrap(), Filter::BlobLimit(1024));
+        assert_eq!(parse("blob:limit=2M").unwrap(), Filter::BlobLimit(2 * 1024 * 1024));
+        assert_eq!(parse("blob:limit=1g").unwrap(), Filter::BlobLimit(1024 * 1024 * 1024));
+        assert_eq!(
+            parse("blob:limit=1m").unwrap().to_string(),
+            "blob:limit=1048576",
+            "sizes are always sent in bytes"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for spec in [
+            "",
+            "blob",
+            "blob:all",
+            "blob:limit=",
+            "blob:limit=k",
+            "blob:limit=-1",
+            "tree:",
+            "tree:x",
+            "sparse:oid=",
+            "sparse:path=foo",
+            "object:type=blob",
+        ] {
+            assert!(parse(spec).is_err(), "{spec:?} should fail to parse");
+        }
+    }
+}
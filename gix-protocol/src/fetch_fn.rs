@@ -138,8 +138,8 @@ where
         let response = Response::from_line_reader(
             protocol_version,
             &mut reader,
-            true,  /* hack, telling us we don't want this delegate approach anymore */
-            false, /* just as much of a hack which causes us to expect a pack immediately */
+            action == Action::Cancel,
+            arguments.can_use_no_done(),
         )
         .await?;
         previous_response = if response.has_pack() {
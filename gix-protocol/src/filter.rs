@@ -0,0 +1,96 @@
+use std::fmt;
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// An object filter as sent with the `filter` argument to a server which supports the `filter` capability,
+/// to receive only a subset of the objects that would otherwise be sent, which creates a *partial clone*.
+///
+/// Objects that are omitted this way are expected to be fetched from the *promisor remote* on demand.
+/// See the [`filter-spec` documentation](https://git-scm.com/docs/git-rev-list#Documentation/git-rev-list.txt---filterltfilter-specgt)
+/// for details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// `blob:none`, omit all blobs.
+    BlobNone,
+    /// `blob:limit=<n>`, omit all blobs whose size is `n` bytes or more.
+    BlobLimit(u64),
+    /// `tree:<depth>`, omit all blobs and trees whose depth from the root tree is `depth` or more.
+    ///
+    /// A depth of `0` omits all trees and blobs, but still sends the commits.
+    TreeDepth(u64),
+    /// `sparse:oid=<blob-ish>`, use the sparse-checkout specification stored in the given blob to decide which blobs
+    /// to omit.
+    SparseOid(BString),
+}
+
+/// The error returned by [`Filter::from_bytes()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The filter specification {spec:?} is not known or not supported")]
+    Unsupported { spec: BString },
+    #[error("The size or depth {value:?} in filter specification {spec:?} could not be parsed")]
+    InvalidNumber { spec: BString, value: BString },
+}
+
+impl Filter {
+    /// Parse a filter specification like `blob:none` or `blob:limit=1m`, as it would be passed to `git clone --filter`
+    /// or be stored in `remote.<name>.partialclonefilter`.
+    pub fn from_bytes(spec: &BStr) -> Result<Self, Error> {
+        let unsupported = || Error::Unsupported { spec: spec.into() };
+        let invalid_number = |value: &[u8]| Error::InvalidNumber {
+            spec: spec.into(),
+            value: value.into(),
+        };
+        Ok(if spec == "blob:none" {
+            Filter::BlobNone
+        } else if let Some(limit) = spec.strip_prefix(b"blob:limit=") {
+            Filter::BlobLimit(parse_size(limit).ok_or_else(|| invalid_number(limit))?)
+        } else if let Some(depth) = spec.strip_prefix(b"tree:") {
+            Filter::TreeDepth(
+                depth
+                    .to_str()
+                    .ok()
+                    .and_then(|d| d.parse().ok())
+                    .ok_or_else(|| invalid_number(depth))?,
+            )
+        } else if let Some(oid) = spec.strip_prefix(b"sparse:oid=") {
+            if oid.is_empty() {
+                return Err(unsupported());
+            }
+            Filter::SparseOid(oid.into())
+        } else {
+            return Err(unsupported());
+        })
+    }
+}
+
+/// Parse `size` with an optional `k`, `m` or `g` unit suffix, just like git does for integers in its configuration.
+fn parse_size(size: &[u8]) -> Option<u64> {
+    let (number, factor) = match size.last()?.to_ascii_lowercase() {
+        b'k' => (&size[..size.len() - 1], 1024),
+        b'm' => (&size[..size.len() - 1], 1024 * 1024),
+        b'g' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    number.to_str().ok()?.parse::<u64>().ok()?.checked_mul(factor)
+}
+
+impl std::str::FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::from_bytes(s.into())
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::BlobNone => f.write_str("blob:none"),
+            Filter::BlobLimit(limit) => write!(f, "blob:limit={limit}"),
+            Filter::TreeDepth(depth) => write!(f, "tree:{depth}"),
+            Filter::SparseOid(oid) => write!(f, "sparse:oid={oid}"),
+        }
+    }
+}
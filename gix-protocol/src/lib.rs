@@ -17,6 +17,10 @@ pub enum Command {
     LsRefs,
     /// Fetch a pack.
     Fetch,
+    /// Obtain information about objects, like their size.
+    ObjectInfo,
+    /// Obtain a list of bundles to download before fetching.
+    BundleUri,
 }
 pub mod command;
 
@@ -40,11 +44,21 @@ mod fetch_fn;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use fetch_fn::{fetch, FetchConnection};
 
+///
+pub mod filter;
+pub use filter::Filter;
+
+/// Server-side implementations of git services which work with any pair of blocking reader and writer.
+#[cfg(feature = "server")]
+pub mod server;
+
 mod remote_progress;
 pub use remote_progress::RemoteProgress;
 
 #[cfg(all(feature = "blocking-client", feature = "async-client"))]
 compile_error!("Cannot set both 'blocking-client' and 'async-client' features as they are mutually exclusive");
+#[cfg(all(feature = "server", feature = "async-client"))]
+compile_error!("Cannot set both 'server' and 'async-client' features as the server only supports blocking IO");
 
 ///
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
@@ -58,6 +72,18 @@ pub mod ls_refs;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use ls_refs::function::ls_refs;
 
+///
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub mod object_info;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use object_info::function::object_info;
+
+///
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub mod bundle_uri;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use bundle_uri::function::bundle_uri;
+
 mod util;
 pub use util::agent;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
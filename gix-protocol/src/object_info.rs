@@ -0,0 +1,117 @@
+mod error {
+    /// The error returned by [`object_info()`][crate::object_info()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Transport(#[from] gix_transport::client::Error),
+        #[error(transparent)]
+        PacketlineDecode(#[from] gix_transport::packetline::decode::Error),
+        #[error("The server does not support the 'object-info' command")]
+        Unsupported,
+        #[error("The server sent an unexpected line: {line:?}")]
+        Parse { line: bstr::BString },
+    }
+
+    impl gix_transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                Error::Transport(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+pub use error::Error;
+
+/// Information about a single object, as returned by [`object_info()`][crate::object_info()].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Info {
+    /// The id of the object the information is about.
+    pub id: gix_hash::ObjectId,
+    /// The size of the object in bytes, or `None` if the server doesn't know the object.
+    pub size: Option<u64>,
+}
+
+pub(crate) mod function {
+    use std::borrow::Cow;
+
+    use bstr::{BString, ByteSlice};
+    use gix_transport::client::{Capabilities, Transport, TransportV2Ext};
+    use maybe_async::maybe_async;
+
+    use super::{Error, Info};
+    use crate::Command;
+
+    /// Invoke an object-info V2 command on `transport` to obtain the size of the objects with `ids` without downloading them.
+    /// It requires a prior handshake that yielded server `capabilities`, and `features` like `(agent, Some(name))` are sent
+    /// along with the command.
+    ///
+    /// The returned information is in the order of `ids`.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    #[maybe_async]
+    pub async fn object_info(
+        mut transport: impl Transport,
+        capabilities: &Capabilities,
+        ids: impl IntoIterator<Item = gix_hash::ObjectId>,
+        features: Vec<(&'static str, Option<Cow<'static, str>>)>,
+        trace: bool,
+    ) -> Result<Vec<Info>, Error> {
+        let _span = gix_features::trace::detail!("gix_protocol::object_info()", capabilities = ?capabilities);
+        if !capabilities.contains(Command::ObjectInfo.as_str()) {
+            return Err(Error::Unsupported);
+        }
+        let object_info = Command::ObjectInfo;
+        let mut arguments = object_info.initial_arguments(&features);
+        arguments.extend(ids.into_iter().map(|id| BString::from(format!("oid {id}"))));
+        object_info.validate_argument_prefixes_or_panic(
+            gix_transport::Protocol::V2,
+            capabilities,
+            &arguments,
+            &features,
+        );
+
+        let mut lines = transport
+            .invoke(
+                object_info.as_str(),
+                features.into_iter(),
+                Some(arguments.into_iter()),
+                trace,
+            )
+            .await?;
+
+        let mut out = Vec::new();
+        let mut has_size = false;
+        while let Some(line) = lines
+            .readline()
+            .await
+            .transpose()?
+            .transpose()?
+            .and_then(|l| l.as_bstr())
+        {
+            let line = line.trim_end();
+            if out.is_empty() && !has_size && line == b"size" {
+                has_size = true;
+                continue;
+            }
+            let parse_err = || Error::Parse { line: line.into() };
+            let (id, size) = match line.split_once_str(" ") {
+                Some((id, size)) => (id, size),
+                None => (line, &b""[..]),
+            };
+            out.push(Info {
+                id: gix_hash::ObjectId::from_hex(id).map_err(|_| parse_err())?,
+                size: match size {
+                    b"" => None,
+                    size => Some(size.to_str().ok().and_then(|s| s.parse().ok()).ok_or_else(parse_err)?),
+                }
+                .filter(|_| has_size),
+            });
+        }
+        Ok(out)
+    }
+}
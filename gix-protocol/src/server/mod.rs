@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use gix_packetline::{encode, Channel};
+
+///
+pub mod receive_pack;
+///
+pub mod upload_pack;
+
+/// The name of the object format of `kind` as used in the `object-format` capability.
+fn object_format(kind: gix_hash::Kind) -> &'static str {
+    match kind {
+        gix_hash::Kind::Sha1 => "sha1",
+    }
+}
+
+/// The largest amount of data that fits into a single side-band packet line.
+const MAX_BAND_DATA_LEN: usize = 65515;
+
+/// A writer which sends all data on `channel` of the side-band.
+struct Band<W> {
+    channel: Channel,
+    inner: W,
+}
+
+impl<W: Write> Write for Band<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = buf.len().min(MAX_BAND_DATA_LEN);
+        encode::band_to_write(self.channel, &buf[..len], &mut self.inner)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
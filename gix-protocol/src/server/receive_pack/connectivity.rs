@@ -0,0 +1,44 @@
+use std::collections::VecDeque;
+
+use gix_hash::ObjectId;
+use gix_hashtable::HashSet;
+use gix_object::{Exists, FindExt};
+
+use crate::server::receive_pack::Error;
+
+/// Return `true` if all objects reachable from `tip` exist in `objects`.
+///
+/// Objects which aren't in the set of `received` objects were present before the push and are assumed to be connected
+/// already, which is where the traversal stops.
+pub(crate) fn is_connected(tip: ObjectId, objects: &gix_odb::Handle, received: &HashSet) -> Result<bool, Error> {
+    let mut missing = false;
+    let mut check = gix_fsck::Connectivity::new(objects, |_, _| missing = true);
+    let mut queue = VecDeque::from_iter(Some(tip));
+    let mut seen = HashSet::default();
+    let mut buf = Vec::new();
+    while let Some(id) = queue.pop_front() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if !received.contains(&id) {
+            if !objects.exists(&id) {
+                return Ok(false);
+            }
+            continue;
+        }
+        let object = objects.find(&id, &mut buf)?;
+        match object.kind {
+            gix_object::Kind::Commit => {
+                queue.extend(object.try_into_commit_iter().expect("commit").parent_ids());
+                check.check_commit(&id)?;
+            }
+            gix_object::Kind::Tag => {
+                queue.extend(object.try_into_tag_iter().expect("tag").target_id().ok());
+            }
+            // Trees pointed to by references directly are only checked for existence.
+            gix_object::Kind::Tree | gix_object::Kind::Blob => {}
+        }
+    }
+    drop(check);
+    Ok(!missing)
+}
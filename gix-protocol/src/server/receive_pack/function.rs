@@ -0,0 +1,415 @@
+use std::{
+    io::{BufReader, Read, Write},
+    path::Path,
+    sync::atomic::AtomicBool,
+};
+
+use bstr::{BString, ByteVec};
+use gix_hash::ObjectId;
+use gix_hashtable::HashSet;
+use gix_packetline::{encode, Channel, PacketLineRef, StreamingPeekableIter};
+use gix_ref::{
+    file::ReferenceExt,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use super::{connectivity, request::Request, Command, Error, Hooks, Options, Outcome, Status};
+use crate::server::{object_format, Band, MAX_BAND_DATA_LEN};
+
+/// The maximum amount of symbolic references to follow, which is the same limit that `git` uses.
+const MAX_SYMREF_DEPTH: usize = 5;
+
+/// Write the reference advertisement of a `receive-pack` server to `out`, listing all references in `refs` along with our
+/// capabilities as configured by `options`.
+///
+/// This is the first thing a server sends to clients once they connected, which is followed by a request handled with
+/// [`serve_request()`].
+pub fn advertise(mut out: impl Write, refs: &gix_ref::file::Store, options: &Options) -> Result<(), Error> {
+    let mut capabilities = BString::from("report-status report-status-v2 delete-refs side-band-64k quiet");
+    if options.allow_atomic {
+        capabilities.push_str(" atomic");
+    }
+    if options.allow_push_options {
+        capabilities.push_str(" push-options");
+    }
+    capabilities.push_str(format!(
+        " ofs-delta object-format={} agent={}",
+        object_format(options.object_hash),
+        options.agent
+    ));
+
+    let mut lines = Vec::new();
+    for reference in refs.iter()?.all()? {
+        let Ok(reference) = reference else { continue };
+        if let Some(id) = resolve(reference.clone(), refs) {
+            lines.push((id, reference.name.as_bstr().to_owned()));
+        }
+    }
+    if lines.is_empty() {
+        lines.push((ObjectId::null(options.object_hash), "capabilities^{}".into()));
+    }
+    for (index, (id, name)) in lines.into_iter().enumerate() {
+        let mut line = BString::from(id.to_string());
+        line.push_byte(b' ');
+        line.push_str(name);
+        if index == 0 {
+            line.push_byte(0);
+            line.push_str(&capabilities);
+        }
+        line.push_byte(b'\n');
+        encode::data_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Follow `reference` to the object it points to, or return `None` if it is dangling or broken.
+fn resolve(mut reference: gix_ref::Reference, refs: &gix_ref::file::Store) -> Option<ObjectId> {
+    for _ in 0..=MAX_SYMREF_DEPTH {
+        match reference.target {
+            Target::Peeled(id) => return Some(id),
+            Target::Symbolic(_) => reference = reference.follow(refs)?.ok()?,
+        }
+    }
+    None
+}
+
+/// Serve a client connected through `read` and `write` by first [advertising](advertise()) all references in `refs`
+/// and then [serving its request](serve_request()).
+///
+/// `objects_dir` is the directory of the object database to receive objects into, and `hooks` decide which
+/// commands to accept.
+pub fn serve(
+    read: impl Read,
+    mut write: impl Write,
+    refs: &gix_ref::file::Store,
+    objects_dir: &Path,
+    hooks: &mut dyn Hooks,
+    options: &Options,
+) -> Result<Outcome, Error> {
+    advertise(&mut write, refs, options)?;
+    serve_request(read, write, refs, objects_dir, hooks, options)
+}
+
+/// Read a single request with commands and the pack with all objects they need from `read`, and update references
+/// in `refs` accordingly, after receiving the pack into a quarantine within `objects_dir` and consulting `hooks`.
+/// The status of each command is reported to the client via `write` if it asked for it.
+///
+/// Received objects are migrated into `objects_dir` only if the [`pre_receive()`](Hooks::pre_receive()) hook accepts them.
+///
+/// Note that commands which are rejected are not an error, and their status is part of the returned [`Outcome`].
+/// If the pack couldn't be received, all commands are rejected and the error is returned after it was reported to the client.
+pub fn serve_request(
+    mut read: impl Read,
+    mut write: impl Write,
+    refs: &gix_ref::file::Store,
+    objects_dir: &Path,
+    hooks: &mut dyn Hooks,
+    options: &Options,
+) -> Result<Outcome, Error> {
+    let mut lines = StreamingPeekableIter::new(&mut read, &[PacketLineRef::Flush], options.trace);
+    let Some(request) = Request::read(&mut lines, options)? else {
+        return Ok(Outcome::default());
+    };
+    let mut report = Report {
+        enabled: request.has("report-status") || request.has("report-status-v2"),
+        side_band: request.has("side-band-64k"),
+        statuses: vec![None; request.commands.len()],
+    };
+
+    let quarantine = gix_odb::Quarantine::new(objects_dir).map_err(Error::Quarantine)?;
+    let objects = quarantine.handle(Default::default()).map_err(Error::Quarantine)?;
+    let received = if request.expects_pack() {
+        match receive_pack(lines.into_inner(), &quarantine, &objects, options) {
+            Ok(received) => received,
+            Err(err) => {
+                report.reject_pending("unpacker error");
+                report.write(&mut write, &request, Some(&err)).ok();
+                return Err(err);
+            }
+        }
+    } else {
+        HashSet::default()
+    };
+
+    let mut names = Vec::with_capacity(request.commands.len());
+    for (command, status) in request.commands.iter().zip(report.statuses.iter_mut()) {
+        let name = FullName::try_from(command.name.clone())
+            .ok()
+            .filter(|name| name.as_bstr().starts_with(b"refs/"));
+        if name.is_none() {
+            *status = rejected("funny refname");
+        } else if !command.is_delete() && !connectivity::is_connected(command.new_id, &objects, &received)? {
+            *status = rejected("missing necessary objects");
+        }
+        names.push(name);
+    }
+    let atomic = request.has("atomic");
+    if atomic {
+        report.fail_atomic();
+    }
+
+    let pending = report.pending(&request.commands);
+    if !pending.is_empty() {
+        if let Err(reason) = hooks.pre_receive(
+            &pending,
+            &request.push_options,
+            &quarantine,
+            &mut messages(report.side_band, &mut write),
+        ) {
+            report.reject_pending(reason);
+        }
+    }
+    drop(objects);
+    if report.pending(&request.commands).is_empty() {
+        quarantine.discard()?;
+        report.write(&mut write, &request, None)?;
+        return Ok(report.into_outcome(request));
+    }
+    if let Err(err) = quarantine.migrate() {
+        let err = Error::from(err);
+        report.reject_pending("unable to migrate objects to permanent storage");
+        report.write(&mut write, &request, None).ok();
+        return Err(err);
+    }
+
+    for (command, status) in request.commands.iter().zip(report.statuses.iter_mut()) {
+        if status.is_none() {
+            if let Err(reason) = hooks.update(command, &mut messages(report.side_band, &mut write)) {
+                *status = rejected(reason);
+            }
+        }
+    }
+    if atomic {
+        report.fail_atomic();
+    }
+
+    let fallback_committer;
+    let committer = match options.committer.as_ref() {
+        Some(committer) => committer.to_ref(),
+        None => {
+            fallback_committer = gix_actor::Signature {
+                name: "gitoxide".into(),
+                email: "gitoxide@localhost".into(),
+                time: gix_date::Time::now_local_or_utc(),
+            };
+            fallback_committer.to_ref()
+        }
+    };
+    let edits: Vec<_> = request
+        .commands
+        .iter()
+        .zip(names)
+        .zip(report.statuses.iter())
+        .filter(|(_, status)| status.is_none())
+        .map(|((command, name), _)| edit(command, name.expect("valid names are pending")))
+        .collect();
+    if atomic {
+        if let Err(reason) = update_refs(refs, edits, committer) {
+            report.reject_pending(reason);
+        }
+    } else {
+        let mut edits = edits.into_iter();
+        for status in report.statuses.iter_mut().filter(|status| status.is_none()) {
+            let edit = edits.next().expect("one edit per pending command");
+            if let Err(reason) = update_refs(refs, Some(edit), committer) {
+                *status = rejected(reason);
+            }
+        }
+    }
+
+    let updated = report.pending(&request.commands);
+    if !updated.is_empty() {
+        hooks.post_receive(
+            &updated,
+            &request.push_options,
+            &mut messages(report.side_band, &mut write),
+        );
+    }
+    report.write(&mut write, &request, None)?;
+    Ok(report.into_outcome(request))
+}
+
+/// Receive the pack from `read` into `quarantine`, and return the ids of all objects in it.
+fn receive_pack(
+    read: impl Read,
+    quarantine: &gix_odb::Quarantine,
+    objects: &gix_odb::Handle,
+    options: &Options,
+) -> Result<HashSet, Error> {
+    let outcome = gix_pack::Bundle::write_to_directory(
+        &mut BufReader::with_capacity(MAX_BAND_DATA_LEN, read),
+        Some(&quarantine.pack_dir()),
+        &mut gix_features::progress::Discard,
+        &AtomicBool::default(),
+        Some(objects),
+        gix_pack::bundle::write::Options {
+            thread_limit: options.thread_limit,
+            object_hash: options.object_hash,
+            ..Default::default()
+        },
+    )?;
+    let mut received = HashSet::default();
+    if let Some(index_path) = outcome.index_path {
+        let index = gix_pack::index::File::at(index_path, options.object_hash).map_err(|err| {
+            Error::WritePack(gix_pack::bundle::write::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                err,
+            )))
+        })?;
+        received.extend(index.iter().map(|entry| entry.oid));
+    }
+    Ok(received)
+}
+
+/// Apply all `edits` to `refs` in a single transaction, or return the reason for why this failed.
+fn update_refs(
+    refs: &gix_ref::file::Store,
+    edits: impl IntoIterator<Item = RefEdit>,
+    committer: gix_actor::SignatureRef<'_>,
+) -> Result<(), String> {
+    refs.transaction()
+        .prepare(
+            edits,
+            gix_lock::acquire::Fail::Immediately,
+            gix_lock::acquire::Fail::Immediately,
+        )
+        .map_err(|err| err.to_string())?
+        .commit(Some(committer))
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+/// Create the edit to apply `command` to the reference `name`.
+fn edit(command: &Command, name: FullName) -> RefEdit {
+    let expected = if command.is_create() {
+        PreviousValue::MustNotExist
+    } else {
+        PreviousValue::MustExistAndMatch(Target::Peeled(command.old_id))
+    };
+    RefEdit {
+        change: if command.is_delete() {
+            Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            }
+        } else {
+            Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "push".into(),
+                },
+                expected,
+                new: Target::Peeled(command.new_id),
+            }
+        },
+        name,
+        deref: false,
+    }
+}
+
+fn rejected(reason: impl Into<BString>) -> Option<Status> {
+    Some(Status::Rejected { reason: reason.into() })
+}
+
+/// Return a writer for messages of hooks, which are sent on the progress channel of the side-band if `side_band` is `true`.
+fn messages<'a>(side_band: bool, out: &'a mut dyn Write) -> Box<dyn Write + 'a> {
+    if side_band {
+        Box::new(Band {
+            channel: Channel::Progress,
+            inner: out,
+        })
+    } else {
+        Box::new(std::io::sink())
+    }
+}
+
+/// The status of all commands, where `None` means the command wasn't rejected so far.
+struct Report {
+    /// If `true`, the client wants the status of all commands.
+    enabled: bool,
+    /// If `true`, the client supports the side-band.
+    side_band: bool,
+    statuses: Vec<Option<Status>>,
+}
+
+impl Report {
+    /// Return all commands that weren't rejected yet.
+    fn pending(&self, commands: &[Command]) -> Vec<Command> {
+        commands
+            .iter()
+            .zip(self.statuses.iter())
+            .filter(|(_, status)| status.is_none())
+            .map(|(command, _)| command.clone())
+            .collect()
+    }
+
+    fn reject_pending(&mut self, reason: impl Into<BString>) {
+        let reason = reason.into();
+        for status in self.statuses.iter_mut().filter(|status| status.is_none()) {
+            *status = rejected(reason.clone());
+        }
+    }
+
+    /// Reject all pending commands if at least one command was rejected, as required by atomic pushes.
+    fn fail_atomic(&mut self) {
+        if self.statuses.iter().any(Option::is_some) {
+            self.reject_pending("atomic push failure");
+        }
+    }
+
+    /// Write the status of each command in `request` to `out` if the client asked for it, with `unpack_error`
+    /// if the pack couldn't be received.
+    fn write(&self, mut out: impl Write, request: &Request, unpack_error: Option<&Error>) -> std::io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut buf = Vec::new();
+        let unpack_status = match unpack_error {
+            Some(err) => format!("unpack {err}\n"),
+            None => "unpack ok\n".into(),
+        };
+        encode::text_to_write(unpack_status.trim_end().as_bytes(), &mut buf)?;
+        for (command, status) in request.commands.iter().zip(self.statuses.iter()) {
+            let mut line = BString::default();
+            match status {
+                None | Some(Status::Ok) => line.push_str("ok "),
+                Some(Status::Rejected { .. }) => line.push_str("ng "),
+            }
+            line.push_str(&command.name);
+            if let Some(Status::Rejected { reason }) = status {
+                line.push_byte(b' ');
+                line.push_str(reason);
+            }
+            encode::text_to_write(&line, &mut buf)?;
+        }
+        encode::flush_to_write(&mut buf)?;
+
+        if self.side_band {
+            Band {
+                channel: Channel::Data,
+                inner: &mut out,
+            }
+            .write_all(&buf)?;
+            encode::flush_to_write(&mut out)?;
+        } else {
+            out.write_all(&buf)?;
+        }
+        out.flush()
+    }
+
+    fn into_outcome(self, request: Request) -> Outcome {
+        Outcome {
+            commands: request
+                .commands
+                .into_iter()
+                .zip(self.statuses)
+                .map(|(command, status)| (command, status.unwrap_or(Status::Ok)))
+                .collect(),
+            push_options: request.push_options,
+        }
+    }
+}
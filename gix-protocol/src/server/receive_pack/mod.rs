@@ -0,0 +1,170 @@
+//! The server side of the `receive-pack` service which accepts pushes from clients, as spoken with protocol V0 and V1.
+//!
+//! The client sends commands to update references along with a pack containing all objects needed by them. The pack is
+//! received into a [quarantine](gix_odb::quarantine) and checked for connectivity, after which the [`Hooks`] may decide
+//! which commands to accept. Only then objects are migrated into the object database, and references are updated.
+//!
+//! Use [`serve()`] to handle a connected client, or [`advertise()`] and [`serve_request()`] for stateless
+//! connections like `http`, where the reference advertisement and the request are handled separately.
+//!
+//! See the [pack protocol documentation](https://git-scm.com/docs/pack-protocol#_pushing_data_to_a_server) for details.
+use std::io::Write;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+mod connectivity;
+mod request;
+
+mod function;
+pub use function::{advertise, serve, serve_request};
+
+/// Configuration for [`serve()`] and related functions.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The value of the `agent` capability, identifying the server to clients.
+    pub agent: String,
+    /// The kind of hash used by the objects in the repository, advertised as `object-format`.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, the `atomic` capability is advertised and clients may request that either all references are updated,
+    /// or none of them.
+    pub allow_atomic: bool,
+    /// If `true`, the `push-options` capability is advertised and clients may send options to be passed to the [`Hooks`].
+    pub allow_push_options: bool,
+    /// The amount of threads to use at most when indexing received packs, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+    /// The identity to use in reference logs when updating references, if these are written.
+    ///
+    /// If `None`, `gitoxide <gitoxide@localhost>` is used with the time at which references are updated.
+    pub committer: Option<gix_actor::Signature>,
+    /// If `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub trace: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            agent: crate::agent(concat!("oxide-", env!("CARGO_PKG_VERSION"))),
+            object_hash: gix_hash::Kind::Sha1,
+            allow_atomic: true,
+            allow_push_options: true,
+            thread_limit: None,
+            committer: None,
+            trace: false,
+        }
+    }
+}
+
+/// A command to update a single reference, as sent by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// The id the client expects the reference to point to, or the null id if it is expected not to exist.
+    pub old_id: ObjectId,
+    /// The id the reference should point to, or the null id if it should be deleted.
+    pub new_id: ObjectId,
+    /// The full name of the reference to update.
+    pub name: BString,
+}
+
+impl Command {
+    /// Return `true` if this command creates a new reference.
+    pub fn is_create(&self) -> bool {
+        self.old_id.is_null()
+    }
+
+    /// Return `true` if this command deletes a reference.
+    pub fn is_delete(&self) -> bool {
+        self.new_id.is_null()
+    }
+}
+
+/// The result of a single [`Command`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The reference was updated as requested.
+    Ok,
+    /// The command was rejected for the given `reason`, which is also sent to the client.
+    Rejected {
+        /// A human-readable description of why the reference wasn't updated.
+        reason: BString,
+    },
+}
+
+/// The result of [`serve()`] and [`serve_request()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// All commands sent by the client along with their status, in the order they were received.
+    pub commands: Vec<(Command, Status)>,
+    /// All push options sent by the client.
+    pub push_options: Vec<BString>,
+}
+
+/// Callbacks which are invoked at the same stages as the hooks of `git receive-pack`, to decide which
+/// [commands](Command) are accepted.
+///
+/// If one of them returns an error message, affected commands are rejected. Anything written to `messages` is sent
+/// to the client as progress message if it supports the side-band, and discarded otherwise.
+pub trait Hooks {
+    /// Called once for all `commands` that passed all checks, after the pack was received into `quarantine`.
+    ///
+    /// If an error is returned, all `commands` are rejected and received objects are discarded.
+    fn pre_receive(
+        &mut self,
+        commands: &[Command],
+        push_options: &[BString],
+        quarantine: &gix_odb::Quarantine,
+        messages: &mut dyn Write,
+    ) -> Result<(), BString> {
+        _ = (commands, push_options, quarantine, messages);
+        Ok(())
+    }
+
+    /// Called for each `command` that passed the [`pre_receive()`](Self::pre_receive()) hook, after received objects
+    /// were migrated into the object database and before the reference is updated.
+    ///
+    /// If an error is returned, only `command` is rejected.
+    fn update(&mut self, command: &Command, messages: &mut dyn Write) -> Result<(), BString> {
+        _ = (command, messages);
+        Ok(())
+    }
+
+    /// Called once with all `commands` whose references were updated, if there was at least one.
+    fn post_receive(&mut self, commands: &[Command], push_options: &[BString], messages: &mut dyn Write) {
+        _ = (commands, push_options, messages);
+    }
+}
+
+/// Accept all commands.
+impl Hooks for () {}
+
+/// The error returned by [`serve()`] and [`serve_request()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Could not decode a packet line sent by the client")]
+    PacketlineDecode(#[from] gix_packetline::decode::Error),
+    #[error("The command {line:?} is malformed")]
+    InvalidCommand { line: BString },
+    #[error("Pushing from shallow repositories is not supported")]
+    Shallow,
+    #[error("The client requested object-format {format:?}, but the repository uses {expected}")]
+    ObjectFormat { format: BString, expected: gix_hash::Kind },
+    #[error("The client requested the {capability:?} capability which wasn't advertised")]
+    UnsupportedCapability { capability: &'static str },
+    #[error("Could not create a quarantine directory for received objects")]
+    Quarantine(#[source] std::io::Error),
+    #[error("Could not receive the pack")]
+    WritePack(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing::Error),
+    #[error("Could not check the connectivity of received objects")]
+    Connectivity(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not move received objects into the object database")]
+    Migrate(#[from] gix_odb::quarantine::migrate::Error),
+    #[error(transparent)]
+    PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
+    #[error("Could not iterate references")]
+    IterRefs(#[from] gix_ref::file::iter::loose_then_packed::Error),
+}
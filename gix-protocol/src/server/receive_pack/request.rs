@@ -0,0 +1,125 @@
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_packetline::StreamingPeekableIter;
+
+use crate::server::receive_pack::{Command, Error, Options};
+
+/// The commands and push options sent by the client, which precede the pack.
+pub(crate) struct Request {
+    /// The commands to update references, in the order they were sent.
+    pub commands: Vec<Command>,
+    /// The capabilities the client sent along with the first command.
+    pub capabilities: Vec<BString>,
+    /// The push options, if the `push-options` capability was requested.
+    pub push_options: Vec<BString>,
+}
+
+impl Request {
+    /// Read the next request from `lines`, or return `None` if the client had nothing to push and either closed the
+    /// connection or sent a flush packet.
+    pub fn read(
+        lines: &mut StreamingPeekableIter<impl std::io::Read>,
+        options: &Options,
+    ) -> Result<Option<Self>, Error> {
+        let mut commands = Vec::new();
+        let mut capabilities = Vec::new();
+        match read_section(lines, |line| {
+            let line = match line.find_byte(0) {
+                Some(pos) if commands.is_empty() => {
+                    capabilities.extend(line[pos + 1..].split_str(" ").filter(|c| !c.is_empty()).map(Into::into));
+                    &line[..pos]
+                }
+                _ => line,
+            };
+            commands.push(parse_command(line)?);
+            Ok(())
+        }) {
+            Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof && commands.is_empty() => {
+                return Ok(None)
+            }
+            res => res?,
+        }
+        if commands.is_empty() {
+            return Ok(None);
+        }
+
+        let mut request = Request {
+            commands,
+            capabilities,
+            push_options: Vec::new(),
+        };
+        if let Some(format) = request.capability("object-format") {
+            if format != crate::server::object_format(options.object_hash).as_bytes() {
+                return Err(Error::ObjectFormat {
+                    format: format.into(),
+                    expected: options.object_hash,
+                });
+            }
+        }
+        for (capability, allowed) in [
+            ("atomic", options.allow_atomic),
+            ("push-options", options.allow_push_options),
+        ] {
+            if !allowed && request.has(capability) {
+                return Err(Error::UnsupportedCapability { capability });
+            }
+        }
+
+        if request.has("push-options") {
+            lines.reset();
+            read_section(lines, |line| {
+                request.push_options.push(line.into());
+                Ok(())
+            })?;
+        }
+        Ok(Some(request))
+    }
+
+    /// Return `true` if the client requested the capability with `name`.
+    pub fn has(&self, name: &str) -> bool {
+        self.capabilities.iter().any(|c| c == name)
+    }
+
+    /// Return the value of the capability with `name`, if it was sent by the client.
+    pub fn capability(&self, name: &str) -> Option<&[u8]> {
+        self.capabilities.iter().find_map(|c| {
+            c.strip_prefix(name.as_bytes())
+                .and_then(|value| value.strip_prefix(b"="))
+        })
+    }
+
+    /// Return `true` if the client will send a pack, which is the case unless all commands are deletions.
+    pub fn expects_pack(&self) -> bool {
+        self.commands.iter().any(|c| !c.is_delete())
+    }
+}
+
+/// Parse a `<old-id> <new-id> <name>` command `line`.
+fn parse_command(line: &[u8]) -> Result<Command, Error> {
+    if line.starts_with(b"shallow ") {
+        return Err(Error::Shallow);
+    }
+    let invalid = || Error::InvalidCommand { line: line.into() };
+    let mut tokens = line.splitn(3, |b| *b == b' ');
+    let (Some(old_id), Some(new_id), Some(name)) = (tokens.next(), tokens.next(), tokens.next()) else {
+        return Err(invalid());
+    };
+    Ok(Command {
+        old_id: ObjectId::from_hex(old_id).map_err(|_| invalid())?,
+        new_id: ObjectId::from_hex(new_id).map_err(|_| invalid())?,
+        name: name.into(),
+    })
+}
+
+/// Pass all data lines until a flush packet to `cb`, without their trailing newline.
+fn read_section(
+    lines: &mut StreamingPeekableIter<impl std::io::Read>,
+    mut cb: impl FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    while let Some(line) = lines.read_line() {
+        if let Some(data) = line??.as_slice() {
+            cb(data.strip_suffix(b"\n").unwrap_or(data))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::io::Write;
+
+use bstr::{BString, ByteVec};
+use gix_packetline::encode;
+
+use crate::server::upload_pack::Error;
+
+/// Handle the `bundle-uri` command with `arguments`, sending the `list` of key-value pairs.
+pub(crate) fn serve(arguments: &[BString], list: &[(BString, BString)], out: &mut impl Write) -> Result<(), Error> {
+    if let Some(argument) = arguments.first() {
+        return Err(Error::InvalidArgument {
+            command: "bundle-uri",
+            argument: argument.clone(),
+        });
+    }
+    for (key, value) in list {
+        let mut line = key.clone();
+        line.push_byte(b'=');
+        line.push_str(value);
+        encode::text_to_write(&line, &mut *out)?;
+    }
+    encode::flush_to_write(out)?;
+    Ok(())
+}
@@ -0,0 +1,677 @@
+use std::{
+    collections::VecDeque,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_hashtable::{HashMap, HashSet};
+use gix_object::{FindExt, HeaderExt};
+use gix_pack::data::output;
+use gix_packetline::{encode, Channel};
+use gix_ref::{file::ReferenceExt, Target};
+
+use crate::{
+    server::{
+        upload_pack::{Error, Options},
+        Band, MAX_BAND_DATA_LEN,
+    },
+    Filter,
+};
+
+/// Handle the `fetch` command with `arguments`, negotiating with the client and sending a pack generated from `objects`.
+/// `refs` are used to resolve `deepen-not` arguments and to find tags for `include-tag`.
+pub(crate) fn serve<O>(
+    arguments: &[BString],
+    refs: &gix_ref::file::Store,
+    objects: &O,
+    out: &mut impl Write,
+    options: &Options,
+) -> Result<(), Error>
+where
+    O: gix_pack::Find + gix_object::Find + gix_object::FindHeader + Send + Clone + 'static,
+{
+    let args = Arguments::parse(arguments, options)?;
+    if let Some(filter @ Filter::SparseOid(_)) = args.filter {
+        return Err(Error::UnsupportedFilter { filter });
+    }
+    if args.wants.is_empty() {
+        // Just like `git`, assume the client didn't want anything.
+        return Ok(());
+    }
+    for want in &args.wants {
+        if !gix_pack::Find::contains(objects, want) {
+            return Err(Error::NotOurRef { id: *want });
+        }
+    }
+
+    let mut graph = Graph::new(objects);
+    let common: Vec<_> = args
+        .haves
+        .iter()
+        .filter(|id| gix_pack::Find::contains(objects, id))
+        .copied()
+        .collect();
+    let wants = graph.peel_wants(&args.wants)?;
+    let common_commits = graph.commits_only(&common)?;
+
+    if !args.done {
+        encode::text_to_write(b"acknowledgments", &mut *out)?;
+        if common.is_empty() {
+            encode::text_to_write(b"NAK", &mut *out)?;
+        }
+        for id in &common {
+            encode::text_to_write(format!("ACK {id}").as_bytes(), &mut *out)?;
+        }
+        if !common.is_empty() && graph.all_reach_common(&wants.commits, &common_commits)? {
+            encode::text_to_write(b"ready", &mut *out)?;
+            encode::delim_to_write(&mut *out)?;
+        } else {
+            encode::flush_to_write(out)?;
+            return Ok(());
+        }
+    }
+
+    let client_shallow: HashSet<_> = args
+        .shallow
+        .iter()
+        .filter(|id| gix_pack::Find::contains(objects, id))
+        .copied()
+        .collect();
+    let shallow = graph.deepen(&args, &wants.commits, &client_shallow, refs)?;
+    if args.is_deepen() || !client_shallow.is_empty() {
+        encode::text_to_write(b"shallow-info", &mut *out)?;
+        for id in shallow.boundary.iter().filter(|id| !client_shallow.contains(*id)) {
+            encode::text_to_write(format!("shallow {id}").as_bytes(), &mut *out)?;
+        }
+        for id in &shallow.unshallow {
+            encode::text_to_write(format!("unshallow {id}").as_bytes(), &mut *out)?;
+        }
+        encode::delim_to_write(&mut *out)?;
+    }
+
+    let ids = graph.objects_to_send(&args, &wants, &common_commits, &client_shallow, &shallow, refs)?;
+    encode::text_to_write(b"packfile", &mut *out)?;
+    write_pack(ids, objects, &mut *out, options)?;
+    encode::flush_to_write(out)?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Arguments {
+    wants: Vec<ObjectId>,
+    haves: Vec<ObjectId>,
+    done: bool,
+    include_tag: bool,
+    shallow: Vec<ObjectId>,
+    depth: Option<usize>,
+    deepen_relative: bool,
+    deepen_since: Option<gix_date::SecondsSinceUnixEpoch>,
+    deepen_not: Vec<BString>,
+    filter: Option<Filter>,
+}
+
+impl Arguments {
+    fn parse(arguments: &[BString], options: &Options) -> Result<Self, Error> {
+        let mut args = Arguments::default();
+        for arg in arguments {
+            let invalid = || Error::InvalidArgument {
+                command: "fetch",
+                argument: arg.clone(),
+            };
+            let id = |hex: &[u8]| ObjectId::from_hex(hex).map_err(|_| invalid());
+            let (name, value) = match arg.find_byte(b' ') {
+                Some(pos) => (&arg[..pos], Some(&arg[pos + 1..])),
+                None => (arg.as_slice(), None),
+            };
+            match (name, value) {
+                (b"want", Some(value)) => args.wants.push(id(value)?),
+                (b"have", Some(value)) => args.haves.push(id(value)?),
+                (b"shallow", Some(value)) => args.shallow.push(id(value)?),
+                (b"done", None) => args.done = true,
+                (b"include-tag", None) => args.include_tag = true,
+                // Packs are never thin, progress is never sent, and deltas are chosen by the pack generator.
+                (b"thin-pack" | b"no-progress" | b"ofs-delta", None) => {}
+                (b"deepen", Some(value)) => {
+                    args.depth = Some(parse_number(value).ok_or_else(invalid)?).filter(|depth| *depth > 0)
+                }
+                (b"deepen-relative", None) => args.deepen_relative = true,
+                (b"deepen-since", Some(value)) => args.deepen_since = Some(parse_number(value).ok_or_else(invalid)?),
+                (b"deepen-not", Some(value)) => args.deepen_not.push(value.into()),
+                (b"filter", Some(value)) if options.allow_filter => {
+                    args.filter = Some(Filter::from_bytes(value.as_bstr())?);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        if args.depth.is_some() && args.is_deepen_by_revisions() {
+            return Err(Error::InvalidArgument {
+                command: "fetch",
+                argument: "deepen".into(),
+            });
+        }
+        Ok(args)
+    }
+
+    fn is_deepen_by_revisions(&self) -> bool {
+        self.deepen_since.is_some() || !self.deepen_not.is_empty()
+    }
+
+    fn is_deepen(&self) -> bool {
+        self.depth.is_some() || self.is_deepen_by_revisions()
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &[u8]) -> Option<T> {
+    value.to_str().ok()?.parse().ok()
+}
+
+/// The objects the client wants, with tags peeled to what they point to.
+#[derive(Default)]
+struct Wants {
+    commits: Vec<ObjectId>,
+    trees: Vec<ObjectId>,
+    blobs: Vec<ObjectId>,
+    tags: Vec<ObjectId>,
+}
+
+/// The way the history of the client will be cut off once it received the pack.
+#[derive(Default)]
+struct Shallow {
+    /// Commits whose parents won't be sent, as they are beyond the requested depth.
+    boundary: HashSet<ObjectId>,
+    /// Shallow commits of the client whose parents will be sent.
+    unshallow: Vec<ObjectId>,
+}
+
+struct Commit {
+    tree: ObjectId,
+    parents: Vec<ObjectId>,
+    time: gix_date::SecondsSinceUnixEpoch,
+}
+
+/// Access to commits in `objects`, with all commits that were seen kept in memory.
+struct Graph<'a, O> {
+    objects: &'a O,
+    commits: HashMap<ObjectId, Commit>,
+    buf: Vec<u8>,
+}
+
+impl<'a, O> Graph<'a, O>
+where
+    O: gix_object::Find + gix_object::FindHeader,
+{
+    fn new(objects: &'a O) -> Self {
+        Graph {
+            objects,
+            commits: Default::default(),
+            buf: Vec::new(),
+        }
+    }
+
+    fn commit(&mut self, id: &ObjectId) -> Result<&Commit, Error> {
+        if !self.commits.contains_key(id) {
+            let commit = self.objects.find_commit(id, &mut self.buf)?;
+            let commit = Commit {
+                tree: commit.tree(),
+                parents: commit.parents().collect(),
+                time: commit.committer.time.seconds,
+            };
+            self.commits.insert(*id, commit);
+        }
+        Ok(&self.commits[id])
+    }
+
+    fn parents(&mut self, id: &ObjectId) -> Result<Vec<ObjectId>, Error> {
+        Ok(self.commit(id)?.parents.clone())
+    }
+
+    /// Peel annotated tags in `ids` and sort the result by kind.
+    fn peel_wants(&mut self, ids: &[ObjectId]) -> Result<Wants, Error> {
+        let mut wants = Wants::default();
+        for id in ids {
+            let mut id = *id;
+            let mut kind = self.objects.header(&id)?.kind;
+            while kind == gix_object::Kind::Tag {
+                wants.tags.push(id);
+                let tag = self.objects.find_tag(&id, &mut self.buf)?;
+                (id, kind) = (tag.target(), tag.target_kind);
+            }
+            match kind {
+                gix_object::Kind::Commit => wants.commits.push(id),
+                gix_object::Kind::Tree => wants.trees.push(id),
+                gix_object::Kind::Blob => wants.blobs.push(id),
+                gix_object::Kind::Tag => unreachable!("tags were peeled"),
+            }
+        }
+        Ok(wants)
+    }
+
+    /// Return the commits among `ids`, ignoring all other kinds of objects.
+    fn commits_only(&mut self, ids: &[ObjectId]) -> Result<HashSet<ObjectId>, Error> {
+        let mut out = HashSet::default();
+        for id in ids {
+            if self.objects.header(id)?.kind == gix_object::Kind::Commit {
+                out.insert(*id);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return `true` if all of `wants` can reach one of the `common` commits, which means that the client has enough
+    /// history for us to send a pack.
+    fn all_reach_common(&mut self, wants: &[ObjectId], common: &HashSet<ObjectId>) -> Result<bool, Error> {
+        let mut oldest_common = None;
+        for id in common {
+            let time = self.commit(id)?.time;
+            oldest_common = Some(oldest_common.map_or(time, |t: gix_date::SecondsSinceUnixEpoch| t.min(time)));
+        }
+        let Some(oldest_common) = oldest_common else {
+            return Ok(false);
+        };
+        for want in wants {
+            let mut seen = HashSet::default();
+            let mut queue = VecDeque::from([*want]);
+            let mut reaches_common = false;
+            while let Some(id) = queue.pop_front() {
+                if common.contains(&id) {
+                    reaches_common = true;
+                    break;
+                }
+                if !seen.insert(id) || self.commit(&id)?.time < oldest_common {
+                    continue;
+                }
+                queue.extend(self.parents(&id)?);
+            }
+            if !reaches_common {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Compute how the history of the client will be cut off, based on the deepen arguments in `args`, the `wants` and
+    /// the commits the client says it has no parents of, `client_shallow`.
+    fn deepen(
+        &mut self,
+        args: &Arguments,
+        wants: &[ObjectId],
+        client_shallow: &HashSet<ObjectId>,
+        refs: &gix_ref::file::Store,
+    ) -> Result<Shallow, Error> {
+        let included = if let Some(depth) = args.depth {
+            let (roots, limit) = if args.deepen_relative {
+                (client_shallow.iter().copied().collect(), depth + 1)
+            } else {
+                (wants.to_vec(), depth)
+            };
+            self.shallow_by_depth(roots, limit)?
+        } else if args.is_deepen_by_revisions() {
+            self.shallow_by_revisions(args, wants, refs)?
+        } else {
+            return Ok(Shallow::default());
+        };
+        let (included, boundary) = included;
+        let mut unshallow: Vec<_> = client_shallow
+            .iter()
+            .filter(|id| included.contains(*id) && !boundary.contains(*id))
+            .copied()
+            .collect();
+        unshallow.sort();
+        Ok(Shallow { boundary, unshallow })
+    }
+
+    /// Return all commits within `limit` generations of `roots`, which are at depth 1, along with the commits at the
+    /// maximum depth which have parents.
+    fn shallow_by_depth(
+        &mut self,
+        roots: Vec<ObjectId>,
+        limit: usize,
+    ) -> Result<(HashSet<ObjectId>, HashSet<ObjectId>), Error> {
+        let mut depths = HashMap::default();
+        let mut queue = VecDeque::new();
+        for root in roots {
+            if depths.insert(root, 1).is_none() {
+                queue.push_back((root, 1));
+            }
+        }
+        let mut boundary = HashSet::default();
+        while let Some((id, depth)) = queue.pop_front() {
+            let parents = self.parents(&id)?;
+            if depth == limit {
+                if !parents.is_empty() {
+                    boundary.insert(id);
+                }
+                continue;
+            }
+            for parent in parents {
+                if !depths.contains_key(&parent) {
+                    depths.insert(parent, depth + 1);
+                    queue.push_back((parent, depth + 1));
+                }
+            }
+        }
+        Ok((depths.into_keys().collect(), boundary))
+    }
+
+    /// Return all commits reachable from `wants` which are not older than `deepen-since` and not reachable from any
+    /// `deepen-not` reference, along with the commits whose parents are excluded that way.
+    fn shallow_by_revisions(
+        &mut self,
+        args: &Arguments,
+        wants: &[ObjectId],
+        refs: &gix_ref::file::Store,
+    ) -> Result<(HashSet<ObjectId>, HashSet<ObjectId>), Error> {
+        let mut excluded_tips = Vec::new();
+        for name in &args.deepen_not {
+            let mut reference = refs.find(name.as_bstr())?;
+            excluded_tips.push(reference.peel_to_id_in_place(refs, self.objects)?);
+        }
+        let excluded_tips = self.commits_only(&excluded_tips)?;
+        let excluded = self.reachable(excluded_tips, &HashSet::default())?;
+
+        let is_included = |graph: &mut Self, id: &ObjectId| -> Result<bool, Error> {
+            if excluded.contains(id) {
+                return Ok(false);
+            }
+            Ok(match args.deepen_since {
+                Some(since) => graph.commit(id)?.time >= since,
+                None => true,
+            })
+        };
+        let mut included = HashSet::default();
+        let mut boundary = HashSet::default();
+        let mut queue = VecDeque::new();
+        for want in wants {
+            if is_included(self, want)? && included.insert(*want) {
+                queue.push_back(*want);
+            }
+        }
+        while let Some(id) = queue.pop_front() {
+            let parents = self.parents(&id)?;
+            let mut included_parents = Vec::with_capacity(parents.len());
+            for parent in parents {
+                if is_included(self, &parent)? {
+                    included_parents.push(parent);
+                } else {
+                    boundary.insert(id);
+                }
+            }
+            if boundary.contains(&id) {
+                continue;
+            }
+            for parent in included_parents {
+                if included.insert(parent) {
+                    queue.push_back(parent);
+                }
+            }
+        }
+        if included.is_empty() {
+            return Err(Error::NoShallowCommits);
+        }
+        Ok((included, boundary))
+    }
+
+    /// Return all commits reachable from `tips`, without traversing the parents of `grafts`.
+    fn reachable(
+        &mut self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        grafts: &HashSet<ObjectId>,
+    ) -> Result<HashSet<ObjectId>, Error> {
+        let mut seen = HashSet::default();
+        let mut queue: VecDeque<_> = tips.into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if !seen.insert(id) || grafts.contains(&id) {
+                continue;
+            }
+            queue.extend(self.parents(&id)?);
+        }
+        Ok(seen)
+    }
+
+    /// Return the commits reachable from `wants` but not from the `common` commits, from newest to oldest, and put the
+    /// commits the client has which are parents of these into `edges`.
+    ///
+    /// Like `git rev-list --not`, the walk stops once only commits the client has are left instead of traversing all history
+    /// reachable from `common`. The parents of `grafts` are not traversed, as history ends there for the client.
+    fn limit_commits(
+        &mut self,
+        wants: &[ObjectId],
+        common: &HashSet<ObjectId>,
+        grafts: &HashSet<ObjectId>,
+        edges: &mut HashSet<ObjectId>,
+    ) -> Result<Vec<ObjectId>, Error> {
+        let commits = gix_traverse::commit::Ancestors::new(
+            wants.iter().copied(),
+            gix_traverse::commit::ancestors::State::default(),
+            self.objects,
+        )
+        .sorting(gix_traverse::commit::Sorting::ByCommitTimeNewestFirst)?
+        .hide(common.iter().copied())
+        .shallow(grafts.iter().copied())
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let sent: HashSet<_> = commits.iter().map(|info| info.id).collect();
+        edges.extend(wants.iter().filter(|id| !sent.contains(*id)));
+        for info in &commits {
+            edges.extend(info.parent_ids.iter().filter(|id| !sent.contains(*id)));
+        }
+        Ok(commits.into_iter().map(|info| info.id).collect())
+    }
+
+    /// Collect all objects to send to the client in the order they should appear in the pack.
+    fn objects_to_send(
+        &mut self,
+        args: &Arguments,
+        wants: &Wants,
+        common: &HashSet<ObjectId>,
+        client_shallow: &HashSet<ObjectId>,
+        shallow: &Shallow,
+        refs: &gix_ref::file::Store,
+    ) -> Result<Vec<ObjectId>, Error> {
+        // Unshallowed commits are at the client already, but their parents will be needed.
+        let mut want_commits = wants.commits.clone();
+        let mut edges = HashSet::default();
+        for id in &shallow.unshallow {
+            want_commits.extend(self.parents(id)?);
+            edges.insert(*id);
+        }
+        let grafts: HashSet<_> = shallow.boundary.iter().chain(client_shallow).copied().collect();
+
+        let commits = self.limit_commits(&want_commits, common, &grafts, &mut edges)?;
+
+        // Objects reachable from the trees of edge commits are at the client already.
+        let mut trees = Trees {
+            objects: self.objects,
+            filter: None,
+            excluded: HashSet::default(),
+            depths: HashMap::default(),
+            out: Vec::new(),
+            buf: Vec::new(),
+        };
+        for id in &edges {
+            let tree = self.commit(id)?.tree;
+            trees.add(tree, 0)?;
+        }
+        let mut trees = Trees {
+            filter: args.filter.clone(),
+            excluded: trees.out.into_iter().collect(),
+            depths: HashMap::default(),
+            out: Vec::new(),
+            ..trees
+        };
+        for id in &commits {
+            let tree = self.commit(id)?.tree;
+            trees.add(tree, 0)?;
+        }
+        for tree in &wants.trees {
+            trees.add_explicit(*tree)?;
+        }
+        for blob in &wants.blobs {
+            trees.add_explicit(*blob)?;
+        }
+
+        let mut sent: HashSet<_> = commits.iter().chain(trees.out.iter()).copied().collect();
+        let mut ids = commits;
+        ids.append(&mut trees.out);
+        for tag in &wants.tags {
+            if sent.insert(*tag) {
+                ids.push(*tag);
+            }
+        }
+        if args.include_tag {
+            for reference in refs.iter()?.prefixed(Path::new("refs/tags/"))? {
+                let Target::Peeled(tag) = reference?.target else {
+                    continue;
+                };
+                let mut chain = Vec::new();
+                let mut id = tag;
+                while self.objects.header(&id)?.kind == gix_object::Kind::Tag {
+                    chain.push(id);
+                    id = self.objects.find_tag(&id, &mut self.buf)?.target();
+                }
+                if !chain.is_empty() && sent.contains(&id) {
+                    for tag in chain {
+                        if sent.insert(tag) {
+                            ids.push(tag);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// A traversal of trees which collects all objects that pass a `filter`.
+struct Trees<'a, O> {
+    objects: &'a O,
+    filter: Option<Filter>,
+    /// Objects that must not be sent.
+    excluded: HashSet<ObjectId>,
+    /// The smallest depth at which a tree was seen.
+    depths: HashMap<ObjectId, u64>,
+    out: Vec<ObjectId>,
+    buf: Vec<u8>,
+}
+
+impl<'a, O> Trees<'a, O>
+where
+    O: gix_object::Find + gix_object::FindHeader,
+{
+    /// Add the tree with `id` at `depth` and all of its children, as far as the filter allows.
+    fn add(&mut self, id: ObjectId, depth: u64) -> Result<(), Error> {
+        let mut stack = vec![(id, depth)];
+        while let Some((id, depth)) = stack.pop() {
+            if self.excluded.contains(&id) || self.is_too_deep(depth) {
+                continue;
+            }
+            match self.depths.get(&id) {
+                // Trees may have to be traversed again if they are seen at a smaller depth, as more of their children may be sent.
+                Some(known) if *known <= depth || !matches!(self.filter, Some(Filter::TreeDepth(_))) => continue,
+                Some(_) => {}
+                None => self.out.push(id),
+            }
+            self.depths.insert(id, depth);
+
+            for (child, mode) in self.children(&id)? {
+                if mode.is_tree() {
+                    stack.push((child, depth + 1));
+                } else if mode.is_blob_or_symlink() && self.is_blob_wanted(&child, depth + 1)? {
+                    self.depths.insert(child, depth + 1);
+                    self.out.push(child);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an object that was explicitly requested, which isn't subject to filtering itself.
+    fn add_explicit(&mut self, id: ObjectId) -> Result<(), Error> {
+        if self.excluded.contains(&id) || self.depths.contains_key(&id) {
+            return Ok(());
+        }
+        self.depths.insert(id, 0);
+        self.out.push(id);
+        if self.objects.header(&id)?.kind != gix_object::Kind::Tree {
+            return Ok(());
+        }
+        for (child, mode) in self.children(&id)? {
+            if mode.is_tree() {
+                self.add(child, 1)?;
+            } else if mode.is_blob_or_symlink() && self.is_blob_wanted(&child, 1)? {
+                self.depths.insert(child, 1);
+                self.out.push(child);
+            }
+        }
+        Ok(())
+    }
+
+    fn children(&mut self, id: &ObjectId) -> Result<Vec<(ObjectId, gix_object::tree::EntryMode)>, Error> {
+        Ok(self
+            .objects
+            .find_tree_iter(id, &mut self.buf)?
+            .map(|entry| entry.map(|e| (e.oid.to_owned(), e.mode)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn is_too_deep(&self, depth: u64) -> bool {
+        matches!(self.filter, Some(Filter::TreeDepth(max)) if depth >= max)
+    }
+
+    fn is_blob_wanted(&mut self, id: &ObjectId, depth: u64) -> Result<bool, Error> {
+        if self.excluded.contains(id) || self.depths.contains_key(id) {
+            return Ok(false);
+        }
+        Ok(match self.filter {
+            None | Some(Filter::SparseOid(_)) => true,
+            Some(Filter::BlobNone) => false,
+            Some(Filter::BlobLimit(limit)) => self.objects.header(id)?.size < limit,
+            Some(Filter::TreeDepth(max)) => depth < max,
+        })
+    }
+}
+
+/// Generate a pack with all objects in `ids` and write it to `out` using the data side-band.
+fn write_pack<O>(ids: Vec<ObjectId>, objects: &O, out: &mut impl Write, options: &Options) -> Result<(), Error>
+where
+    O: gix_pack::Find + Send + Clone + 'static,
+{
+    let counts: Vec<_> = ids
+        .into_iter()
+        .map(|id| output::Count {
+            id,
+            entry_pack_location: output::count::PackLocation::NotLookedUp,
+        })
+        .collect();
+    let num_entries = counts.len() as u32;
+    let entries = output::entry::iter_from_counts(
+        counts,
+        objects.clone(),
+        Box::new(gix_features::progress::Discard),
+        output::entry::iter_from_counts::Options {
+            thread_limit: options.thread_limit,
+            allow_thin_pack: false,
+            ..Default::default()
+        },
+    );
+    let mut band = BufWriter::with_capacity(
+        MAX_BAND_DATA_LEN,
+        Band {
+            channel: Channel::Data,
+            inner: out,
+        },
+    );
+    for written in output::bytes::FromEntriesIter::new(
+        gix_features::parallel::InOrderIter::from(entries),
+        &mut band,
+        num_entries,
+        gix_pack::data::Version::V2,
+        options.object_hash,
+    ) {
+        written?;
+    }
+    band.flush()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::io::{Read, Write};
+
+use gix_packetline::encode;
+
+use super::{bundle_uri, fetch, ls_refs, object_info, request::Request, Error, Options};
+use crate::server::object_format;
+
+/// Write the capability advertisement of a protocol V2 `upload-pack` server to `out`, as configured by `options`.
+///
+/// This is the first thing a server sends to clients once they connected, which is followed by requests handled with
+/// [`serve_request()`].
+pub fn advertise(mut out: impl Write, options: &Options) -> std::io::Result<()> {
+    let mut fetch = String::from("fetch=shallow");
+    if options.allow_filter {
+        fetch.push_str(" filter");
+    }
+    let bundle_uri = (!options.bundle_uri.is_empty()).then(|| "bundle-uri".to_owned());
+    for capability in [
+        "version 2".into(),
+        format!("agent={}", options.agent),
+        "ls-refs=unborn".into(),
+        fetch,
+        "object-info".into(),
+        format!("object-format={}", object_format(options.object_hash)),
+    ]
+    .into_iter()
+    .chain(bundle_uri)
+    {
+        encode::text_to_write(capability.as_bytes(), &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    out.flush()
+}
+
+/// Serve a client connected through `read` and `write` in a stateful session, by first [advertising](advertise()) our capabilities
+/// and then [serving all requests](serve_request()) until the client ends the session.
+///
+/// `refs` are the references to advertise, and `objects` is the object database to generate packs from.
+/// Note that `objects` must be configured to keep pack locations stable, as with `gix_odb::Handle::prevent_pack_unload()`.
+pub fn serve<O>(
+    mut read: impl Read,
+    mut write: impl Write,
+    refs: &gix_ref::file::Store,
+    objects: O,
+    options: &Options,
+) -> Result<(), Error>
+where
+    O: gix_pack::Find + gix_object::Find + gix_object::FindHeader + Send + Clone + 'static,
+{
+    advertise(&mut write, options)?;
+    while serve_request(&mut read, &mut write, refs, objects.clone(), options)? {}
+    Ok(())
+}
+
+/// Read a single request from `read` and write the response to `write`, using `refs` and `objects` to obtain the requested
+/// information as configured by `options`.
+///
+/// Returns `false` if the client ended the session instead of sending another request, or `true` otherwise.
+/// Errors in the request are also reported to the client as `ERR` packet line if nothing was written yet.
+pub fn serve_request<O>(
+    read: impl Read,
+    write: impl Write,
+    refs: &gix_ref::file::Store,
+    objects: O,
+    options: &Options,
+) -> Result<bool, Error>
+where
+    O: gix_pack::Find + gix_object::Find + gix_object::FindHeader + Send + Clone + 'static,
+{
+    let mut out = Tracked {
+        inner: write,
+        has_written: false,
+    };
+    let res = Request::read(read, options.trace).and_then(|request| {
+        let Some(request) = request else { return Ok(false) };
+        if let Some(format) = request.capability("object-format") {
+            if format != object_format(options.object_hash).as_bytes() {
+                return Err(Error::ObjectFormat {
+                    format: format.into(),
+                    expected: options.object_hash,
+                });
+            }
+        }
+        match request.command.as_slice() {
+            b"ls-refs" => ls_refs::serve(&request.arguments, refs, &objects, &mut out),
+            b"fetch" => fetch::serve(&request.arguments, refs, &objects, &mut out, options),
+            b"object-info" => object_info::serve(&request.arguments, &objects, &mut out),
+            b"bundle-uri" if !options.bundle_uri.is_empty() => {
+                bundle_uri::serve(&request.arguments, &options.bundle_uri, &mut out)
+            }
+            _ => Err(Error::UnknownCommand {
+                command: request.command,
+            }),
+        }?;
+        Ok(true)
+    });
+    match res {
+        Err(err) if !matches!(err, Error::Io(_)) && !out.has_written => {
+            encode::error_to_write(err.to_string().as_bytes(), &mut out.inner)?;
+            out.inner.flush()?;
+            Err(err)
+        }
+        Ok(true) => {
+            out.flush()?;
+            Ok(true)
+        }
+        res => res,
+    }
+}
+
+/// A writer which keeps track of whether something was written to it.
+struct Tracked<W> {
+    inner: W,
+    has_written: bool,
+}
+
+impl<W: Write> Write for Tracked<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.has_written = true;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
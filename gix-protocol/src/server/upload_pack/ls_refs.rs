@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use bstr::{BStr, BString, ByteVec};
+use gix_packetline::encode;
+use gix_ref::{file::ReferenceExt, Reference, Target};
+
+use crate::server::upload_pack::Error;
+
+/// The maximum amount of symbolic references to follow, which is the same limit that `git` uses.
+const MAX_SYMREF_DEPTH: usize = 5;
+
+/// Handle the `ls-refs` command with `arguments`, listing references in `refs` and peeling them with `objects`.
+pub(crate) fn serve(
+    arguments: &[BString],
+    refs: &gix_ref::file::Store,
+    objects: &dyn gix_object::Find,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let mut args = Arguments::default();
+    for arg in arguments {
+        match arg.as_slice() {
+            b"peel" => args.peel = true,
+            b"symrefs" => args.symrefs = true,
+            b"unborn" => args.unborn = true,
+            _ => match arg.strip_prefix(b"ref-prefix ") {
+                Some(prefix) => args.prefixes.push(prefix.into()),
+                None => {
+                    return Err(Error::InvalidArgument {
+                        command: "ls-refs",
+                        argument: arg.clone(),
+                    })
+                }
+            },
+        }
+    }
+
+    if let Some(head) = refs.try_find("HEAD")? {
+        write_ref(head, &args, refs, objects, out)?;
+    }
+    for reference in refs.iter()?.all()? {
+        write_ref(reference?, &args, refs, objects, out)?;
+    }
+    encode::flush_to_write(out)?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Arguments {
+    peel: bool,
+    symrefs: bool,
+    unborn: bool,
+    prefixes: Vec<BString>,
+}
+
+impl Arguments {
+    fn is_wanted(&self, name: &BStr) -> bool {
+        self.prefixes.is_empty() || self.prefixes.iter().any(|prefix| name.starts_with(prefix))
+    }
+}
+
+fn write_ref(
+    reference: Reference,
+    args: &Arguments,
+    refs: &gix_ref::file::Store,
+    objects: &dyn gix_object::Find,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    if !args.is_wanted(reference.name.as_bstr()) {
+        return Ok(());
+    }
+    let mut symref_target = None;
+    let mut current = reference.clone();
+    let mut depth = 0;
+    let id = loop {
+        match current.target {
+            Target::Peeled(id) => break id,
+            Target::Symbolic(ref name) => {
+                depth += 1;
+                symref_target = Some(name.clone());
+                match current.follow(refs) {
+                    Some(Ok(next)) if depth <= MAX_SYMREF_DEPTH => current = next,
+                    Some(Err(gix_ref::file::find::existing::Error::NotFound { .. })) => {
+                        if args.unborn && reference.name.as_bstr() == "HEAD" {
+                            let mut line = BString::from("unborn HEAD");
+                            if args.symrefs {
+                                line.push_str(" symref-target:");
+                                line.push_str(name.as_bstr());
+                            }
+                            encode::text_to_write(&line, out)?;
+                        }
+                        return Ok(());
+                    }
+                    Some(Err(err)) => return Err(err.into()),
+                    // broken references are ignored, just like `git` does.
+                    Some(Ok(_)) | None => return Ok(()),
+                }
+            }
+        }
+    };
+
+    let mut line = BString::from(id.to_string());
+    line.push_byte(b' ');
+    line.push_str(reference.name.as_bstr());
+    if let Some(target) = symref_target.filter(|_| args.symrefs) {
+        line.push_str(" symref-target:");
+        line.push_str(target.as_bstr());
+    }
+    if args.peel {
+        let peeled = current.peel_to_id_in_place(refs, objects)?;
+        if peeled != id {
+            line.push_str(" peeled:");
+            line.push_str(peeled.to_string());
+        }
+    }
+    encode::text_to_write(&line, out)?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! The server side of the `upload-pack` service as spoken with protocol V2, which serves `ls-refs`, `fetch`, `object-info`
+//! and `bundle-uri` commands so that clients can clone and fetch from a repository.
+//!
+//! Use [`serve()`] for stateful connections like `ssh` or `file`, or [`advertise()`] and [`serve_request()`] for stateless
+//! connections like `http`, where the capability advertisement and each request are handled separately.
+//!
+//! See the [protocol V2 documentation](https://git-scm.com/docs/protocol-v2) for details.
+use bstr::BString;
+
+mod bundle_uri;
+mod fetch;
+mod ls_refs;
+mod object_info;
+mod request;
+
+mod function;
+pub use function::{advertise, serve, serve_request};
+
+/// Configuration for [`serve()`] and related functions.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The value of the `agent` capability, identifying the server to clients.
+    pub agent: String,
+    /// The kind of hash used by the objects in the repository, advertised as `object-format`.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, the `filter` capability is advertised and clients may request partial clones.
+    pub allow_filter: bool,
+    /// The amount of threads to use at most when generating packs, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+    /// The bundle list to send with the `bundle-uri` command as pairs of keys like `bundle.<id>.uri` and their values.
+    /// If empty, the `bundle-uri` capability isn't advertised.
+    pub bundle_uri: Vec<(BString, BString)>,
+    /// If `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub trace: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            agent: crate::agent(concat!("oxide-", env!("CARGO_PKG_VERSION"))),
+            object_hash: gix_hash::Kind::Sha1,
+            allow_filter: true,
+            thread_limit: None,
+            bundle_uri: Vec::new(),
+            trace: false,
+        }
+    }
+}
+
+/// The error returned by [`serve()`] and [`serve_request()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Could not decode a packet line sent by the client")]
+    PacketlineDecode(#[from] gix_packetline::decode::Error),
+    #[error("The request did not contain a command")]
+    MissingCommand,
+    #[error("The command {command:?} is not supported")]
+    UnknownCommand { command: BString },
+    #[error("The argument {argument:?} is invalid or not supported by the {command} command")]
+    InvalidArgument { command: &'static str, argument: BString },
+    #[error("The client requested object-format {format:?}, but the repository uses {expected}")]
+    ObjectFormat { format: BString, expected: gix_hash::Kind },
+    #[error("not our ref {id}")]
+    NotOurRef { id: gix_hash::ObjectId },
+    #[error(transparent)]
+    Filter(#[from] crate::filter::Error),
+    #[error("The filter '{filter}' is not supported")]
+    UnsupportedFilter { filter: crate::Filter },
+    #[error("No commits were selected for the requested shallow history")]
+    NoShallowCommits,
+    #[error(transparent)]
+    PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
+    #[error("Could not iterate references")]
+    IterRefs(#[from] gix_ref::file::iter::loose_then_packed::Error),
+    #[error(transparent)]
+    FindRef(#[from] gix_ref::file::find::Error),
+    #[error(transparent)]
+    FindExistingRef(#[from] gix_ref::file::find::existing::Error),
+    #[error(transparent)]
+    PeelRef(#[from] gix_ref::peel::to_id::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    FindObjectHeader(#[from] gix_object::find::existing::Error),
+    #[error(transparent)]
+    FindObjectIter(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    TraverseCommits(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>),
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use bstr::{BString, ByteSlice, ByteVec};
+use gix_packetline::encode;
+
+use crate::server::upload_pack::Error;
+
+/// Handle the `object-info` command with `arguments`, obtaining the requested information from `objects`.
+pub(crate) fn serve(
+    arguments: &[BString],
+    objects: &dyn gix_object::FindHeader,
+    out: &mut impl Write,
+) -> Result<(), Error> {
+    let mut size = false;
+    let mut ids = Vec::new();
+    for arg in arguments {
+        let invalid = || Error::InvalidArgument {
+            command: "object-info",
+            argument: arg.clone(),
+        };
+        if arg == "size" {
+            size = true;
+        } else if let Some(hex) = arg.strip_prefix(b"oid ") {
+            ids.push(gix_hash::ObjectId::from_hex(hex).map_err(|_| invalid())?);
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    if size {
+        encode::text_to_write(b"size", &mut *out)?;
+    }
+    for id in ids {
+        let mut line = BString::from(id.to_string());
+        if size {
+            line.push_byte(b' ');
+            // Unknown objects are listed without a size.
+            if let Some(header) = objects
+                .try_header(&id)
+                .map_err(gix_object::find::existing::Error::Find)?
+            {
+                line.push_str(header.size.to_string());
+            }
+        }
+        encode::text_to_write(line.as_bytes(), &mut *out)?;
+    }
+    encode::flush_to_write(out)?;
+    Ok(())
+}
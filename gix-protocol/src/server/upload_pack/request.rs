@@ -0,0 +1,66 @@
+use bstr::{BString, ByteSlice};
+use gix_packetline::{PacketLineRef, StreamingPeekableIter};
+
+use crate::server::upload_pack::Error;
+
+/// A single command request as sent by the client.
+pub(crate) struct Request {
+    /// The name of the command to run, like `fetch`.
+    pub command: BString,
+    /// All capabilities sent along with the command, except for the command itself.
+    pub capabilities: Vec<BString>,
+    /// The arguments to the command, one per line.
+    pub arguments: Vec<BString>,
+}
+
+impl Request {
+    /// Read the next request from `read`, or return `None` if the client ended the session, either by closing
+    /// the connection or by sending an empty request.
+    pub fn read(read: impl std::io::Read, trace: bool) -> Result<Option<Self>, Error> {
+        let mut lines = StreamingPeekableIter::new(read, &[PacketLineRef::Delimiter, PacketLineRef::Flush], trace);
+        let mut capabilities = Vec::new();
+        match read_section(&mut lines, &mut capabilities) {
+            Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof && capabilities.is_empty() => {
+                return Ok(None)
+            }
+            res => res?,
+        }
+        let mut arguments = Vec::new();
+        if lines.stopped_at() == Some(PacketLineRef::Delimiter) {
+            lines.reset();
+            read_section(&mut lines, &mut arguments)?;
+        }
+        if capabilities.is_empty() && arguments.is_empty() {
+            return Ok(None);
+        }
+
+        let command = capabilities
+            .iter()
+            .position(|c| c.starts_with(b"command="))
+            .map(|pos| capabilities.remove(pos)["command=".len()..].into())
+            .ok_or(Error::MissingCommand)?;
+        Ok(Some(Request {
+            command,
+            capabilities,
+            arguments,
+        }))
+    }
+
+    /// Return the value of the capability with `name`, if it was sent by the client.
+    pub fn capability(&self, name: &str) -> Option<&[u8]> {
+        self.capabilities.iter().find_map(|c| {
+            c.strip_prefix(name.as_bytes())
+                .and_then(|value| value.strip_prefix(b"="))
+        })
+    }
+}
+
+/// Read data lines until a delimiter or flush packet into `out`, without their trailing newline.
+fn read_section(lines: &mut StreamingPeekableIter<impl std::io::Read>, out: &mut Vec<BString>) -> Result<(), Error> {
+    while let Some(line) = lines.read_line() {
+        if let Some(data) = line??.as_slice() {
+            out.push(data.strip_suffix(b"\n").unwrap_or(data).as_bstr().to_owned());
+        }
+    }
+    Ok(())
+}
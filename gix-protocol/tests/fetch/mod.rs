@@ -303,6 +303,8 @@ pub fn transport<W: std::io::Write>(
     )
 }
 
+#[cfg(feature = "blocking-client")]
+mod replay;
 pub mod response;
 mod v1;
 mod v2;
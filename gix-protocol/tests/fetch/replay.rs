@@ -0,0 +1,448 @@
+//! Replay V1 transcripts of `git upload-pack`, as recorded with `GIT_TRACE_PACKET`, through a stateful transport.
+//!
+//! The transport only provides what the server wrote once the client sent everything the server read beforehand.
+//! Reading more than that would block forever with a real server, so it's an error here.
+use std::{cell::RefCell, io, path::PathBuf, rc::Rc};
+
+use bstr::{BString, ByteSlice};
+use gix_features::progress;
+use gix_protocol::{
+    fetch::{self, response::Acknowledgement, Action, Arguments, Response},
+    handshake, FetchConnection,
+};
+use gix_transport::{client::git, Protocol};
+
+use crate::fetch::helper_unused;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    Data(BString),
+    Flush,
+}
+
+impl Line {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Line::Data(data) => {
+                gix_packetline::encode::text_to_write(data, &mut out).expect("write to memory");
+            }
+            Line::Flush => {
+                gix_packetline::encode::flush_to_write(&mut out).expect("write to memory");
+            }
+        }
+        out
+    }
+}
+
+/// A conversation as seen by the server.
+struct Transcript {
+    /// What the server wrote, along with the amount of client lines it read beforehand.
+    server: Vec<(usize, Line)>,
+    /// What the server read from the client.
+    client: Vec<Line>,
+}
+
+impl Transcript {
+    fn at(name: &str) -> gix_testtools::Result<Self> {
+        let trace = std::fs::read(fixture_dir()?.join(format!("{name}.trace")))?;
+        let mut out = Transcript {
+            server: Vec::new(),
+            client: Vec::new(),
+        };
+        for line in trace.lines() {
+            let Some(pos) = line.find("upload-pack") else { continue };
+            let line = &line[pos + "upload-pack".len()..];
+            let (direction, content) = (line[0], unescape(&line[2..]));
+            let line = if content == "0000" {
+                Line::Flush
+            } else {
+                Line::Data(content)
+            };
+            match direction {
+                b'>' => out.server.push((out.client.len(), line)),
+                b'<' => out.client.push(line),
+                _ => unreachable!("invalid direction in trace"),
+            }
+        }
+        Ok(out)
+    }
+
+    /// The lines the client sent before its first flush, which include the wants.
+    fn arguments(&self) -> impl Iterator<Item = &BString> {
+        self.client.iter().map_while(|line| match line {
+            Line::Data(data) => Some(data),
+            Line::Flush => None,
+        })
+    }
+
+    /// The `have` lines of each round after the arguments, and whether the round ended with `done`.
+    fn rounds(&self) -> Vec<(Vec<gix_hash::ObjectId>, bool)> {
+        let mut rounds = Vec::new();
+        let mut haves = Vec::new();
+        for line in self.client.iter().skip(self.arguments().count() + 1) {
+            match line {
+                Line::Data(data) if data == "done" => rounds.push((std::mem::take(&mut haves), true)),
+                Line::Data(data) => haves.push(id(data.strip_prefix(b"have ").expect("only haves"))),
+                Line::Flush => rounds.push((std::mem::take(&mut haves), false)),
+            }
+        }
+        rounds
+    }
+
+    /// The features the client requested with the first `want` line.
+    fn features(&self) -> Vec<(&'static str, Option<std::borrow::Cow<'static, str>>)> {
+        let first_want = self.arguments().next().expect("at least one want");
+        let requested: Vec<_> = first_want.split_str(" ").skip(2).collect();
+        FEATURES
+            .iter()
+            .filter(|name| requested.contains(&name.as_bytes()))
+            .map(|name| (*name, None))
+            .collect()
+    }
+}
+
+/// All features used by clients in transcripts.
+const FEATURES: &[&str] = &[
+    "multi_ack",
+    "multi_ack_detailed",
+    "side-band-64k",
+    "ofs-delta",
+    "no-progress",
+    "no-done",
+    "shallow",
+];
+
+fn fixture_dir() -> gix_testtools::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_v1_transcripts.sh")
+}
+
+fn id(hex: &[u8]) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex).expect("valid hex")
+}
+
+/// Undo the escaping of `GIT_TRACE_PACKET`, which shows non-printable bytes as octal and omits newlines.
+fn unescape(content: &[u8]) -> BString {
+    let mut out = BString::default();
+    let mut bytes = content.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            out.push(byte);
+            continue;
+        }
+        let mut value = 0u8;
+        while let Some(digit) = bytes.next_if(|b| (b'0'..=b'7').contains(b)) {
+            value = value * 8 + (digit - b'0');
+        }
+        out.push(value);
+    }
+    out
+}
+
+#[derive(Default)]
+struct State {
+    /// What the client wrote so far, decoded into lines.
+    client: Vec<Line>,
+    /// Bytes written by the client which don't yet form a complete line.
+    partial: Vec<u8>,
+    /// The index of the next server line to read.
+    server_pos: usize,
+}
+
+/// The server side of the transport, which the client reads from.
+struct Server {
+    state: Rc<RefCell<State>>,
+    lines: Vec<(usize, Line)>,
+    buf: Vec<u8>,
+}
+
+impl io::Read for Server {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.buf.is_empty() {
+            let mut state = self.state.borrow_mut();
+            let Some((client_lines_needed, line)) = self.lines.get(state.server_pos) else {
+                return Ok(0);
+            };
+            if state.client.len() < *client_lines_needed {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!(
+                        "The client would hang waiting for {line:?}, which the server sends only after reading \
+                         {client_lines_needed} lines, but the client sent {}",
+                        state.client.len()
+                    ),
+                ));
+            }
+            state.server_pos += 1;
+            self.buf = line.encode();
+        }
+        let len = out.len().min(self.buf.len());
+        out[..len].copy_from_slice(&self.buf[..len]);
+        self.buf.drain(..len);
+        Ok(len)
+    }
+}
+
+/// The client side of the transport, which the client writes to.
+struct Client(Rc<RefCell<State>>);
+
+impl io::Write for Client {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0.borrow_mut();
+        state.partial.extend_from_slice(buf);
+        while state.partial.len() >= 4 {
+            let len = usize::from_str_radix(state.partial[..4].to_str().expect("hex"), 16).expect("valid length");
+            let line = match len {
+                0 => Line::Flush,
+                len if state.partial.len() >= len => {
+                    let data = &state.partial[4..len];
+                    Line::Data(data.strip_suffix(b"\n").unwrap_or(data).into())
+                }
+                _ => break,
+            };
+            state.partial.drain(..len.max(4));
+            state.client.push(line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn transport(transcript: &Transcript) -> (git::Connection<Server, Client>, Rc<RefCell<State>>) {
+    let state = Rc::new(RefCell::new(State::default()));
+    let transport = git::Connection::new(
+        Server {
+            state: state.clone(),
+            lines: transcript.server.clone(),
+            buf: Vec::new(),
+        },
+        Client(state.clone()),
+        Protocol::V1,
+        "does/not/matter",
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+        false,
+    );
+    (transport, state)
+}
+
+/// Assert that the client sent exactly what the server read, ignoring the features of the first `want` line.
+fn assert_client_sent(transcript: &Transcript, state: &State) {
+    let without_features = |lines: &[Line]| -> Vec<Line> {
+        lines
+            .iter()
+            .map(|line| match line {
+                Line::Data(data) if data.starts_with(b"want ") => Line::Data(
+                    data.splitn_str(3, " ")
+                        .take(2)
+                        .collect::<Vec<_>>()
+                        .join(&b" "[..])
+                        .into(),
+                ),
+                line => line.clone(),
+            })
+            .collect()
+    };
+    assert_eq!(without_features(&state.client), without_features(&transcript.client));
+}
+
+/// Negotiate like the client in the transcript `name` and return the acknowledgements of each response,
+/// along with whether the last one was followed by a pack.
+fn replay(name: &str) -> gix_testtools::Result<(Vec<Vec<Acknowledgement>>, bool)> {
+    let transcript = Transcript::at(name)?;
+    let (mut transport, state) = transport(&transcript);
+    gix_protocol::handshake(
+        &mut transport,
+        gix_transport::Service::UploadPack,
+        helper_unused,
+        Vec::new(),
+        &mut progress::Discard,
+    )?;
+
+    let mut arguments = Arguments::new(Protocol::V1, transcript.features(), false);
+    for argument in transcript.arguments() {
+        let mut tokens = argument.split_str(" ");
+        match (tokens.next().expect("non-empty"), tokens.next().expect("a value")) {
+            (b"want", hex) => arguments.want(id(hex)),
+            (b"deepen", depth) => arguments.deepen(depth.to_str()?.parse()?),
+            _ => unreachable!("unsupported argument in transcript: {argument}"),
+        }
+    }
+
+    let mut acks = Vec::new();
+    let mut has_pack = false;
+    for (haves, done) in transcript.rounds() {
+        for have in haves {
+            arguments.have(have);
+        }
+        let mut reader = arguments.send(&mut transport, done)?;
+        let response = Response::from_line_reader(Protocol::V1, &mut reader, done, arguments.can_use_no_done())?;
+        acks.push(response.acknowledgements().to_vec());
+        if response.has_pack() {
+            io::copy(&mut reader, &mut io::sink())?;
+            has_pack = true;
+            break;
+        }
+    }
+    drop(transport);
+
+    let state = state.borrow();
+    assert_client_sent(&transcript, &state);
+    assert_eq!(
+        state.server_pos,
+        transcript.server.len(),
+        "everything the server sent was read"
+    );
+    Ok((acks, has_pack))
+}
+
+mod negotiation {
+    use gix_protocol::fetch::response::Acknowledgement::{Common, Nak, Ready};
+
+    use super::{id, replay};
+
+    #[test]
+    fn clone() -> gix_testtools::Result {
+        assert_eq!(replay("clone")?, (vec![vec![Nak]], true));
+        Ok(())
+    }
+
+    #[test]
+    fn shallow_clone() -> gix_testtools::Result {
+        assert_eq!(replay("clone-deepen-1")?, (vec![vec![Nak]], true));
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_in_common() -> gix_testtools::Result {
+        assert_eq!(
+            replay("nothing-in-common")?,
+            (vec![vec![Nak], vec![Nak]], true),
+            "`done` is answered with `NAK` if nothing is in common"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_ack_detailed() -> gix_testtools::Result {
+        let common = id(b"882d426c04aaa886097bd962f858539271109aa1");
+        assert_eq!(
+            replay("multi-ack-detailed")?,
+            (
+                vec![vec![Nak], vec![Common(common), Ready, Nak], vec![Common(common)]],
+                true
+            ),
+            "`NAK` ends each round, and the final `ACK` comes before the pack"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_ack_detailed_with_no_done() -> gix_testtools::Result {
+        let common = id(b"882d426c04aaa886097bd962f858539271109aa1");
+        assert_eq!(
+            replay("multi-ack-detailed-no-done")?,
+            (vec![vec![Nak], vec![Common(common), Ready, Nak]], true),
+            "once ready, the server sends the pack without the client sending `done`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_ack() -> gix_testtools::Result {
+        let common = id(b"882d426c04aaa886097bd962f858539271109aa1");
+        assert_eq!(
+            replay("multi-ack")?,
+            (vec![vec![Nak], vec![Common(common), Nak], vec![Common(common)]], true),
+            "`ACK <id> continue` indicates a common commit"
+        );
+        Ok(())
+    }
+}
+
+/// A delegate which sends the `have` lines of the client in a transcript, round by round.
+struct Delegate {
+    rounds: std::vec::IntoIter<(Vec<gix_hash::ObjectId>, bool)>,
+    features: Vec<(&'static str, Option<std::borrow::Cow<'static, str>>)>,
+    acks: Vec<Vec<Acknowledgement>>,
+    pack_received: bool,
+}
+
+impl fetch::DelegateBlocking for Delegate {
+    fn prepare_fetch(
+        &mut self,
+        _version: Protocol,
+        _server: &gix_transport::client::Capabilities,
+        features: &mut Vec<(&str, Option<std::borrow::Cow<'_, str>>)>,
+        _refs: &[handshake::Ref],
+    ) -> io::Result<Action> {
+        features.clear();
+        features.extend(self.features.iter().cloned());
+        Ok(Action::Continue)
+    }
+
+    fn negotiate(
+        &mut self,
+        refs: &[handshake::Ref],
+        arguments: &mut Arguments,
+        previous_response: Option<&Response>,
+    ) -> io::Result<Action> {
+        match previous_response {
+            Some(response) => self.acks.push(response.acknowledgements().to_vec()),
+            None => {
+                for r in refs.iter().filter(|r| r.unpack().0.starts_with(b"refs/")) {
+                    arguments.want(r.unpack().1.expect("born"));
+                }
+            }
+        }
+        let (haves, done) = self.rounds.next().expect("the transcript has another round");
+        for have in haves {
+            arguments.have(have);
+        }
+        Ok(if done { Action::Cancel } else { Action::Continue })
+    }
+}
+
+impl fetch::Delegate for Delegate {
+    fn receive_pack(
+        &mut self,
+        mut input: impl io::BufRead,
+        _progress: impl gix_features::progress::NestedProgress,
+        _refs: &[handshake::Ref],
+        previous_response: &Response,
+    ) -> io::Result<()> {
+        self.acks.push(previous_response.acknowledgements().to_vec());
+        io::copy(&mut input, &mut io::sink())?;
+        self.pack_received = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn fetch_negotiates_in_multiple_rounds_without_blocking() -> gix_testtools::Result {
+    let transcript = Transcript::at("multi-ack-detailed")?;
+    let (transport, state) = transport(&transcript);
+    let mut delegate = Delegate {
+        rounds: transcript.rounds().into_iter(),
+        features: transcript.features(),
+        acks: Vec::new(),
+        pack_received: false,
+    };
+    gix_protocol::fetch(
+        transport,
+        &mut delegate,
+        helper_unused,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+        "agent",
+        false,
+    )?;
+
+    assert!(delegate.pack_received);
+    assert_eq!(delegate.acks.len(), 3, "one response per round");
+    assert_client_sent(&transcript, &state.borrow());
+    Ok(())
+}
@@ -73,7 +73,7 @@ mod v1 {
         async fn unshallow_fetch() -> crate::Result {
             let mut provider = mock_reader("v1/fetch-unshallow.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, false).await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -103,9 +103,13 @@ mod v1 {
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn fetch_acks_without_pack() -> crate::Result {
             let mut provider = mock_reader("v1/fetch-no-pack.response");
-            let r =
-                fetch::Response::from_line_reader(Protocol::V1, &mut provider.as_read_without_sidebands(), true, true)
-                    .await?;
+            let r = fetch::Response::from_line_reader(
+                Protocol::V1,
+                &mut provider.as_read_without_sidebands(),
+                false, /* done not sent */
+                false, /* no-done */
+            )
+            .await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -117,11 +121,36 @@ mod v1 {
             Ok(())
         }
 
+        #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+        async fn fetch_acks_stop_at_nak_without_no_done() -> crate::Result {
+            let mut provider = mock_reader("v1/fetch.response");
+            let mut reader = provider.as_read_without_sidebands();
+            let r = fetch::Response::from_line_reader(
+                Protocol::V1,
+                &mut reader,
+                false, /* done not sent */
+                false, /* no-done */
+            )
+            .await?;
+            assert_eq!(r.acknowledgements().last(), Some(&Acknowledgement::Nak));
+            assert!(
+                !r.has_pack(),
+                "without no-done, the server waits for the client after NAK, so we must not read on"
+            );
+            Ok(())
+        }
+
         #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
         async fn fetch_acks_and_pack() -> crate::Result {
             let mut provider = mock_reader("v1/fetch.response");
             let mut reader = provider.as_read_without_sidebands();
-            let r = fetch::Response::from_line_reader(Protocol::V1, &mut reader, true, true).await?;
+            let r = fetch::Response::from_line_reader(
+                Protocol::V1,
+                &mut reader,
+                false, /* done not sent */
+                true,  /* no-done, so the pack follows once the server is ready */
+            )
+            .await?;
             assert_eq!(
                 r.acknowledgements(),
                 &[
@@ -0,0 +1,27 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q client
+(cd client
+  git checkout -q -b main
+  for n in 1 2; do
+    echo "content $n" > file
+    git add file
+    git commit -q -m "c$n"
+  done
+  git branch other
+)
+
+git clone -q --bare client server.git
+
+(cd client
+  for n in 3 4; do
+    echo "content $n" > file
+    mkdir -p dir
+    echo "nested $n" > dir/file
+    git add .
+    git commit -q -m "c$n"
+  done
+  git branch feature
+  git tag -a -m "annotated" annotated
+)
@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for n in $(seq 1 20); do
+  echo "content $n" > file
+  git add file
+  git commit -q -m "c$n"
+done
+
+git branch other HEAD~3
+
+# Negotiation must not need the history of common commits, so it is made unavailable.
+root=$(git rev-parse HEAD~19)
+rm ".git/objects/${root:0:2}/${root:2}"
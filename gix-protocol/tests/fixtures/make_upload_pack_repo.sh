@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for n in 1 2 3 4 5; do
+  echo "content $n" > file
+  mkdir -p dir/sub
+  echo "nested $n" > dir/sub/file
+  seq 1 $((n * 1000)) > big
+  git add .
+  git commit -q -m "c$n"
+done
+
+git tag -a -m "annotated" annotated HEAD~2
+git tag lightweight HEAD~1
+git branch other HEAD~3
@@ -0,0 +1,90 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Record what `git upload-pack` reads and writes in protocol V1 when negotiating with a stateful client.
+# Each transcript is a `GIT_TRACE_PACKET` log, produced by feeding the client side of the conversation
+# to the server all at once, which is fine as it reads it in order.
+
+git init -q server
+(cd server
+  git checkout -q -b main
+  for n in 1 2 3 4 5; do
+    echo "content $n" > file
+    git add file
+    git commit -q -m "c$n"
+  done
+)
+
+tip=$(git -C server rev-parse main)
+common=$(git -C server rev-parse main~2)
+unknown1=1111111111111111111111111111111111111111
+unknown2=2222222222222222222222222222222222222222
+
+function pkt() {
+  printf '%04x%s\n' $((${#1} + 5)) "$1"
+}
+
+function flush() {
+  printf '0000'
+}
+
+# Run upload-pack with the client lines on stdin and write the trace to `$1.trace`.
+# Like our clients, we don't ask for `version=1` explicitly, which has the server respond like V0.
+function record() {
+  local name=${1:?first argument is the transcript name}
+  GIT_TRACE_PACKET="$PWD/$name.trace" git upload-pack --strict server/.git >/dev/null
+}
+
+{
+  pkt "want $tip multi_ack_detailed side-band-64k ofs-delta no-progress"
+  flush
+  pkt "done"
+} | record clone
+
+{
+  pkt "want $tip multi_ack_detailed side-band-64k ofs-delta no-progress shallow"
+  pkt "deepen 1"
+  flush
+  pkt "done"
+} | record clone-deepen-1
+
+{
+  pkt "want $tip multi_ack_detailed side-band-64k ofs-delta no-progress"
+  flush
+  pkt "have $unknown1"
+  pkt "have $unknown2"
+  flush
+  pkt "done"
+} | record nothing-in-common
+
+{
+  pkt "want $tip multi_ack_detailed side-band-64k ofs-delta no-progress"
+  flush
+  pkt "have $unknown1"
+  pkt "have $unknown2"
+  flush
+  pkt "have $common"
+  flush
+  pkt "done"
+} | record multi-ack-detailed
+
+{
+  pkt "want $tip multi_ack_detailed side-band-64k ofs-delta no-progress no-done"
+  flush
+  pkt "have $unknown1"
+  pkt "have $unknown2"
+  flush
+  pkt "have $common"
+  flush
+} | record multi-ack-detailed-no-done
+
+{
+  pkt "want $tip multi_ack side-band-64k ofs-delta no-progress"
+  flush
+  pkt "have $unknown1"
+  pkt "have $unknown2"
+  flush
+  pkt "have $common"
+  flush
+  pkt "done"
+} | record multi-ack
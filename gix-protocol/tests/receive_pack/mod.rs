@@ -0,0 +1,495 @@
+use std::{io::Write, net::TcpListener, path::PathBuf, process::Output};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_object::Exists;
+use gix_packetline::{PacketLineRef, StreamingPeekableIter};
+use gix_protocol::server::receive_pack::{self, Command, Hooks, Outcome, Status};
+
+struct Fixture {
+    _dir: gix_testtools::tempfile::TempDir,
+    client: PathBuf,
+    server: PathBuf,
+    write_reflog: gix_ref::store::WriteReflog,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let dir = gix_testtools::scripted_fixture_writable("make_receive_pack_repo.sh")?;
+        Ok(Fixture {
+            client: dir.path().join("client"),
+            server: dir.path().join("server.git"),
+            write_reflog: gix_ref::store::WriteReflog::Disable,
+            _dir: dir,
+        })
+    }
+
+    fn refs(&self) -> gix_ref::file::Store {
+        refs_at(self.server.clone(), self.write_reflog)
+    }
+
+    fn server_ref(&self, name: &str) -> crate::Result<Option<ObjectId>> {
+        Ok(self.refs().try_find(name)?.map(|r| r.target.into_id()))
+    }
+
+    fn client_rev(&self, spec: &str) -> crate::Result<ObjectId> {
+        let out = std::process::Command::new("git")
+            .args(["rev-parse", spec])
+            .current_dir(&self.client)
+            .output()?;
+        assert!(out.status.success(), "{spec} must be valid");
+        Ok(ObjectId::from_hex(out.stdout.trim())?)
+    }
+
+    fn server_has_object(&self, id: &ObjectId) -> crate::Result<bool> {
+        Ok(gix_odb::at(self.server.join("objects"))?.exists(id))
+    }
+
+    fn quarantine_directories(&self) -> crate::Result<usize> {
+        Ok(std::fs::read_dir(self.server.join("objects"))?
+            .filter_map(Result::ok)
+            .filter(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with(gix_odb::quarantine::DIRECTORY_PREFIX)
+            })
+            .count())
+    }
+
+    /// Run `git push <args>` with the server as remote, which is served over `git://` on a thread using `hooks`.
+    fn push(&self, mut hooks: TestHooks, args: &[&str]) -> crate::Result<(Outcome, TestHooks, Output)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server_dir = self.server.clone();
+        let write_reflog = self.write_reflog;
+        let server = std::thread::spawn(move || -> crate::Result<(Outcome, TestHooks)> {
+            let (mut stream, _) = listener.accept()?;
+            // The `git://` protocol starts with a request line naming the service and the repository.
+            let mut lines = StreamingPeekableIter::new(&mut stream, &[PacketLineRef::Flush], false);
+            let request = lines.read_line().expect("request line")??;
+            assert!(request
+                .as_bstr()
+                .expect("data")
+                .starts_with(b"git-receive-pack /server.git\0"));
+            drop(lines);
+
+            let outcome = receive_pack::serve(
+                &stream,
+                &stream,
+                &refs_at(server_dir.clone(), write_reflog),
+                &server_dir.join("objects"),
+                &mut hooks,
+                &receive_pack::Options::default(),
+            )?;
+            Ok((outcome, hooks))
+        });
+
+        let output = std::process::Command::new("git")
+            .args(["-c", "protocol.version=0", "push", "--porcelain"])
+            .arg(format!("git://{addr}/server.git"))
+            .args(args)
+            .current_dir(&self.client)
+            .output()?;
+        let (outcome, hooks) = server.join().expect("no panic")?;
+        Ok((outcome, hooks, output))
+    }
+}
+
+fn refs_at(git_dir: PathBuf, write_reflog: gix_ref::store::WriteReflog) -> gix_ref::file::Store {
+    gix_ref::file::Store::at(git_dir, write_reflog, gix_hash::Kind::Sha1, false)
+}
+
+/// Hooks which record their invocations and reject commands as configured.
+#[derive(Default)]
+struct TestHooks {
+    /// If set, the pre-receive hook rejects all commands with this message.
+    reject_all: Option<&'static str>,
+    /// The name of the reference to reject in the update hook.
+    reject_ref: Option<&'static str>,
+
+    pre_receive: Vec<Command>,
+    /// Whether the pre-receive hook could read all objects of the new commits from the quarantine.
+    pre_receive_sees_objects: bool,
+    updates: Vec<BString>,
+    post_receive: Vec<Command>,
+    push_options: Vec<BString>,
+}
+
+impl Hooks for TestHooks {
+    fn pre_receive(
+        &mut self,
+        commands: &[Command],
+        push_options: &[BString],
+        quarantine: &gix_odb::Quarantine,
+        messages: &mut dyn Write,
+    ) -> Result<(), BString> {
+        self.pre_receive = commands.to_owned();
+        self.push_options = push_options.to_owned();
+        let objects = quarantine.handle(Default::default()).expect("valid");
+        self.pre_receive_sees_objects = commands
+            .iter()
+            .filter(|c| !c.is_delete())
+            .all(|c| objects.exists(&c.new_id));
+        writeln!(messages, "pre-receive saw {} commands", commands.len()).expect("no io error");
+        match self.reject_all {
+            Some(reason) => Err(reason.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn update(&mut self, command: &Command, _messages: &mut dyn Write) -> Result<(), BString> {
+        self.updates.push(command.name.clone());
+        if self.reject_ref.map(str::as_bytes) == Some(command.name.as_slice()) {
+            return Err("protected by test".into());
+        }
+        Ok(())
+    }
+
+    fn post_receive(&mut self, commands: &[Command], _push_options: &[BString], _messages: &mut dyn Write) {
+        self.post_receive = commands.to_owned();
+    }
+}
+
+fn statuses(outcome: &Outcome) -> Vec<(&str, Status)> {
+    outcome
+        .commands
+        .iter()
+        .map(|(command, status)| (command.name.to_str().expect("valid"), status.clone()))
+        .collect()
+}
+
+fn rejected(reason: &str) -> Status {
+    Status::Rejected { reason: reason.into() }
+}
+
+mod push {
+    use bstr::ByteSlice;
+    use gix_protocol::server::receive_pack::Status;
+
+    use gix_ref::file::ReferenceExt;
+
+    use crate::receive_pack::{rejected, statuses, Fixture, TestHooks};
+
+    #[test]
+    fn fast_forward_create_and_tag() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let (outcome, hooks, output) = fixture.push(Default::default(), &["main", "feature", "annotated"])?;
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(
+            statuses(&outcome),
+            [
+                ("refs/heads/main", Status::Ok),
+                ("refs/heads/feature", Status::Ok),
+                ("refs/tags/annotated", Status::Ok)
+            ]
+        );
+        for name in ["main", "feature", "annotated"] {
+            assert_eq!(fixture.server_ref(name)?, Some(fixture.client_rev(name)?));
+        }
+        assert!(fixture.server_has_object(&fixture.client_rev("main:dir/file")?)?);
+        assert_eq!(fixture.quarantine_directories()?, 0);
+
+        assert_eq!(hooks.pre_receive.len(), 3);
+        assert!(
+            hooks.pre_receive_sees_objects,
+            "objects are readable from the quarantine"
+        );
+        assert_eq!(
+            hooks.updates,
+            ["refs/heads/main", "refs/heads/feature", "refs/tags/annotated"]
+        );
+        assert_eq!(hooks.post_receive, hooks.pre_receive);
+        assert!(
+            output.stderr.contains_str("remote: pre-receive saw 3 commands"),
+            "hook messages are sent over the side-band"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reflogs_are_written_with_default_options() -> crate::Result {
+        let mut fixture = Fixture::new()?;
+        fixture.write_reflog = gix_ref::store::WriteReflog::Always;
+        let (outcome, _hooks, output) = fixture.push(Default::default(), &["main"])?;
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(statuses(&outcome), [("refs/heads/main", Status::Ok)]);
+
+        let refs = fixture.refs();
+        let reference = refs.find("main")?;
+        let mut platform = reference.log_iter(&refs);
+        let log: Vec<_> = platform.all()?.expect("log exists").collect::<Result<_, _>>()?;
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].new_oid(), fixture.client_rev("main")?);
+        assert_eq!(log[0].message, "push");
+        assert_eq!(
+            (log[0].signature.name, log[0].signature.email),
+            ("gitoxide".into(), "gitoxide@localhost".into()),
+            "a fallback identity is used if no committer is configured"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deletion_without_pack() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let (outcome, hooks, output) = fixture.push(Default::default(), &[":other"])?;
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(statuses(&outcome), [("refs/heads/other", Status::Ok)]);
+        assert_eq!(fixture.server_ref("other")?, None);
+        assert!(hooks.pre_receive[0].is_delete());
+        assert_eq!(fixture.quarantine_directories()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn push_options_are_passed_to_hooks() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let (outcome, hooks, output) = fixture.push(Default::default(), &["-o", "first", "-o", "second=2", "main"])?;
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(outcome.push_options, ["first", "second=2"]);
+        assert_eq!(hooks.push_options, outcome.push_options);
+        Ok(())
+    }
+
+    #[test]
+    fn pre_receive_rejection_discards_objects() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let previous = fixture.server_ref("main")?;
+        let (outcome, hooks, output) = fixture.push(
+            TestHooks {
+                reject_all: Some("not today"),
+                ..Default::default()
+            },
+            &["main", "feature"],
+        )?;
+        assert!(!output.status.success());
+        assert!(output.stdout.contains_str("[remote rejected] (not today)"));
+        assert_eq!(
+            statuses(&outcome),
+            [
+                ("refs/heads/main", rejected("not today")),
+                ("refs/heads/feature", rejected("not today"))
+            ]
+        );
+        assert!(hooks.updates.is_empty() && hooks.post_receive.is_empty());
+        assert_eq!(fixture.server_ref("main")?, previous);
+        assert_eq!(fixture.server_ref("feature")?, None);
+        assert!(!fixture.server_has_object(&fixture.client_rev("main")?)?);
+        assert_eq!(fixture.quarantine_directories()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn update_rejection_affects_only_its_reference() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let (outcome, hooks, output) = fixture.push(
+            TestHooks {
+                reject_ref: Some("refs/heads/feature"),
+                ..Default::default()
+            },
+            &["main", "feature"],
+        )?;
+        assert!(!output.status.success(), "one reference was rejected");
+        assert_eq!(
+            statuses(&outcome),
+            [
+                ("refs/heads/main", Status::Ok),
+                ("refs/heads/feature", rejected("protected by test"))
+            ]
+        );
+        assert_eq!(fixture.server_ref("main")?, Some(fixture.client_rev("main")?));
+        assert_eq!(fixture.server_ref("feature")?, None);
+        assert_eq!(hooks.post_receive.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_pushes_update_all_references_or_none() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let previous = fixture.server_ref("main")?;
+        let (outcome, hooks, output) = fixture.push(
+            TestHooks {
+                reject_ref: Some("refs/heads/feature"),
+                ..Default::default()
+            },
+            &["--atomic", "main", "feature"],
+        )?;
+        assert!(!output.status.success());
+        assert_eq!(
+            statuses(&outcome),
+            [
+                ("refs/heads/main", rejected("atomic push failure")),
+                ("refs/heads/feature", rejected("protected by test"))
+            ]
+        );
+        assert_eq!(fixture.server_ref("main")?, previous);
+        assert!(hooks.post_receive.is_empty());
+
+        let (outcome, _hooks, output) = fixture.push(Default::default(), &["--atomic", "main", "feature"])?;
+        assert!(output.status.success(), "{output:?}");
+        assert!(outcome.commands.iter().all(|(_, status)| *status == Status::Ok));
+        Ok(())
+    }
+}
+
+mod request {
+    use bstr::ByteSlice;
+    use gix_hash::ObjectId;
+    use gix_packetline::{encode, PacketLineRef, StreamingPeekableIter};
+    use gix_protocol::server::receive_pack::{self, Status};
+
+    use crate::receive_pack::{refs_at, rejected, statuses, Fixture};
+
+    /// Serve a request consisting of `commands` followed by `pack` data, and return the outcome along with the report.
+    fn serve(
+        fixture: &Fixture,
+        commands: &[String],
+        pack: &[u8],
+    ) -> crate::Result<(receive_pack::Outcome, Vec<String>)> {
+        let mut input = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            let mut line = command.clone();
+            if index == 0 {
+                line.push_str("\0report-status");
+            }
+            encode::text_to_write(line.as_bytes(), &mut input)?;
+        }
+        encode::flush_to_write(&mut input)?;
+        input.extend_from_slice(pack);
+
+        let mut out = Vec::new();
+        let outcome = receive_pack::serve_request(
+            input.as_slice(),
+            &mut out,
+            &fixture.refs(),
+            &fixture.server.join("objects"),
+            &mut (),
+            &Default::default(),
+        )?;
+        let mut lines = StreamingPeekableIter::new(out.as_slice(), &[PacketLineRef::Flush], false);
+        let mut report = Vec::new();
+        while let Some(line) = (!out.is_empty()).then(|| lines.read_line()).flatten() {
+            report.push(line??.as_bstr().expect("data").trim_end().to_str()?.to_owned());
+        }
+        Ok((outcome, report))
+    }
+
+    fn empty_pack() -> Vec<u8> {
+        let mut pack = b"PACK\0\0\0\x02\0\0\0\0".to_vec();
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha1);
+        hasher.update(&pack);
+        pack.extend_from_slice(&hasher.digest());
+        pack
+    }
+
+    #[test]
+    fn invalid_names_and_missing_objects_are_rejected() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let null = ObjectId::null(gix_hash::Kind::Sha1);
+        let main = fixture.server_ref("main")?.expect("present");
+        let unknown = ObjectId::from_hex(b"0000000000000000000000000000000000000001")?;
+        let (outcome, report) = serve(
+            &fixture,
+            &[
+                format!("{null} {main} HEAD"),
+                format!("{null} {unknown} refs/heads/missing"),
+                format!("{null} {main} refs/heads/copy"),
+            ],
+            &empty_pack(),
+        )?;
+        assert_eq!(
+            statuses(&outcome),
+            [
+                ("HEAD", rejected("funny refname")),
+                ("refs/heads/missing", rejected("missing necessary objects")),
+                ("refs/heads/copy", Status::Ok)
+            ]
+        );
+        assert_eq!(
+            report,
+            [
+                "unpack ok",
+                "ng HEAD funny refname",
+                "ng refs/heads/missing missing necessary objects",
+                "ok refs/heads/copy"
+            ]
+        );
+        assert_eq!(fixture.server_ref("copy")?, Some(main));
+        Ok(())
+    }
+
+    #[test]
+    fn stale_old_ids_are_rejected() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let null = ObjectId::null(gix_hash::Kind::Sha1);
+        let stale = ObjectId::from_hex(b"0000000000000000000000000000000000000001")?;
+        let (outcome, report) = serve(&fixture, &[format!("{stale} {null} refs/heads/other")], &[])?;
+        assert!(matches!(&outcome.commands[0].1, Status::Rejected { .. }));
+        assert!(report[1].starts_with("ng refs/heads/other "));
+        assert!(fixture.server_ref("other")?.is_some(), "the reference wasn't deleted");
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_packs_are_reported_as_unpack_errors() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let null = ObjectId::null(gix_hash::Kind::Sha1);
+        let main = fixture.server_ref("main")?.expect("present");
+        let mut input = Vec::new();
+        encode::text_to_write(
+            format!("{null} {main} refs/heads/copy\0report-status").as_bytes(),
+            &mut input,
+        )?;
+        encode::flush_to_write(&mut input)?;
+        input.extend_from_slice(b"PACK\0\0\0\x02\0\0\0\x01garbage");
+
+        let mut out = Vec::new();
+        receive_pack::serve_request(
+            input.as_slice(),
+            &mut out,
+            &fixture.refs(),
+            &fixture.server.join("objects"),
+            &mut (),
+            &Default::default(),
+        )
+        .expect_err("the pack is invalid");
+        assert!(out.contains_str("unpack "));
+        assert!(out.contains_str("ng refs/heads/copy unpacker error"));
+        assert_eq!(fixture.server_ref("copy")?, None);
+        assert_eq!(fixture.quarantine_directories()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_requests_do_nothing() -> crate::Result {
+        let fixture = Fixture::new()?;
+        let (outcome, report) = serve(&fixture, &[], &[])?;
+        assert_eq!(outcome, Default::default());
+        assert!(report.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn advertisement_without_references() -> crate::Result {
+        let dir = gix_testtools::tempfile::TempDir::new()?;
+        let refs = refs_at(dir.path().into(), gix_ref::store::WriteReflog::Disable);
+        let mut out = Vec::new();
+        receive_pack::advertise(
+            &mut out,
+            &refs,
+            &receive_pack::Options {
+                agent: "git/agent".into(),
+                ..Default::default()
+            },
+        )?;
+        let mut lines = StreamingPeekableIter::new(out.as_slice(), &[PacketLineRef::Flush], false);
+        let line = lines.read_line().expect("one line")??;
+        assert_eq!(
+            line.as_bstr().expect("data"),
+            "0000000000000000000000000000000000000000 capabilities^{}\0report-status report-status-v2 delete-refs \
+             side-band-64k quiet atomic push-options ofs-delta object-format=sha1 agent=git/agent\n"
+        );
+        assert!(lines.read_line().is_none(), "followed by a flush");
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+mod receive_pack;
+mod upload_pack;
@@ -0,0 +1,811 @@
+use std::{
+    borrow::Cow,
+    io,
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+};
+
+use bstr::{BString, ByteSlice};
+use gix_features::progress::{self, NestedProgress};
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+use gix_protocol::{
+    fetch::{self, response::ShallowUpdate, Action, Arguments, Response},
+    handshake, ls_refs,
+    server::upload_pack,
+    FetchConnection,
+};
+use gix_transport::client::{git, Capabilities};
+
+fn repo_dir() -> crate::Result<PathBuf> {
+    Ok(gix_testtools::scripted_fixture_read_only("make_upload_pack_repo.sh")?.join(".git"))
+}
+
+fn odb() -> crate::Result<gix_odb::HandleArc> {
+    odb_at(&repo_dir()?)
+}
+
+fn odb_at(git_dir: &std::path::Path) -> crate::Result<gix_odb::HandleArc> {
+    let mut odb = gix_odb::at(git_dir.join("objects"))?.into_arc()?;
+    odb.prevent_pack_unload();
+    Ok(odb)
+}
+
+fn refs() -> crate::Result<gix_ref::file::Store> {
+    refs_at(repo_dir()?)
+}
+
+fn refs_at(git_dir: PathBuf) -> crate::Result<gix_ref::file::Store> {
+    Ok(gix_ref::file::Store::at(
+        git_dir,
+        gix_ref::store::WriteReflog::Disable,
+        gix_hash::Kind::Sha1,
+        false,
+    ))
+}
+
+fn rev_parse(spec: &str) -> crate::Result<ObjectId> {
+    rev_parse_at(&repo_dir()?, spec)
+}
+
+fn rev_parse_at(git_dir: &std::path::Path, spec: &str) -> crate::Result<ObjectId> {
+    let out = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(git_dir)
+        .output()?;
+    assert!(out.status.success(), "{spec} must be valid");
+    Ok(ObjectId::from_hex(out.stdout.trim())?)
+}
+
+#[allow(clippy::result_large_err)]
+fn helper_unused(_action: gix_credentials::helper::Action) -> gix_credentials::protocol::Result {
+    panic!("Call to credentials helper is unexpected")
+}
+
+/// Run an `upload-pack` server on a thread and `fetch` from it with `client`.
+fn fetch_with(client: &mut Client, options: upload_pack::Options) -> crate::Result {
+    fetch_from(repo_dir()?, client, options)
+}
+
+/// Like [`fetch_with()`], but serves the repository at `git_dir`.
+fn fetch_from(git_dir: PathBuf, client: &mut Client, options: upload_pack::Options) -> crate::Result {
+    let (server, mut transport) = serve_on_thread_at(git_dir, options)?;
+    gix_protocol::fetch(
+        &mut transport,
+        client,
+        helper_unused,
+        progress::Discard,
+        FetchConnection::TerminateOnSuccessfulCompletion,
+        "agent",
+        false,
+    )?;
+    drop(transport);
+    server.join().expect("no panic")
+}
+
+/// Run an `upload-pack` server on a thread that serves a single connection, and return a transport connected to it.
+fn serve_on_thread(
+    options: upload_pack::Options,
+) -> crate::Result<(
+    std::thread::JoinHandle<crate::Result>,
+    git::Connection<TcpStream, TcpStream>,
+)> {
+    serve_on_thread_at(repo_dir()?, options)
+}
+
+fn serve_on_thread_at(
+    git_dir: PathBuf,
+    options: upload_pack::Options,
+) -> crate::Result<(
+    std::thread::JoinHandle<crate::Result>,
+    git::Connection<TcpStream, TcpStream>,
+)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = std::thread::spawn(move || -> crate::Result {
+        let (stream, _) = listener.accept()?;
+        upload_pack::serve(
+            &stream,
+            &stream,
+            &refs_at(git_dir.clone())?,
+            odb_at(&git_dir)?,
+            &options,
+        )?;
+        Ok(())
+    });
+
+    let stream = TcpStream::connect(addr)?;
+    let transport = git::Connection::new(
+        stream.try_clone()?,
+        stream,
+        gix_transport::Protocol::V2,
+        "does/not/matter",
+        None::<(&str, _)>,
+        git::ConnectMode::Process,
+        false,
+    );
+    Ok((server, transport))
+}
+
+/// A client that records what the server sends.
+#[derive(Default)]
+struct Client {
+    /// If set, these are sent as `ref-prefix` arguments.
+    ref_prefixes: Vec<&'static str>,
+    /// If `true`, don't fetch anything after listing refs.
+    ls_refs_only: bool,
+    /// If set, these objects are wanted instead of all advertised refs.
+    wants: Option<Vec<ObjectId>>,
+    haves: Vec<ObjectId>,
+    shallow: Vec<ObjectId>,
+    /// The amount of rounds to negotiate without sending `done`.
+    rounds_before_done: usize,
+    deepen: Option<usize>,
+    filter: Option<&'static str>,
+    include_tag: bool,
+
+    refs: Vec<handshake::Ref>,
+    rounds: usize,
+    acks: Vec<fetch::response::Acknowledgement>,
+    shallow_updates: Vec<ShallowUpdate>,
+    /// The ids of all objects in the received pack.
+    objects: Vec<ObjectId>,
+}
+
+impl fetch::DelegateBlocking for Client {
+    fn prepare_ls_refs(
+        &mut self,
+        _server: &Capabilities,
+        arguments: &mut Vec<BString>,
+        _features: &mut Vec<(&str, Option<Cow<'_, str>>)>,
+    ) -> io::Result<ls_refs::Action> {
+        for prefix in &self.ref_prefixes {
+            arguments.push(format!("ref-prefix {prefix}").into());
+        }
+        Ok(ls_refs::Action::Continue)
+    }
+
+    fn prepare_fetch(
+        &mut self,
+        _version: gix_transport::Protocol,
+        _server: &Capabilities,
+        _features: &mut Vec<(&str, Option<Cow<'_, str>>)>,
+        refs: &[handshake::Ref],
+    ) -> io::Result<Action> {
+        self.refs = refs.to_owned();
+        Ok(if self.ls_refs_only {
+            Action::Cancel
+        } else {
+            Action::Continue
+        })
+    }
+
+    fn negotiate(
+        &mut self,
+        refs: &[handshake::Ref],
+        arguments: &mut Arguments,
+        previous_response: Option<&Response>,
+    ) -> io::Result<Action> {
+        self.rounds += 1;
+        match previous_response {
+            Some(response) => self.acks.extend(response.acknowledgements().iter().copied()),
+            None => {
+                match &self.wants {
+                    Some(wants) => wants.iter().for_each(|id| arguments.want(id)),
+                    None => refs
+                        .iter()
+                        .filter_map(|r| r.unpack().1)
+                        .for_each(|id| arguments.want(id)),
+                }
+                for id in &self.shallow {
+                    arguments.shallow(id);
+                }
+                if let Some(depth) = self.deepen {
+                    arguments.deepen(depth);
+                }
+                if let Some(filter) = self.filter {
+                    arguments.filter(filter);
+                }
+                if self.include_tag {
+                    arguments.use_include_tag();
+                }
+            }
+        }
+        for id in &self.haves {
+            arguments.have(id);
+        }
+        Ok(if self.rounds > self.rounds_before_done {
+            Action::Cancel
+        } else {
+            Action::Continue
+        })
+    }
+}
+
+impl fetch::Delegate for Client {
+    fn receive_pack(
+        &mut self,
+        mut input: impl io::BufRead,
+        _progress: impl NestedProgress,
+        _refs: &[handshake::Ref],
+        response: &Response,
+    ) -> io::Result<()> {
+        self.acks.extend(response.acknowledgements().iter().copied());
+        self.shallow_updates = response.shallow_updates().to_owned();
+
+        let dir = gix_testtools::tempfile::TempDir::new()?;
+        let outcome = gix_pack::Bundle::write_to_directory(
+            &mut input,
+            Some(dir.path()),
+            &mut progress::Discard,
+            &AtomicBool::default(),
+            None::<gix_object::find::Never>,
+            Default::default(),
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let index = gix_pack::index::File::at(outcome.index_path.expect("written"), gix_hash::Kind::Sha1)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.objects = index.iter().map(|e| e.oid).collect();
+        Ok(())
+    }
+}
+
+/// Return the ids of all objects of `kind` in `ids`.
+fn of_kind(ids: &[ObjectId], kind: gix_object::Kind) -> crate::Result<Vec<ObjectId>> {
+    of_kind_at(&repo_dir()?, ids, kind)
+}
+
+fn of_kind_at(git_dir: &std::path::Path, ids: &[ObjectId], kind: gix_object::Kind) -> crate::Result<Vec<ObjectId>> {
+    let odb = odb_at(git_dir)?;
+    let mut buf = Vec::new();
+    let mut out = Vec::new();
+    for id in ids {
+        if odb.find(id, &mut buf)?.kind == kind {
+            out.push(*id);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+mod ls_refs_command {
+    use gix_protocol::{handshake::Ref, server::upload_pack};
+
+    use crate::upload_pack::{fetch_with, rev_parse, Client};
+
+    #[test]
+    fn symbolic_refs_and_peeled_tags_are_listed() -> crate::Result {
+        let mut client = Client {
+            ls_refs_only: true,
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        let main = rev_parse("main")?;
+        assert_eq!(
+            client.refs,
+            vec![
+                Ref::Symbolic {
+                    full_ref_name: "HEAD".into(),
+                    target: "refs/heads/main".into(),
+                    tag: None,
+                    object: main,
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/heads/main".into(),
+                    object: main,
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/heads/other".into(),
+                    object: rev_parse("other")?,
+                },
+                Ref::Peeled {
+                    full_ref_name: "refs/tags/annotated".into(),
+                    tag: rev_parse("annotated")?,
+                    object: rev_parse("annotated^{commit}")?,
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/tags/lightweight".into(),
+                    object: rev_parse("lightweight")?,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ref_prefixes_limit_the_listed_refs() -> crate::Result {
+        let mut client = Client {
+            ls_refs_only: true,
+            ref_prefixes: vec!["refs/tags/l", "refs/heads/oth"],
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            client.refs,
+            vec![
+                Ref::Direct {
+                    full_ref_name: "refs/heads/other".into(),
+                    object: rev_parse("other")?,
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/tags/lightweight".into(),
+                    object: rev_parse("lightweight")?,
+                },
+            ]
+        );
+        Ok(())
+    }
+}
+
+mod fetch_command {
+    use gix_object::Kind;
+    use gix_protocol::{
+        fetch::response::{Acknowledgement, ShallowUpdate},
+        server::upload_pack,
+    };
+
+    use crate::upload_pack::{fetch_from, fetch_with, odb, of_kind, of_kind_at, rev_parse, rev_parse_at, Client};
+
+    #[test]
+    fn clone_receives_all_objects() -> crate::Result {
+        let mut client = Client::default();
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        let mut expected: Vec<_> = odb()?.iter()?.collect::<Result<_, _>>()?;
+        expected.sort();
+        client.objects.sort();
+        assert_eq!(client.objects, expected, "all objects are reachable and thus sent");
+        assert_eq!(client.rounds, 1);
+        assert!(
+            client.acks.is_empty(),
+            "no acknowledgments are sent once the client is done"
+        );
+        assert!(client.shallow_updates.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn negotiation_acknowledges_common_commits_and_sends_only_what_is_missing() -> crate::Result {
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            haves: vec![rev_parse("other")?],
+            rounds_before_done: 1,
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            client.rounds, 1,
+            "the server is ready right away as the want reaches the have"
+        );
+        assert_eq!(
+            client.acks,
+            vec![Acknowledgement::Common(rev_parse("other")?), Acknowledgement::Ready]
+        );
+        assert_eq!(
+            of_kind(&client.objects, Kind::Commit)?,
+            sorted(vec![rev_parse("main")?, rev_parse("main~1")?, rev_parse("main~2")?]),
+        );
+        assert_eq!(
+            of_kind(&client.objects, Kind::Tree)?.len(),
+            3 * 3,
+            "each commit changed all trees, and trees of the common commit aren't sent"
+        );
+        assert_eq!(of_kind(&client.objects, Kind::Blob)?.len(), 3 * 3);
+        Ok(())
+    }
+
+    #[test]
+    fn negotiation_continues_without_common_commits() -> crate::Result {
+        let unknown = gix_hash::ObjectId::from_hex(b"0000000000000000000000000000000000000001")?;
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            haves: vec![unknown],
+            rounds_before_done: 1,
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            client.rounds, 2,
+            "the client had to send done as the server wasn't ready"
+        );
+        assert_eq!(client.acks, vec![Acknowledgement::Nak]);
+        assert_eq!(
+            of_kind(&client.objects, Kind::Commit)?.len(),
+            5,
+            "the whole history is sent"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn include_tag_adds_annotated_tags_pointing_to_sent_objects() -> crate::Result {
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            include_tag: true,
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(of_kind(&client.objects, Kind::Tag)?, vec![rev_parse("annotated")?]);
+
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert!(of_kind(&client.objects, Kind::Tag)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn history_of_common_commits_is_not_traversed_in_full() -> crate::Result {
+        let git_dir = gix_testtools::scripted_fixture_read_only("make_upload_pack_long_history.sh")?.join(".git");
+        let mut client = Client {
+            wants: Some(vec![rev_parse_at(&git_dir, "main")?]),
+            haves: vec![rev_parse_at(&git_dir, "other")?],
+            ..Default::default()
+        };
+        fetch_from(git_dir.clone(), &mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            of_kind_at(&git_dir, &client.objects, Kind::Commit)?,
+            sorted(vec![
+                rev_parse_at(&git_dir, "main")?,
+                rev_parse_at(&git_dir, "main~1")?,
+                rev_parse_at(&git_dir, "main~2")?
+            ]),
+            "the root commit is missing, which is fine as the walk stops shortly after the common commit"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shallow_clone_with_depth() -> crate::Result {
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            deepen: Some(2),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            client.shallow_updates,
+            vec![ShallowUpdate::Shallow(rev_parse("main~1")?)]
+        );
+        assert_eq!(
+            of_kind(&client.objects, Kind::Commit)?,
+            sorted(vec![rev_parse("main")?, rev_parse("main~1")?])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deepening_a_shallow_clone_unshallows_its_boundary() -> crate::Result {
+        let main = rev_parse("main")?;
+        let mut client = Client {
+            wants: Some(vec![main]),
+            haves: vec![main],
+            shallow: vec![main],
+            deepen: Some(3),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            client.shallow_updates,
+            vec![
+                ShallowUpdate::Shallow(rev_parse("main~2")?),
+                ShallowUpdate::Unshallow(main)
+            ]
+        );
+        assert_eq!(
+            of_kind(&client.objects, Kind::Commit)?,
+            sorted(vec![rev_parse("main~1")?, rev_parse("main~2")?]),
+            "only the commits the client doesn't have yet are sent"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn filter_blob_none_omits_all_blobs() -> crate::Result {
+        let mut client = Client {
+            filter: Some("blob:none"),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert!(of_kind(&client.objects, Kind::Blob)?.is_empty());
+        assert_eq!(of_kind(&client.objects, Kind::Commit)?.len(), 5);
+        assert_eq!(of_kind(&client.objects, Kind::Tree)?.len(), 5 * 3);
+        Ok(())
+    }
+
+    #[test]
+    fn filter_blob_limit_omits_large_blobs() -> crate::Result {
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            filter: Some("blob:limit=5k"),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(
+            of_kind(&client.objects, Kind::Blob)?.len(),
+            5 * 2 + 1,
+            "only the first version of `big` is smaller than the limit"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn filter_tree_depth_omits_deep_trees_and_blobs() -> crate::Result {
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            filter: Some("tree:1"),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(of_kind(&client.objects, Kind::Tree)?.len(), 5, "only root trees");
+        assert!(of_kind(&client.objects, Kind::Blob)?.is_empty());
+
+        let mut client = Client {
+            wants: Some(vec![rev_parse("main")?]),
+            filter: Some("tree:2"),
+            ..Default::default()
+        };
+        fetch_with(&mut client, upload_pack::Options::default())?;
+        assert_eq!(of_kind(&client.objects, Kind::Tree)?.len(), 5 * 2);
+        assert_eq!(
+            of_kind(&client.objects, Kind::Blob)?.len(),
+            5 * 2,
+            "blobs in root trees"
+        );
+        Ok(())
+    }
+
+    fn sorted(mut ids: Vec<gix_hash::ObjectId>) -> Vec<gix_hash::ObjectId> {
+        ids.sort();
+        ids
+    }
+}
+
+mod object_info_command {
+    use crate::upload_pack::{helper_unused, rev_parse, serve_on_thread};
+    use gix_features::progress;
+    use gix_protocol::{object_info::Info, server::upload_pack};
+
+    #[test]
+    fn sizes_are_obtained_without_fetching_objects() -> crate::Result {
+        let (server, mut transport) = serve_on_thread(upload_pack::Options::default())?;
+        let outcome = gix_protocol::handshake(
+            &mut transport,
+            gix_transport::Service::UploadPack,
+            helper_unused,
+            Vec::new(),
+            &mut progress::Discard,
+        )?;
+        let (blob, tree) = (rev_parse("main:big")?, rev_parse("main^{tree}")?);
+        let unknown = gix_hash::ObjectId::from_hex(b"0000000000000000000000000000000000000001")?;
+        let info = gix_protocol::object_info(
+            &mut transport,
+            &outcome.capabilities,
+            [blob, unknown, tree],
+            vec![("agent", Some("agent".into()))],
+            false,
+        )?;
+        gix_protocol::indicate_end_of_interaction(&mut transport, false)?;
+        drop(transport);
+        server.join().expect("no panic")?;
+
+        assert_eq!(info.len(), 3, "the order of the requested ids is retained");
+        assert_eq!(
+            info[0],
+            Info {
+                id: blob,
+                size: Some(23893)
+            }
+        );
+        assert_eq!(
+            info[1],
+            Info {
+                id: unknown,
+                size: None
+            },
+            "unknown objects have no size"
+        );
+        assert_eq!(info[2].id, tree);
+        assert!(info[2].size.is_some());
+        Ok(())
+    }
+}
+
+mod bundle_uri_command {
+    use gix_features::progress;
+    use gix_protocol::server::upload_pack;
+
+    use crate::upload_pack::{helper_unused, serve_on_thread};
+
+    fn handshake_with(
+        options: upload_pack::Options,
+    ) -> crate::Result<(
+        std::thread::JoinHandle<crate::Result>,
+        gix_transport::client::git::Connection<std::net::TcpStream, std::net::TcpStream>,
+        gix_transport::client::Capabilities,
+    )> {
+        let (server, mut transport) = serve_on_thread(options)?;
+        let outcome = gix_protocol::handshake(
+            &mut transport,
+            gix_transport::Service::UploadPack,
+            helper_unused,
+            Vec::new(),
+            &mut progress::Discard,
+        )?;
+        Ok((server, transport, outcome.capabilities))
+    }
+
+    #[test]
+    fn list_is_received_as_key_value_pairs() -> crate::Result {
+        let list = vec![
+            ("bundle.version".into(), "1".into()),
+            ("bundle.mode".into(), "all".into()),
+            ("bundle.one.uri".into(), "https://example.com/one.bundle?a=b".into()),
+        ];
+        let (server, mut transport, capabilities) = handshake_with(upload_pack::Options {
+            bundle_uri: list.clone(),
+            ..Default::default()
+        })?;
+        let received = gix_protocol::bundle_uri(&mut transport, &capabilities, Vec::new(), false)?;
+        gix_protocol::indicate_end_of_interaction(&mut transport, false)?;
+        drop(transport);
+        server.join().expect("no panic")?;
+
+        assert_eq!(received, list, "values may contain '=' as well");
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_without_list() -> crate::Result {
+        let (server, mut transport, capabilities) = handshake_with(upload_pack::Options::default())?;
+        let err = gix_protocol::bundle_uri(&mut transport, &capabilities, Vec::new(), false).unwrap_err();
+        assert!(matches!(err, gix_protocol::bundle_uri::Error::Unsupported));
+        gix_protocol::indicate_end_of_interaction(&mut transport, false)?;
+        drop(transport);
+        server.join().expect("no panic")?;
+        Ok(())
+    }
+}
+
+mod stateless {
+    use bstr::ByteSlice;
+    use gix_packetline::{encode, PacketLineRef, StreamingPeekableIter};
+    use gix_protocol::server::upload_pack;
+
+    use crate::upload_pack::{odb, refs, rev_parse};
+
+    fn request(lines: &[&str]) -> crate::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        for line in lines {
+            match *line {
+                "" => encode::delim_to_write(&mut out)?,
+                line => encode::text_to_write(line.as_bytes(), &mut out)?,
+            };
+        }
+        encode::flush_to_write(&mut out)?;
+        Ok(out)
+    }
+
+    fn response(out: &[u8]) -> crate::Result<Vec<String>> {
+        let mut lines = StreamingPeekableIter::new(out, &[PacketLineRef::Flush], false);
+        let mut out = Vec::new();
+        while let Some(line) = lines.read_line() {
+            let line = line??;
+            if let Some(err) = line.check_error() {
+                // Errors end the response without a flush packet.
+                out.push(format!("ERR {}", err.0.as_bstr()));
+                break;
+            }
+            out.push(line.as_bstr().expect("data").trim_end().to_str()?.to_owned());
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn advertisement() -> crate::Result {
+        let mut out = Vec::new();
+        upload_pack::advertise(
+            &mut out,
+            &upload_pack::Options {
+                agent: "git/agent".into(),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            response(&out)?,
+            [
+                "version 2",
+                "agent=git/agent",
+                "ls-refs=unborn",
+                "fetch=shallow filter",
+                "object-info",
+                "object-format=sha1"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn object_info() -> crate::Result {
+        let (main, tree) = (rev_parse("main")?, rev_parse("main:big")?);
+        let unknown = "0000000000000000000000000000000000000001";
+        let input = request(&[
+            "command=object-info",
+            "",
+            "size",
+            &format!("oid {main}"),
+            &format!("oid {tree}"),
+            &format!("oid {unknown}"),
+        ])?;
+        let mut out = Vec::new();
+        let more = upload_pack::serve_request(input.as_slice(), &mut out, &refs()?, odb()?, &Default::default())?;
+        assert!(more, "the request was served");
+        let lines = response(&out)?;
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "size");
+        assert!(lines[1].starts_with(&format!("{main} ")));
+        assert_eq!(lines[2], format!("{tree} 23893"));
+        assert_eq!(lines[3], unknown, "unknown objects have no size");
+        Ok(())
+    }
+
+    #[test]
+    fn empty_requests_end_the_session() -> crate::Result {
+        let input = request(&[])?;
+        let mut out = Vec::new();
+        assert!(!upload_pack::serve_request(
+            input.as_slice(),
+            &mut out,
+            &refs()?,
+            odb()?,
+            &Default::default()
+        )?);
+        assert!(!upload_pack::serve_request(
+            &[][..],
+            &mut out,
+            &refs()?,
+            odb()?,
+            &Default::default()
+        )?);
+        assert!(out.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn errors_are_sent_to_the_client() -> crate::Result {
+        for (request_lines, expected) in [
+            (&["command=unknown"][..], "ERR The command \"unknown\" is not supported"),
+            (
+                &[
+                    "command=fetch",
+                    "",
+                    "want 0000000000000000000000000000000000000001",
+                    "done",
+                ],
+                "ERR not our ref 0000000000000000000000000000000000000001",
+            ),
+            (
+                &["command=ls-refs", "object-format=sha256"],
+                "ERR The client requested object-format \"sha256\", but the repository uses SHA1",
+            ),
+            (
+                &["command=fetch", "", "filter sparse:oid=main:.sparse"],
+                "ERR The filter 'sparse:oid=main:.sparse' is not supported",
+            ),
+        ] {
+            let mut out = Vec::new();
+            upload_pack::serve_request(
+                request(request_lines)?.as_slice(),
+                &mut out,
+                &refs()?,
+                odb()?,
+                &Default::default(),
+            )
+            .expect_err("invalid request");
+            assert_eq!(response(&out)?, [expected]);
+        }
+        Ok(())
+    }
+}
@@ -51,6 +51,7 @@ mod init {
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                quarantine: false,
             }
         }
 
@@ -75,6 +76,7 @@ mod init {
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
                 object_hash,
                 precompose_unicode,
+                quarantine: false,
             }
         }
     }
@@ -34,6 +34,11 @@ pub struct Store {
     /// Iterated references will be returned verbatim, thus when sending them over the wire they have to be precomposed
     /// as needed.
     pub precompose_unicode: bool,
+    /// If `true`, all reference transactions will fail to prepare as objects are currently in quarantine, and references
+    /// must not point to them before they are migrated into the main object database.
+    ///
+    /// This is the case when `GIT_QUARANTINE_PATH` is set, for instance in `pre-receive` hooks.
+    pub quarantine: bool,
     /// A packed buffer which can be mapped in one version and shared as such.
     /// It's updated only in one spot, which is prior to reading it based on file stamps.
     /// Doing it like this has the benefit of being able to hand snapshots out to people without blocking others from updating it.
@@ -204,6 +204,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
     /// If the operation succeeds, the transaction can be committed or dropped to cause a rollback automatically.
     /// Rollbacks happen automatically on failure and they tend to be perfect.
     /// This method is idempotent.
+    ///
+    /// Note that it fails if the store is set to be in [quarantine](crate::file::Store::quarantine).
     pub fn prepare(
         self,
         edits: impl IntoIterator<Item = RefEdit>,
@@ -225,6 +227,9 @@ impl<'s, 'p> Transaction<'s, 'p> {
     ) -> Result<Self, Error> {
         assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
         let store = self.store;
+        if store.quarantine {
+            return Err(Error::Quarantine);
+        }
         let mut updates: Vec<_> = edits
             .map(|update| Edit {
                 update,
@@ -453,6 +458,8 @@ mod error {
     #[derive(Debug, thiserror::Error)]
     #[allow(missing_docs)]
     pub enum Error {
+        #[error("Reference updates are forbidden while objects are in quarantine")]
+        Quarantine,
         #[error("The packed ref buffer could not be loaded")]
         Packed(#[from] packed::buffer::open::Error),
         #[error("The lock for the packed-ref file could not be obtained")]
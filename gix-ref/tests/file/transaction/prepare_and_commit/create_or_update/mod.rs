@@ -26,6 +26,27 @@ use crate::{
 
 mod collisions;
 
+#[test]
+fn transactions_are_refused_in_quarantine() -> crate::Result {
+    let (dir, mut store) = empty_store()?;
+    store.quarantine = true;
+
+    let err = store
+        .transaction()
+        .prepare([create_at("refs/heads/main")], Fail::Immediately, Fail::Immediately)
+        .unwrap_err();
+    assert!(matches!(err, transaction::prepare::Error::Quarantine));
+    assert!(!dir.path().join("refs").exists(), "nothing was touched");
+
+    store.quarantine = false;
+    store
+        .transaction()
+        .prepare([create_at("refs/heads/main")], Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref())?;
+    assert!(store.try_find_loose("main")?.is_some());
+    Ok(())
+}
+
 #[test]
 fn intermediate_directories_are_removed_on_rollback() -> crate::Result {
     for explicit_rollback in [false, true] {
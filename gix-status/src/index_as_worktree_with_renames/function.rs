@@ -0,0 +1,481 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::Read,
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use bstr::BStr;
+use gix_diff::rewrites::tracker::{visit::SourceKind, ChangeKind};
+use gix_filter::pipeline::convert::ToGitOutcome;
+use gix_hash::ObjectId;
+use gix_object::tree::{EntryKind, EntryMode};
+
+use crate::{
+    index_as_worktree::{
+        traits::{CompareBlobs, SubmoduleStatus},
+        Change, EntryStatus, Recorder,
+    },
+    index_as_worktree_with_renames::{
+        Context, DirwalkContext, Entry, Error, Options, Outcome, PathspecAttributesFn, RewriteDestination,
+        RewriteSource, VisitEntry,
+    },
+};
+
+/// Like [`index_as_worktree()`](crate::index_as_worktree()), but also performs a directory walk to find untracked files,
+/// and tracks rewrites from deleted index entries to untracked files and files added with `git add --intent-to-add`.
+///
+/// All output is passed to `collector`, and `compare`, `submodule`, `objects` and `progress` are used just like in
+/// [`index_as_worktree()`](crate::index_as_worktree()).
+/// `ctx` provides the pathspec and filters to use, along with everything that is needed for the directory walk, while
+/// `options` control which of these operations are performed.
+///
+/// To track rewrites, untracked files and intent-to-add files are hashed after converting them to their representation in
+/// git, and their similarity to the blobs of deleted index entries is determined with [`Context::resource_cache`].
+/// Sources of copies are modified index entries, or all index entries if configured.
+///
+/// ### Note
+///
+/// Entries are emitted once all work is done, and the `index` may require updates just like with
+/// [`index_as_worktree()`](crate::index_as_worktree()).
+#[allow(clippy::too_many_arguments)]
+pub fn index_as_worktree_with_renames<'index, T, U, Find, E>(
+    index: &'index gix_index::State,
+    worktree: &Path,
+    collector: &mut impl VisitEntry<'index, ContentChange = T, SubmoduleStatus = U>,
+    compare: impl CompareBlobs<Output = T> + Send + Clone,
+    submodule: impl SubmoduleStatus<Output = U, Error = E> + Send + Clone,
+    objects: Find,
+    progress: &mut dyn gix_features::progress::Progress,
+    ctx: Context<'_>,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    T: Send,
+    U: Send,
+    E: std::error::Error + Send + Sync + 'static,
+    Find: gix_object::Find + gix_object::FindHeader + Send + Clone,
+{
+    let _span = gix_features::trace::coarse!("gix_status::index_as_worktree_with_renames()");
+    let Context {
+        mut pathspec,
+        pathspec_attributes,
+        filter,
+        should_interrupt,
+        resource_cache,
+        dirwalk: dirwalk_ctx,
+    } = ctx;
+
+    let mut tracked = Recorder { records: Vec::new() };
+    let tracked_file_modification = crate::index_as_worktree(
+        index,
+        worktree,
+        &mut tracked,
+        compare,
+        submodule,
+        objects.clone(),
+        progress,
+        Pathspec {
+            search: pathspec.clone(),
+            attributes: pathspec_attributes.clone(),
+        },
+        filter.clone(),
+        should_interrupt,
+        options.tracked_file_modifications.clone(),
+    )?;
+
+    let mut untracked = gix_dir::walk::delegate::Collect::default();
+    let dirwalk = options
+        .dirwalk
+        .map(|dirwalk_options| {
+            let DirwalkContext {
+                git_dir_realpath,
+                current_dir,
+                ignore_case_index_lookup,
+                excludes,
+            } = dirwalk_ctx;
+            gix_dir::walk(
+                worktree,
+                worktree,
+                gix_dir::walk::Context {
+                    git_dir_realpath,
+                    current_dir,
+                    index,
+                    ignore_case_index_lookup,
+                    pathspec: &mut pathspec,
+                    pathspec_attributes: &mut |path, case, is_dir, out| pathspec_attributes(path, case, is_dir, out),
+                    excludes,
+                    objects: &objects,
+                },
+                dirwalk_options,
+                &mut untracked,
+            )
+        })
+        .transpose()?;
+
+    let mut emitter = Emitter {
+        collector,
+        buffer: options.sorting.map(|_| Vec::new()),
+    };
+    let entries = index.entries();
+    let rewrites = match options.rewrites {
+        None => {
+            for record in tracked.records {
+                emitter.emit(Entry::Modification {
+                    entries,
+                    entry: record.entry,
+                    entry_index: record.entry_index,
+                    rela_path: record.relative_path,
+                    status: record.status,
+                });
+            }
+            for (entry, collapsed_directory_status) in untracked.unorded_entries {
+                emitter.emit(Entry::DirectoryContents {
+                    entry,
+                    collapsed_directory_status,
+                });
+            }
+            None
+        }
+        Some(rewrites) => {
+            let mut tracker = gix_diff::rewrites::Tracker::<Rewrite>::new(rewrites);
+            let mut hasher = Hasher {
+                stack: gix_worktree::Stack::from_state_and_ignore_case(
+                    worktree,
+                    options.tracked_file_modifications.fs.ignore_case,
+                    gix_worktree::stack::State::AttributesStack(options.tracked_file_modifications.attributes),
+                    index,
+                    index.path_backing(),
+                ),
+                filter,
+                worktree,
+                buf: Vec::new(),
+                object_hash: index.object_hash(),
+                symlinks: options.tracked_file_modifications.fs.symlink,
+                should_interrupt,
+            };
+
+            // The statuses of all index entries that are tracked, to be emitted unless they are the source of a rename.
+            let mut statuses = BTreeMap::new();
+            for record in tracked.records {
+                let kind = match record.status {
+                    EntryStatus::Change(Change::Removed) => Some(ChangeKind::Deletion),
+                    EntryStatus::Change(Change::Modification { .. }) => Some(ChangeKind::Modification),
+                    EntryStatus::IntentToAdd => Some(ChangeKind::Addition),
+                    _ => None,
+                };
+                if let Some((kind, mode)) = kind.zip(record.entry.mode.to_tree_entry_mode()) {
+                    let id = match kind {
+                        ChangeKind::Addition => hasher.hash(record.relative_path, mode.is_link(), &objects)?,
+                        ChangeKind::Deletion | ChangeKind::Modification => record.entry.id,
+                    };
+                    let change = Rewrite {
+                        id,
+                        kind,
+                        mode,
+                        origin: Origin::Index(record.entry_index),
+                    };
+                    if tracker.try_push_change(change, record.relative_path).is_none() {
+                        statuses.insert(record.entry_index, record.status);
+                        continue;
+                    }
+                }
+                emitter.emit(Entry::Modification {
+                    entries,
+                    entry: record.entry,
+                    entry_index: record.entry_index,
+                    rela_path: record.relative_path,
+                    status: record.status,
+                });
+            }
+
+            let mut dirwalk_entries = Vec::new();
+            for (entry, collapsed_directory_status) in untracked.unorded_entries {
+                let kind = match (entry.status, entry.disk_kind) {
+                    (gix_dir::entry::Status::Untracked, Some(gix_dir::entry::Kind::File)) => Some(EntryKind::Blob),
+                    (gix_dir::entry::Status::Untracked, Some(gix_dir::entry::Kind::Symlink)) => Some(EntryKind::Link),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    let change = Rewrite {
+                        id: hasher.hash(entry.rela_path.as_ref(), kind == EntryKind::Link, &objects)?,
+                        kind: ChangeKind::Addition,
+                        mode: kind.into(),
+                        origin: Origin::Dirwalk(dirwalk_entries.len()),
+                    };
+                    if tracker.try_push_change(change, entry.rela_path.as_ref()).is_none() {
+                        dirwalk_entries.push(Some((entry, collapsed_directory_status)));
+                        continue;
+                    }
+                }
+                emitter.emit(Entry::DirectoryContents {
+                    entry,
+                    collapsed_directory_status,
+                });
+            }
+
+            let tracked_entries: HashSet<_> = statuses.keys().copied().collect();
+            let outcome = tracker.emit(
+                |dest, source| {
+                    match source {
+                        None => match dest.change.origin {
+                            Origin::Index(entry_index) => {
+                                let status = statuses
+                                    .remove(&entry_index)
+                                    .expect("each tracked entry is emitted once");
+                                let entry = &entries[entry_index];
+                                emitter.emit(Entry::Modification {
+                                    entries,
+                                    entry,
+                                    entry_index,
+                                    rela_path: entry.path(index),
+                                    status,
+                                });
+                            }
+                            Origin::Dirwalk(idx) => {
+                                let (entry, collapsed_directory_status) =
+                                    dirwalk_entries[idx].take().expect("each entry is emitted once");
+                                emitter.emit(Entry::DirectoryContents {
+                                    entry,
+                                    collapsed_directory_status,
+                                });
+                            }
+                        },
+                        Some(source) => {
+                            let source_index = index
+                                .entry_index_by_path_and_stage(source.location, 0)
+                                .expect("sources are always index entries");
+                            if source.kind == SourceKind::Rename {
+                                statuses.remove(&source_index);
+                            }
+                            let source_entry = &entries[source_index];
+                            let destination = match dest.change.origin {
+                                Origin::Index(entry_index) => {
+                                    statuses.remove(&entry_index);
+                                    let entry = &entries[entry_index];
+                                    RewriteDestination::IntentToAdd {
+                                        entry,
+                                        entry_index,
+                                        rela_path: entry.path(index),
+                                    }
+                                }
+                                Origin::Dirwalk(idx) => {
+                                    let (entry, collapsed_directory_status) =
+                                        dirwalk_entries[idx].take().expect("each entry is emitted once");
+                                    RewriteDestination::Untracked {
+                                        entry,
+                                        collapsed_directory_status,
+                                    }
+                                }
+                            };
+                            emitter.emit(Entry::Rewrite {
+                                source: RewriteSource {
+                                    entries,
+                                    entry: source_entry,
+                                    entry_index: source_index,
+                                    rela_path: source_entry.path(index),
+                                },
+                                destination,
+                                destination_id: dest.change.id,
+                                diff: source.diff,
+                                copy: source.kind == SourceKind::Copy,
+                            });
+                        }
+                    }
+                    if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+                        gix_diff::tree::visit::Action::Cancel
+                    } else {
+                        gix_diff::tree::visit::Action::Continue
+                    }
+                },
+                resource_cache,
+                &objects,
+                |push| {
+                    for (entry_index, entry) in entries.iter().enumerate() {
+                        if entry.stage() != 0 || tracked_entries.contains(&entry_index) {
+                            continue;
+                        }
+                        if let Some(mode) = entry.mode.to_tree_entry_mode() {
+                            let change = Rewrite {
+                                id: entry.id,
+                                kind: ChangeKind::Modification,
+                                mode,
+                                origin: Origin::Index(entry_index),
+                            };
+                            push(change, entry.path(index));
+                        }
+                    }
+                    Ok::<_, std::convert::Infallible>(())
+                },
+            )?;
+
+            // Modified sources of copies are still to be emitted.
+            for (entry_index, status) in statuses {
+                let entry = &entries[entry_index];
+                emitter.emit(Entry::Modification {
+                    entries,
+                    entry,
+                    entry_index,
+                    rela_path: entry.path(index),
+                    status,
+                });
+            }
+            Some(outcome)
+        }
+    };
+    emitter.finish();
+
+    Ok(Outcome {
+        tracked_file_modification,
+        dirwalk,
+        rewrites,
+    })
+}
+
+/// The change as seen by the rewrite tracker.
+#[derive(Clone)]
+struct Rewrite {
+    id: ObjectId,
+    kind: ChangeKind,
+    mode: EntryMode,
+    origin: Origin,
+}
+
+/// Where a [`Rewrite`] comes from.
+#[derive(Clone, Copy)]
+enum Origin {
+    /// The index of an entry in the index.
+    Index(usize),
+    /// The index of an entry in the list of dirwalk entries.
+    Dirwalk(usize),
+}
+
+impl gix_diff::rewrites::tracker::Change for Rewrite {
+    fn id(&self) -> &gix_hash::oid {
+        &self.id
+    }
+
+    fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    fn entry_mode(&self) -> EntryMode {
+        self.mode
+    }
+
+    fn id_and_entry_mode(&self) -> (&gix_hash::oid, EntryMode) {
+        (&self.id, self.mode)
+    }
+}
+
+/// Pass entries to the collector, or keep them until they are sorted.
+struct Emitter<'a, 'index, V: VisitEntry<'index>> {
+    collector: &'a mut V,
+    buffer: Option<Vec<Entry<'index, V::ContentChange, V::SubmoduleStatus>>>,
+}
+
+impl<'a, 'index, V: VisitEntry<'index>> Emitter<'a, 'index, V> {
+    fn emit(&mut self, entry: Entry<'index, V::ContentChange, V::SubmoduleStatus>) {
+        match self.buffer.as_mut() {
+            Some(buffer) => buffer.push(entry),
+            None => self.collector.visit_entry(entry),
+        }
+    }
+
+    fn finish(self) {
+        if let Some(mut buffer) = self.buffer {
+            buffer.sort_by(|a, b| a.rela_path().cmp(b.rela_path()));
+            for entry in buffer {
+                self.collector.visit_entry(entry);
+            }
+        }
+    }
+}
+
+/// Compute the ids of worktree files as they would be stored in git.
+struct Hasher<'a> {
+    stack: gix_worktree::Stack,
+    filter: gix_filter::Pipeline,
+    worktree: &'a Path,
+    buf: Vec<u8>,
+    object_hash: gix_hash::Kind,
+    symlinks: bool,
+    should_interrupt: &'a AtomicBool,
+}
+
+impl Hasher<'_> {
+    fn hash(&mut self, rela_path: &BStr, is_link: bool, objects: &dyn gix_object::Find) -> Result<ObjectId, Error> {
+        let io_err = |source| Error::HashFile {
+            rela_path: rela_path.to_owned(),
+            source,
+        };
+        let path = self.worktree.join(gix_path::from_bstr(rela_path));
+        self.buf.clear();
+        if is_link && self.symlinks {
+            let target = std::fs::read_link(&path).map_err(io_err)?;
+            self.buf.extend_from_slice(&gix_path::into_bstr(target));
+            return Ok(gix_object::compute_hash(
+                self.object_hash,
+                gix_object::Kind::Blob,
+                &self.buf,
+            ));
+        }
+
+        let platform = self.stack.at_entry(rela_path, Some(false), objects).map_err(io_err)?;
+        let file = std::fs::File::open(&path).map_err(io_err)?;
+        let len = file.metadata().map_err(io_err)?.len();
+        let out = self
+            .filter
+            .convert_to_git(
+                file,
+                gix_path::from_bstr(rela_path).as_ref(),
+                &mut |_path, attrs| {
+                    platform.matching_attributes(attrs);
+                },
+                // Untracked files have no version in the index that could be used instead of re-applying filters.
+                &mut |_buf| Ok(None),
+            )
+            .map_err(|source| Error::ConvertToGit {
+                rela_path: rela_path.to_owned(),
+                source,
+            })?;
+        Ok(match out {
+            ToGitOutcome::Unchanged(mut file) => gix_object::compute_stream_hash(
+                self.object_hash,
+                gix_object::Kind::Blob,
+                &mut file,
+                len,
+                &mut gix_features::progress::Discard,
+                self.should_interrupt,
+            )
+            .map_err(io_err)?,
+            ToGitOutcome::Buffer(buf) => gix_object::compute_hash(self.object_hash, gix_object::Kind::Blob, buf),
+            ToGitOutcome::Process(mut stream) => {
+                stream.read_to_end(&mut self.buf).map_err(io_err)?;
+                gix_object::compute_hash(self.object_hash, gix_object::Kind::Blob, &self.buf)
+            }
+        })
+    }
+}
+
+/// A pathspec that can be shared among threads to check index entries for modifications.
+#[derive(Clone)]
+struct Pathspec {
+    search: gix_pathspec::Search,
+    attributes: Arc<PathspecAttributesFn>,
+}
+
+impl crate::Pathspec for Pathspec {
+    fn common_prefix(&self) -> &BStr {
+        self.search.common_prefix()
+    }
+
+    fn is_included(&mut self, relative_path: &BStr, is_dir: Option<bool>) -> bool {
+        let attributes = &self.attributes;
+        self.search
+            .pattern_matching_relative_path(relative_path, is_dir, &mut |path, case, is_dir, out| {
+                attributes(path, case, is_dir, out)
+            })
+            .map_or(false, |m| !m.is_excluded())
+    }
+}
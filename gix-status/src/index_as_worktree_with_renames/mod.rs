@@ -0,0 +1,227 @@
+//! Changes between the index and the worktree along with optional rename tracking.
+use std::sync::{atomic::AtomicBool, Arc};
+
+use bstr::BStr;
+use gix_hash::ObjectId;
+
+use crate::index_as_worktree::EntryStatus;
+
+pub(crate) mod function;
+
+mod recorder;
+pub use recorder::Recorder;
+
+/// The error returned by [index_as_worktree_with_renames()`](crate::index_as_worktree_with_renames()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    TrackedFileModifications(#[from] crate::index_as_worktree::Error),
+    #[error(transparent)]
+    DirWalk(#[from] gix_dir::walk::Error),
+    #[error("Could not hash the worktree file at '{rela_path}' to find its rename source")]
+    HashFile {
+        rela_path: bstr::BString,
+        source: std::io::Error,
+    },
+    #[error("Could not convert the worktree file at '{rela_path}' to its representation in git")]
+    ConvertToGit {
+        rela_path: bstr::BString,
+        source: gix_filter::pipeline::convert::to_git::Error,
+    },
+    #[error(transparent)]
+    RewriteTracker(#[from] gix_diff::rewrites::tracker::emit::Error),
+}
+
+/// Options that control how the index status of a worktree is computed, along with the rename tracking between them.
+#[derive(Clone, Default)]
+pub struct Options {
+    /// Options to configure how modifications to tracked files should be obtained.
+    pub tracked_file_modifications: crate::index_as_worktree::Options,
+    /// Options to control the directory walk that informs about untracked files.
+    ///
+    /// If `None`, the directory walk is skipped, and untracked files can't be destinations of renames or copies.
+    ///
+    /// Note that only files that are emitted individually can be matched, so untracked directories should not be
+    /// collapsed with [`EmissionMode::CollapseDirectory`](gix_dir::walk::EmissionMode::CollapseDirectory) if their
+    /// files should be considered.
+    pub dirwalk: Option<gix_dir::walk::Options>,
+    /// The way all output should be sorted.
+    ///
+    /// If `None`, entries are emitted in no particular order, but rewrites are always emitted before all other entries.
+    pub sorting: Option<Sorting>,
+    /// If `Some(…)`, deleted index entries are matched with untracked files and files added with `git add --intent-to-add`
+    /// to find renames, and copies if configured.
+    ///
+    /// If `None`, no rename tracking is performed.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// Determine the order in which entries are emitted to the [`VisitEntry`] implementation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Sorting {
+    /// Emit entries in the order of their repository-relative path, with rewrites being sorted by their destination.
+    ///
+    /// This requires all entries to be kept in memory before they can be emitted.
+    ByPathCaseSensitive,
+}
+
+/// All information that is required to compute the status and to perform the directory walk.
+pub struct Context<'a> {
+    /// The pathspec to limit the index entries to check for modifications, and the directory walk.
+    pub pathspec: gix_pathspec::Search,
+    /// A function to provide attributes to `pathspec` if it needs them, see [`gix_dir::walk::Context::pathspec_attributes`].
+    pub pathspec_attributes: Arc<PathspecAttributesFn>,
+    /// The filter used to convert worktree files into their representation in git, both to check for modifications and to
+    /// hash untracked files.
+    pub filter: gix_filter::Pipeline,
+    /// Set to `true` to stop all processing.
+    pub should_interrupt: &'a AtomicBool,
+    /// The cache of resources to compare for similarity when tracking rewrites.
+    ///
+    /// It should be configured with the worktree as [new root](gix_diff::blob::pipeline::WorktreeRoots::new_root) and
+    /// to [convert to git](gix_diff::blob::pipeline::Mode::ToGit) so that untracked files are compared in the same form
+    /// as the blobs of deleted index entries.
+    pub resource_cache: &'a mut gix_diff::blob::Platform,
+    /// Additional information that is only needed for the directory walk.
+    pub dirwalk: DirwalkContext<'a>,
+}
+
+/// The signature of the function to obtain attributes for pathspecs, as used in [`Context::pathspec_attributes`].
+pub type PathspecAttributesFn = dyn Fn(
+        &BStr,
+        gix_pathspec::attributes::glob::pattern::Case,
+        bool,
+        &mut gix_pathspec::attributes::search::Outcome,
+    ) -> bool
+    + Send
+    + Sync;
+
+/// All information that is needed to perform the directory walk, see [`gix_dir::walk::Context`] for details.
+pub struct DirwalkContext<'a> {
+    /// The `git_dir` of the repository, after a call to [`gix_path::realpath()`].
+    pub git_dir_realpath: &'a std::path::Path,
+    /// The current working directory as returned by `gix_fs::current_dir()`.
+    pub current_dir: &'a std::path::Path,
+    /// A utility to lookup index entries faster, and deal with ignore-case handling.
+    pub ignore_case_index_lookup: Option<&'a gix_index::AccelerateLookup<'a>>,
+    /// A way to query the `.gitignore` files to see if a directory or file is ignored, or `None` to treat ignored files as untracked.
+    pub excludes: Option<&'a mut gix_worktree::Stack>,
+}
+
+/// Provide additional information collected during the runtime of [`index_as_worktree_with_renames()`](crate::index_as_worktree_with_renames()).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// The outcome of the modification check of tracked files.
+    pub tracked_file_modification: crate::index_as_worktree::Outcome,
+    /// The outcome of the directory walk, or `None` if its [options](Options::dirwalk) were not present.
+    pub dirwalk: Option<gix_dir::walk::Outcome>,
+    /// The result of the rewrite operation, if [rewrites were configured](Options::rewrites).
+    pub rewrites: Option<gix_diff::rewrites::Outcome>,
+}
+
+/// Either an index entry for renames, or an untracked or intent-to-add file, or a rewrite of one to the other.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Entry<'index, ContentChange, SubmoduleStatus> {
+    /// A tracked file was modified, and index-specific information is passed.
+    Modification {
+        /// All entries in the index.
+        entries: &'index [gix_index::Entry],
+        /// The entry with modifications.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` in `entries`.
+        entry_index: usize,
+        /// The repository-relative path of `entry`.
+        rela_path: &'index BStr,
+        /// The computed status of `entry`.
+        status: EntryStatus<ContentChange, SubmoduleStatus>,
+    },
+    /// An entry returned by the directory walk that isn't the destination of a rewrite, like untracked or ignored files.
+    DirectoryContents {
+        /// The entry found during the directory walk.
+        entry: gix_dir::Entry,
+        /// The status of the directory containing `entry` if it was collapsed into it, see [`gix_dir::walk::Delegate::emit()`].
+        collapsed_directory_status: Option<gix_dir::entry::Status>,
+    },
+    /// The rewrite tracking discovered that the file at `source` was renamed or copied to `destination`.
+    Rewrite {
+        /// The index entry that was renamed or copied.
+        source: RewriteSource<'index>,
+        /// The untracked or intent-to-add file that `source` was renamed or copied to.
+        destination: RewriteDestination<'index>,
+        /// The id of the content of `destination` as it would be stored in git.
+        destination_id: ObjectId,
+        /// The diff-statistics of both files if they were compared by similarity, or `None` if they are identical.
+        diff: Option<gix_diff::blob::DiffLineStats>,
+        /// If `true`, `source` still exists and was copied, otherwise it was renamed.
+        copy: bool,
+    },
+}
+
+/// The source of a [rewrite](Entry::Rewrite), which is always an entry in the index.
+///
+/// If it's the source of a rename, its file was removed from the worktree. If it's the source of a copy, it still exists,
+/// and possible modifications to it are reported separately.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RewriteSource<'index> {
+    /// All entries in the index.
+    pub entries: &'index [gix_index::Entry],
+    /// The entry that is the source of the rewrite.
+    pub entry: &'index gix_index::Entry,
+    /// The index of `entry` in `entries`.
+    pub entry_index: usize,
+    /// The repository-relative path of `entry`.
+    pub rela_path: &'index BStr,
+}
+
+/// The destination of a [rewrite](Entry::Rewrite), which is a file in the worktree.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RewriteDestination<'index> {
+    /// The file isn't tracked, as found by the directory walk.
+    Untracked {
+        /// The entry found during the directory walk.
+        entry: gix_dir::Entry,
+        /// The status of the directory containing `entry` if it was collapsed into it, see [`gix_dir::walk::Delegate::emit()`].
+        collapsed_directory_status: Option<gix_dir::entry::Status>,
+    },
+    /// The file was added with `git add --intent-to-add`.
+    IntentToAdd {
+        /// The entry marked as intent-to-add.
+        entry: &'index gix_index::Entry,
+        /// The index of `entry` in all entries of the index.
+        entry_index: usize,
+        /// The repository-relative path of `entry`.
+        rela_path: &'index BStr,
+    },
+}
+
+impl<'index, ContentChange, SubmoduleStatus> Entry<'index, ContentChange, SubmoduleStatus> {
+    /// Return the repository-relative path of this entry, which is the destination in case of rewrites.
+    pub fn rela_path(&self) -> &BStr {
+        match self {
+            Entry::Modification { rela_path, .. } => rela_path,
+            Entry::DirectoryContents { entry, .. } => entry.rela_path.as_ref(),
+            Entry::Rewrite { destination, .. } => destination.rela_path(),
+        }
+    }
+}
+
+impl<'index> RewriteDestination<'index> {
+    /// Return the repository-relative path of this destination.
+    pub fn rela_path(&self) -> &BStr {
+        match self {
+            RewriteDestination::Untracked { entry, .. } => entry.rela_path.as_ref(),
+            RewriteDestination::IntentToAdd { rela_path, .. } => rela_path,
+        }
+    }
+}
+
+/// Observe the status of index entries, untracked files, and the rewrites between them.
+pub trait VisitEntry<'index> {
+    /// Data generated by comparing an entry with a file.
+    type ContentChange;
+    /// Data obtained when checking the submodule status.
+    type SubmoduleStatus;
+    /// Observe the status of `entry`.
+    fn visit_entry(&mut self, entry: Entry<'index, Self::ContentChange, Self::SubmoduleStatus>);
+}
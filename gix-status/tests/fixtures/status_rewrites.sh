@@ -0,0 +1,18 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+seq 1 20 >to-rename
+seq 30 50 >to-rename-similar
+echo "intent to add" >to-rename-intent
+seq 60 80 >to-copy
+mkdir dir && echo "deleted" >dir/to-delete
+git add . && git commit -qm "init"
+
+mv to-rename renamed
+mv to-rename-similar renamed-similar && echo 51 >>renamed-similar
+mv to-rename-intent intent-renamed && git add --intent-to-add intent-renamed
+rm dir/to-delete
+cp to-copy copied
+echo "not similar to anything" >untracked
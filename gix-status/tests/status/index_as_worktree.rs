@@ -21,7 +21,7 @@ use crate::fixture_path;
 // changes when extracting the data so we need to disable all advanced stat
 // changes and only look at mtime seconds and file size to properly
 // test all code paths (and to trigger racy git).
-const TEST_OPTIONS: index::entry::stat::Options = index::entry::stat::Options {
+pub(crate) const TEST_OPTIONS: index::entry::stat::Options = index::entry::stat::Options {
     trust_ctime: false,
     check_stat: false,
     use_nsec: false,
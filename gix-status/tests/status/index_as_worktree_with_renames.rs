@@ -0,0 +1,182 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use gix_diff::{
+    rewrites::{Copies, CopySource},
+    Rewrites,
+};
+use gix_status::{
+    index_as_worktree::{traits::FastEq, Change, EntryStatus},
+    index_as_worktree_with_renames::{Context, DirwalkContext, Entry, Options, Recorder, RewriteDestination, Sorting},
+};
+
+use super::index_as_worktree::TEST_OPTIONS;
+use crate::fixture_path;
+
+#[test]
+fn renames_to_untracked_and_intent_to_add_files() -> crate::Result {
+    assert_eq!(
+        fixture("status_rewrites", Some(Rewrites::default()))?,
+        [
+            "? copied",
+            "D dir/to-delete",
+            "R to-rename-intent -> intent-renamed (intent-to-add)",
+            "R to-rename -> renamed",
+            "R to-rename-similar -> renamed-similar (similar)",
+            "? untracked",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn copies_from_all_sources() -> crate::Result {
+    assert_eq!(
+        fixture(
+            "status_rewrites",
+            Some(Rewrites {
+                copies: Some(Copies {
+                    source: CopySource::FromSetOfModifiedFilesAndAllSources,
+                    percentage: Some(0.5),
+                }),
+                ..Default::default()
+            })
+        )?,
+        [
+            "C to-copy -> copied",
+            "D dir/to-delete",
+            "R to-rename-intent -> intent-renamed (intent-to-add)",
+            "R to-rename -> renamed",
+            "R to-rename-similar -> renamed-similar (similar)",
+            "? untracked",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn without_rewrites() -> crate::Result {
+    assert_eq!(
+        fixture("status_rewrites", None)?,
+        [
+            "? copied",
+            "D dir/to-delete",
+            "A intent-renamed",
+            "? renamed",
+            "? renamed-similar",
+            "D to-rename",
+            "D to-rename-intent",
+            "D to-rename-similar",
+            "? untracked",
+        ]
+    );
+    Ok(())
+}
+
+/// Return the status of the fixture at `name` in the form of `git status --short`, sorted by path.
+fn fixture(name: &str, rewrites: Option<Rewrites>) -> crate::Result<Vec<String>> {
+    let worktree = fixture_path(name);
+    let git_dir = worktree.join(".git");
+    let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default())?;
+    let objects = gix_odb::at(git_dir.join("objects"))?.into_arc()?;
+    let mut resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            gix_diff::blob::pipeline::WorktreeRoots {
+                old_root: None,
+                new_root: Some(worktree.clone()),
+            },
+            gix_filter::Pipeline::default(),
+            Vec::new(),
+            Default::default(),
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        gix_worktree::Stack::new(
+            &worktree,
+            gix_worktree::stack::State::AttributesStack(Default::default()),
+            gix_worktree::glob::pattern::Case::Sensitive,
+            Vec::new(),
+            Vec::new(),
+        ),
+    );
+    let git_dir_realpath = gix_path::realpath(&git_dir)?;
+    let current_dir = std::env::current_dir()?;
+    let mut recorder = Recorder::default();
+    gix_status::index_as_worktree_with_renames(
+        &index,
+        &worktree,
+        &mut recorder,
+        FastEq,
+        SubmoduleStatusMock,
+        objects,
+        &mut gix_features::progress::Discard,
+        Context {
+            pathspec: gix_pathspec::Search::from_specs(None, None, std::path::Path::new(""))?,
+            pathspec_attributes: Arc::new(|_, _, _, _| unreachable!("we don't use attributes in our pathspecs")),
+            filter: Default::default(),
+            should_interrupt: &AtomicBool::default(),
+            resource_cache: &mut resource_cache,
+            dirwalk: DirwalkContext {
+                git_dir_realpath: &git_dir_realpath,
+                current_dir: &current_dir,
+                ignore_case_index_lookup: None,
+                excludes: None,
+            },
+        },
+        Options {
+            tracked_file_modifications: gix_status::index_as_worktree::Options {
+                fs: gix_fs::Capabilities::probe(&git_dir),
+                stat: TEST_OPTIONS,
+                ..Default::default()
+            },
+            dirwalk: Some(Default::default()),
+            sorting: Some(Sorting::ByPathCaseSensitive),
+            rewrites,
+        },
+    )?;
+    Ok(recorder.records.iter().filter_map(summarize).collect())
+}
+
+fn summarize(entry: &Entry<'_, (), ()>) -> Option<String> {
+    Some(match entry {
+        Entry::Modification { rela_path, status, .. } => match status {
+            EntryStatus::Change(Change::Removed) => format!("D {rela_path}"),
+            EntryStatus::IntentToAdd => format!("A {rela_path}"),
+            EntryStatus::NeedsUpdate(_) => return None,
+            status => unreachable!("unexpected status of {rela_path}: {status:?}"),
+        },
+        Entry::DirectoryContents { entry, .. } => format!("? {}", entry.rela_path),
+        Entry::Rewrite {
+            source,
+            destination,
+            diff,
+            copy,
+            ..
+        } => format!(
+            "{} {} -> {}{}",
+            if *copy { "C" } else { "R" },
+            source.rela_path,
+            destination.rela_path(),
+            match (destination, diff) {
+                (RewriteDestination::IntentToAdd { .. }, _) => " (intent-to-add)",
+                (_, Some(_)) => " (similar)",
+                (_, None) => "",
+            }
+        ),
+    })
+}
+
+#[derive(Clone)]
+struct SubmoduleStatusMock;
+
+impl gix_status::index_as_worktree::traits::SubmoduleStatus for SubmoduleStatusMock {
+    type Output = ();
+    type Error = std::convert::Infallible;
+
+    fn status(
+        &mut self,
+        _entry: &gix_index::Entry,
+        _rela_path: &bstr::BStr,
+    ) -> Result<Option<Self::Output>, Self::Error> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::{atomic::AtomicBool, Arc, Mutex};
+
+/// An implementation for HTTP requests via the async client of `reqwest`.
+///
+/// Requests are only sent once their response is read, which is when the body to post is known as it is collected in memory.
+pub struct Remote {
+    /// The client to perform requests with, created on first use with the configuration at that time.
+    client: Option<reqwest::Client>,
+    /// If `true`, the redirect policy of `client` allows the request that is currently made to follow redirects.
+    allow_redirects: Arc<AtomicBool>,
+    /// How to handle redirects, which changes after the first request if only the initial one may be redirected.
+    follow: Option<crate::client::http::options::FollowRedirects>,
+    /// The base url we were redirected to, to be used instead of the original base url in all subsequent requests.
+    redirected_base_url: Arc<Mutex<Option<String>>>,
+    /// A mechanism for configuring the remote.
+    config: crate::client::http::Options,
+}
+
+/// A function to configure a single request prior to sending it, support most complex configuration beyond what's possible with
+/// basic `git` http configuration.
+pub type ConfigureRequestFn = dyn FnMut(&mut reqwest::Request) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
+    + Send
+    + Sync
+    + 'static;
+
+/// Options to configure the reqwest HTTP handler.
+#[derive(Default)]
+pub struct Options {
+    /// A function to configure the request that is about to be made.
+    pub configure_request: Option<Box<ConfigureRequestFn>>,
+}
+
+///
+pub mod remote;
@@ -0,0 +1,560 @@
+use std::{
+    any::Any,
+    convert::TryFrom,
+    future::Future,
+    io,
+    pin::Pin,
+    str::FromStr,
+    sync::{atomic, Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use bytes::Buf;
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use futures_lite::ready;
+
+use crate::client::http::{self, options::FollowRedirects, redirect, reqwest::Remote, PostBodyDataKind};
+
+/// The error returned by the 'remote' helper, a purely internal construct to perform http requests.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Request configuration failed")]
+    ConfigureRequest(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    Redirect(#[from] redirect::Error),
+}
+
+impl crate::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => {
+                err.is_timeout() || err.is_connect() || err.status().map_or(false, |status| status.is_server_error())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for Remote {
+    fn default() -> Self {
+        Remote {
+            client: None,
+            allow_redirects: Arc::new(atomic::AtomicBool::new(false)),
+            follow: None,
+            redirected_base_url: Default::default(),
+            config: http::Options::default(),
+        }
+    }
+}
+
+/// utilities
+impl Remote {
+    fn client(&mut self) -> Result<reqwest::Client, http::Error> {
+        if let Some(client) = &self.client {
+            return Ok(client.clone());
+        }
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(
+                self.config
+                    .connect_timeout
+                    .unwrap_or(std::time::Duration::from_secs(20)),
+            )
+            .http1_title_case_headers()
+            .redirect(reqwest::redirect::Policy::custom({
+                let allow_redirects = self.allow_redirects.clone();
+                move |attempt| {
+                    if allow_redirects.load(atomic::Ordering::Relaxed) {
+                        match attempt.previous().first() {
+                            // Redirects to other hosts are followed when sending the request, without passing on
+                            // extra headers.
+                            Some(prev_url) if prev_url.origin() != attempt.url().origin() => attempt.stop(),
+                            _ if attempt.previous().len() >= CURL_DEFAULT_REDIRS => attempt.error("too many redirects"),
+                            _ => attempt.follow(),
+                        }
+                    } else {
+                        attempt.stop()
+                    }
+                }
+            }))
+            .build()
+            .map_err(|err| http::Error::InitHttpClient {
+                source: Box::new(Error::from(err)),
+            })?;
+        self.client = Some(client.clone());
+        Ok(client)
+    }
+
+    fn make_request(
+        &mut self,
+        url: &str,
+        base_url: &str,
+        headers: impl IntoIterator<Item = impl AsRef<str>>,
+        upload_body_kind: Option<PostBodyDataKind>,
+    ) -> Result<http::PostResponse<Headers, Body, PostBody>, http::Error> {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        append_headers(&mut header_map, headers);
+        let headers_for_other_hosts = without_credentials(&header_map);
+        append_headers(&mut header_map, &self.config.extra_headers);
+
+        let follow = self.follow.get_or_insert(self.config.follow_redirects);
+        self.allow_redirects.store(
+            matches!(follow, FollowRedirects::Initial | FollowRedirects::All),
+            atomic::Ordering::Relaxed,
+        );
+        if *follow == FollowRedirects::Initial {
+            *follow = FollowRedirects::None;
+        }
+
+        let client = self.client()?;
+        let effective_url = redirect::swap_tails(
+            self.redirected_base_url
+                .lock()
+                .expect("no panic while holding the lock")
+                .as_deref(),
+            base_url,
+            url.to_owned(),
+        );
+        let builder = if upload_body_kind.is_some() {
+            client.post(&effective_url)
+        } else {
+            client.get(&effective_url)
+        }
+        .headers(header_map);
+
+        let follow_to_other_hosts = upload_body_kind.is_none() && self.allow_redirects.load(atomic::Ordering::Relaxed);
+        let exchange = Arc::new(Mutex::new(State::Prepared {
+            request: Request {
+                client,
+                builder,
+                headers_for_other_hosts: follow_to_other_hosts.then_some(headers_for_other_hosts),
+                url: url.to_owned(),
+                base_url: base_url.to_owned(),
+                effective_url,
+                redirected_base_url: self.redirected_base_url.clone(),
+                backend: self.config.backend.clone(),
+            },
+            post_body: upload_body_kind.map(|_| Vec::with_capacity(512)),
+        }));
+        Ok(http::PostResponse {
+            post_body: PostBody(exchange.clone()),
+            headers: Headers {
+                exchange: exchange.clone(),
+                buf: None,
+            },
+            body: Body {
+                exchange,
+                state: BodyState::Waiting,
+            },
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl http::Http for Remote {
+    type Headers = Headers;
+    type ResponseBody = Body;
+    type PostBody = PostBody;
+
+    async fn get(
+        &mut self,
+        url: &str,
+        base_url: &str,
+        headers: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<http::GetResponse<Self::Headers, Self::ResponseBody>, http::Error> {
+        self.make_request(url, base_url, headers, None).map(Into::into)
+    }
+
+    fn post(
+        &mut self,
+        url: &str,
+        base_url: &str,
+        headers: impl IntoIterator<Item = impl AsRef<str>>,
+        post_body_kind: PostBodyDataKind,
+    ) -> Result<http::PostResponse<Self::Headers, Self::ResponseBody, Self::PostBody>, http::Error> {
+        self.make_request(url, base_url, headers, Some(post_body_kind))
+    }
+
+    fn redirected_base_url(&self) -> Option<String> {
+        self.redirected_base_url
+            .lock()
+            .expect("no panic while holding the lock")
+            .clone()
+    }
+
+    fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        if let Some(config) = config.downcast_ref::<http::Options>() {
+            self.config = config.clone();
+        }
+        Ok(())
+    }
+}
+
+/// Emulate default git behaviour which relies on curl default behaviour apparently.
+const CURL_DEFAULT_REDIRS: usize = 50;
+
+/// Return the url to follow if a response with `status` and `headers` for `url` redirects to another host, port or
+/// scheme, which isn't done by `reqwest` as it would pass on headers that are meant for the original host only.
+fn redirect_to_other_host(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    url: &reqwest::Url,
+) -> Option<reqwest::Url> {
+    if !status.is_redirection() {
+        return None;
+    }
+    let location = headers.get(reqwest::header::LOCATION)?.to_str().ok()?;
+    url.join(location)
+        .ok()
+        .filter(|location| location.origin() != url.origin())
+}
+
+/// Return `headers` without those that carry credentials, to be sent to hosts we were redirected to.
+fn without_credentials(headers: &reqwest::header::HeaderMap) -> reqwest::header::HeaderMap {
+    let mut headers = headers.clone();
+    headers.remove(reqwest::header::AUTHORIZATION);
+    headers.remove(reqwest::header::COOKIE);
+    headers
+}
+
+/// Parse `lines` like `name: value` and append them to `headers`, ignoring those that are invalid.
+fn append_headers(headers: &mut reqwest::header::HeaderMap, lines: impl IntoIterator<Item = impl AsRef<str>>) {
+    for line in lines {
+        let Some((name, value)) = line.as_ref().split_once(':') else {
+            continue;
+        };
+        if let Some((name, value)) = reqwest::header::HeaderName::from_str(name)
+            .ok()
+            .zip(reqwest::header::HeaderValue::try_from(value.trim()).ok())
+        {
+            headers.append(name, value);
+        }
+    }
+}
+
+type ResponseFuture = Pin<Box<dyn Future<Output = io::Result<reqwest::Response>> + Send>>;
+type ChunkFuture = Pin<Box<dyn Future<Output = (reqwest::Response, reqwest::Result<Option<bytes::Bytes>>)> + Send>>;
+
+/// Everything needed to send a request once the body to post is known.
+struct Request {
+    client: reqwest::Client,
+    builder: reqwest::RequestBuilder,
+    /// The headers to send when following redirects to other hosts, or `None` if these must not be followed.
+    headers_for_other_hosts: Option<reqwest::header::HeaderMap>,
+    url: String,
+    base_url: String,
+    effective_url: String,
+    redirected_base_url: Arc<Mutex<Option<String>>>,
+    backend: Option<Arc<Mutex<dyn Any + Send + Sync + 'static>>>,
+}
+
+impl Request {
+    fn send(self, post_body: Option<Vec<u8>>) -> Result<ResponseFuture, Error> {
+        let Request {
+            client,
+            mut builder,
+            headers_for_other_hosts,
+            url,
+            base_url,
+            effective_url,
+            redirected_base_url,
+            backend,
+        } = self;
+        if let Some(body) = post_body {
+            builder = builder.body(body);
+        }
+        let mut req = builder.build()?;
+        if let Some(ref mut request_options) = backend.as_ref().and_then(|backend| backend.lock().ok()) {
+            if let Some(options) = request_options.downcast_mut::<super::Options>() {
+                if let Some(configure_request) = &mut options.configure_request {
+                    configure_request(&mut req)?;
+                }
+            }
+        }
+
+        Ok(Box::pin(async move {
+            let mut res = client.execute(req).await.and_then(reqwest::Response::error_for_status);
+            if let Some(headers) = headers_for_other_hosts {
+                // Redirects to other hosts are left to us so neither credentials nor extra headers are sent there.
+                let mut num_redirects = 0;
+                while let Some(location) = res
+                    .as_ref()
+                    .ok()
+                    .and_then(|res| redirect_to_other_host(res.status(), res.headers(), res.url()))
+                {
+                    num_redirects += 1;
+                    if num_redirects > CURL_DEFAULT_REDIRS {
+                        break;
+                    }
+                    res = client
+                        .get(location)
+                        .headers(headers.clone())
+                        .send()
+                        .await
+                        .and_then(reqwest::Response::error_for_status);
+                }
+            }
+            // Remember redirects even if the request failed, as authentication might be needed for the new host.
+            let actual_url = match &res {
+                Ok(res) => Some(res.url().as_str().to_owned()),
+                Err(err) => err.url().map(|url| url.as_str().to_owned()),
+            };
+            if let Some(actual_url) = actual_url.filter(|actual_url| *actual_url != effective_url) {
+                let base_url = redirect::base_url(&actual_url, &base_url, url)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                *redirected_base_url.lock().expect("no panic while holding the lock") = Some(base_url);
+            }
+            let res = res.map_err(|err| {
+                let (kind, err) = match err.status() {
+                    Some(status) => {
+                        let kind = if status == reqwest::StatusCode::UNAUTHORIZED {
+                            io::ErrorKind::PermissionDenied
+                        } else if status.is_server_error() {
+                            io::ErrorKind::ConnectionAborted
+                        } else {
+                            io::ErrorKind::Other
+                        };
+                        (kind, format!("Received HTTP status {}", status.as_str()))
+                    }
+                    None => (io::ErrorKind::Other, err.to_string()),
+                };
+                io::Error::new(kind, err)
+            })?;
+            Ok(res)
+        }))
+    }
+}
+
+/// The state of a single request, shared by the handles to its post body and its response.
+enum State {
+    /// The request wasn't sent yet, and the body to post is collected if there is one.
+    Prepared {
+        request: Request,
+        post_body: Option<Vec<u8>>,
+    },
+    /// The request was sent and we are waiting for its response.
+    Sent(ResponseFuture),
+    /// The response was received, and its headers and body are waiting to be picked up by their respective readers.
+    Received {
+        headers: Option<Vec<u8>>,
+        response: Option<reqwest::Response>,
+    },
+    /// The request failed, which is reported to all readers.
+    Failed { kind: io::ErrorKind, message: String },
+}
+
+impl State {
+    fn failed(err: &dyn std::error::Error, kind: io::ErrorKind) -> Self {
+        State::Failed {
+            kind,
+            message: err.to_string(),
+        }
+    }
+
+    /// Send the request if this wasn't done yet, and return when its response was received.
+    fn poll_received(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match self {
+                State::Prepared { .. } => {
+                    let placeholder = State::Failed {
+                        kind: io::ErrorKind::Other,
+                        message: String::new(),
+                    };
+                    let State::Prepared { request, post_body } = std::mem::replace(self, placeholder) else {
+                        unreachable!("we just matched this variant")
+                    };
+                    *self = match request.send(post_body) {
+                        Ok(fut) => State::Sent(fut),
+                        Err(err) => State::failed(&err, io::ErrorKind::Other),
+                    };
+                }
+                State::Sent(fut) => {
+                    *self = match ready!(fut.as_mut().poll(cx)) {
+                        Ok(response) => {
+                            let mut headers = Vec::new();
+                            for (name, value) in response.headers() {
+                                headers.extend_from_slice(name.as_str().as_bytes());
+                                headers.push(b':');
+                                headers.extend_from_slice(value.as_bytes());
+                                headers.push(b'\n');
+                            }
+                            State::Received {
+                                headers: Some(headers),
+                                response: Some(response),
+                            }
+                        }
+                        Err(err) => State::failed(&err, err.kind()),
+                    };
+                }
+                State::Received { .. } => return Poll::Ready(Ok(())),
+                State::Failed { kind, message } => return Poll::Ready(Err(io::Error::new(*kind, message.clone()))),
+            }
+        }
+    }
+}
+
+/// The handle to write the body to post with.
+///
+/// Everything written is kept in memory until the response is read, which is when the request is sent.
+pub struct PostBody(Arc<Mutex<State>>);
+
+impl AsyncWrite for PostBody {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match &mut *self.0.lock().expect("no panic while holding the lock") {
+            State::Prepared {
+                post_body: Some(post_body),
+                ..
+            } => {
+                post_body.extend_from_slice(buf);
+                Poll::Ready(Ok(buf.len()))
+            }
+            _ => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Cannot write the body to post once its response is read",
+            ))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The headers of a response, one `name:value` pair per line.
+pub struct Headers {
+    exchange: Arc<Mutex<State>>,
+    buf: Option<futures_lite::io::Cursor<Vec<u8>>>,
+}
+
+impl Headers {
+    fn poll_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&mut futures_lite::io::Cursor<Vec<u8>>>> {
+        if self.buf.is_none() {
+            let mut state = self.exchange.lock().expect("no panic while holding the lock");
+            ready!(state.poll_received(cx))?;
+            let State::Received { headers, .. } = &mut *state else {
+                unreachable!("the response was received")
+            };
+            self.buf = Some(futures_lite::io::Cursor::new(headers.take().unwrap_or_default()));
+        }
+        Poll::Ready(Ok(self.buf.as_mut().expect("set above")))
+    }
+}
+
+impl AsyncRead for Headers {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let headers = ready!(self.get_mut().poll_buf(cx))?;
+        Pin::new(headers).poll_read(cx, buf)
+    }
+}
+
+impl AsyncBufRead for Headers {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let headers = ready!(self.get_mut().poll_buf(cx))?;
+        Pin::new(headers).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        if let Some(headers) = self.get_mut().buf.as_mut() {
+            Pin::new(headers).consume(amt)
+        }
+    }
+}
+
+/// The body of a response, which is streamed as it is received.
+pub struct Body {
+    exchange: Arc<Mutex<State>>,
+    state: BodyState,
+}
+
+enum BodyState {
+    /// We are waiting for the response.
+    Waiting,
+    /// The response was received, with `chunk` being the data not yet consumed, and `next` producing the next chunk
+    /// unless the body was read entirely.
+    Streaming {
+        chunk: bytes::Bytes,
+        next: Option<ChunkFuture>,
+    },
+}
+
+fn next_chunk(mut response: reqwest::Response) -> ChunkFuture {
+    Box::pin(async move {
+        let chunk = response.chunk().await;
+        (response, chunk)
+    })
+}
+
+impl Body {
+    /// Return when there is data in our chunk to be read, or when the body was read entirely.
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                BodyState::Waiting => {
+                    let mut state = self.exchange.lock().expect("no panic while holding the lock");
+                    ready!(state.poll_received(cx))?;
+                    let State::Received { response, .. } = &mut *state else {
+                        unreachable!("the response was received")
+                    };
+                    self.state = BodyState::Streaming {
+                        chunk: bytes::Bytes::new(),
+                        next: response.take().map(next_chunk),
+                    };
+                }
+                BodyState::Streaming { chunk, next } => {
+                    if !chunk.is_empty() {
+                        return Poll::Ready(Ok(()));
+                    }
+                    let Some(fut) = next else {
+                        return Poll::Ready(Ok(()));
+                    };
+                    let (response, res) = ready!(fut.as_mut().poll(cx));
+                    match res {
+                        Ok(Some(data)) => {
+                            *chunk = data;
+                            *next = Some(next_chunk(response));
+                        }
+                        Ok(None) => *next = None,
+                        Err(err) => {
+                            *next = None;
+                            return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, err)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AsyncRead for Body {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let data = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl AsyncBufRead for Body {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        ready!(this.poll_chunk(cx))?;
+        Poll::Ready(Ok(match &this.state {
+            BodyState::Streaming { chunk, .. } => chunk,
+            BodyState::Waiting => unreachable!("we are streaming once the response was received"),
+        }))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        if let BodyState::Streaming { chunk, .. } = &mut self.get_mut().state {
+            chunk.advance(amt);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::VecDeque,
+    io::BufRead,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_packetline::PacketLineRef;
+
+use crate::{
+    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    Protocol, Service,
+};
+
+/// The error used in [`connect()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open bundle at '{}'", path.display())]
+    Open {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("Could not read the header of the bundle at '{}'", path.display())]
+    Header {
+        source: gix_bundle::header::decode::Error,
+        path: std::path::PathBuf,
+    },
+}
+
+impl crate::IsSpuriousError for Error {}
+
+/// A transport that serves the references and the pack of a bundle file as if it was `git upload-pack` speaking protocol V1.
+///
+/// It can only be instantiated using [`connect()`].
+///
+/// Note that the [prerequisites](gix_bundle::Header::prerequisites) of the bundle can't be checked here as the transport
+/// doesn't have access to the objects of the receiving repository. If these are missing, the received pack will fail to resolve.
+pub struct Connection {
+    url: BString,
+    header: gix_bundle::Header,
+    connection: git::Connection<Reader, Writer>,
+}
+
+impl Connection {
+    /// Return the header of the bundle we are reading from.
+    pub fn header(&self) -> &gix_bundle::Header {
+        &self.header
+    }
+}
+
+impl client::TransportWithoutIO for Connection {
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection.request(write_mode, on_into_read, trace)
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Borrowed(self.url.as_ref())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl client::Transport for Connection {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        if service != Service::UploadPack {
+            return Err(client::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "bundles can only be fetched from, they don't support {}",
+                    service.as_str()
+                ),
+            )));
+        }
+        self.connection.handshake(service, extra_parameters)
+    }
+}
+
+/// Open the bundle at `path` to serve its references and pack as if it was a remote repository.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+///
+/// Only protocol V1 is supported, and the desired protocol version is ignored.
+pub fn connect(path: impl AsRef<Path>, trace: bool) -> Result<Connection, Error> {
+    let path = path.as_ref();
+    let mut pack = std::io::BufReader::new(std::fs::File::open(path).map_err(|source| Error::Open {
+        source,
+        path: path.to_owned(),
+    })?);
+    let header = gix_bundle::Header::from_read(&mut pack).map_err(|source| Error::Header {
+        source,
+        path: path.to_owned(),
+    })?;
+
+    let state = Arc::new(Mutex::new(State {
+        input: Vec::new(),
+        output: advertisement(&header).into(),
+        stage: Stage::Wants,
+        side_band_len: None,
+        pack: Some(Box::new(pack)),
+    }));
+    let url = BString::from(path.to_string_lossy().as_ref());
+    let connection = git::Connection::new(
+        Reader(state.clone()),
+        Writer(state),
+        Protocol::V1,
+        url.clone(),
+        None::<(String, Option<u16>)>,
+        git::ConnectMode::Process,
+        trace,
+    );
+    Ok(Connection {
+        url,
+        header,
+        connection,
+    })
+}
+
+/// Produce the V1 reference advertisement for the refs in `header`, with `HEAD` first if present.
+fn advertisement(header: &gix_bundle::Header) -> Vec<u8> {
+    // We always send the whole pack of the bundle, so annotated tags are included if they were bundled, which is as close to
+    // `include-tag` as it gets.
+    let mut capabilities = BString::from("multi_ack side-band side-band-64k ofs-delta include-tag multi_ack_detailed");
+    capabilities.push_str(format!(
+        " object-format={}",
+        header.object_hash.to_string().to_ascii_lowercase()
+    ));
+    let head = header.refs.iter().find(|r| r.name == "HEAD");
+    if let Some(head) = head {
+        let branches = || {
+            header
+                .refs
+                .iter()
+                .filter(|r| r.id == head.id && r.name.starts_with(b"refs/heads/"))
+        };
+        if let Some(branch) = branches()
+            .find(|r| r.name == "refs/heads/master")
+            .or_else(|| branches().next())
+        {
+            capabilities.push_str(" symref=HEAD:");
+            capabilities.push_str(&branch.name);
+        }
+    }
+
+    let mut lines = Vec::new();
+    let refs = head
+        .into_iter()
+        .chain(header.refs.iter().filter(|r| r.name != "HEAD"))
+        .map(|r| (r.id.to_string(), r.name.clone()));
+    for (id, name) in refs {
+        let mut line = BString::from(id);
+        line.push(b' ');
+        line.push_str(&name);
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(format!("{} capabilities^{{}}", header.object_hash.null()).into());
+    }
+    lines[0].push(0);
+    lines[0].push_str(&capabilities);
+
+    let mut out = Vec::new();
+    for line in lines {
+        gix_packetline::encode::text_to_write(&line, &mut out).expect("write to memory");
+    }
+    gix_packetline::encode::flush_to_write(&mut out).expect("write to memory");
+    out
+}
+
+/// The part of the V1 negotiation we are in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Stage {
+    /// Receive `want` lines until a flush.
+    Wants,
+    /// Receive `have` lines and answer each flush with `NAK`, until `done` is received.
+    Haves,
+    /// Send the pack.
+    Pack,
+    /// There is nothing more to send.
+    Done,
+}
+
+struct State {
+    /// Bytes written by the client that weren't processed yet.
+    input: Vec<u8>,
+    /// Bytes to be read by the client.
+    output: VecDeque<u8>,
+    stage: Stage,
+    /// The maximum amount of pack bytes per side-band packet line, or `None` if the client doesn't use a side-band.
+    side_band_len: Option<usize>,
+    pack: Option<Box<dyn BufRead + Send>>,
+}
+
+impl State {
+    /// Process all complete lines in our input to produce more output.
+    fn process_input(&mut self) -> std::io::Result<()> {
+        let mut consumed = 0;
+        while matches!(self.stage, Stage::Wants | Stage::Haves) {
+            let (line, bytes_consumed) = match gix_packetline::decode::streaming(&self.input[consumed..])
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?
+            {
+                gix_packetline::decode::Stream::Complete { line, bytes_consumed } => (line, bytes_consumed),
+                gix_packetline::decode::Stream::Incomplete { .. } => break,
+            };
+            consumed += bytes_consumed;
+            match (self.stage, line) {
+                (Stage::Wants, PacketLineRef::Data(line)) => {
+                    if let Some(want) = line.strip_prefix(b"want ") {
+                        if self.side_band_len.is_none() {
+                            let mut capabilities = want.trim_end().split_str(" ").skip(1);
+                            self.side_band_len = if capabilities.clone().any(|c| c == b"side-band-64k") {
+                                Some(65515)
+                            } else if capabilities.any(|c| c == b"side-band") {
+                                Some(995)
+                            } else {
+                                None
+                            };
+                        }
+                    }
+                }
+                (Stage::Wants, _) => self.stage = Stage::Haves,
+                (Stage::Haves, PacketLineRef::Data(line)) if line.trim_end() == b"done" => {
+                    gix_packetline::encode::text_to_write(b"NAK", &mut self.output)?;
+                    self.stage = Stage::Pack;
+                }
+                (Stage::Haves, PacketLineRef::Flush) => {
+                    gix_packetline::encode::text_to_write(b"NAK", &mut self.output)?;
+                }
+                (Stage::Haves, _) => {}
+                (Stage::Pack | Stage::Done, _) => unreachable!("loop only runs while receiving"),
+            }
+        }
+        self.input.drain(..consumed);
+        Ok(())
+    }
+
+    /// Put the next chunk of the pack into our output, or finish the stream if there is no more pack data.
+    fn send_pack(&mut self) -> std::io::Result<()> {
+        let pack = self.pack.as_mut().expect("pack is present until we are done");
+        let chunk = pack.fill_buf()?;
+        if chunk.is_empty() {
+            if self.side_band_len.is_some() {
+                gix_packetline::encode::flush_to_write(&mut self.output)?;
+            }
+            self.pack = None;
+            self.stage = Stage::Done;
+            return Ok(());
+        }
+        let len = match self.side_band_len {
+            Some(max) => {
+                let len = chunk.len().min(max);
+                gix_packetline::encode::band_to_write(gix_packetline::Channel::Data, &chunk[..len], &mut self.output)?;
+                len
+            }
+            None => {
+                self.output.extend(chunk);
+                chunk.len()
+            }
+        };
+        pack.consume(len);
+        Ok(())
+    }
+}
+
+/// The read-end of our connection, producing what `git upload-pack` would send.
+struct Reader(Arc<Mutex<State>>);
+
+/// The write-end of our connection, receiving what the client sends.
+struct Writer(Arc<Mutex<State>>);
+
+impl std::io::Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut state = self.0.lock().expect("no panic while holding the lock");
+        if state.output.is_empty() {
+            match state.stage {
+                Stage::Wants | Stage::Haves => state.process_input()?,
+                Stage::Pack => state.send_pack()?,
+                Stage::Done => {}
+            }
+        }
+        // Without output here, the client expects an answer that we can't give, which is the end of the stream.
+        state.output.read(buf)
+    }
+}
+
+impl std::io::Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .expect("no panic while holding the lock")
+            .input
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
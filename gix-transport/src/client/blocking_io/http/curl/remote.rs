@@ -14,7 +14,7 @@ use crate::client::{
     http::{
         curl::curl_is_spurious,
         options::{FollowRedirects, HttpVersion, ProxyAuthMethod, SslVersion},
-        traits::PostBodyDataKind,
+        PostBodyDataKind,
     },
 };
 
@@ -6,6 +6,8 @@ pub struct Remote {
     request: std::sync::mpsc::SyncSender<remote::Request>,
     /// A channel to receive the result of the prior request.
     response: std::sync::mpsc::Receiver<remote::Response>,
+    /// The base url we were redirected to, as set by the worker thread and used instead of the original base url.
+    redirected_base_url: std::sync::Arc<std::sync::Mutex<Option<String>>>,
     /// A mechanism for configuring the remote.
     config: crate::client::http::Options,
 }
@@ -3,12 +3,18 @@ use std::{
     convert::TryFrom,
     io::{Read, Write},
     str::FromStr,
-    sync::{atomic, Arc},
+    sync::{atomic, Arc, Mutex},
 };
 
 use gix_features::io::pipe;
 
-use crate::client::http::{self, options::FollowRedirects, redirect, reqwest::Remote, traits::PostBodyDataKind};
+use crate::client::http::{
+    self,
+    options::{FollowRedirects, ProxyAuthMethod},
+    redirect,
+    reqwest::Remote,
+    PostBodyDataKind,
+};
 
 /// The error returned by the 'remote' helper, a purely internal construct to perform http requests.
 #[derive(Debug, thiserror::Error)]
@@ -22,6 +28,10 @@ pub enum Error {
     ConfigureRequest(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
     #[error(transparent)]
     Redirect(#[from] redirect::Error),
+    #[error(transparent)]
+    Authenticate(#[from] gix_credentials::protocol::Error),
+    #[error("The proxy authentication method {0:?} is not supported, only basic authentication is")]
+    UnsupportedProxyAuthMethod(ProxyAuthMethod),
 }
 
 impl crate::IsSpuriousError for Error {
@@ -39,45 +49,14 @@ impl Default for Remote {
     fn default() -> Self {
         let (req_send, req_recv) = std::sync::mpsc::sync_channel(0);
         let (res_send, res_recv) = std::sync::mpsc::sync_channel(0);
+        let redirected_base_url = Arc::new(Mutex::new(None::<String>));
+        let redirected_base_url_for_remote = redirected_base_url.clone();
         let handle = std::thread::spawn(move || -> Result<(), Error> {
             let mut follow = None;
-            let mut redirected_base_url = None::<String>;
+            let mut client = None;
+            let mut proxy_auth_action = None;
             let allow_redirects = Arc::new(atomic::AtomicBool::new(false));
 
-            // We may error while configuring, which is expected as part of the internal protocol. The error will be
-            // received and the sender of the request might restart us.
-            let client = reqwest::blocking::ClientBuilder::new()
-                .connect_timeout(std::time::Duration::from_secs(20))
-                .http1_title_case_headers()
-                .redirect(reqwest::redirect::Policy::custom({
-                    let allow_redirects = allow_redirects.clone();
-                    move |attempt| {
-                        if allow_redirects.load(atomic::Ordering::Relaxed) {
-                            let curr_url = attempt.url();
-                            let prev_urls = attempt.previous();
-
-                            match prev_urls.first() {
-                                Some(prev_url) if prev_url.host_str() != curr_url.host_str() => {
-                                    // git does not want to be redirected to a different host.
-                                    attempt.stop()
-                                }
-                                _ => {
-                                    // emulate default git behaviour which relies on curl default behaviour apparently.
-                                    const CURL_DEFAULT_REDIRS: usize = 50;
-                                    if prev_urls.len() >= CURL_DEFAULT_REDIRS {
-                                        attempt.error("too many redirects")
-                                    } else {
-                                        attempt.follow()
-                                    }
-                                }
-                            }
-                        } else {
-                            attempt.stop()
-                        }
-                    }
-                }))
-                .build()?;
-
             for Request {
                 url,
                 base_url,
@@ -86,13 +65,32 @@ impl Default for Remote {
                 config,
             } in req_recv
             {
-                let effective_url = redirect::swap_tails(redirected_base_url.as_deref(), &base_url, url.clone());
+                // We may error while configuring, which is expected as part of the internal protocol. The error will be
+                // received and the sender of the request might restart us.
+                let client = match &client {
+                    Some(client) => client,
+                    None => {
+                        let (new_client, action) = new_client(&config, allow_redirects.clone())?;
+                        proxy_auth_action = action;
+                        client.insert(new_client)
+                    }
+                };
+                let effective_url = redirect::swap_tails(
+                    redirected_base_url
+                        .lock()
+                        .expect("no panic while holding the lock")
+                        .as_deref(),
+                    &base_url,
+                    url.clone(),
+                );
+                let mut request_headers = headers.clone();
+                append_headers(&mut request_headers, &config.extra_headers);
                 let mut req_builder = if upload_body_kind.is_some() {
                     client.post(&effective_url)
                 } else {
                     client.get(&effective_url)
                 }
-                .headers(headers);
+                .headers(request_headers);
                 let (post_body_tx, mut post_body_rx) = pipe::unidirectional(0);
                 let (mut response_body_tx, response_body_rx) = pipe::unidirectional(0);
                 let (mut headers_tx, headers_rx) = pipe::unidirectional(0);
@@ -136,10 +134,51 @@ impl Default for Remote {
                     *follow = FollowRedirects::None;
                 }
 
-                let mut res = match client
+                let mut res = client
                     .execute(req)
-                    .and_then(reqwest::blocking::Response::error_for_status)
-                {
+                    .and_then(reqwest::blocking::Response::error_for_status);
+                if upload_body_kind.is_none() && allow_redirects.load(atomic::Ordering::Relaxed) {
+                    // Redirects to other hosts are left to us so neither credentials nor extra headers are sent there.
+                    let mut num_redirects = 0;
+                    while let Some(location) = res
+                        .as_ref()
+                        .ok()
+                        .and_then(|res| redirect_to_other_host(res.status(), res.headers(), res.url()))
+                    {
+                        num_redirects += 1;
+                        if num_redirects > CURL_DEFAULT_REDIRS {
+                            break;
+                        }
+                        res = client
+                            .get(location)
+                            .headers(without_credentials(&headers))
+                            .send()
+                            .and_then(reqwest::blocking::Response::error_for_status);
+                    }
+                }
+                if let Some((action, authenticate)) = proxy_auth_action.take() {
+                    match &res {
+                        Ok(_) => {
+                            authenticate.lock().expect("no panics in other threads")(action.store())?;
+                        }
+                        Err(err) if err.status() == Some(reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED) => {
+                            authenticate.lock().expect("no panics in other threads")(action.erase())?;
+                        }
+                        Err(_) => proxy_auth_action = Some((action, authenticate)),
+                    }
+                }
+
+                // Remember redirects even if the request failed, as authentication might be needed for the new host.
+                let actual_url = match &res {
+                    Ok(res) => Some(res.url().as_str()),
+                    Err(err) => err.url().map(reqwest::Url::as_str),
+                };
+                if let Some(actual_url) = actual_url.filter(|actual_url| *actual_url != effective_url.as_str()) {
+                    *redirected_base_url.lock().expect("no panic while holding the lock") =
+                        redirect::base_url(actual_url, &base_url, url)?.into();
+                }
+
+                let mut res = match res {
                     Ok(res) => res,
                     Err(err) => {
                         let (kind, err) = match err.status() {
@@ -161,11 +200,6 @@ impl Default for Remote {
                     }
                 };
 
-                let actual_url = res.url().as_str();
-                if actual_url != effective_url.as_str() {
-                    redirected_base_url = redirect::base_url(actual_url, &base_url, url)?.into();
-                }
-
                 let send_headers = {
                     let headers = res.headers();
                     move || -> std::io::Result<()> {
@@ -198,11 +232,106 @@ impl Default for Remote {
             handle: Some(handle),
             request: req_send,
             response: res_recv,
+            redirected_base_url: redirected_base_url_for_remote,
             config: http::Options::default(),
         }
     }
 }
 
+/// Emulate default git behaviour which relies on curl default behaviour apparently.
+const CURL_DEFAULT_REDIRS: usize = 50;
+
+/// Return the url to follow if a response with `status` and `headers` for `url` redirects to another host, port or
+/// scheme, which isn't done by `reqwest` as it would pass on headers that are meant for the original host only.
+fn redirect_to_other_host(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    url: &reqwest::Url,
+) -> Option<reqwest::Url> {
+    if !status.is_redirection() {
+        return None;
+    }
+    let location = headers.get(reqwest::header::LOCATION)?.to_str().ok()?;
+    url.join(location)
+        .ok()
+        .filter(|location| location.origin() != url.origin())
+}
+
+/// Return `headers` without those that carry credentials, to be sent to hosts we were redirected to.
+fn without_credentials(headers: &reqwest::header::HeaderMap) -> reqwest::header::HeaderMap {
+    let mut headers = headers.clone();
+    headers.remove(reqwest::header::AUTHORIZATION);
+    headers.remove(reqwest::header::COOKIE);
+    headers
+}
+
+/// Parse `lines` like `name: value` and append them to `headers`, ignoring those that are invalid.
+fn append_headers(headers: &mut reqwest::header::HeaderMap, lines: impl IntoIterator<Item = impl AsRef<str>>) {
+    for line in lines {
+        let Some((name, value)) = line.as_ref().split_once(':') else {
+            continue;
+        };
+        if let Some((name, value)) = reqwest::header::HeaderName::from_str(name)
+            .ok()
+            .zip(reqwest::header::HeaderValue::try_from(value.trim()).ok())
+        {
+            headers.append(name, value);
+        }
+    }
+}
+
+/// The credentials to use for the proxy, along with the function to store or erase them once we know if they worked.
+type ProxyAuthAction = (
+    gix_credentials::helper::NextAction,
+    Arc<Mutex<http::options::AuthenticateFn>>,
+);
+
+/// Create a new client configured according to `config`, which follows redirects only while `allow_redirects` is `true`.
+///
+/// If the proxy needs authentication, also return the action to perform with the credentials obtained for it.
+fn new_client(
+    config: &http::Options,
+    allow_redirects: Arc<atomic::AtomicBool>,
+) -> Result<(reqwest::blocking::Client, Option<ProxyAuthAction>), Error> {
+    let mut builder = reqwest::blocking::ClientBuilder::new()
+        .connect_timeout(config.connect_timeout.unwrap_or(std::time::Duration::from_secs(20)))
+        .http1_title_case_headers()
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if allow_redirects.load(atomic::Ordering::Relaxed) {
+                match attempt.previous().first() {
+                    // Redirects to other hosts are followed by the caller, without passing on extra headers.
+                    Some(prev_url) if prev_url.origin() != attempt.url().origin() => attempt.stop(),
+                    _ if attempt.previous().len() >= CURL_DEFAULT_REDIRS => attempt.error("too many redirects"),
+                    _ => attempt.follow(),
+                }
+            } else {
+                attempt.stop()
+            }
+        }));
+
+    let mut proxy_auth_action = None;
+    match config.proxy.as_deref() {
+        Some("") => builder = builder.no_proxy(),
+        Some(proxy_url) => {
+            let mut proxy = reqwest::Proxy::all(proxy_url)?
+                .no_proxy(config.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string));
+            if let Some((obtain_creds_action, authenticate)) = &config.proxy_authenticate {
+                match config.proxy_auth_method {
+                    ProxyAuthMethod::AnyAuth | ProxyAuthMethod::Basic => {}
+                    method => return Err(Error::UnsupportedProxyAuthMethod(method)),
+                }
+                let creds = authenticate.lock().expect("no panics in other threads")(obtain_creds_action.clone())?
+                    .expect("action to fetch credentials");
+                proxy = proxy.basic_auth(&creds.identity.username, &creds.identity.password);
+                proxy_auth_action = Some((creds.next, authenticate.clone()));
+            }
+            builder = builder.proxy(proxy);
+        }
+        None => {}
+    }
+    Ok((builder.build()?, proxy_auth_action))
+}
+
 /// utilities
 impl Remote {
     fn restore_thread_after_failure(&mut self) -> http::Error {
@@ -300,6 +429,13 @@ impl http::Http for Remote {
         self.make_request(url, base_url, headers, Some(post_body_kind))
     }
 
+    fn redirected_base_url(&self) -> Option<String> {
+        self.redirected_base_url
+            .lock()
+            .expect("no panic while holding the lock")
+            .clone()
+    }
+
     fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         if let Some(config) = config.downcast_ref::<http::Options>() {
             self.config = config.clone();
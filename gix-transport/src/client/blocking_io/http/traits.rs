@@ -1,4 +1,4 @@
-use crate::client::WriteMode;
+use crate::client::http::PostBodyDataKind;
 
 /// The error used by the [Http] trait.
 #[derive(Debug, thiserror::Error)]
@@ -55,25 +55,6 @@ pub struct PostResponse<H, B, PB> {
     pub body: B,
 }
 
-/// Whether or not the post body is expected to fit into memory or not.
-#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
-pub enum PostBodyDataKind {
-    /// We know how much data we are sending and think it will fit into memory. This allows to collect it into a buffer
-    /// and send it with `Content-Length: <body-len>`.
-    BoundedAndFitsIntoMemory,
-    /// We don't know how much data we will send and assume it won't fit into memory. This enables streaming mode.
-    Unbounded,
-}
-
-impl From<WriteMode> for PostBodyDataKind {
-    fn from(m: WriteMode) -> Self {
-        match m {
-            WriteMode::Binary => PostBodyDataKind::Unbounded,
-            WriteMode::OneLfTerminatedLinePerWriteCall => PostBodyDataKind::BoundedAndFitsIntoMemory,
-        }
-    }
-}
-
 impl<A, B, C> From<PostResponse<A, B, C>> for GetResponse<A, B> {
     fn from(v: PostResponse<A, B, C>) -> Self {
         GetResponse {
@@ -123,6 +104,14 @@ pub trait Http {
         body: PostBodyDataKind,
     ) -> Result<PostResponse<Self::Headers, Self::ResponseBody, Self::PostBody>, Error>;
 
+    /// Return the base url that requests are sent to after the server redirected us, or `None` if there was no redirect.
+    ///
+    /// It's used to ask for credentials again if the redirect led to another host, as `git` does.
+    /// Implementations that don't keep track of redirects don't have to implement it.
+    fn redirected_base_url(&self) -> Option<String> {
+        None
+    }
+
     /// Pass `config` which can deserialize in the implementation's configuration, as documented separately.
     ///
     /// The caller must know how that `config` data looks like for the intended implementation.
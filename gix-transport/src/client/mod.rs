@@ -1,5 +1,7 @@
 #[cfg(feature = "async-client")]
 mod async_io;
+#[cfg(feature = "async-http-client")]
+pub use async_io::http;
 #[cfg(feature = "async-client")]
 pub use async_io::{
     connect, ExtendedBufRead, HandleProgress, ReadlineBufRead, RequestWriter, SetServiceResponse, Transport,
@@ -15,8 +17,8 @@ mod blocking_io;
 pub use blocking_io::http;
 #[cfg(feature = "blocking-client")]
 pub use blocking_io::{
-    connect, file, ssh, ExtendedBufRead, HandleProgress, ReadlineBufRead, RequestWriter, SetServiceResponse, Transport,
-    TransportV2Ext,
+    bundle, connect, file, ssh, ExtendedBufRead, HandleProgress, ReadlineBufRead, RequestWriter, SetServiceResponse,
+    Transport, TransportV2Ext,
 };
 #[cfg(feature = "blocking-client")]
 #[doc(inline)]
@@ -27,6 +29,9 @@ pub mod capabilities;
 #[doc(inline)]
 pub use capabilities::Capabilities;
 
+#[cfg(any(feature = "http-client", feature = "async-http-client"))]
+mod http_shared;
+
 mod non_io_types;
 pub use gix_sec::identity::Account;
 pub use non_io_types::{Error, MessageKind, WriteMode};
@@ -61,9 +61,13 @@ pub(crate) mod connect {
         },
         #[error("The '{0}' protocol is currently unsupported")]
         UnsupportedScheme(gix_url::Scheme),
-        #[cfg(not(any(feature = "http-client-curl", feature = "http-client-reqwest")))]
+        #[cfg(not(any(
+            feature = "http-client-curl",
+            feature = "http-client-reqwest",
+            feature = "async-http-client-reqwest"
+        )))]
         #[error(
-            "'{0}' is not compiled in. Compile with the 'http-client-curl' or 'http-client-reqwest' cargo feature"
+            "'{0}' is not compiled in. Compile with the 'http-client-curl' or 'http-client-reqwest' cargo feature, or with 'async-http-client-reqwest' for the async client"
         )]
         CompiledWithoutHttp(gix_url::Scheme),
     }
@@ -94,16 +98,16 @@ mod error {
     use bstr::BString;
 
     use crate::client::capabilities;
-    #[cfg(feature = "http-client")]
+    #[cfg(any(feature = "http-client", feature = "async-http-client"))]
     use crate::client::http;
     #[cfg(feature = "blocking-client")]
     use crate::client::ssh;
 
-    #[cfg(feature = "http-client")]
+    #[cfg(any(feature = "http-client", feature = "async-http-client"))]
     type HttpError = http::Error;
     #[cfg(feature = "blocking-client")]
     type SshInvocationError = ssh::invocation::Error;
-    #[cfg(not(feature = "http-client"))]
+    #[cfg(not(any(feature = "http-client", feature = "async-http-client")))]
     type HttpError = std::convert::Infallible;
     #[cfg(not(feature = "blocking-client"))]
     type SshInvocationError = std::convert::Infallible;
@@ -2,15 +2,22 @@ use gix_object::FindExt;
 use smallvec::SmallVec;
 
 /// An iterator over the ancestors one or more starting commits
-pub struct Ancestors<Find, Predicate, StateMut> {
+pub struct Ancestors<Find, Predicate, StateMut, Simplify = SimplifyFn> {
     objects: Find,
     cache: Option<gix_commitgraph::Graph>,
     predicate: Predicate,
     state: StateMut,
     parents: Parents,
     sorting: Sorting,
+    simplify: Option<Simplify>,
 }
 
+/// The error returned by functions to [simplify](Ancestors::simplify()) the history.
+pub type SimplifyError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The type of function to [simplify](Ancestors::simplify()) the history, which is used if none is set.
+pub type SimplifyFn = fn(&gix_hash::oid, &mut ParentIds) -> Result<bool, SimplifyError>;
+
 /// Specify how to handle commit parents during traversal.
 #[derive(Default, Copy, Clone)]
 pub enum Parents {
@@ -43,7 +50,7 @@ pub enum Sorting {
     /// ### Note
     ///
     /// This is not to be confused with `git log/rev-list --topo-order`, which is notably different from
-    /// as it avoids overlapping branches.
+    /// as it avoids overlapping branches, and which is available as [`Sorting::TopoOrder`].
     #[default]
     BreadthFirst,
     /// Commits are sorted by their commit time in descending order, that is newest first.
@@ -67,6 +74,32 @@ pub enum Sorting {
         /// The amount of seconds since unix epoch, the same value obtained by any `gix_date::Time` structure and the way git counts time.
         seconds: gix_date::SecondsSinceUnixEpoch,
     },
+    /// Commits are never returned before all of their children, and the commits of one line of history are returned
+    /// together instead of being intermixed with those of other lines of history, similar to `git log/rev-list --topo-order`.
+    ///
+    /// In the *sample history* the order would be `8, 7, 4, 2, 6, 5, 3, 1`
+    ///
+    /// # Performance
+    ///
+    /// With a [commit-graph](Ancestors::commit_graph()) the traversal is incremental as it uses generation numbers to
+    /// learn how many children a commit has, and without it the whole ancestry is traversed before the first commit is returned.
+    TopoOrder,
+    /// Commits are never returned before all of their children, and otherwise sorted by their commit time in descending order,
+    /// similar to `git log/rev-list --date-order`.
+    ///
+    /// In the *sample history* the order would be `8, 7, 6, 5, 4, 3, 2, 1`
+    ///
+    /// # Performance
+    ///
+    /// See [`Sorting::TopoOrder`].
+    DateOrder,
+    /// Commits are never returned before all of their children, and otherwise sorted by their author time in descending order,
+    /// similar to `git log/rev-list --author-date-order`.
+    ///
+    /// # Performance
+    ///
+    /// See [`Sorting::TopoOrder`], but note that author times always have to be read from the object database.
+    AuthorDateOrder,
 }
 
 /// The collection of parent ids we saw as part of the iteration.
@@ -95,11 +128,11 @@ pub mod ancestors {
 
     use gix_date::SecondsSinceUnixEpoch;
     use gix_hash::{oid, ObjectId};
-    use gix_hashtable::HashSet;
+    use gix_hashtable::{HashMap, HashSet};
     use gix_object::{CommitRefIter, FindExt};
     use smallvec::SmallVec;
 
-    use crate::commit::{collect_parents, Ancestors, Either, Info, ParentIds, Parents, Sorting};
+    use crate::commit::{collect_parents, Ancestors, Either, Info, ParentIds, Parents, SimplifyError, Sorting};
 
     /// The error is part of the item returned by the [Ancestors] iterator.
     #[derive(Debug, thiserror::Error)]
@@ -109,6 +142,8 @@ pub mod ancestors {
         Find(#[from] gix_object::find::existing_iter::Error),
         #[error(transparent)]
         ObjectDecode(#[from] gix_object::decode::Error),
+        #[error("Could not simplify the history")]
+        Simplify(#[source] SimplifyError),
     }
 
     /// The state used and potentially shared by multiple graph traversals.
@@ -120,6 +155,36 @@ pub mod ancestors {
         seen: HashSet<ObjectId>,
         parents_buf: Vec<u8>,
         parent_ids: SmallVec<[(ObjectId, SecondsSinceUnixEpoch); 2]>,
+        /// Tips whose ancestry, including themselves, is to be hidden, still to be processed before the first iteration.
+        hidden_tips: Vec<ObjectId>,
+        /// If hidden tips were processed, the set of commits that may be returned as they aren't reachable from hidden tips.
+        visible: Option<HashSet<ObjectId>>,
+        /// Commits whose parents are never traversed, like the commits of a shallow boundary.
+        shallow: HashSet<ObjectId>,
+        /// The state of a topological traversal, set once the first commit is requested.
+        topo: Option<Topo>,
+    }
+
+    /// The state needed to return commits in topological order, which is similar to what `git` does.
+    ///
+    /// It counts the children of all commits whose generation is at least as high as the one of the next commit to return,
+    /// and a commit is returned only once all of its children have been returned.
+    #[derive(Clone)]
+    struct Topo {
+        /// The amount of children of a commit that weren't returned yet, plus one.
+        /// Commits that aren't contained are neither returned nor traversed.
+        indegree: HashMap<ObjectId, usize>,
+        /// Commits whose parents still have to be counted, highest generation first.
+        indegree_queue: gix_revwalk::PriorityQueue<(u32, SecondsSinceUnixEpoch), ObjectId>,
+        /// Commits whose children were all returned, and which are returned by descending time, or in insertion order for ties.
+        by_time: gix_revwalk::PriorityQueue<(SecondsSinceUnixEpoch, std::cmp::Reverse<usize>), ObjectId>,
+        /// Commits whose children were all returned, and which are returned last-in first-out to keep lines of history together.
+        stack: Vec<ObjectId>,
+        /// The amount of commits put into `by_time` so far.
+        num_queued: usize,
+        /// The parents to traverse of commits whose children were counted, along with whether they are returned,
+        /// if the history is [simplified](Ancestors::simplify()).
+        simplified: HashMap<ObjectId, (ParentIds, bool)>,
     }
 
     impl Default for State {
@@ -131,6 +196,10 @@ pub mod ancestors {
                 seen: Default::default(),
                 parents_buf: vec![],
                 parent_ids: Default::default(),
+                hidden_tips: Vec::new(),
+                visible: None,
+                shallow: Default::default(),
+                topo: None,
             }
         }
     }
@@ -141,11 +210,31 @@ pub mod ancestors {
             self.queue.clear();
             self.buf.clear();
             self.seen.clear();
+            self.hidden_tips.clear();
+            self.visible = None;
+            self.shallow.clear();
+            self.topo = None;
+        }
+    }
+
+    /// Return `true` if `id` isn't reachable from any hidden tip, according to the `visible` commits if these were computed.
+    fn is_visible(visible: Option<&HashSet<ObjectId>>, id: &oid) -> bool {
+        visible.map_or(true, |visible| visible.contains(id))
+    }
+
+    /// Let `simplify`, if set, remove parents of `id` that shouldn't be traversed, and return `true` if `id` should be returned.
+    fn simplify<Simplify>(simplify: Option<&mut Simplify>, id: &oid, parents: &mut ParentIds) -> Result<bool, Error>
+    where
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
+    {
+        match simplify {
+            Some(simplify) => simplify(id, parents).map_err(Error::Simplify),
+            None => Ok(true),
         }
     }
 
     /// Builder
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
     where
         Find: gix_object::Find,
         StateMut: BorrowMut<State>,
@@ -154,7 +243,7 @@ pub mod ancestors {
         pub fn sorting(mut self, sorting: Sorting) -> Result<Self, Error> {
             self.sorting = sorting;
             match self.sorting {
-                Sorting::BreadthFirst => {
+                Sorting::BreadthFirst | Sorting::TopoOrder | Sorting::DateOrder | Sorting::AuthorDateOrder => {
                     self.queue_to_vecdeque();
                 }
                 Sorting::ByCommitTimeNewestFirst | Sorting::ByCommitTimeNewestFirstCutoffOlderThan { .. } => {
@@ -187,6 +276,58 @@ pub mod ancestors {
             self
         }
 
+        /// Hide the given `tips` along with all of their ancestors, so that none of them are returned by the traversal.
+        ///
+        /// This is how `A..B` (with `A` being hidden) or `A...B` (with the merge-bases of `A` and `B` being hidden) are implemented,
+        /// and it works with all [sorting][Sorting] and [parent][Parents] modes.
+        ///
+        /// Note that before the first commit is returned, the ancestry of both tips and hidden tips is traversed
+        /// by commit time until only hidden commits are left, similar to what `git rev-list` does for the same purpose.
+        pub fn hide(mut self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+            self.state
+                .borrow_mut()
+                .hidden_tips
+                .extend(tips.into_iter().map(Into::into));
+            self
+        }
+
+        /// Don't traverse the parents of the given `commits`, just like `git` does for the commits on the boundary of a
+        /// shallow repository, even if these parents are available.
+        ///
+        /// This also applies to the ancestry of [hidden tips](Self::hide()), and the shallow commits are returned
+        /// without parent ids.
+        pub fn shallow(mut self, commits: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+            self.state
+                .borrow_mut()
+                .shallow
+                .extend(commits.into_iter().map(Into::into));
+            self
+        }
+
+        /// Let `simplify` decide which parents of each commit to traverse, and whether the commit itself should be returned,
+        /// which allows to implement history simplification like `git log -- <path>` does.
+        ///
+        /// It's called once for each commit with the commit id and its parent ids, from which parents that shouldn't be traversed
+        /// can be removed, and returns `true` if the commit should be returned. The parent ids it leaves are
+        /// the [parent ids][Info::parent_ids] of the returned commit, and commits that aren't returned are still traversed.
+        ///
+        /// Note that only parents according to the [parent mode](Parents) are passed, and that the order in which it sees commits
+        /// depends on the [sorting](Sorting).
+        pub fn simplify<S>(self, simplify: S) -> Ancestors<Find, Predicate, StateMut, S>
+        where
+            S: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
+        {
+            Ancestors {
+                objects: self.objects,
+                cache: self.cache,
+                predicate: self.predicate,
+                state: self.state,
+                parents: self.parents,
+                sorting: self.sorting,
+                simplify: Some(simplify),
+            }
+        }
+
         /// Set the commitgraph as `cache` to greatly accelerate any traversal.
         ///
         /// The cache will be used if possible, but we will fall-back without error to using the object
@@ -271,11 +412,12 @@ pub mod ancestors {
                 state,
                 parents: Default::default(),
                 sorting: Default::default(),
+                simplify: None,
             }
         }
     }
     /// Access
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
     where
         StateMut: Borrow<State>,
     {
@@ -290,24 +432,41 @@ pub mod ancestors {
         }
     }
 
-    impl<Find, Predicate, StateMut> Iterator for Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Simplify> Iterator for Ancestors<Find, Predicate, StateMut, Simplify>
     where
         Find: gix_object::Find,
         Predicate: FnMut(&oid) -> bool,
         StateMut: BorrowMut<State>,
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
     {
         type Item = Result<Info, Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            if matches!(self.parents, Parents::First) {
-                self.next_by_topology()
-            } else {
-                match self.sorting {
-                    Sorting::BreadthFirst => self.next_by_topology(),
-                    Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
-                    Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => {
-                        self.next_by_commit_date(seconds.into())
+            if !self.state.borrow().hidden_tips.is_empty() {
+                if let Err(err) = self.hide_ancestry_of_hidden_tips() {
+                    return Some(Err(err));
+                }
+            }
+            loop {
+                let res = if let Sorting::TopoOrder | Sorting::DateOrder | Sorting::AuthorDateOrder = self.sorting {
+                    self.next_in_topo_order()
+                } else if matches!(self.parents, Parents::First) {
+                    self.next_by_topology()
+                } else {
+                    match self.sorting {
+                        Sorting::BreadthFirst | Sorting::TopoOrder | Sorting::DateOrder | Sorting::AuthorDateOrder => {
+                            self.next_by_topology()
+                        }
+                        Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
+                        Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => {
+                            self.next_by_commit_date(seconds.into())
+                        }
                     }
+                };
+                match res? {
+                    Ok((_info, false)) => continue,
+                    Ok((info, true)) => return Some(Ok(info)),
+                    Err(err) => return Some(Err(err)),
                 }
             }
         }
@@ -324,16 +483,169 @@ pub mod ancestors {
     }
 
     /// Utilities
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
+    where
+        Find: gix_object::Find,
+        Predicate: FnMut(&oid) -> bool,
+        StateMut: BorrowMut<State>,
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
+    {
+        /// Traverse the ancestry of all tips and hidden tips by commit time, newest first, until only hidden commits are left,
+        /// and remember all commits that were reached from the tips but not from hidden tips as the ones we may return.
+        ///
+        /// A commit may be reached from a tip before it's reached from a hidden tip, which is why hidden-ness is propagated
+        /// to all ancestors we already know, and why we continue for a few more commits once only hidden ones are left to protect
+        /// against clock skew.
+        fn hide_ancestry_of_hidden_tips(&mut self) -> Result<(), Error> {
+            // The amount of commits to traverse once only hidden commits are left, the same value as used by `git`.
+            const SLOP: usize = 5;
+            let state = self.state.borrow_mut();
+            let hidden_tips = std::mem::take(&mut state.hidden_tips);
+
+            let mut queue = gix_revwalk::PriorityQueue::<SecondsSinceUnixEpoch, ObjectId>::new();
+            let mut is_hidden = HashMap::<ObjectId, bool>::default();
+            let mut parents_of_traversed = HashMap::<ObjectId, ParentIds>::default();
+            let mut reached_from_tips = Vec::new();
+            let tips: Vec<_> = state.next.iter().chain(state.queue.iter_unordered()).copied().collect();
+            for (tip, hidden) in tips
+                .into_iter()
+                .map(|tip| (tip, false))
+                .chain(hidden_tips.into_iter().map(|tip| (tip, true)))
+            {
+                match is_hidden.get_mut(&tip) {
+                    Some(is_hidden) => *is_hidden |= hidden,
+                    None => {
+                        is_hidden.insert(tip, hidden);
+                        let time = commit_time(self.cache.as_ref(), &self.objects, &tip, &mut state.buf)?;
+                        queue.insert(time, tip);
+                    }
+                }
+            }
+
+            let mut slop = SLOP;
+            while let Some((_time, id)) = queue.pop() {
+                let hidden = is_hidden[&id];
+                if !hidden {
+                    reached_from_tips.push(id);
+                }
+                parents_with_commit_time(
+                    self.cache.as_ref(),
+                    &self.objects,
+                    &id,
+                    &mut state.buf,
+                    &mut state.parents_buf,
+                    &mut state.parent_ids,
+                )?;
+                if state.shallow.contains(&id) {
+                    state.parent_ids.clear();
+                }
+                let num_parents = if hidden || matches!(self.parents, Parents::All) {
+                    state.parent_ids.len()
+                } else {
+                    1
+                };
+                let mut parents = ParentIds::new();
+                for (parent_id, parent_commit_time) in state.parent_ids.drain(..).take(num_parents) {
+                    parents.push(parent_id);
+                    match is_hidden.get(&parent_id).copied() {
+                        None => {
+                            is_hidden.insert(parent_id, hidden);
+                            queue.insert(parent_commit_time, parent_id);
+                        }
+                        Some(false) if hidden => {
+                            hide_with_known_ancestors(parent_id, &mut is_hidden, &parents_of_traversed);
+                        }
+                        Some(_) => {}
+                    }
+                }
+                parents_of_traversed.insert(id, parents);
+
+                if queue.iter_unordered().all(|id| is_hidden[id]) {
+                    slop -= 1;
+                    if slop == 0 {
+                        break;
+                    }
+                } else {
+                    slop = SLOP;
+                }
+            }
+
+            let visible: HashSet<_> = reached_from_tips.into_iter().filter(|id| !is_hidden[id]).collect();
+            state.next.retain(|id| visible.contains(id));
+            let queue = std::mem::replace(&mut state.queue, gix_revwalk::PriorityQueue::new());
+            for (time, id) in queue.into_iter_unordered().filter(|(_, id)| visible.contains(id)) {
+                state.queue.insert(time, id);
+            }
+            state.visible = Some(visible);
+            Ok(())
+        }
+    }
+
+    /// Mark `id` as hidden, along with all of its ancestors that we already know.
+    fn hide_with_known_ancestors(
+        id: ObjectId,
+        is_hidden: &mut HashMap<ObjectId, bool>,
+        parents_of_traversed: &HashMap<ObjectId, ParentIds>,
+    ) {
+        let mut to_hide = vec![id];
+        while let Some(id) = to_hide.pop() {
+            match is_hidden.get_mut(&id) {
+                Some(hidden) if !*hidden => *hidden = true,
+                _ => continue,
+            }
+            if let Some(parents) = parents_of_traversed.get(&id) {
+                to_hide.extend(parents.iter().copied());
+            }
+        }
+    }
+
+    /// Return the commit time of the commit with `id`.
+    fn commit_time(
+        cache: Option<&gix_commitgraph::Graph>,
+        objects: &impl gix_object::Find,
+        id: &oid,
+        buf: &mut Vec<u8>,
+    ) -> Result<SecondsSinceUnixEpoch, Error> {
+        if let Some(commit) = cache.and_then(|cache| cache.commit_by_id(id)) {
+            return Ok(commit.committer_timestamp() as SecondsSinceUnixEpoch);
+        }
+        Ok(objects.find_commit_iter(id, buf)?.committer()?.time.seconds)
+    }
+
+    /// Place all parents of the commit with `id` along with their commit time into `out`.
+    fn parents_with_commit_time(
+        cache: Option<&gix_commitgraph::Graph>,
+        objects: &impl gix_object::Find,
+        id: &oid,
+        buf: &mut Vec<u8>,
+        parents_buf: &mut Vec<u8>,
+        out: &mut SmallVec<[(ObjectId, SecondsSinceUnixEpoch); 2]>,
+    ) -> Result<(), Error> {
+        if let Some(commit) = cache.and_then(|cache| cache.commit_by_id(id)) {
+            if collect_parents(out, cache, commit.iter_parents()) {
+                return Ok(());
+            }
+        }
+        out.clear();
+        for parent_id in objects.find_commit_iter(id, buf)?.parent_ids() {
+            let parent_commit_time = commit_time(None, objects, &parent_id, parents_buf).unwrap_or_default();
+            out.push((parent_id, parent_commit_time));
+        }
+        Ok(())
+    }
+
+    /// Utilities
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
     where
         Find: gix_object::Find,
         Predicate: FnMut(&oid) -> bool,
         StateMut: BorrowMut<State>,
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
     {
         fn next_by_commit_date(
             &mut self,
             cutoff_older_than: Option<SecondsSinceUnixEpoch>,
-        ) -> Option<Result<Info, Error>> {
+        ) -> Option<Result<(Info, bool), Error>> {
             let state = self.state.borrow_mut();
 
             let (commit_time, oid) = state.queue.pop()?;
@@ -345,40 +657,14 @@ pub mod ancestors {
                         self.cache = None;
                         return self.next_by_commit_date(cutoff_older_than);
                     }
-                    for (id, parent_commit_time) in state.parent_ids.drain(..) {
-                        parents.push(id);
-                        let was_inserted = state.seen.insert(id);
-                        if !(was_inserted && (self.predicate)(&id)) {
-                            continue;
-                        }
-
-                        match cutoff_older_than {
-                            Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                            Some(_) | None => state.queue.insert(parent_commit_time, id),
-                        }
-                    }
+                    parents.extend(state.parent_ids.iter().map(|(id, _commit_time)| *id));
                 }
                 Ok(Either::CommitRefIter(commit_iter)) => {
+                    state.parent_ids.clear();
                     for token in commit_iter {
                         match token {
                             Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
-                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
-                                parents.push(id);
-                                let was_inserted = state.seen.insert(id);
-                                if !(was_inserted && (self.predicate)(&id)) {
-                                    continue;
-                                }
-
-                                let parent = self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
-                                let parent_commit_time = parent
-                                    .and_then(|parent| parent.committer().ok().map(|committer| committer.time.seconds))
-                                    .unwrap_or_default();
-
-                                match cutoff_older_than {
-                                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                                    Some(_) | None => state.queue.insert(parent_commit_time, id),
-                                }
-                            }
+                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => parents.push(id),
                             Ok(_unused_token) => break,
                             Err(err) => return Some(Err(err.into())),
                         }
@@ -386,22 +672,55 @@ pub mod ancestors {
                 }
                 Err(err) => return Some(Err(err.into())),
             }
-            Some(Ok(Info {
-                id: oid,
-                parent_ids: parents,
-                commit_time: Some(commit_time),
-            }))
+            if state.shallow.contains(&oid) {
+                parents.clear();
+            }
+            let is_returned = match simplify(self.simplify.as_mut(), &oid, &mut parents) {
+                Ok(is_returned) => is_returned,
+                Err(err) => return Some(Err(err)),
+            };
+
+            for id in parents.iter().copied() {
+                let was_inserted = state.seen.insert(id);
+                if !(was_inserted && is_visible(state.visible.as_ref(), &id) && (self.predicate)(&id)) {
+                    continue;
+                }
+
+                let parent_commit_time = match state.parent_ids.iter().find(|(parent_id, _)| *parent_id == id) {
+                    Some((_, parent_commit_time)) => *parent_commit_time,
+                    None => {
+                        let parent = self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
+                        parent
+                            .and_then(|parent| parent.committer().ok().map(|committer| committer.time.seconds))
+                            .unwrap_or_default()
+                    }
+                };
+
+                match cutoff_older_than {
+                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
+                    Some(_) | None => state.queue.insert(parent_commit_time, id),
+                }
+            }
+            Some(Ok((
+                Info {
+                    id: oid,
+                    parent_ids: parents,
+                    commit_time: Some(commit_time),
+                },
+                is_returned,
+            )))
         }
     }
 
     /// Utilities
-    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
     where
         Find: gix_object::Find,
         Predicate: FnMut(&oid) -> bool,
         StateMut: BorrowMut<State>,
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
     {
-        fn next_by_topology(&mut self) -> Option<Result<Info, Error>> {
+        fn next_by_topology(&mut self) -> Option<Result<(Info, bool), Error>> {
             let state = self.state.borrow_mut();
             let oid = state.next.pop_front()?;
             let mut parents: ParentIds = Default::default();
@@ -415,10 +734,6 @@ pub mod ancestors {
 
                     for (id, _commit_time) in state.parent_ids.drain(..) {
                         parents.push(id);
-                        let was_inserted = state.seen.insert(id);
-                        if was_inserted && (self.predicate)(&id) {
-                            state.next.push_back(id);
-                        }
                         if matches!(self.parents, Parents::First) {
                             break;
                         }
@@ -430,10 +745,6 @@ pub mod ancestors {
                             Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
                             Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
                                 parents.push(id);
-                                let was_inserted = state.seen.insert(id);
-                                if was_inserted && (self.predicate)(&id) {
-                                    state.next.push_back(id);
-                                }
                                 if matches!(self.parents, Parents::First) {
                                     break;
                                 }
@@ -445,12 +756,234 @@ pub mod ancestors {
                 }
                 Err(err) => return Some(Err(err.into())),
             }
-            Some(Ok(Info {
-                id: oid,
-                parent_ids: parents,
-                commit_time: None,
-            }))
+            if state.shallow.contains(&oid) {
+                parents.clear();
+            }
+            let is_returned = match simplify(self.simplify.as_mut(), &oid, &mut parents) {
+                Ok(is_returned) => is_returned,
+                Err(err) => return Some(Err(err)),
+            };
+
+            for id in parents.iter().copied() {
+                let was_inserted = state.seen.insert(id);
+                if was_inserted && is_visible(state.visible.as_ref(), &id) && (self.predicate)(&id) {
+                    state.next.push_back(id);
+                }
+            }
+            Some(Ok((
+                Info {
+                    id: oid,
+                    parent_ids: parents,
+                    commit_time: None,
+                },
+                is_returned,
+            )))
+        }
+    }
+
+    /// Utilities
+    impl<Find, Predicate, StateMut, Simplify> Ancestors<Find, Predicate, StateMut, Simplify>
+    where
+        Find: gix_object::Find,
+        Predicate: FnMut(&oid) -> bool,
+        StateMut: BorrowMut<State>,
+        Simplify: FnMut(&oid, &mut ParentIds) -> Result<bool, SimplifyError>,
+    {
+        fn next_in_topo_order(&mut self) -> Option<Result<(Info, bool), Error>> {
+            let mut topo = match self.state.borrow_mut().topo.take() {
+                Some(topo) => topo,
+                None => match self.init_topo_order() {
+                    Ok(topo) => topo,
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+            let res = self.next_topo(&mut topo);
+            self.state.borrow_mut().topo = Some(topo);
+            res.transpose()
+        }
+
+        /// Count the children of all tips and queue those that aren't reachable from other tips, newest first like `git` does.
+        fn init_topo_order(&mut self) -> Result<Topo, Error> {
+            let mut topo = Topo {
+                indegree: Default::default(),
+                indegree_queue: gix_revwalk::PriorityQueue::new(),
+                by_time: gix_revwalk::PriorityQueue::new(),
+                stack: Vec::new(),
+                num_queued: 0,
+                simplified: Default::default(),
+            };
+            let state = self.state.borrow_mut();
+            let mut tips = Vec::with_capacity(state.next.len());
+            let mut min_generation = gix_commitgraph::GENERATION_NUMBER_INFINITY;
+            for tip in state.next.drain(..) {
+                let generation = generation(self.cache.as_ref(), &tip);
+                min_generation = min_generation.min(generation);
+                let time = commit_time(self.cache.as_ref(), &self.objects, &tip, &mut state.parents_buf)?;
+                topo.indegree.insert(tip, 1);
+                topo.indegree_queue.insert((generation, time), tip);
+                tips.push((time, tip));
+            }
+            self.count_children_down_to(&mut topo, min_generation)?;
+
+            tips.sort_by(|(a, _), (b, _)| b.cmp(a));
+            for (_time, tip) in tips {
+                if topo.indegree[&tip] == 1 {
+                    self.queue_in_topo_order(&mut topo, tip)?;
+                }
+            }
+            // The newest tip is supposed to be returned first.
+            topo.stack.reverse();
+            Ok(topo)
+        }
+
+        fn next_topo(&mut self, topo: &mut Topo) -> Result<Option<(Info, bool)>, Error> {
+            let id = match self.sorting {
+                Sorting::DateOrder | Sorting::AuthorDateOrder => topo.by_time.pop_value(),
+                _ => topo.stack.pop(),
+            };
+            let Some(id) = id else { return Ok(None) };
+
+            let state = self.state.borrow_mut();
+            let (mut parents, commit_time) = parents_and_commit_time(
+                self.cache.as_ref(),
+                &self.objects,
+                &id,
+                &mut state.buf,
+                self.parents,
+                &state.shallow,
+            )?;
+            let is_returned = match topo.simplified.remove(&id) {
+                Some((simplified_parents, is_returned)) => {
+                    parents = simplified_parents;
+                    is_returned
+                }
+                None => simplify(self.simplify.as_mut(), &id, &mut parents)?,
+            };
+            for parent_id in &parents {
+                self.count_children_down_to(topo, generation(self.cache.as_ref(), parent_id))?;
+                let Some(indegree) = topo.indegree.get_mut(parent_id) else {
+                    continue;
+                };
+                *indegree -= 1;
+                if *indegree == 1 {
+                    self.queue_in_topo_order(topo, *parent_id)?;
+                }
+            }
+            Ok(Some((
+                Info {
+                    id,
+                    parent_ids: parents,
+                    commit_time: Some(commit_time),
+                },
+                is_returned,
+            )))
+        }
+
+        /// Count the children of all commits with a generation of at least `min_generation` so that we know when all children
+        /// of such commits were returned. Without generation numbers, this counts the children of all commits.
+        fn count_children_down_to(&mut self, topo: &mut Topo, min_generation: u32) -> Result<(), Error> {
+            let state = self.state.borrow_mut();
+            while topo
+                .indegree_queue
+                .peek()
+                .map_or(false, |((queued_generation, _time), _id)| {
+                    *queued_generation >= min_generation
+                })
+            {
+                let id = topo.indegree_queue.pop_value().expect("just peeked");
+                let (mut parents, _commit_time) = parents_and_commit_time(
+                    self.cache.as_ref(),
+                    &self.objects,
+                    &id,
+                    &mut state.parents_buf,
+                    self.parents,
+                    &state.shallow,
+                )?;
+                if self.simplify.is_some() {
+                    let is_returned = simplify(self.simplify.as_mut(), &id, &mut parents)?;
+                    topo.simplified.insert(id, (parents.clone(), is_returned));
+                }
+                for parent_id in parents {
+                    if !state.seen.insert(parent_id) {
+                        if let Some(indegree) = topo.indegree.get_mut(&parent_id) {
+                            *indegree += 1;
+                        }
+                        continue;
+                    }
+                    if !(is_visible(state.visible.as_ref(), &parent_id) && (self.predicate)(&parent_id)) {
+                        continue;
+                    }
+                    topo.indegree.insert(parent_id, 2);
+                    let time = commit_time(self.cache.as_ref(), &self.objects, &parent_id, &mut state.parents_buf)?;
+                    topo.indegree_queue
+                        .insert((generation(self.cache.as_ref(), &parent_id), time), parent_id);
+                }
+            }
+            Ok(())
+        }
+
+        /// Queue `id` to be returned as all of its children have been returned.
+        fn queue_in_topo_order(&mut self, topo: &mut Topo, id: ObjectId) -> Result<(), Error> {
+            let state = self.state.borrow_mut();
+            let time = match self.sorting {
+                Sorting::DateOrder => commit_time(self.cache.as_ref(), &self.objects, &id, &mut state.parents_buf)?,
+                Sorting::AuthorDateOrder => {
+                    self.objects
+                        .find_commit_iter(&id, &mut state.parents_buf)?
+                        .author()?
+                        .time
+                        .seconds
+                }
+                _ => {
+                    topo.stack.push(id);
+                    return Ok(());
+                }
+            };
+            topo.by_time.insert((time, std::cmp::Reverse(topo.num_queued)), id);
+            topo.num_queued += 1;
+            Ok(())
+        }
+    }
+
+    /// Return the generation number of the commit with `id`, or [infinity](gix_commitgraph::GENERATION_NUMBER_INFINITY)
+    /// if it isn't in the commit-graph.
+    fn generation(cache: Option<&gix_commitgraph::Graph>, id: &oid) -> u32 {
+        cache
+            .and_then(|cache| cache.commit_by_id(id))
+            .map_or(gix_commitgraph::GENERATION_NUMBER_INFINITY, |commit| {
+                commit.generation()
+            })
+    }
+
+    /// Return the parents of the commit with `id` according to `mode`, or none if it's `shallow`, along with its commit time.
+    fn parents_and_commit_time(
+        cache: Option<&gix_commitgraph::Graph>,
+        objects: &impl gix_object::Find,
+        id: &oid,
+        buf: &mut Vec<u8>,
+        mode: Parents,
+        shallow: &HashSet<ObjectId>,
+    ) -> Result<(ParentIds, SecondsSinceUnixEpoch), Error> {
+        let num_parents = match mode {
+            _ if shallow.contains(id) => 0,
+            Parents::All => usize::MAX,
+            Parents::First => 1,
+        };
+        if let Some((cache, commit)) = cache.and_then(|cache| cache.commit_by_id(id).map(|commit| (cache, commit))) {
+            let time = commit.committer_timestamp() as SecondsSinceUnixEpoch;
+            let parents: Result<ParentIds, _> = commit
+                .iter_parents()
+                .take(num_parents)
+                .map(|pos| pos.map(|pos| cache.commit_at(pos).id().to_owned()))
+                .collect();
+            // fall back to the object database if the cache is corrupt
+            if let Ok(parents) = parents {
+                return Ok((parents, time));
+            }
         }
+        let commit = objects.find_commit_iter(id, buf)?;
+        let time = commit.committer()?.time.seconds;
+        Ok((commit.parent_ids().take(num_parents).collect(), time))
     }
 }
 
@@ -9,6 +9,8 @@ mod ancestor {
         repo_name: &'a str,
         tips: &'a [&'a str],
         expected: &'a [&'a str],
+        hidden: &'a [&'a str],
+        shallow: &'a [&'a str],
         mode: commit::Parents,
         sorting: commit::Sorting,
     }
@@ -24,6 +26,8 @@ mod ancestor {
                 repo_name,
                 tips,
                 expected,
+                hidden: &[],
+                shallow: &[],
                 mode: Default::default(),
                 sorting: Default::default(),
             }
@@ -38,6 +42,16 @@ mod ancestor {
             self.sorting = sorting;
             self
         }
+
+        fn with_hidden(&mut self, hidden: &'a [&'a str]) -> &mut Self {
+            self.hidden = hidden;
+            self
+        }
+
+        fn with_shallow(&mut self, shallow: &'a [&'a str]) -> &mut Self {
+            self.shallow = shallow;
+            self
+        }
     }
 
     impl TraversalAssertion<'_> {
@@ -88,6 +102,8 @@ mod ancestor {
                 let oids = commit::Ancestors::new(tips.clone(), commit::ancestors::State::default(), &store)
                     .sorting(self.sorting)?
                     .parents(self.mode)
+                    .hide(self.hidden.iter().copied().map(hex_to_id))
+                    .shallow(self.shallow.iter().copied().map(hex_to_id))
                     .commit_graph(self.setup_commitgraph(store.store_ref(), use_commitgraph))
                     .map(|res| res.map(|info| info.id))
                     .collect::<Result<Vec<_>, _>>()?;
@@ -427,4 +443,461 @@ mod ancestor {
             .check()
         }
     }
+
+    mod hidden {
+        use gix_traverse::commit::{ancestors, Ancestors, Parents, Sorting};
+
+        use crate::{commit::ancestor::TraversalAssertion, hex_to_id};
+
+        #[test]
+        fn merge_without_branch() -> crate::Result {
+            for sorting in [Sorting::BreadthFirst, Sorting::ByCommitTimeNewestFirst] {
+                TraversalAssertion::new_at(
+                    "make_repos.sh",
+                    "simple",
+                    &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                    // This is exactly what git shows for `main ^branch2`.
+                    &[
+                        "0edb95c0c0d9933d88f532ec08fcd405d0eee882", /* c5 */
+                        "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                        "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    ],
+                )
+                .with_hidden(&["48e8dac19508f4238f06c8de2b10301ce64a641c" /* b2c2 */])
+                .with_sorting(sorting)
+                .check()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn symmetric_difference_by_hiding_the_merge_base() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &[
+                    "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                    "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                ],
+                &[
+                    "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                ],
+            )
+            .with_hidden(&["8cb5f13b66ce52a49399a2c49f537ee2b812369c" /* c4 */])
+            .check()
+        }
+
+        #[test]
+        fn hidden_tip_shares_the_commit_time_of_the_tip() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                // This is exactly what git shows for `main ^main~1`.
+                &[
+                    "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                    "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                    "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                ],
+            )
+            .with_hidden(&["0edb95c0c0d9933d88f532ec08fcd405d0eee882" /* c5 */])
+            .with_sorting(Sorting::ByCommitTimeNewestFirst)
+            .check()
+        }
+
+        #[test]
+        fn first_parent_only() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                &["0edb95c0c0d9933d88f532ec08fcd405d0eee882"], /* c5 */
+            )
+            .with_hidden(&["66a309480201c4157b0eae86da69f2d606aadbe7" /* b1c2 */])
+            .with_sorting(Sorting::ByCommitTimeNewestFirst)
+            .with_parents(Parents::First)
+            .check()
+        }
+
+        #[test]
+        fn commits_reached_before_their_hidden_descendant_are_hidden_as_well() -> crate::Result {
+            for sorting in [Sorting::BreadthFirst, Sorting::ByCommitTimeNewestFirst] {
+                TraversalAssertion::new(
+                    "make_traversal_repo_for_commits_with_dates.sh",
+                    &["288e509293165cb5630d08f4185bdf2445bf6170"], /* m1b1 */
+                    // `c1` has the same time as `c2`, and is reachable from both `b1c1` and `c2`.
+                    &["bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac"], /* b1c1 */
+                )
+                .with_hidden(&["9902e3c3e8f0c569b4ab295ddf473e6de763e1e7" /* c2 */])
+                .with_sorting(sorting)
+                .check()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn hidden_tips_hide_tips() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repos.sh")?;
+            let store = gix_odb::at(dir.join("simple").join(".git").join("objects"))?;
+            let merge = hex_to_id("f49838d84281c3988eeadd988d97dd358c9f9dc4");
+            for sorting in [Sorting::BreadthFirst, Sorting::ByCommitTimeNewestFirst] {
+                let iter = Ancestors::new(Some(merge), ancestors::State::default(), &store)
+                    .sorting(sorting)?
+                    .hide(Some(merge));
+                assert_eq!(
+                    iter.count(),
+                    0,
+                    "a tip that is hidden hides all of its ancestors as well"
+                );
+            }
+            Ok(())
+        }
+    }
+
+    mod shallow {
+        use gix_traverse::commit::Sorting;
+
+        use crate::commit::ancestor::TraversalAssertion;
+
+        #[test]
+        fn parents_of_shallow_commits_are_not_traversed() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                &[
+                    "0edb95c0c0d9933d88f532ec08fcd405d0eee882", /* c5 */
+                    "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                    "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                    "8cb5f13b66ce52a49399a2c49f537ee2b812369c", /* c4 */
+                    "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                ],
+            )
+            .with_shallow(&["8cb5f13b66ce52a49399a2c49f537ee2b812369c" /* c4 */])
+            .check()
+        }
+
+        #[test]
+        fn ancestry_of_hidden_tips_is_cut_off_as_well() -> crate::Result {
+            for sorting in [Sorting::ByCommitTimeNewestFirst, Sorting::DateOrder] {
+                TraversalAssertion::new_at(
+                    "make_repos.sh",
+                    "simple",
+                    &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                    // The ancestors of `c5` aren't hidden as its parents aren't known.
+                    &[
+                        "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                        "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                        "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                        "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                        "8cb5f13b66ce52a49399a2c49f537ee2b812369c", /* c4 */
+                        "33aa07785dd667c0196064e3be3c51dd9b4744ef", /* c3 */
+                        "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                        "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                    ],
+                )
+                .with_hidden(&["0edb95c0c0d9933d88f532ec08fcd405d0eee882" /* c5 */])
+                .with_shallow(&["0edb95c0c0d9933d88f532ec08fcd405d0eee882" /* c5 */])
+                .with_sorting(sorting)
+                .check()?;
+            }
+            Ok(())
+        }
+    }
+
+    /// All expectations are exactly what `git` shows with the respective `--topo-order`, `--date-order` or `--author-date-order`.
+    mod topo_order {
+        use gix_traverse::commit::{ancestors, Ancestors, Parents, Sorting};
+
+        use crate::{commit::ancestor::TraversalAssertion, hex_to_id};
+
+        #[test]
+        fn head_topo_order() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                // Each branch is shown in full before going on with the next one, starting with the last parent.
+                &[
+                    "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                    "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                    "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                    "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    "0edb95c0c0d9933d88f532ec08fcd405d0eee882", /* c5 */
+                    "8cb5f13b66ce52a49399a2c49f537ee2b812369c", /* c4 */
+                    "33aa07785dd667c0196064e3be3c51dd9b4744ef", /* c3 */
+                    "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                    "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                ],
+            )
+            .with_sorting(Sorting::TopoOrder)
+            .check()
+        }
+
+        #[test]
+        fn intermixed_topo_order() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "intermixed",
+                &["58912d92944087dcb09dca79cdd2a937cc158bed"], /* merge */
+                &[
+                    "a9c28710e058af4e5163699960234adb9fb2abc7", /* b2c2 */
+                    "b648f955b930ca95352fae6f22cb593ee0244b27", /* b2c1 */
+                    "0f6632a5a7d81417488b86692b729e49c1b73056", /* b1c2 */
+                    "77fd3c6832c0cd542f7a39f3af9250c3268db979", /* b1c1 */
+                    "2dce37be587e07caef8c4a5ab60b423b13a8536a", /* c3 */
+                    "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                    "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                ],
+            )
+            .with_sorting(Sorting::TopoOrder)
+            .check()
+        }
+
+        #[test]
+        fn intermixed_date_order() -> crate::Result {
+            for sorting in [Sorting::DateOrder, Sorting::AuthorDateOrder] {
+                TraversalAssertion::new_at(
+                    "make_repos.sh",
+                    "intermixed",
+                    &["58912d92944087dcb09dca79cdd2a937cc158bed"], /* merge */
+                    // Author and commit dates are the same here.
+                    &[
+                        "2dce37be587e07caef8c4a5ab60b423b13a8536a", /* c3 */
+                        "0f6632a5a7d81417488b86692b729e49c1b73056", /* b1c2 */
+                        "a9c28710e058af4e5163699960234adb9fb2abc7", /* b2c2 */
+                        "77fd3c6832c0cd542f7a39f3af9250c3268db979", /* b1c1 */
+                        "b648f955b930ca95352fae6f22cb593ee0244b27", /* b2c1 */
+                        "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                        "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                    ],
+                )
+                .with_sorting(sorting)
+                .check()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn same_date_topo_order() -> crate::Result {
+            TraversalAssertion::new(
+                "make_traversal_repo_for_commits_same_date.sh",
+                &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"], /* m1b1 */
+                &[
+                    "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353", /* b1c2 */
+                    "9152eeee2328073cf23dcf8e90c949170b711659", /* b1c1 */
+                    "efd9a841189668f1bab5b8ebade9cd0a1b139a37", /* c5 */
+                    "9556057aee5abb06912922e9f26c46386a816822", /* c4 */
+                    "17d78c64cef6c33a10a604573fd2c429e477fd63", /* c3 */
+                    "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7", /* c2 */
+                    "134385f6d781b7e97062102c6a483440bfda2a03", /* c1 */
+                ],
+            )
+            .with_sorting(Sorting::TopoOrder)
+            .check()
+        }
+
+        #[test]
+        fn same_date_date_order() -> crate::Result {
+            TraversalAssertion::new(
+                "make_traversal_repo_for_commits_same_date.sh",
+                &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"], /* m1b1 */
+                // Commits with the same date are returned in the order in which they were discovered.
+                &[
+                    "efd9a841189668f1bab5b8ebade9cd0a1b139a37", /* c5 */
+                    "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353", /* b1c2 */
+                    "9152eeee2328073cf23dcf8e90c949170b711659", /* b1c1 */
+                    "9556057aee5abb06912922e9f26c46386a816822", /* c4 */
+                    "17d78c64cef6c33a10a604573fd2c429e477fd63", /* c3 */
+                    "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7", /* c2 */
+                    "134385f6d781b7e97062102c6a483440bfda2a03", /* c1 */
+                ],
+            )
+            .with_sorting(Sorting::DateOrder)
+            .check()
+        }
+
+        #[test]
+        fn same_date_first_parent_only() -> crate::Result {
+            for sorting in [Sorting::TopoOrder, Sorting::DateOrder] {
+                TraversalAssertion::new(
+                    "make_traversal_repo_for_commits_same_date.sh",
+                    &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"], /* m1b1 */
+                    &[
+                        "efd9a841189668f1bab5b8ebade9cd0a1b139a37", /* c5 */
+                        "9556057aee5abb06912922e9f26c46386a816822", /* c4 */
+                        "17d78c64cef6c33a10a604573fd2c429e477fd63", /* c3 */
+                        "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7", /* c2 */
+                        "134385f6d781b7e97062102c6a483440bfda2a03", /* c1 */
+                    ],
+                )
+                .with_sorting(sorting)
+                .with_parents(Parents::First)
+                .check()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn parents_are_never_returned_before_their_children() -> crate::Result {
+            for sorting in [Sorting::TopoOrder, Sorting::DateOrder] {
+                TraversalAssertion::new(
+                    "make_traversal_repo_for_commits_with_dates.sh",
+                    &["288e509293165cb5630d08f4185bdf2445bf6170"], /* m1b1 */
+                    // `c1` is older than `b1c1`, but has the same time as its child `c2`.
+                    &[
+                        "bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac", /* b1c1 */
+                        "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7", /* c2 */
+                        "134385f6d781b7e97062102c6a483440bfda2a03", /* c1 */
+                    ],
+                )
+                .with_sorting(sorting)
+                .check()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn author_date_order() -> crate::Result {
+            TraversalAssertion::new(
+                "make_traversal_repo_for_commits_with_dates.sh",
+                &["288e509293165cb5630d08f4185bdf2445bf6170"], /* m1b1 */
+                // Only the commit dates were adjusted, so all author dates are the same.
+                &[
+                    "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7", /* c2 */
+                    "bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac", /* b1c1 */
+                    "134385f6d781b7e97062102c6a483440bfda2a03", /* c1 */
+                ],
+            )
+            .with_sorting(Sorting::AuthorDateOrder)
+            .check()
+        }
+
+        #[test]
+        fn hidden() -> crate::Result {
+            TraversalAssertion::new_at(
+                "make_repos.sh",
+                "simple",
+                &["f49838d84281c3988eeadd988d97dd358c9f9dc4"], /* merge */
+                // This is what git shows for `main ^branch2`.
+                &[
+                    "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                    "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                    "0edb95c0c0d9933d88f532ec08fcd405d0eee882", /* c5 */
+                ],
+            )
+            .with_hidden(&["48e8dac19508f4238f06c8de2b10301ce64a641c" /* b2c2 */])
+            .with_sorting(Sorting::TopoOrder)
+            .check()
+        }
+
+        #[test]
+        fn multiple_tips_start_with_the_newest_one() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repos.sh")?;
+            let store = gix_odb::at(dir.join("simple").join(".git").join("objects"))?;
+            for sorting in [Sorting::TopoOrder, Sorting::DateOrder] {
+                for use_commitgraph in [false, true] {
+                    let cache = use_commitgraph
+                        .then(|| gix_commitgraph::at(store.store_ref().path().join("info")))
+                        .transpose()?;
+                    let ids = Ancestors::new(
+                        [
+                            hex_to_id("48e8dac19508f4238f06c8de2b10301ce64a641c"), /* b2c2 */
+                            hex_to_id("66a309480201c4157b0eae86da69f2d606aadbe7"), /* b1c2 */
+                        ],
+                        ancestors::State::default(),
+                        &store,
+                    )
+                    .sorting(sorting)?
+                    .commit_graph(cache)
+                    .map(|res| res.map(|info| info.id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                    assert_eq!(
+                        ids,
+                        [
+                            "66a309480201c4157b0eae86da69f2d606aadbe7", /* b1c2 */
+                            "80947acb398362d8236fcb8bf0f8a9dac640583f", /* b1c1 */
+                            "48e8dac19508f4238f06c8de2b10301ce64a641c", /* b2c2 */
+                            "cb6a6befc0a852ac74d74e0354e0f004af29cb79", /* b2c1 */
+                            "8cb5f13b66ce52a49399a2c49f537ee2b812369c", /* c4 */
+                            "33aa07785dd667c0196064e3be3c51dd9b4744ef", /* c3 */
+                            "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                            "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                        ]
+                        .map(hex_to_id),
+                        "this is what `git log {sorting:?} branch2 branch1` shows"
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+
+    mod simplify {
+        use gix_traverse::commit::{ancestors, Ancestors, Sorting};
+
+        use crate::hex_to_id;
+
+        #[test]
+        fn pruned_parents_are_not_traversed_and_hidden_commits_not_returned() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repos.sh")?;
+            let store = gix_odb::at(dir.join("simple").join(".git").join("objects"))?;
+            let merge = hex_to_id("f49838d84281c3988eeadd988d97dd358c9f9dc4");
+            for sorting in [
+                Sorting::BreadthFirst,
+                Sorting::ByCommitTimeNewestFirst,
+                Sorting::TopoOrder,
+                Sorting::DateOrder,
+            ] {
+                for use_commitgraph in [false, true] {
+                    let cache = use_commitgraph
+                        .then(|| gix_commitgraph::at(store.store_ref().path().join("info")))
+                        .transpose()?;
+                    let mut num_calls = 0;
+                    let infos = Ancestors::new(Some(merge), ancestors::State::default(), &store)
+                        .sorting(sorting)?
+                        .commit_graph(cache)
+                        .simplify(|id, parents| {
+                            num_calls += 1;
+                            if parents.len() > 1 {
+                                parents.truncate(1);
+                            }
+                            Ok(id != merge)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    assert_eq!(
+                        infos.iter().map(|info| info.id).collect::<Vec<_>>(),
+                        [
+                            "0edb95c0c0d9933d88f532ec08fcd405d0eee882", /* c5 */
+                            "8cb5f13b66ce52a49399a2c49f537ee2b812369c", /* c4 */
+                            "33aa07785dd667c0196064e3be3c51dd9b4744ef", /* c3 */
+                            "ad33ff2d0c4fc77d56b5fbff6f86f332fe792d83", /* c2 */
+                            "65d6af66f60b8e39fd1ba6a1423178831e764ec5", /* c1 */
+                        ]
+                        .map(hex_to_id),
+                        "the merge isn't returned, but its first parent is traversed, for {sorting:?}"
+                    );
+                    assert_eq!(num_calls, 6, "it's called exactly once per traversed commit");
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn errors_are_returned() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repos.sh")?;
+            let store = gix_odb::at(dir.join("simple").join(".git").join("objects"))?;
+            let mut iter = Ancestors::new(
+                Some(hex_to_id("f49838d84281c3988eeadd988d97dd358c9f9dc4")),
+                ancestors::State::default(),
+                &store,
+            )
+            .simplify(|_id, _parents| Err("failure".into()));
+            assert!(matches!(iter.next(), Some(Err(ancestors::Error::Simplify(_)))));
+            Ok(())
+        }
+    }
 }
@@ -35,6 +35,29 @@ impl PrepareFetch {
         self
     }
 
+    /// Make this clone a partial one which only receives the objects that pass `filter`, like `git clone --filter=<filter-spec>`.
+    ///
+    /// The remote will be configured as promisor remote from which missing objects are fetched on demand
+    /// when they are looked up, which requires the `blocking-network-client` feature.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    pub fn with_filter(mut self, filter: gix_protocol::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Download the bundle or bundle list at `uri` and unbundle it before fetching from the remote,
+    /// like `git clone --bundle-uri=<uri>`. This way, only what's not contained in the bundles has to be fetched from the remote.
+    ///
+    /// `uri` is a local path, a `file://` url, or an `http(s)://` url if one of the `blocking-http-transport-*` features is enabled.
+    /// If unset and `transfer.bundleURI` is `true`, the bundle list advertised by the remote is used instead.
+    /// If the bundle list has a heuristic, `uri` is stored in `fetch.bundleURI` so that later fetches use it as well.
+    /// See [`bundle_uri::apply()`](crate::remote::fetch::bundle_uri::apply()) for details.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    pub fn with_bundle_uri(mut self, uri: impl Into<BString>) -> Self {
+        self.bundle_uri = Some(uri.into());
+        self
+    }
+
     /// Apply the given configuration `values` right before readying the actual fetch from the remote.
     /// The configuration is marked with [source API](gix_config::Source::Api), and will not be written back, it's
     /// retained only in memory.
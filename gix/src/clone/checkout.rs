@@ -31,6 +31,8 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error("Could not fetch the objects that are missing in the partial clone")]
+        FetchMissingObjects(#[from] crate::object::find::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -99,6 +101,16 @@ pub mod main_worktree {
                 source: err,
             })?;
             let mut index = gix_index::File::from_state(index, repo.index_path());
+            if is_partial_clone(repo) {
+                // Fetch all missing blobs at once instead of one at a time while checking them out.
+                repo.try_find_headers(
+                    index
+                        .entries()
+                        .iter()
+                        .filter(|entry| !entry.mode.is_submodule())
+                        .map(|entry| entry.id),
+                )?;
+            }
 
             let mut opts = repo
                 .config
@@ -128,6 +140,17 @@ pub mod main_worktree {
             Ok((self.repo.take().expect("still present"), outcome))
         }
     }
+
+    fn is_partial_clone(repo: &Repository) -> bool {
+        use crate::config::tree::{Extensions, Key};
+        repo.config
+            .resolved
+            .string_filter_by_key(
+                Extensions::PARTIAL_CLONE.logical_name().as_str(),
+                &mut repo.filter_config_section(),
+            )
+            .is_some_and(|name| !name.is_empty())
+    }
 }
 
 /// Access
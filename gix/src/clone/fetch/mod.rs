@@ -35,6 +35,22 @@ pub enum Error {
     },
     #[error("Failed to update HEAD with values from remote")]
     HeadUpdate(#[from] crate::reference::edit::Error),
+    #[error("Failed to configure the remote as promisor remote of a partial clone")]
+    PartialCloneConfig(#[from] gix_config::file::set_raw_value::Error),
+    #[error("Could not lock the repository configuration to configure a partial clone")]
+    AcquireConfigLock(#[from] gix_lock::acquire::Error),
+    #[error("Could not commit the repository configuration of a partial clone")]
+    CommitConfigLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error("Failed to re-open the repository after configuring it as partial clone")]
+    ReopenPartialClone(#[from] crate::open::Error),
+    #[error(transparent)]
+    BundleUri(#[from] crate::remote::fetch::bundle_uri::Error),
+    #[error("The bundle list advertised by the remote could not be obtained")]
+    RemoteBundleList(#[from] gix_protocol::bundle_uri::Error),
+    #[error("The bundle list advertised by the remote is invalid")]
+    ParseRemoteBundleList(#[from] gix_bundle::list::parse::Error),
+    #[error("Could not read the `transfer.bundleURI` configuration")]
+    BundleUriConfig(#[from] crate::config::boolean::Error),
 }
 
 /// Modification
@@ -110,7 +126,21 @@ impl PrepareFetch {
             clone_fetch_tags = remote::fetch::Tags::All.into();
         }
 
-        let config = util::write_remote_to_local_config_file(&mut remote, remote_name.clone())?;
+        let mut config = util::write_remote_to_local_config_file(&mut remote, remote_name.clone())?;
+
+        let use_remote_bundle_list = match self.bundle_uri.as_ref() {
+            Some(uri) => {
+                let outcome = remote::fetch::bundle_uri::apply(repo, uri.as_ref(), &mut *progress, should_interrupt)?;
+                if outcome.heuristic.is_some() {
+                    util::write_bundle_uri_config(repo, &mut config, uri.as_ref())?;
+                }
+                if let Some(token) = outcome.creation_token {
+                    util::set_bundle_creation_token(&mut config, token)?;
+                }
+                false
+            }
+            None => util::use_remote_bundle_list(repo)?,
+        };
 
         // Now we are free to apply remote configuration we don't want to be written to disk.
         if let Some(fetch_tags) = clone_fetch_tags {
@@ -125,7 +155,7 @@ impl PrepareFetch {
         )
         .expect("valid")
         .to_owned();
-        let pending_pack: remote::fetch::Prepare<'_, '_, _> = {
+        let mut pending_pack: remote::fetch::Prepare<'_, '_, _> = {
             let mut connection = remote.connect(remote::Direction::Fetch).await?;
             if let Some(f) = self.configure_connection.as_mut() {
                 f(&mut connection).map_err(Error::RemoteConnection)?;
@@ -143,19 +173,36 @@ impl PrepareFetch {
         if pending_pack.ref_map().object_hash != repo.object_hash() {
             unimplemented!("configure repository to expect a different object hash as advertised by the server")
         }
+        if use_remote_bundle_list {
+            if let Some(pairs) = pending_pack.bundle_uri().await? {
+                let mut list = gix_bundle::List::from_key_value_pairs(
+                    pairs.iter().map(|(key, value)| (key.as_ref(), value.as_ref())),
+                )?;
+                // Relative uris are relative to the remote, as if it was a directory.
+                let mut base = self.url.to_bstring();
+                base.push_byte(b'/');
+                list.resolve_relative_uris(base.as_ref());
+                let outcome = remote::fetch::bundle_uri::apply_list(repo, &list, &mut *progress, should_interrupt)?;
+                if let Some(token) = outcome.creation_token {
+                    util::set_bundle_creation_token(&mut config, token)?;
+                }
+            }
+        }
         let reflog_message = {
             let mut b = self.url.to_bstring();
             b.insert_str(0, "clone: from ");
             b
         };
-        let outcome = pending_pack
+        let mut pending_pack = pending_pack
             .with_write_packed_refs_only(true)
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
-            .with_shallow(self.shallow.clone())
-            .receive_inner(progress, should_interrupt)
-            .await?;
+            .with_shallow(self.shallow.clone());
+        if let Some(filter) = self.filter.clone() {
+            pending_pack = pending_pack.with_filter(filter);
+        }
+        let outcome = pending_pack.receive_inner(progress, should_interrupt).await?;
 
         util::append_config_to_repo_config(repo, config);
         util::update_head(
@@ -164,6 +211,16 @@ impl PrepareFetch {
             reflog_message.as_ref(),
             remote_name.as_ref(),
         )?;
+        if let Some(filter) = self.filter.as_ref() {
+            util::write_partial_clone_config(repo, remote_name.as_ref(), filter)?;
+            // Re-open to be able to fetch missing objects from the now configured promisor remote, keeping our
+            // configuration overrides for the repository and for lazy fetches alike.
+            let mut options = repo.options.clone();
+            options
+                .api_config_overrides
+                .extend(self.config_overrides.iter().cloned());
+            *repo = crate::ThreadSafeRepository::open_opts(repo.git_dir(), options)?.to_thread_local();
+        }
 
         Ok((self.repo.take().expect("still present"), outcome))
     }
@@ -53,6 +53,82 @@ fn write_to_local_config(config: &gix_config::File<'static>, mode: WriteMode) ->
     config.write_to_filter(&mut local_config, &mut |s| s.meta().source == gix_config::Source::Local)
 }
 
+/// Configure the remote named `remote_name` as promisor remote of a partial clone that was created with `filter`, just like git does.
+#[allow(clippy::result_large_err)]
+pub fn write_partial_clone_config(
+    repo: &Repository,
+    remote_name: &BStr,
+    filter: &gix_protocol::fetch::Filter,
+) -> Result<(), Error> {
+    use crate::config::tree::{Core, Extensions, Key, Remote};
+
+    let path = local_config_meta(repo).path.expect("local config with path set");
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut config = gix_config::File::from_path_no_includes(path, gix_config::Source::Local)?;
+    config.set_raw_value("core", None, Core::REPOSITORY_FORMAT_VERSION.name(), "1")?;
+    config.set_raw_value("remote", Some(remote_name), Remote::PROMISOR.name(), "true")?;
+    config.set_raw_value(
+        "remote",
+        Some(remote_name),
+        Remote::PARTIAL_CLONE_FILTER.name(),
+        filter.to_string().as_str(),
+    )?;
+    config.set_raw_value("extensions", None, Extensions::PARTIAL_CLONE.name(), remote_name)?;
+    config.write_to(&mut lock)?;
+    lock.commit()?;
+    Ok(())
+}
+
+/// Remember `uri` in `fetch.bundleURI` so its bundle list is used in later fetches, just like git does for lists with a heuristic.
+/// The value is also set in `config`, which is to be appended to the configuration of `repo`.
+#[allow(clippy::result_large_err)]
+pub fn write_bundle_uri_config(
+    repo: &Repository,
+    config: &mut gix_config::File<'static>,
+    uri: &BStr,
+) -> Result<(), Error> {
+    use crate::config::tree::{Fetch, Key};
+
+    let path = local_config_meta(repo).path.expect("local config with path set");
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut local_config = gix_config::File::from_path_no_includes(path, gix_config::Source::Local)?;
+    local_config.set_raw_value("fetch", None, Fetch::BUNDLE_URI.name(), uri)?;
+    local_config.write_to(&mut lock)?;
+    lock.commit()?;
+    config.set_raw_value("fetch", None, Fetch::BUNDLE_URI.name(), uri)?;
+    Ok(())
+}
+
+/// Set `fetch.bundleCreationToken` to `token` in `config`, which is to be appended to the configuration of `repo`, to keep the
+/// value that was stored when applying bundles once the local configuration file is rewritten.
+#[allow(clippy::result_large_err)]
+pub fn set_bundle_creation_token(config: &mut gix_config::File<'static>, token: u64) -> Result<(), Error> {
+    use crate::config::tree::{Fetch, Key};
+
+    config.set_raw_value(
+        "fetch",
+        None,
+        Fetch::BUNDLE_CREATION_TOKEN.name(),
+        token.to_string().as_str(),
+    )?;
+    Ok(())
+}
+
+/// Return `true` if `transfer.bundleURI` allows to unbundle the bundle list advertised by the remote before fetching.
+#[allow(clippy::result_large_err)]
+pub fn use_remote_bundle_list(repo: &Repository) -> Result<bool, Error> {
+    use crate::config::{cache::util::ApplyLeniency, tree::Transfer};
+
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter_by_key("transfer.bundleURI", &mut repo.filter_config_section())
+        .map(|val| Transfer::BUNDLE_URI.enrich_error(val))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?
+        .unwrap_or(false))
+}
+
 pub fn append_config_to_repo_config(repo: &mut Repository, config: gix_config::File<'static>) {
     let repo_config = gix_features::threading::OwnShared::make_mut(&mut repo.config.resolved);
     repo_config.append(config);
@@ -36,6 +36,12 @@ pub struct PrepareFetch {
     /// How to handle shallow clones
     #[cfg_attr(not(feature = "blocking-network-client"), allow(dead_code))]
     shallow: remote::fetch::Shallow,
+    /// The object filter to use for a partial clone.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    filter: Option<gix_protocol::fetch::Filter>,
+    /// The location of a bundle or bundle list to unbundle before fetching from the remote.
+    #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+    bundle_uri: Option<BString>,
 }
 
 /// The error returned by [`PrepareFetch::new()`].
@@ -134,6 +140,10 @@ impl PrepareFetch {
             #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
             configure_connection: None,
             shallow: remote::fetch::Shallow::NoChange,
+            #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+            filter: None,
+            #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
+            bundle_uri: None,
         })
     }
 }
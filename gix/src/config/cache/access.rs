@@ -80,6 +80,9 @@ impl Cache {
             if let Some(textconv) = section.value(config::tree::Diff::DRIVER_TEXTCONV.name) {
                 driver.binary_to_text_command = textconv.into_owned().into();
             }
+            if let Some(patterns) = section.value(config::tree::Diff::DRIVER_XFUNCNAME.name) {
+                driver.function_name_patterns = patterns.into_owned().into();
+            }
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
@@ -506,6 +506,10 @@ fn apply_environment_overrides(
                     let key = &gitoxide::Objects::CACHE_LIMIT;
                     (env(key), key.name)
                 },
+                {
+                    let key = &gitoxide::Objects::QUARANTINE_PATH;
+                    (env(key), key.name)
+                },
             ],
         ),
         (
@@ -4,7 +4,7 @@ pub use error::Error;
 
 use crate::config::cache::util::ApplyLeniency;
 use crate::{
-    bstr::{ByteSlice, ByteVec},
+    bstr::{BStr, ByteSlice, ByteVec},
     config::{
         cache::util::IgnoreEmptyPath,
         tree::{credential, gitoxide::Credentials, Core, Credential, Key},
@@ -47,7 +47,6 @@ impl Snapshot<'_> {
     /// - Upper-case scheme and host will be lower-cased automatically when parsing into a url, so prompts differ compared to git.
     /// - A **difference in prompt might affect the matching of getting existing stored credentials**, and it's a question of this being
     ///   a feature or a bug.
-    // TODO: when dealing with `http.*.*` configuration, generalize this algorithm as needed and support precedence.
     pub fn credential_helpers(
         &self,
         mut url: gix_url::Url,
@@ -63,6 +62,7 @@ impl Snapshot<'_> {
         let mut use_http_path = false;
         let url_had_user_initially = url.user().is_some();
         normalize(&mut url);
+        let url_to_match = gix_config::url_match::Url::parse(url.to_bstring().as_ref());
 
         if let Some(credential_sections) = self
             .repo
@@ -72,29 +72,12 @@ impl Snapshot<'_> {
         {
             for section in credential_sections {
                 let section = match section.header().subsection_name() {
-                    Some(pattern) => gix_url::parse(pattern).ok().and_then(|mut pattern| {
-                        normalize(&mut pattern);
-                        let is_http = matches!(pattern.scheme, gix_url::Scheme::Https | gix_url::Scheme::Http);
-                        let scheme = &pattern.scheme;
-                        let host = pattern.host();
-                        let ports = is_http
-                            .then(|| (pattern.port_or_default(), url.port_or_default()))
-                            .unwrap_or((pattern.port, url.port));
-                        let path = (!(is_http && pattern.path_is_root())).then_some(&pattern.path);
-
-                        if !path.map_or(true, |path| path == &url.path) {
-                            return None;
-                        }
-                        if pattern.user().is_some() && pattern.user() != url.user() {
-                            return None;
-                        }
-                        (scheme == &url.scheme && host_matches(host, url.host()) && ports.0 == ports.1).then_some((
-                            section,
-                            &credential::UrlParameter::HELPER,
-                            &credential::UrlParameter::USERNAME,
-                            &credential::UrlParameter::USE_HTTP_PATH,
-                        ))
-                    }),
+                    Some(pattern) => url_matches(&url, url_to_match.as_ref(), pattern).then_some((
+                        section,
+                        &credential::UrlParameter::HELPER,
+                        &credential::UrlParameter::USERNAME,
+                        &credential::UrlParameter::USE_HTTP_PATH,
+                    )),
                     None => Some((
                         section,
                         &Credential::HELPER,
@@ -175,23 +158,38 @@ impl Snapshot<'_> {
     }
 }
 
-fn host_matches(pattern: Option<&str>, host: Option<&str>) -> bool {
-    match (pattern, host) {
-        (Some(pattern), Some(host)) => {
-            let lfields = pattern.split('.');
-            let rfields = host.split('.');
-            if lfields.clone().count() != rfields.clone().count() {
-                return false;
-            }
-            lfields
-                .zip(rfields)
-                .all(|(pat, value)| gix_glob::wildmatch(pat.into(), value.into(), gix_glob::wildmatch::Mode::empty()))
-        }
-        (None, None) => true,
-        (Some(_), None) | (None, Some(_)) => false,
+/// Return `true` if the section for `pattern` applies to `url`, which is the parsed and normalized `url_to_match` if valid.
+///
+/// Like `git`, all sections with matching URL patterns apply regardless of how specific they are, and patterns without
+/// scheme like `example.com/path` match if all of their parts are the same as in `url`.
+fn url_matches(url: &gix_url::Url, url_to_match: Option<&gix_config::url_match::Url>, pattern: &BStr) -> bool {
+    match gix_config::url_match::Url::parse(pattern) {
+        Some(pattern) => url_to_match.map_or(false, |url| url.matches(&pattern).is_some()),
+        None => !pattern.contains_str("://") && partial_url_matches(url, pattern),
     }
 }
 
+fn partial_url_matches(url: &gix_url::Url, pattern: &BStr) -> bool {
+    let (user_and_host, path) = match pattern.split_once_str("/") {
+        Some((user_and_host, path)) => (user_and_host, Some(path.trim_end_with(|c| c == '/'))),
+        None => (pattern.as_bytes(), None),
+    };
+    let (user, host) = match user_and_host.rfind_byte(b'@') {
+        Some(at) => (Some(&user_and_host[..at]), &user_and_host[at + 1..]),
+        None => (None, user_and_host),
+    };
+    let url_host = match (url.host(), url.port) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (host, _) => host.unwrap_or_default().to_owned(),
+    };
+    let url_path = url.path.strip_prefix(b"/").unwrap_or(&url.path);
+    (host.is_empty() || host.eq_ignore_ascii_case(url_host.as_bytes()))
+        && user.map_or(true, |user| url.user().map(str::as_bytes) == Some(user))
+        && path.map_or(true, |path| {
+            path.is_empty() || path == url_path.trim_end_with(|c| c == '/')
+        })
+}
+
 fn normalize(url: &mut gix_url::Url) {
     if !url.path_is_root() && url.path.ends_with(b"/") {
         url.path.pop();
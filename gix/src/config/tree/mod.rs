@@ -59,6 +59,8 @@ pub(crate) mod root {
         pub const SAFE: sections::Safe = sections::Safe;
         /// The `ssh` section.
         pub const SSH: sections::Ssh = sections::Ssh;
+        /// The `transfer` section.
+        pub const TRANSFER: sections::Transfer = sections::Transfer;
         /// The `user` section.
         pub const USER: sections::User = sections::User;
         /// The `url` section.
@@ -89,6 +91,7 @@ pub(crate) mod root {
                 &Self::REMOTE,
                 &Self::SAFE,
                 &Self::SSH,
+                &Self::TRANSFER,
                 &Self::USER,
                 &Self::URL,
             ]
@@ -100,7 +103,7 @@ mod sections;
 pub use sections::{
     branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, ssh, Author,
     Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init, Mailmap,
-    Pack, Protocol, Push, Remote, Safe, Ssh, Url, User,
+    Pack, Protocol, Push, Remote, Safe, Ssh, Transfer, Url, User,
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{diff, Diff};
@@ -31,6 +31,12 @@ impl Diff {
     /// The `diff.<driver>.binary` key.
     pub const DRIVER_BINARY: Binary = Binary::new_with_validate("binary", &config::Tree::DIFF, validate::Binary)
         .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `diff.<driver>.xfuncname` key.
+    pub const DRIVER_XFUNCNAME: keys::String = keys::String::new_string("xfuncname", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")))
+        .with_deviation(
+            "built-in patterns of drivers for well-known languages, like 'rust' or 'python', are not available",
+        );
 
     /// The `diff.external` key.
     pub const EXTERNAL: keys::Program =
@@ -51,6 +57,7 @@ impl Section for Diff {
             &Self::DRIVER_TEXTCONV,
             &Self::DRIVER_ALGORITHM,
             &Self::DRIVER_BINARY,
+            &Self::DRIVER_XFUNCNAME,
             &Self::EXTERNAL,
         ]
     }
@@ -6,6 +6,9 @@ use crate::{
 impl Extensions {
     /// The `extensions.worktreeConfig` key.
     pub const WORKTREE_CONFIG: keys::Boolean = keys::Boolean::new_boolean("worktreeConfig", &config::Tree::EXTENSIONS);
+    /// The `extensions.partialClone` key, naming the remote objects missing in a partial clone can be fetched from.
+    pub const PARTIAL_CLONE: keys::RemoteName =
+        keys::RemoteName::new_remote_name("partialClone", &config::Tree::EXTENSIONS);
     /// The `extensions.objectFormat` key.
     pub const OBJECT_FORMAT: ObjectFormat =
         ObjectFormat::new_with_validate("objectFormat", &config::Tree::EXTENSIONS, validate::ObjectFormat).with_note(
@@ -41,7 +44,7 @@ impl Section for Extensions {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::OBJECT_FORMAT, &Self::WORKTREE_CONFIG]
+        &[&Self::OBJECT_FORMAT, &Self::PARTIAL_CLONE, &Self::WORKTREE_CONFIG]
     }
 }
 
@@ -4,6 +4,11 @@ use crate::{
 };
 
 impl Fetch {
+    /// The `fetch.bundleURI` key.
+    pub const BUNDLE_URI: keys::String = keys::String::new_string("bundleURI", &config::Tree::FETCH);
+    /// The `fetch.bundleCreationToken` key.
+    pub const BUNDLE_CREATION_TOKEN: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("bundleCreationToken", &config::Tree::FETCH);
     /// The `fetch.negotiationAlgorithm` key.
     pub const NEGOTIATION_ALGORITHM: NegotiationAlgorithm = NegotiationAlgorithm::new_with_validate(
         "negotiationAlgorithm",
@@ -23,6 +28,8 @@ impl Section for Fetch {
 
     fn keys(&self) -> &[&dyn Key] {
         &[
+            &Self::BUNDLE_URI,
+            &Self::BUNDLE_CREATION_TOKEN,
             &Self::NEGOTIATION_ALGORITHM,
             #[cfg(feature = "attributes")]
             &Self::RECURSE_SUBMODULES,
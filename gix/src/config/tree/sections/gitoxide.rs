@@ -426,6 +426,10 @@ mod subsections {
         /// The `gitoxide.objects.replaceRefBase` key.
         pub const REPLACE_REF_BASE: keys::Any =
             keys::Any::new("replaceRefBase", &Gitoxide::OBJECTS).with_environment_override("GIT_REPLACE_REF_BASE");
+        /// The `gitoxide.objects.quarantinePath` key.
+        pub const QUARANTINE_PATH: keys::Path = keys::Path::new_path("quarantinePath", &Gitoxide::OBJECTS)
+            .with_environment_override("GIT_QUARANTINE_PATH")
+            .with_note("If set, objects are in quarantine and reference transactions will be refused");
     }
 
     impl Section for Objects {
@@ -434,7 +438,7 @@ mod subsections {
         }
 
         fn keys(&self) -> &[&dyn Key] {
-            &[&Self::CACHE_LIMIT, &Self::REPLACE_REF_BASE]
+            &[&Self::CACHE_LIMIT, &Self::REPLACE_REF_BASE, &Self::QUARANTINE_PATH]
         }
 
         fn parent(&self) -> Option<&dyn Section> {
@@ -106,6 +106,11 @@ mod safe;
 pub struct Ssh;
 pub mod ssh;
 
+/// The `transfer` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Transfer;
+mod transfer;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
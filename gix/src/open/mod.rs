@@ -20,10 +20,16 @@ pub struct Permissions {
 /// The environment variables `GIT_REPLACE_REF_BASE` and `GIT_NO_REPLACE_OBJECTS` are mapped to `gitoxide.objects.replaceRefBase`
 /// and `gitoxide.objects.noReplace` respectively and then interpreted exactly as their environment variable counterparts.
 ///
+/// ### Quarantine
+///
+/// The environment variable `GIT_QUARANTINE_PATH` is mapped to `gitoxide.objects.quarantinePath`. If set, objects are considered to be
+/// in quarantine and all reference transactions will fail, just like in `git`.
+///
 /// Use [Permissions] to control which environment variables can be read, and config-overrides to control these values programmatically.
 #[derive(Clone)]
 pub struct Options {
     pub(crate) object_store_slots: gix_odb::store::init::Slots,
+    pub(crate) pack_backing: gix_pack::Backing,
     /// Define what is allowed while opening a repository.
     pub permissions: Permissions,
     pub(crate) git_dir_trust: Option<gix_sec::Trust>,
@@ -35,6 +41,7 @@ pub struct Options {
     pub(crate) api_config_overrides: Vec<BString>,
     pub(crate) cli_config_overrides: Vec<BString>,
     pub(crate) open_path_as_is: bool,
+    pub(crate) lazy_fetch: bool,
     /// Internal to pass an already obtained CWD on to where it may also be used. This avoids the CWD being queried more than once per repo.
     pub(crate) current_dir: Option<PathBuf>,
 }
@@ -7,6 +7,7 @@ impl Default for Options {
     fn default() -> Self {
         Options {
             object_store_slots: Default::default(),
+            pack_backing: Default::default(),
             permissions: Default::default(),
             git_dir_trust: None,
             filter_config_section: None,
@@ -14,6 +15,7 @@ impl Default for Options {
             lenient_config: true,
             bail_if_untrusted: false,
             open_path_as_is: false,
+            lazy_fetch: true,
             api_config_overrides: Vec::new(),
             cli_config_overrides: Vec::new(),
             current_dir: None,
@@ -63,6 +65,17 @@ impl Options {
         self
     }
 
+    /// Set how the contents of packs and their indices are accessed, with memory maps being the default.
+    ///
+    /// Use [`Backing::InMemory`](gix_pack::Backing::InMemory) to read them fully into memory when they are first needed
+    /// so that IO errors, like truncated files on network filesystems, are reported as errors instead of crashing the process.
+    /// [`Backing::PositionedRead`](gix_pack::Backing::PositionedRead) does the same without holding pack data files in memory
+    /// by reading only what's accessed.
+    pub fn pack_backing(mut self, backing: gix_pack::Backing) -> Self {
+        self.pack_backing = backing;
+        self
+    }
+
     // TODO: tests
     /// Set the given permissions, which are typically derived by a `Trust` level.
     pub fn permissions(mut self, permissions: Permissions) -> Self {
@@ -79,6 +92,16 @@ impl Options {
         self
     }
 
+    /// If `true`, default `true`, objects that are missing in a partial clone will be fetched from the promisor remote
+    /// named in `extensions.partialClone` when they are looked up.
+    ///
+    /// If `false`, these objects will appear to be missing instead, similar to `git --no-lazy-fetch`.
+    /// Note that this only works with the `blocking-network-client` feature enabled.
+    pub fn lazy_fetch(mut self, toggle: bool) -> Self {
+        self.lazy_fetch = toggle;
+        self
+    }
+
     /// Set the trust level of the `.git` directory we are about to open.
     ///
     /// This can be set manually to force trust even though otherwise it might
@@ -160,6 +183,7 @@ impl gix_sec::trust::DefaultForLevel for Options {
         match level {
             gix_sec::Trust::Full => Options {
                 object_store_slots: Default::default(),
+                pack_backing: Default::default(),
                 permissions: Permissions::default_for_level(level),
                 git_dir_trust: gix_sec::Trust::Full.into(),
                 filter_config_section: Some(config::section::is_trusted),
@@ -167,18 +191,22 @@ impl gix_sec::trust::DefaultForLevel for Options {
                 bail_if_untrusted: false,
                 lenient_config: true,
                 open_path_as_is: false,
+                lazy_fetch: true,
                 api_config_overrides: Vec::new(),
                 cli_config_overrides: Vec::new(),
                 current_dir: None,
             },
             gix_sec::Trust::Reduced => Options {
                 object_store_slots: gix_odb::store::init::Slots::Given(32), // limit resource usage
+                pack_backing: Default::default(),
                 permissions: Permissions::default_for_level(level),
                 git_dir_trust: gix_sec::Trust::Reduced.into(),
                 filter_config_section: Some(config::section::is_trusted),
                 bail_if_untrusted: false,
                 lenient_config: true,
                 open_path_as_is: false,
+                // don't let untrusted repositories cause network activity
+                lazy_fetch: false,
                 lossy_config: None,
                 api_config_overrides: Vec::new(),
                 cli_config_overrides: Vec::new(),
@@ -108,7 +108,7 @@ impl ThreadSafeRepository {
     ///
     /// Note that this will read various `GIT_*` environment variables to check for overrides, and is probably most useful when implementing
     /// custom hooks.
-    // TODO: tests, with hooks, GIT_QUARANTINE for ref-log and transaction control (needs gix-sec support to remove write access in gix-ref)
+    // TODO: tests, with hooks
     // TODO: The following vars should end up as overrides of the respective configuration values (see git-config).
     //       GIT_PROXY_SSL_CERT, GIT_PROXY_SSL_KEY, GIT_PROXY_SSL_CERT_PASSWORD_PROTECTED.
     //       GIT_PROXY_SSL_CAINFO, GIT_SSL_CIPHER_LIST, GIT_HTTP_MAX_REQUESTS, GIT_CURL_FTP_NO_EPSV,
@@ -160,11 +160,13 @@ impl ThreadSafeRepository {
         let Options {
             git_dir_trust,
             object_store_slots,
+            pack_backing,
             filter_config_section,
             lossy_config,
             lenient_config,
             bail_if_untrusted,
             open_path_as_is: _,
+            lazy_fetch,
             permissions:
                 Permissions {
                     ref env,
@@ -300,6 +302,13 @@ impl ThreadSafeRepository {
 
         refs.write_reflog = config::cache::util::reflog_or_default(config.reflog, worktree_dir.is_some());
         refs.namespace = config.refs_namespace.clone();
+        refs.quarantine = config
+            .resolved
+            .path_filter_by_key(
+                gitoxide::Objects::QUARANTINE_PATH.logical_name().as_str(),
+                &mut filter_config_section,
+            )
+            .is_some();
         let replacements = replacement_objects_refs_prefix(&config.resolved, lenient_config, filter_config_section)?
             .and_then(|prefix| {
                 let _span = gix_trace::detail!("find replacement objects");
@@ -319,15 +328,38 @@ impl ThreadSafeRepository {
             })
             .unwrap_or_default();
 
+        let objects_dir = common_dir_ref.join("objects");
+        let current_dir = current_dir.to_owned();
+        #[cfg(feature = "blocking-network-client")]
+        let promisor = (lazy_fetch && git_dir_trust == gix_sec::Trust::Full)
+            .then(|| {
+                crate::remote::promisor::LazyFetch::from_config(
+                    &config,
+                    filter_config_section,
+                    git_dir.clone(),
+                    worktree_dir.clone(),
+                    options.clone(),
+                )
+            })
+            .flatten()
+            .map(|fetch| std::sync::Arc::new(fetch) as std::sync::Arc<dyn gix_odb::promisor::Fetch>);
+        #[cfg(not(feature = "blocking-network-client"))]
+        let promisor = {
+            _ = lazy_fetch;
+            None
+        };
+
         Ok(ThreadSafeRepository {
             objects: OwnShared::new(gix_odb::Store::at_opts(
-                common_dir_ref.join("objects"),
+                objects_dir,
                 &mut replacements.into_iter(),
                 gix_odb::store::init::Options {
                     slots: object_store_slots,
                     object_hash: config.object_hash,
                     use_multi_pack_index: config.use_multi_pack_index,
-                    current_dir: current_dir.to_owned().into(),
+                    current_dir: current_dir.into(),
+                    promisor,
+                    pack_backing,
                 },
             )?),
             common_dir,
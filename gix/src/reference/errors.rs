@@ -98,3 +98,17 @@ pub mod find {
         PackedRefsOpen(#[from] gix_ref::packed::buffer::open::Error),
     }
 }
+
+///
+pub mod tracking {
+    /// The error returned by [`Reference::upstream()`](crate::Reference::upstream()) and
+    /// [`Reference::push_tracking()`](crate::Reference::push_tracking()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        TrackingRefName(#[from] crate::repository::branch_remote_tracking_ref_name::Error),
+        #[error(transparent)]
+        FindTrackingRef(#[from] crate::reference::find::existing::Error),
+    }
+}
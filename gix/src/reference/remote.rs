@@ -1,3 +1,4 @@
+use crate::reference::tracking;
 use crate::repository::{branch_remote_ref_name, branch_remote_tracking_ref_name};
 use crate::{remote, Reference};
 use gix_ref::FullNameRef;
@@ -44,4 +45,35 @@ impl<'repo> Reference<'repo> {
     ) -> Option<Result<Cow<'_, FullNameRef>, branch_remote_tracking_ref_name::Error>> {
         self.repo.branch_remote_tracking_ref_name(self.name(), direction)
     }
+
+    /// Return the upstream tracking branch of this reference, the one that `@{upstream}` would resolve to, like
+    /// `refs/remotes/origin/main` for `refs/heads/main`.
+    ///
+    /// Return `None` if no upstream is configured, or if the configured upstream isn't tracked locally.
+    /// It's an error if the tracking branch doesn't exist.
+    ///
+    /// See [`Repository::branch_remote_tracking_ref_name()`](crate::Repository::branch_remote_tracking_ref_name()) for details.
+    pub fn upstream(&self) -> Option<Result<Reference<'repo>, tracking::Error>> {
+        self.tracking(remote::Direction::Fetch)
+    }
+
+    /// Return the tracking branch of the remote branch that this reference would be pushed to, the one that `@{push}`
+    /// would resolve to.
+    ///
+    /// Return `None` if this reference wouldn't be pushed, or if the branch it would be pushed to isn't tracked locally.
+    /// It's an error if the tracking branch doesn't exist.
+    ///
+    /// See [`Repository::branch_remote_tracking_ref_name()`](crate::Repository::branch_remote_tracking_ref_name()) for details.
+    pub fn push_tracking(&self) -> Option<Result<Reference<'repo>, tracking::Error>> {
+        self.tracking(remote::Direction::Push)
+    }
+
+    fn tracking(&self, direction: remote::Direction) -> Option<Result<Reference<'repo>, tracking::Error>> {
+        let repo = self.repo;
+        let name = match repo.branch_remote_tracking_ref_name(self.name(), direction)? {
+            Ok(name) => name,
+            Err(err) => return Some(Err(err.into())),
+        };
+        Some(repo.find_reference(name.as_ref()).map_err(Into::into))
+    }
 }
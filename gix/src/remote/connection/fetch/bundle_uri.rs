@@ -0,0 +1,564 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_object::Exists;
+use gix_protocol::transport::client::Transport;
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{config, Prepare};
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::tree::Fetch,
+    Repository,
+};
+
+/// The error returned by [`apply()`] and [`apply_list()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Url(#[from] gix_url::parse::Error),
+    #[error("The bundle uri {uri:?} uses an unsupported scheme")]
+    UnsupportedScheme { uri: BString },
+    #[error("Could not read from \"{}\"", path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("Could not download {uri:?}")]
+    Download {
+        uri: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("Could not write the download of {uri:?} to \"{}\"", path.display())]
+    WriteDownload {
+        uri: BString,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not parse the bundle list at {uri:?}")]
+    ParseListConfig {
+        uri: BString,
+        source: gix_config::file::init::Error,
+    },
+    #[error("Could not parse the bundle list at {uri:?}")]
+    ParseList {
+        uri: BString,
+        source: gix_bundle::list::parse::Error,
+    },
+    #[error("Could not read the header of bundle {uri:?}")]
+    Header {
+        uri: BString,
+        source: gix_bundle::header::decode::Error,
+    },
+    #[error("The bundle {uri:?} uses {actual} object ids, but the repository uses {expected}")]
+    IncompatibleObjectHash {
+        uri: BString,
+        actual: gix_hash::Kind,
+        expected: gix_hash::Kind,
+    },
+    #[error("The prerequisites of bundle {uri:?} are missing")]
+    MissingPrerequisites { uri: BString },
+    #[error("The bundle {uri:?} doesn't contain the object that {name:?} points to")]
+    MissingTip { uri: BString, name: BString },
+    #[error(transparent)]
+    PackConfig(#[from] super::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    EditReferences(#[from] crate::reference::edit::Error),
+}
+
+/// The outcome of [`apply()`] and [`apply_list()`].
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The ids of the bundles that were unbundled, in the order in which they were applied.
+    pub applied: Vec<BString>,
+    /// The references in `refs/bundles/` that were created for the branches of the applied bundles.
+    pub refs: Vec<gix_ref::FullName>,
+    /// The ids of the bundles that were skipped, along with the reason for it.
+    pub skipped: Vec<(BString, Error)>,
+    /// The heuristic of the bundle list, which, if set, makes the list suitable for use in later fetches with `fetch.bundleURI`.
+    pub heuristic: Option<gix_bundle::list::Heuristic>,
+    /// The largest creation token of the applied bundles if the [creation token heuristic](gix_bundle::list::Heuristic::CreationToken)
+    /// was used and all bundles needed to complete their history were applied.
+    /// It was stored in `fetch.bundleCreationToken` so bundles at or below it aren't downloaded again.
+    pub creation_token: Option<u64>,
+}
+
+/// Obtain the content at `uri`, which is either a bundle list or a single bundle, and unbundle all bundles into `repo`
+/// so a following fetch only has to receive what isn't contained in them.
+///
+/// `uri` is a local path, a `file://` url, or an `http(s)://` url if one of the `blocking-http-transport-*` features is enabled.
+/// See [`apply_list()`] for details on how bundles are applied.
+pub fn apply(
+    repo: &Repository,
+    uri: &BStr,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    let downloads = Downloads::default();
+    let path = downloads.fetch(repo, uri)?;
+    let list = match read_bundle_header(&path, uri) {
+        Ok(_) => gix_bundle::List {
+            version: 1,
+            mode: gix_bundle::list::Mode::All,
+            heuristic: None,
+            entries: vec![gix_bundle::list::Entry {
+                id: "bundle".into(),
+                uri: uri.to_owned(),
+                creation_token: None,
+            }],
+        },
+        Err(_) => {
+            let mut list = parse_list(&path, uri)?;
+            list.resolve_relative_uris(uri);
+            list
+        }
+    };
+    apply_list_inner(repo, &list, &downloads, progress, should_interrupt)
+}
+
+/// Download the bundles in `list` and unbundle them into `repo` so a following fetch only has to receive what isn't contained in them.
+///
+/// Bundles are applied in the [order suggested by the list](gix_bundle::List::entries_in_application_order()), and
+/// bundles whose prerequisites are missing are retried after the other bundles were applied.
+/// With [`Mode::Any`](gix_bundle::list::Mode::Any), only the first bundle that can be applied is downloaded.
+///
+/// With the [creation token heuristic](gix_bundle::list::Heuristic::CreationToken), bundles are downloaded like `git` does,
+/// starting with the newest one and going back in time only until a bundle can be applied, before the newer ones are applied on top of it.
+/// Bundles with creation tokens at or below `fetch.bundleCreationToken` aren't considered, and once all bundles needed to
+/// complete the history were applied, the largest of their creation tokens is stored there.
+/// All relative uris in `list` must have been [resolved](gix_bundle::List::resolve_relative_uris()) already.
+///
+/// For each branch in an applied bundle, a reference in `refs/bundles/` is created so that its commits will be
+/// negotiated as common with the remote.
+///
+/// Like `git`, bundles that can't be downloaded or applied are skipped after emitting a warning, and they are listed
+/// in [`Outcome::skipped`].
+pub fn apply_list(
+    repo: &Repository,
+    list: &gix_bundle::List,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    apply_list_inner(repo, list, &Downloads::default(), progress, should_interrupt)
+}
+
+fn apply_list_inner(
+    repo: &Repository,
+    list: &gix_bundle::List,
+    downloads: &Downloads,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    let _span = gix_trace::coarse!("remote::fetch::bundle_uri::apply_list()");
+    if list.heuristic == Some(gix_bundle::list::Heuristic::CreationToken) {
+        return Ok(apply_by_creation_token(
+            repo,
+            list,
+            downloads,
+            progress,
+            should_interrupt,
+        ));
+    }
+    let mut out = Outcome::default();
+    let mut pending = Vec::new();
+    for entry in list.entries_in_application_order() {
+        let path = match downloads.fetch(repo, entry.uri.as_ref()) {
+            Ok(path) => path,
+            Err(err) => {
+                out.skip(entry, err);
+                continue;
+            }
+        };
+        if list.mode == gix_bundle::list::Mode::Any {
+            match unbundle(repo, entry, &path, progress, should_interrupt) {
+                Ok(refs) => {
+                    out.apply(entry, refs);
+                    break;
+                }
+                Err(err) => out.skip(entry, err),
+            }
+        } else {
+            pending.push((entry, path));
+        }
+    }
+
+    // Bundles may depend on each other in ways the list doesn't tell, so retry until no bundle can be applied anymore.
+    loop {
+        let mut failed = Vec::new();
+        let num_pending = pending.len();
+        for (entry, path) in pending {
+            match unbundle(repo, entry, &path, progress, should_interrupt) {
+                Ok(refs) => out.apply(entry, refs),
+                Err(Error::MissingPrerequisites { .. }) => failed.push((entry, path)),
+                Err(err) => out.skip(entry, err),
+            }
+        }
+        if failed.is_empty() || failed.len() == num_pending {
+            for (entry, _) in failed {
+                out.skip(entry, Error::MissingPrerequisites { uri: entry.uri.clone() });
+            }
+            break;
+        }
+        pending = failed;
+    }
+    Ok(out)
+}
+
+/// Apply the bundles of `list` newest first, and only go back to older bundles while the prerequisites of newer ones are missing.
+/// This way, the bundles that were applied in previous fetches, or whose content is present otherwise, aren't downloaded.
+fn apply_by_creation_token(
+    repo: &Repository,
+    list: &gix_bundle::List,
+    downloads: &Downloads,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Outcome {
+    enum Status {
+        Unknown,
+        MissingPrerequisites(PathBuf),
+        Done,
+    }
+
+    let mut out = Outcome {
+        heuristic: list.heuristic,
+        ..Default::default()
+    };
+    let previous_token = stored_creation_token(repo);
+    if list.max_creation_token().map_or(true, |token| token <= previous_token) {
+        return out;
+    }
+    let mut entries: Vec<_> = list
+        .entries
+        .iter()
+        .filter(|e| e.creation_token.unwrap_or_default() > previous_token)
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.creation_token));
+    let mut status: Vec<_> = entries.iter().map(|_| Status::Unknown).collect();
+
+    let mut idx = 0;
+    let mut towards_older = true;
+    let mut max_token = None;
+    let is_complete = loop {
+        let Some(entry) = entries.get(idx).copied() else {
+            break false;
+        };
+        let path = match std::mem::replace(&mut status[idx], Status::Done) {
+            Status::Unknown => match downloads.fetch(repo, entry.uri.as_ref()) {
+                Ok(path) => Some(path),
+                Err(err) => {
+                    out.skip(entry, err);
+                    towards_older = true;
+                    None
+                }
+            },
+            Status::MissingPrerequisites(path) => Some(path),
+            Status::Done => None,
+        };
+        if let Some(path) = path {
+            match unbundle(repo, entry, &path, progress, should_interrupt) {
+                Ok(refs) => {
+                    out.apply(entry, refs);
+                    max_token = max_token.max(entry.creation_token);
+                    towards_older = false;
+                }
+                Err(Error::MissingPrerequisites { .. }) => {
+                    status[idx] = Status::MissingPrerequisites(path);
+                    towards_older = true;
+                }
+                Err(err) => {
+                    out.skip(entry, err);
+                    towards_older = true;
+                }
+            }
+        }
+        match (towards_older, idx) {
+            (true, _) => idx += 1,
+            (false, 0) => break true,
+            (false, _) => idx -= 1,
+        }
+    };
+
+    for (entry, status) in entries.iter().zip(status) {
+        if let Status::MissingPrerequisites(_) = status {
+            out.skip(entry, Error::MissingPrerequisites { uri: entry.uri.clone() });
+        }
+    }
+    if is_complete {
+        out.creation_token = max_token;
+        if let Some(token) = max_token {
+            if let Err(_err) = store_creation_token(repo, token) {
+                gix_trace::warn!("Failed to store the creation token {token} in `fetch.bundleCreationToken`: {_err}");
+            }
+        }
+    }
+    out
+}
+
+/// Return the value of `fetch.bundleCreationToken`, which is `0` if it is unset or invalid, just like in `git`.
+fn stored_creation_token(repo: &Repository) -> u64 {
+    repo.config
+        .resolved
+        .integer_filter(
+            "fetch",
+            None,
+            Fetch::BUNDLE_CREATION_TOKEN.name,
+            &mut repo.filter_config_section(),
+        )
+        .and_then(Result::ok)
+        .and_then(|value| u64::try_from(value).ok())
+        .unwrap_or_default()
+}
+
+/// Write `token` to `fetch.bundleCreationToken` in the configuration file of the repository.
+fn store_creation_token(
+    repo: &Repository,
+    token: u64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let path = repo.common_dir().join("config");
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+    let mut config = gix_config::File::from_path_no_includes(path, gix_config::Source::Local)?;
+    config.set_raw_value(
+        "fetch",
+        None,
+        Fetch::BUNDLE_CREATION_TOKEN.name,
+        token.to_string().as_str(),
+    )?;
+    config.write_to(&mut lock)?;
+    lock.commit()?;
+    Ok(())
+}
+
+impl Outcome {
+    fn apply(&mut self, entry: &gix_bundle::list::Entry, refs: Vec<gix_ref::FullName>) {
+        self.applied.push(entry.id.clone());
+        self.refs.extend(refs);
+    }
+
+    fn skip(&mut self, entry: &gix_bundle::list::Entry, err: Error) {
+        gix_trace::warn!("Skipping bundle {:?}: {err}", entry.id);
+        self.skipped.push((entry.id.clone(), err));
+    }
+}
+
+/// Write the pack of the bundle at `path` into `repo` and create references for its branches.
+fn unbundle(
+    repo: &Repository,
+    entry: &gix_bundle::list::Entry,
+    path: &Path,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Vec<gix_ref::FullName>, Error> {
+    let (header, mut pack) = read_bundle_header(path, entry.uri.as_ref())?;
+    if header.object_hash != repo.object_hash() {
+        return Err(Error::IncompatibleObjectHash {
+            uri: entry.uri.clone(),
+            actual: header.object_hash,
+            expected: repo.object_hash(),
+        });
+    }
+    if header.prerequisites.iter().any(|p| !repo.objects.exists(&p.id)) {
+        return Err(Error::MissingPrerequisites { uri: entry.uri.clone() });
+    }
+
+    gix_pack::Bundle::write_to_directory(
+        &mut pack,
+        Some(&repo.objects.store_ref().path().join("pack")),
+        progress,
+        should_interrupt,
+        Some(Box::new(repo.objects.clone())),
+        gix_pack::bundle::write::Options {
+            thread_limit: config::index_threads(repo)?,
+            index_version: config::pack_index_version(repo)?,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            object_hash: repo.object_hash(),
+        },
+    )?;
+
+    // This also makes the new pack visible to the object database, which may otherwise not see it when refreshes are disabled.
+    if let Some(r) = header.refs.iter().find(|r| !repo.objects.exists(&r.id)) {
+        return Err(Error::MissingTip {
+            uri: entry.uri.clone(),
+            name: r.name.clone(),
+        });
+    }
+
+    // Like `git`, only branches are made available, as they are what's negotiated in the following fetch.
+    let edits: Vec<_> = header
+        .refs
+        .iter()
+        .filter_map(|r| {
+            let branch = r.name.strip_prefix(b"refs/heads/")?;
+            let mut name = BString::from("refs/bundles/");
+            name.push_str(branch);
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: format!("bundle-uri: unbundle {}", entry.uri).into(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: gix_ref::Target::Peeled(r.id),
+                },
+                name: name.try_into().ok()?,
+                deref: false,
+            })
+        })
+        .collect();
+    let edits = repo.edit_references(edits)?;
+    Ok(edits.into_iter().map(|edit| edit.name).collect())
+}
+
+fn read_bundle_header(
+    path: &Path,
+    uri: &BStr,
+) -> Result<(gix_bundle::Header, std::io::BufReader<std::fs::File>), Error> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path).map_err(|source| Error::Read {
+        path: path.to_owned(),
+        source,
+    })?);
+    let header = gix_bundle::Header::from_read(&mut file).map_err(|source| Error::Header {
+        uri: uri.to_owned(),
+        source,
+    })?;
+    Ok((header, file))
+}
+
+/// Parse the bundle list in `git-config` format at `path`, as downloaded from `uri`.
+fn parse_list(path: &Path, uri: &BStr) -> Result<gix_bundle::List, Error> {
+    let mut buf = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut buf))
+        .map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+    let config = gix_config::File::from_bytes_no_includes(&buf, gix_config::file::Metadata::api(), Default::default())
+        .map_err(|source| Error::ParseListConfig {
+            uri: uri.to_owned(),
+            source,
+        })?;
+    let pairs: Vec<(BString, BString)> = config
+        .sections()
+        .filter(|section| section.header().name().eq_ignore_ascii_case(b"bundle"))
+        .flat_map(|section| {
+            let prefix = match section.header().subsection_name() {
+                Some(id) => format!("bundle.{id}."),
+                None => "bundle.".into(),
+            };
+            section.body().clone().into_iter().map(move |(key, value)| {
+                let mut name = BString::from(prefix.as_str());
+                name.push_str(key.as_ref());
+                (name, value.into_owned())
+            })
+        })
+        .collect();
+    gix_bundle::List::from_key_value_pairs(pairs.iter().map(|(key, value)| (key.as_ref(), value.as_ref()))).map_err(
+        |source| Error::ParseList {
+            uri: uri.to_owned(),
+            source,
+        },
+    )
+}
+
+/// A utility to obtain local paths to the content of uris, downloading it into a temporary directory if needed.
+#[derive(Default)]
+struct Downloads {
+    /// A unique directory for all downloads, created on first use and removed along with its content when dropped.
+    dir: std::cell::RefCell<Option<tempfile::TempDir>>,
+    /// A counter to produce unique file names.
+    count: std::cell::Cell<usize>,
+}
+
+impl Downloads {
+    /// Return a path to the content at `uri`, which is downloaded if it isn't local.
+    #[cfg_attr(
+        not(any(
+            feature = "blocking-http-transport-reqwest",
+            feature = "blocking-http-transport-curl"
+        )),
+        allow(unused_variables)
+    )]
+    fn fetch(&self, repo: &Repository, uri: &BStr) -> Result<PathBuf, Error> {
+        let url = gix_url::parse(uri)?;
+        match url.scheme {
+            gix_url::Scheme::File => Ok(gix_path::from_bstr(url.path.as_bstr()).into_owned()),
+            #[cfg(any(
+                feature = "blocking-http-transport-reqwest",
+                feature = "blocking-http-transport-curl"
+            ))]
+            gix_url::Scheme::Http | gix_url::Scheme::Https => {
+                use gix_protocol::transport::client::http::{self, Http};
+
+                let download_err = |source: Box<dyn std::error::Error + Send + Sync + 'static>| Error::Download {
+                    uri: uri.to_owned(),
+                    source,
+                };
+                let mut client = http::Impl::default();
+                if let Some(options) = repo
+                    .transport_options(uri, None)
+                    .map_err(|err| download_err(err.into()))?
+                {
+                    client.configure(options.as_ref()).map_err(download_err)?;
+                }
+                let uri_str = uri.to_str().map_err(|err| download_err(err.into()))?;
+                let mut body = client
+                    .get(uri_str, uri_str, None::<&str>)
+                    .map_err(|err| download_err(err.into()))?
+                    .body;
+
+                let objects_dir = repo.objects.store_ref().path();
+                let mut dir = self.dir.borrow_mut();
+                let dir = match dir.as_mut() {
+                    Some(dir) => dir,
+                    None => dir.insert(
+                        tempfile::tempdir_in(objects_dir).map_err(|source| Error::WriteDownload {
+                            uri: uri.to_owned(),
+                            path: objects_dir.to_owned(),
+                            source,
+                        })?,
+                    ),
+                };
+                let path = dir.path().join(format!("tmp_uri_{}", self.count.get()));
+                self.count.set(self.count.get() + 1);
+                let write_err = |source| Error::WriteDownload {
+                    uri: uri.to_owned(),
+                    path: path.clone(),
+                    source,
+                };
+                let mut file = std::fs::File::create(&path).map_err(write_err)?;
+                std::io::copy(&mut body, &mut file).map_err(write_err)?;
+                Ok(path)
+            }
+            _ => Err(Error::UnsupportedScheme { uri: uri.to_owned() }),
+        }
+    }
+}
+
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Ask the remote for the bundle list it advertises with the `bundle-uri` command, returned as pairs of keys like
+    /// `bundle.<id>.uri` and their values, or `None` if the remote doesn't support the command.
+    ///
+    /// The pairs can be parsed with [`gix_bundle::List::from_key_value_pairs()`], and the list can be applied with
+    /// [`apply_list()`] before [receiving](Self::receive()) the pack.
+    #[gix_protocol::maybe_async::maybe_async]
+    pub async fn bundle_uri(&mut self) -> Result<Option<Vec<(BString, BString)>>, gix_protocol::bundle_uri::Error> {
+        let con = self.con.as_mut().expect("receive() wasn't called yet");
+        let capabilities = &self.ref_map.handshake.capabilities;
+        if self.ref_map.handshake.server_protocol_version != gix_protocol::transport::Protocol::V2
+            || !capabilities.contains(gix_protocol::Command::BundleUri.as_str())
+        {
+            return Ok(None);
+        }
+        let agent_feature = con.remote.repo.config.user_agent_tuple();
+        gix_protocol::bundle_uri(&mut con.transport, capabilities, vec![agent_feature], con.trace)
+            .await
+            .map(Some)
+    }
+}
@@ -1,6 +1,10 @@
 use super::Error;
 use crate::{
-    config::{cache::util::ApplyLeniency, tree::Pack},
+    bstr::{BStr, BString},
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Extensions, Fetch, Key, Pack, Remote},
+    },
     Repository,
 };
 
@@ -24,3 +28,47 @@ pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version,
         .with_leniency(repo.options.lenient_config)?
         .unwrap_or(gix_pack::index::Version::V2))
 }
+
+/// Return the uri of the bundle list to apply before fetching, as configured in `fetch.bundleURI`.
+pub fn bundle_uri(repo: &Repository) -> Option<BString> {
+    repo.config
+        .resolved
+        .string_filter("fetch", None, Fetch::BUNDLE_URI.name, &mut repo.filter_config_section())
+        .map(std::borrow::Cow::into_owned)
+}
+
+/// Return `true` if the remote named `remote_name` is a promisor remote, along with the object filter configured for it.
+pub fn promisor(
+    repo: &Repository,
+    remote_name: Option<&str>,
+) -> Result<(bool, Option<gix_protocol::fetch::Filter>), Error> {
+    let Some(name) = remote_name else {
+        return Ok((false, None));
+    };
+    let name: &BStr = name.into();
+    let mut filter = repo.filter_config_section();
+    let is_promisor = repo
+        .config
+        .resolved
+        .boolean_filter("remote", Some(name), Remote::PROMISOR.name, &mut filter)
+        .map(|value| Remote::PROMISOR.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::PromisorConfig)?
+        .unwrap_or(false)
+        || repo
+            .config
+            .resolved
+            .string_filter_by_key(Extensions::PARTIAL_CLONE.logical_name().as_str(), &mut filter)
+            .map_or(false, |partial_clone_remote| partial_clone_remote.as_ref() == name);
+    if !is_promisor {
+        return Ok((false, None));
+    }
+    let filter = repo
+        .config
+        .resolved
+        .string_filter("remote", Some(name), Remote::PARTIAL_CLONE_FILTER.name, &mut filter)
+        .map(|spec| gix_protocol::fetch::Filter::from_bytes(spec.as_ref()))
+        .transpose()?;
+    Ok((true, filter))
+}
@@ -45,6 +45,15 @@ pub enum Error {
     RejectShallowRemote,
     #[error(transparent)]
     NegotiationAlgorithmConfig(#[from] config::key::GenericErrorWithValue),
+    #[error("Could not determine if the remote is a promisor remote")]
+    PromisorConfig(#[source] config::boolean::Error),
+    #[error("The object filter configured in `remote.<name>.partialCloneFilter` is invalid")]
+    PartialCloneFilter(#[from] gix_protocol::fetch::filter::Error),
+    #[error("Failed to mark the received pack as coming from a promisor remote at \"{}\"", path.display())]
+    WritePromisorFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
@@ -193,6 +193,7 @@ where
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
             shallow: Default::default(),
+            filter: None,
         })
     }
 }
@@ -207,6 +208,8 @@ where
     }
 }
 
+///
+pub mod bundle_uri;
 mod config;
 mod receive_pack;
 ///
@@ -224,6 +227,7 @@ where
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
     shallow: remote::fetch::Shallow,
+    filter: Option<gix_protocol::fetch::Filter>,
 }
 
 /// Builder
@@ -265,6 +269,15 @@ where
         self.shallow = shallow;
         self
     }
+
+    /// Have the server omit objects according to `filter`, which is required to support the `filter` capability.
+    ///
+    /// If unset and the remote is a promisor remote, the filter configured in `remote.<name>.partialCloneFilter` is used.
+    /// Packs received with a filter or from a promisor remote are marked with a `.promisor` file.
+    pub fn with_filter(mut self, filter: gix_protocol::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
 }
 
 impl<'remote, 'repo, T> Drop for Prepare<'remote, 'repo, T>
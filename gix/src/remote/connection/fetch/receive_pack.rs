@@ -71,6 +71,8 @@ where
     /// ### Configuration
     ///
     /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    /// - `fetch.bundleURI` is read to [apply the bundles](fetch::bundle_uri::apply()) at the given uri before fetching, unless
+    ///   this is a dry-run.
     ///
     #[gix_protocol::maybe_async::maybe_async]
     pub async fn receive<P>(self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
@@ -78,6 +80,18 @@ where
         P: gix_features::progress::NestedProgress,
         P::SubProgress: 'static,
     {
+        let repo = self
+            .con
+            .as_ref()
+            .expect("receive() can only be called once")
+            .remote
+            .repo;
+        if let (fetch::DryRun::No, Some(uri)) = (self.dry_run, config::bundle_uri(repo)) {
+            // Like `git`, failing to apply bundles isn't fatal, as the remote sends what's missing.
+            if let Err(_err) = fetch::bundle_uri::apply(repo, uri.as_ref(), &mut progress, should_interrupt) {
+                gix_trace::warn!("Failed to fetch bundles from {uri:?}: {_err}");
+            }
+        }
         self.receive_inner(&mut progress, should_interrupt).await
     }
 
@@ -116,6 +130,19 @@ where
             }
             arguments.use_include_tag();
         }
+        let (is_promisor, configured_filter) =
+            config::promisor(repo, con.remote.name().and_then(|name| name.as_symbol()))?;
+        let filter = self.filter.take().or(configured_filter).filter(|_| {
+            // Like git, we proceed without filter, receiving all objects.
+            let can_use_filter = arguments.can_use_filter();
+            if !can_use_filter {
+                gix_trace::warn!("The server doesn't support filters and will send all objects");
+            }
+            can_use_filter
+        });
+        if let Some(filter) = &filter {
+            arguments.filter(&filter.to_string());
+        }
         let (shallow_commits, mut shallow_lock) = add_shallow_args(&mut arguments, &self.shallow, repo)?;
 
         if self.ref_map.object_hash != repo.object_hash() {
@@ -140,7 +167,7 @@ where
             .into_negotiator();
         let graph_repo = {
             let mut r = repo.clone();
-            // assure that checking for unknown server refs doesn't trigger ODB refreshes.
+            // assure that checking for unknown server refs doesn't trigger ODB refreshes, or lazy fetches from promisor remotes.
             r.objects.refresh = RefreshMode::Never;
             // we cache everything of importance in the graph and thus don't need an object cache.
             r.objects.unset_object_cache();
@@ -167,7 +194,7 @@ where
                 remote_ref_target_known,
             } => {
                 negotiate::add_wants(
-                    repo,
+                    &graph_repo,
                     &mut arguments,
                     &self.ref_map,
                     remote_ref_target_known,
@@ -225,7 +252,7 @@ where
                         protocol_version,
                         &mut reader,
                         is_done,
-                        !is_done,
+                        arguments.can_use_no_done(),
                     )
                     .await?;
                     let has_pack = response.has_pack();
@@ -281,6 +308,12 @@ where
                         })),
                         options,
                     )?;
+                    if is_promisor || filter.is_some() {
+                        if let Some(data_path) = res.data_path.as_deref() {
+                            let path = data_path.with_extension(gix_odb::promisor::MARKER_EXTENSION);
+                            std::fs::write(&path, b"").map_err(|err| Error::WritePromisorFile { path, source: err })?;
+                        }
+                    }
                     // Assure the final flush packet is consumed.
                     #[cfg(feature = "async-network-client")]
                     let has_read_to_end = { rd.get_ref().stopped_at().is_some() };
@@ -315,6 +348,7 @@ where
                         crate::shallow::write(shallow_lock, shallow_commits, previous_response.shallow_updates())?;
                     }
                 }
+                refs::apply_wanted_refs(&mut self.ref_map.mappings, previous_response.wanted_refs());
                 (write_pack_bundle, Some(outcome::Negotiate { graph, rounds }))
             }
         };
@@ -43,6 +43,43 @@ impl From<Mode> for Update {
     }
 }
 
+/// Replace the object ids of remote references in `mappings` with the ones in `wanted_refs`, as sent by the server in response
+/// to `want-ref` arguments.
+///
+/// This is necessary as references may have changed on the remote since they were listed, while the received pack
+/// is based on the state the server reported in `wanted_refs`.
+pub(crate) fn apply_wanted_refs(
+    mappings: &mut [fetch::Mapping],
+    wanted_refs: &[gix_protocol::fetch::response::WantedRef],
+) {
+    use gix_protocol::handshake::Ref;
+    for mapping in mappings {
+        let Source::Ref(remote) = &mut mapping.remote else {
+            continue;
+        };
+        let (name, id, _) = remote.unpack();
+        let Some(wanted) = wanted_refs.iter().find(|wanted| wanted.path == name) else {
+            continue;
+        };
+        if id == Some(wanted.id.as_ref()) {
+            continue;
+        }
+        let full_ref_name = name.to_owned();
+        *remote = match remote {
+            Ref::Peeled { .. } | Ref::Direct { .. } => Ref::Direct {
+                full_ref_name,
+                object: wanted.id,
+            },
+            Ref::Symbolic { target, .. } | Ref::Unborn { target, .. } => Ref::Symbolic {
+                full_ref_name,
+                target: target.clone(),
+                tag: None,
+                object: wanted.id,
+            },
+        };
+    }
+}
+
 /// Update all refs as derived from `refmap.mappings` and produce an `Outcome` informing about all applied changes in detail, with each
 /// [`update`][Update] corresponding to the [`fetch::Mapping`] of at the same index.
 /// If `dry_run` is true, ref transactions won't actually be applied, but are assumed to work without error so the underlying
@@ -679,6 +679,57 @@ mod update {
         );
     }
 
+    #[test]
+    fn wanted_refs_override_the_advertised_object() -> Result {
+        let repo = repo("two-origins");
+        let (mut mappings, specs) = mapping_from_spec("refs/heads/main:refs/remotes/origin/new-main", &repo);
+        assert_eq!(mappings.len(), 1);
+        let wanted = repo
+            .head_id()?
+            .object()?
+            .into_commit()
+            .parent_ids()
+            .next()
+            .expect("parent")
+            .detach();
+        fetch::refs::apply_wanted_refs(
+            &mut mappings,
+            &[
+                gix_protocol::fetch::response::WantedRef {
+                    id: wanted,
+                    path: "refs/heads/main".into(),
+                },
+                gix_protocol::fetch::response::WantedRef {
+                    id: hex_to_id("0000000000000000000000000000000000000001"),
+                    path: "refs/heads/does-not-match".into(),
+                },
+            ],
+        );
+        let out = fetch::refs::update(
+            &repo,
+            prefixed("action"),
+            &mappings,
+            &specs,
+            &[],
+            fetch::Tags::None,
+            fetch::DryRun::Yes,
+            fetch::WritePackedRefs::Never,
+        )?;
+
+        assert_eq!(out.edits.len(), 1);
+        match &out.edits[0].change {
+            Change::Update { new, .. } => {
+                assert_eq!(
+                    new.try_id(),
+                    Some(wanted.as_ref()),
+                    "the object the server reported for the wanted ref is used, not the one seen when listing refs"
+                );
+            }
+            _ => unreachable!("only updates"),
+        }
+        Ok(())
+    }
+
     #[test]
     fn remote_refs_cannot_map_to_local_head() {
         let repo = repo("two-origins");
@@ -0,0 +1,74 @@
+use gix_features::progress::Progress;
+use gix_protocol::transport::client::Transport;
+
+use crate::remote::{connection::ref_map, Connection};
+
+/// The error returned by [`Connection::object_info()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Handshake(#[from] ref_map::Error),
+    #[error(transparent)]
+    ObjectInfo(#[from] gix_protocol::object_info::Error),
+}
+
+impl gix_protocol::transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Handshake(err) => err.is_spurious(),
+            Error::ObjectInfo(err) => err.is_spurious(),
+        }
+    }
+}
+
+impl<'remote, 'repo, T> Connection<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Ask the remote for the size of the objects with `ids` without downloading them, in the order of `ids`.
+    ///
+    /// Objects the remote doesn't know have no size. This requires protocol V2 and a server that advertises the
+    /// `object-info` command, and fails otherwise.
+    ///
+    /// # Consumption
+    ///
+    /// Like [`ref_map()`][Connection::ref_map()], the connection is consumed as it's only used for a single interaction.
+    ///
+    /// ### Configuration
+    ///
+    /// - `gitoxide.userAgent` is read to obtain the application user agent for git servers and for HTTP servers as well.
+    #[allow(clippy::result_large_err)]
+    #[gix_protocol::maybe_async::maybe_async]
+    pub async fn object_info(
+        mut self,
+        ids: impl IntoIterator<Item = gix_hash::ObjectId>,
+        progress: impl Progress,
+    ) -> Result<Vec<gix_protocol::object_info::Info>, Error> {
+        let _span = gix_trace::coarse!("remote::Connection::object_info()");
+        let res = self.object_info_inner(ids, progress).await;
+        gix_protocol::indicate_end_of_interaction(&mut self.transport, self.trace)
+            .await
+            .ok();
+        res
+    }
+
+    #[allow(clippy::result_large_err)]
+    #[gix_protocol::maybe_async::maybe_async]
+    async fn object_info_inner(
+        &mut self,
+        ids: impl IntoIterator<Item = gix_hash::ObjectId>,
+        progress: impl Progress,
+    ) -> Result<Vec<gix_protocol::object_info::Info>, Error> {
+        let outcome = self.handshake(Vec::new(), progress).await?;
+        let agent_feature = self.remote.repo.config.user_agent_tuple();
+        Ok(gix_protocol::object_info(
+            &mut self.transport,
+            &outcome.capabilities,
+            ids,
+            vec![agent_feature],
+            self.trace,
+        )
+        .await?)
+    }
+}
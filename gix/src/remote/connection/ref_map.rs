@@ -188,35 +188,7 @@ where
         mut progress: impl Progress,
     ) -> Result<HandshakeWithRefs, Error> {
         let _span = gix_trace::coarse!("remote::Connection::fetch_refs()");
-        let mut credentials_storage;
-        let url = self.transport.to_url();
-        let authenticate = match self.authenticate.as_mut() {
-            Some(f) => f,
-            None => {
-                let url = self.remote.url(Direction::Fetch).map_or_else(
-                    || gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"),
-                    ToOwned::to_owned,
-                );
-                credentials_storage = self.configured_credentials(url)?;
-                &mut credentials_storage
-            }
-        };
-
-        if self.transport_options.is_none() {
-            self.transport_options = self
-                .remote
-                .repo
-                .transport_options(url.as_ref(), self.remote.name().map(crate::remote::Name::as_bstr))
-                .map_err(|err| Error::GatherTransportConfig {
-                    source: err,
-                    url: url.into_owned(),
-                })?;
-        }
-        if let Some(config) = self.transport_options.as_ref() {
-            self.transport.configure(&**config)?;
-        }
-        let mut outcome =
-            gix_protocol::fetch::handshake(&mut self.transport, authenticate, extra_parameters, &mut progress).await?;
+        let mut outcome = self.handshake(extra_parameters, &mut progress).await?;
         let refs = match outcome.refs.take() {
             Some(refs) => refs,
             None => {
@@ -250,6 +222,45 @@ where
         };
         Ok(HandshakeWithRefs { outcome, refs })
     }
+
+    /// Perform the handshake with the remote, configuring credentials and the transport beforehand, with
+    /// `extra_parameters` passed to the server.
+    #[allow(clippy::result_large_err)]
+    #[gix_protocol::maybe_async::maybe_async]
+    pub(crate) async fn handshake(
+        &mut self,
+        extra_parameters: Vec<(String, Option<String>)>,
+        mut progress: impl Progress,
+    ) -> Result<gix_protocol::handshake::Outcome, Error> {
+        let mut credentials_storage;
+        let url = self.transport.to_url();
+        let authenticate = match self.authenticate.as_mut() {
+            Some(f) => f,
+            None => {
+                let url = self.remote.url(Direction::Fetch).map_or_else(
+                    || gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"),
+                    ToOwned::to_owned,
+                );
+                credentials_storage = self.configured_credentials(url)?;
+                &mut credentials_storage
+            }
+        };
+
+        if self.transport_options.is_none() {
+            self.transport_options = self
+                .remote
+                .repo
+                .transport_options(url.as_ref(), self.remote.name().map(crate::remote::Name::as_bstr))
+                .map_err(|err| Error::GatherTransportConfig {
+                    source: err,
+                    url: url.into_owned(),
+                })?;
+        }
+        if let Some(config) = self.transport_options.as_ref() {
+            self.transport.configure(&**config)?;
+        }
+        Ok(gix_protocol::fetch::handshake(&mut self.transport, authenticate, extra_parameters, &mut progress).await?)
+    }
 }
 
 /// Assume sha1 if server says nothing, otherwise configure anything beyond sha1 in the local repo configuration
@@ -13,7 +13,7 @@ pub mod negotiate {
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 pub use super::connection::fetch::{
-    outcome, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
+    bundle_uri, outcome, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
 };
 
 /// If `Yes`, don't really make changes but do as much as possible to get an idea of what would be done.
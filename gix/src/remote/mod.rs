@@ -52,7 +52,10 @@ pub mod connect;
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 mod connection;
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
-pub use connection::{ref_map, AuthenticateFn, Connection};
+pub use connection::{object_info, ref_map, AuthenticateFn, Connection};
+
+#[cfg(feature = "blocking-network-client")]
+pub(crate) mod promisor;
 
 ///
 pub mod save;
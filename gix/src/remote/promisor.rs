@@ -0,0 +1,107 @@
+//! Fetching objects that are missing in a partial clone from the promisor remote, on demand.
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Mutex, PoisonError},
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::tree::{Extensions, Fetch, Key},
+    open, remote, ThreadSafeRepository,
+};
+
+/// An implementation of [`gix_odb::promisor::Fetch`] which fetches missing objects from the remote configured in
+/// `extensions.partialClone`.
+///
+/// To do so, it opens the repository once more on first use, without the ability to fetch lazily to prevent recursion,
+/// and asks for all missing objects without negotiation to avoid receiving thin packs with bases that are missing locally as well.
+/// The opened repository is kept for all subsequent fetches.
+pub(crate) struct LazyFetch {
+    git_dir: PathBuf,
+    worktree_dir: Option<PathBuf>,
+    options: open::Options,
+    remote_name: BString,
+    repo: Mutex<Option<ThreadSafeRepository>>,
+}
+
+impl LazyFetch {
+    /// Return a new instance if `config` names a promisor remote, for use with the repository at `git_dir` that is being
+    /// opened with `options`.
+    pub(crate) fn from_config(
+        config: &crate::config::Cache,
+        mut filter_config_section: fn(&gix_config::file::Metadata) -> bool,
+        git_dir: PathBuf,
+        worktree_dir: Option<PathBuf>,
+        mut options: open::Options,
+    ) -> Option<Self> {
+        let remote_name = config
+            .resolved
+            .string_filter_by_key(
+                Extensions::PARTIAL_CLONE.logical_name().as_str(),
+                &mut filter_config_section,
+            )
+            .filter(|name| !name.is_empty())?
+            .into_owned();
+        options.lazy_fetch = false;
+        options
+            .api_config_overrides
+            .push(format!("{}=noop", Fetch::NEGOTIATION_ALGORITHM.logical_name()).into());
+        Some(LazyFetch {
+            git_dir,
+            worktree_dir,
+            options,
+            remote_name,
+            repo: Mutex::new(None),
+        })
+    }
+}
+
+impl std::fmt::Debug for LazyFetch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyFetch")
+            .field("git_dir", &self.git_dir)
+            .field("remote_name", &self.remote_name)
+            .finish()
+    }
+}
+
+impl gix_odb::promisor::Fetch for LazyFetch {
+    fn fetch(&self, ids: &[gix_hash::ObjectId]) -> Result<(), gix_odb::promisor::Error> {
+        let _span = gix_trace::coarse!("gix::remote::promisor::fetch()", num_ids = ids.len());
+        let repo = {
+            let mut repo = self.repo.lock().unwrap_or_else(PoisonError::into_inner);
+            if repo.is_none() {
+                *repo = Some(ThreadSafeRepository::open_from_paths(
+                    self.git_dir.clone(),
+                    self.worktree_dir.clone(),
+                    self.options.clone(),
+                )?);
+            }
+            repo.as_ref().expect("just set").to_thread_local()
+        };
+        let mut remote = repo
+            .find_remote(self.remote_name.as_bstr())?
+            .with_fetch_tags(remote::fetch::Tags::None);
+        remote.replace_refspecs(
+            ids.iter().map(|id| BString::from(id.to_string())),
+            remote::Direction::Fetch,
+        )?;
+        let outcome = remote
+            .connect(remote::Direction::Fetch)?
+            .prepare_fetch(gix_features::progress::Discard, Default::default())?
+            .receive(gix_features::progress::Discard, &AtomicBool::default())?;
+        // No refs are updated, so the `.keep` file would remain otherwise.
+        if let remote::fetch::Status::Change {
+            write_pack_bundle:
+                gix_pack::bundle::write::Outcome {
+                    keep_path: Some(keep_path),
+                    ..
+                },
+            ..
+        } = outcome.status
+        {
+            std::fs::remove_file(keep_path)?;
+        }
+        Ok(())
+    }
+}
@@ -50,20 +50,12 @@ impl crate::Repository {
                     .map(|name| crate::config::tree::branch::Merge::try_into_fullrefname(name).map_err(Into::into))
             }
             remote::Direction::Push => {
-                let remote = match self.branch_remote(name.shorten(), direction)? {
+                let remote = match self.push_remote(name)? {
                     Ok(r) => r,
                     Err(err) => return Some(Err(err.into())),
                 };
                 if remote.push_specs.is_empty() {
-                    let push_default = match self
-                        .config
-                        .resolved
-                        .string(Push.name(), None, Push::DEFAULT.name)
-                        .map_or(Ok(Default::default()), |v| {
-                            Push::DEFAULT
-                                .try_into_default(v)
-                                .with_lenient_default(self.config.lenient_config)
-                        }) {
+                    let push_default = match self.push_default() {
                         Ok(v) => v,
                         Err(err) => return Some(Err(err.into())),
                     };
@@ -97,6 +89,12 @@ impl crate::Repository {
     ///   `refs/heads/*:refs/remotes/origin/*` fetch refspec, hence `refs/remotes/origin/main` is returned.
     ///   Note that `push` refspecs can be used to map `main` to `other` (using a push refspec `refs/heads/main:refs/heads/other`),
     ///   which would then lead to `refs/remotes/origin/other` to be returned instead.
+    ///   Without push refspecs, `push.default` decides: `current` and `matching` push to the branch of the same name,
+    ///   `upstream` pushes to the upstream branch whose tracking branch is returned, `simple` only yields a tracking branch
+    ///   if it's the same as the upstream tracking branch, and `nothing` returns `None`.
+    ///
+    /// If the remote for fetching is `.`, the current repository is the remote, and the value of `branch.<name>.merge`
+    /// is returned as is. If there is no remote to push to, `origin` is used if it exists.
     ///
     /// Note that if there is an ambiguity, that is if `name` maps to multiple tracking branches, the first matching mapping
     /// is returned, according to the order in which the fetch or push refspecs occur in the configuration file.
@@ -108,20 +106,60 @@ impl crate::Repository {
         name: &FullNameRef,
         direction: remote::Direction,
     ) -> Option<Result<Cow<'_, FullNameRef>, branch_remote_tracking_ref_name::Error>> {
-        let remote_ref = match self.branch_remote_ref_name(name, direction)? {
-            Ok(r) => r,
-            Err(err) => return Some(Err(err.into())),
-        };
-        let remote = match self.branch_remote(name.shorten(), direction)? {
-            Ok(r) => r,
-            Err(err) => return Some(Err(err.into())),
-        };
-
-        if remote.fetch_specs.is_empty() {
-            return None;
+        match direction {
+            remote::Direction::Fetch => {
+                let remote_ref = match self.branch_remote_ref_name(name, direction)? {
+                    Ok(r) => r,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                // A remote named `.` is this repository, so the branch to merge is tracked by itself.
+                if self.branch_remote_name(name.shorten(), direction)?.as_bstr() == "." {
+                    return Some(Ok(remote_ref));
+                }
+                let remote = match self.branch_remote(name.shorten(), direction)? {
+                    Ok(r) => r,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                matching_remote(remote_ref.as_ref(), remote.fetch_specs.iter(), self.object_hash())
+                    .map(|res| res.map_err(Into::into))
+            }
+            remote::Direction::Push => {
+                let remote = match self.push_remote(name)? {
+                    Ok(r) => r,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                let tracking_ref_of = |remote_ref: &FullNameRef| {
+                    matching_remote(remote_ref, remote.fetch_specs.iter(), self.object_hash())
+                        .map(|res| res.map_err(Into::into))
+                };
+                if !remote.push_specs.is_empty() {
+                    return match self.branch_remote_ref_name(name, direction)? {
+                        Ok(remote_ref) => tracking_ref_of(remote_ref.as_ref()),
+                        Err(err) => Some(Err(err.into())),
+                    };
+                }
+                let push_default = match self.push_default() {
+                    Ok(v) => v,
+                    Err(err) => return Some(Err(branch_remote_ref_name::Error::from(err).into())),
+                };
+                match push_default {
+                    push::Default::Nothing => None,
+                    push::Default::Current | push::Default::Matching => tracking_ref_of(name),
+                    push::Default::Upstream => self.branch_remote_tracking_ref_name(name, remote::Direction::Fetch),
+                    push::Default::Simple => {
+                        let upstream = match self.branch_remote_tracking_ref_name(name, remote::Direction::Fetch)? {
+                            Ok(r) => r,
+                            Err(err) => return Some(Err(err)),
+                        };
+                        match tracking_ref_of(name)? {
+                            Ok(current) if current == upstream => Some(Ok(current)),
+                            Ok(_different_tracking_ref) => None,
+                            Err(err) => Some(Err(err)),
+                        }
+                    }
+                }
+            }
         }
-        matching_remote(remote_ref.as_ref(), remote.fetch_specs.iter(), self.object_hash())
-            .map(|res| res.map_err(Into::into))
     }
 
     /// Returns the unvalidated name of the remote associated with the given `short_branch_name`,
@@ -180,6 +218,25 @@ impl crate::Repository {
                 remote::Name::Symbol(_) => None,
             })
     }
+
+    /// Return the remote to push `name` to, falling back to the remote named `origin` if none is configured, just like Git.
+    fn push_remote(&self, name: &FullNameRef) -> Option<Result<crate::Remote<'_>, remote::find::existing::Error>> {
+        match self.branch_remote_name(name.shorten(), remote::Direction::Push) {
+            Some(_) => self.branch_remote(name.shorten(), remote::Direction::Push),
+            None => self.try_find_remote("origin").map(|res| res.map_err(Into::into)),
+        }
+    }
+
+    fn push_default(&self) -> Result<push::Default, crate::config::key::GenericErrorWithValue> {
+        self.config
+            .resolved
+            .string(Push.name(), None, Push::DEFAULT.name)
+            .map_or(Ok(Default::default()), |v| {
+                Push::DEFAULT
+                    .try_into_default(v)
+                    .with_lenient_default(self.config.lenient_config)
+            })
+    }
 }
 
 fn matching_remote<'a>(
@@ -203,11 +260,8 @@ fn matching_remote<'a>(
         })
         .into_iter(),
     );
-    out.mappings.into_iter().next().and_then(|m| {
-        m.rhs.map(|name| {
-            FullName::try_from(name.into_owned())
-                .map(Cow::Owned)
-                .map_err(Into::into)
-        })
-    })
+    out.mappings
+        .into_iter()
+        .next()
+        .and_then(|m| m.rhs.map(|name| FullName::try_from(name.into_owned()).map(Cow::Owned)))
 }
@@ -104,6 +104,7 @@ impl crate::Repository {
                         config: &gix_config::File<'static>,
                         key_str: &'static str,
                         key: &'static config::tree::http::SslVersion,
+                        url: Option<&gix_config::url_match::Url>,
                         mut filter: fn(&gix_config::file::Metadata) -> bool,
                         lenient: bool,
                     ) -> Result<Option<SslVersion>, config::transport::Error> {
@@ -112,16 +113,18 @@ impl crate::Repository {
                             key.logical_name(),
                             "BUG: hardcoded and generated key names must match"
                         );
-                        config
-                            .string_filter_by_key(key_str, &mut filter)
-                            .filter(|v| !v.is_empty())
-                            .map(|v| {
-                                key.try_into_ssl_version(v)
-                                    .map_err(crate::config::transport::http::Error::from)
-                            })
-                            .transpose()
-                            .with_leniency(lenient)
-                            .map_err(Into::into)
+                        match url {
+                            Some(url) => config.string_filter_by_url(key_str, url, &mut filter),
+                            None => config.string_filter_by_key(key_str, &mut filter),
+                        }
+                        .filter(|v| !v.is_empty())
+                        .map(|v| {
+                            key.try_into_ssl_version(v)
+                                .map_err(crate::config::transport::http::Error::from)
+                        })
+                        .transpose()
+                        .with_leniency(lenient)
+                        .map_err(Into::into)
                     }
 
                     fn proxy(
@@ -143,13 +146,14 @@ impl crate::Repository {
 
                     let mut opts = http::Options::default();
                     let config = &self.config.resolved;
+                    let url_to_match = gix_config::url_match::Url::parse(url.to_bstring().as_ref()).unwrap_or_default();
                     let mut trusted_only = self.filter_config_section();
                     let lenient = self.config.lenient_config;
                     opts.extra_headers = {
                         let key = "http.extraHeader";
                         debug_assert_eq!(key, &config::tree::Http::EXTRA_HEADER.logical_name());
                         config
-                            .strings_filter_by_key(key, &mut trusted_only)
+                            .strings_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .map(|values| config::tree::Http::EXTRA_HEADER.try_into_extra_header(values))
                             .transpose()
                             .map_err(|err| config::transport::Error::IllformedUtf8 {
@@ -164,10 +168,12 @@ impl crate::Repository {
 
                         config::tree::Http::FOLLOW_REDIRECTS
                             .try_into_follow_redirects(
-                                config.string_filter_by_key(key, &mut trusted_only).unwrap_or_default(),
+                                config
+                                    .string_filter_by_url(key, &url_to_match, &mut trusted_only)
+                                    .unwrap_or_default(),
                                 || {
                                     config
-                                        .boolean_filter_by_key(key, &mut trusted_only)
+                                        .boolean_filter_by_url(key, &url_to_match, &mut trusted_only)
                                         .transpose()
                                         .with_leniency(lenient)
                                 },
@@ -176,14 +182,14 @@ impl crate::Repository {
                     };
 
                     opts.low_speed_time_seconds = config
-                        .integer_filter_by_key("http.lowSpeedTime", &mut trusted_only)
+                        .integer_filter_by_url("http.lowSpeedTime", &url_to_match, &mut trusted_only)
                         .map(|value| config::tree::Http::LOW_SPEED_TIME.try_into_u64(value))
                         .transpose()
                         .with_leniency(lenient)
                         .map_err(config::transport::http::Error::from)?
                         .unwrap_or_default();
                     opts.low_speed_limit_bytes_per_second = config
-                        .integer_filter_by_key("http.lowSpeedLimit", &mut trusted_only)
+                        .integer_filter_by_url("http.lowSpeedLimit", &url_to_match, &mut trusted_only)
                         .map(|value| config::tree::Http::LOW_SPEED_LIMIT.try_into_u32(value))
                         .transpose()
                         .with_leniency(lenient)
@@ -200,7 +206,7 @@ impl crate::Repository {
                                 let key = "http.proxy";
                                 debug_assert_eq!(key, config::tree::Http::PROXY.logical_name());
                                 let http_proxy = config
-                                    .string_filter_by_key(key, &mut trusted_only)
+                                    .string_filter_by_url(key, &url_to_match, &mut trusted_only)
                                     .map(|v| (v, cow_bstr(key), &config::tree::Http::PROXY))
                                     .or_else(|| {
                                         let key = "gitoxide.http.proxy";
@@ -263,9 +269,11 @@ impl crate::Repository {
                                     .or_else(|| {
                                         let key = "http.proxyAuthMethod";
                                         debug_assert_eq!(key, config::tree::Http::PROXY_AUTH_METHOD.logical_name());
-                                        config.string_filter_by_key(key, &mut trusted_only).map(|v| {
-                                            (v, Cow::Borrowed(key.into()), &config::tree::Http::PROXY_AUTH_METHOD)
-                                        })
+                                        config
+                                            .string_filter_by_url(key, &url_to_match, &mut trusted_only)
+                                            .map(|v| {
+                                                (v, Cow::Borrowed(key.into()), &config::tree::Http::PROXY_AUTH_METHOD)
+                                            })
                                     })
                             })
                     })?;
@@ -302,7 +310,7 @@ impl crate::Repository {
                     {
                         let key = "http.userAgent";
                         opts.user_agent = config
-                            .string_filter_by_key(key, &mut trusted_only)
+                            .string_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .and_then(|v| {
                                 try_cow_to_string(
                                     v,
@@ -319,7 +327,7 @@ impl crate::Repository {
                     {
                         let key = "http.version";
                         opts.http_version = config
-                            .string_filter_by_key(key, &mut trusted_only)
+                            .string_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .map(|v| {
                                 config::tree::Http::VERSION
                                     .try_into_http_version(v)
@@ -343,7 +351,7 @@ impl crate::Repository {
                     let may_use_cainfo = {
                         let key = "http.schannelUseSSLCAInfo";
                         config
-                            .boolean_filter_by_key(key, &mut trusted_only)
+                            .boolean_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .map(|value| config::tree::Http::SCHANNEL_USE_SSL_CA_INFO.enrich_error(value))
                             .transpose()
                             .with_leniency(lenient)
@@ -355,7 +363,7 @@ impl crate::Repository {
                         let key = "http.sslCAInfo";
                         debug_assert_eq!(key, config::tree::Http::SSL_CA_INFO.logical_name());
                         opts.ssl_ca_info = config
-                            .path_filter_by_key(key, &mut trusted_only)
+                            .path_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .map(|p| {
                                 use crate::config::cache::interpolate_context;
                                 p.interpolate(interpolate_context(
@@ -374,6 +382,7 @@ impl crate::Repository {
                             config,
                             "http.sslVersion",
                             &config::tree::Http::SSL_VERSION,
+                            Some(&url_to_match),
                             trusted_only,
                             lenient,
                         )?
@@ -382,6 +391,7 @@ impl crate::Repository {
                             config,
                             "gitoxide.http.sslVersionMin",
                             &gitoxide::Http::SSL_VERSION_MIN,
+                            None,
                             trusted_only,
                             lenient,
                         )
@@ -390,6 +400,7 @@ impl crate::Repository {
                                 config,
                                 "gitoxide.http.sslVersionMax",
                                 &gitoxide::Http::SSL_VERSION_MAX,
+                                None,
                                 trusted_only,
                                 lenient,
                             )
@@ -420,7 +431,7 @@ impl crate::Repository {
                         } else {
                             let key = "http.sslVerify";
                             opts.ssl_verify = config
-                                .boolean_filter_by_key(key, &mut trusted_only)
+                                .boolean_filter_by_url(key, &url_to_match, &mut trusted_only)
                                 .map(|value| config::tree::Http::SSL_VERIFY.enrich_error(value))
                                 .transpose()
                                 .with_leniency(lenient)
@@ -433,7 +444,7 @@ impl crate::Repository {
                     {
                         let key = "http.schannelCheckRevoke";
                         let schannel_check_revoke = config
-                            .boolean_filter_by_key(key, &mut trusted_only)
+                            .boolean_filter_by_url(key, &url_to_match, &mut trusted_only)
                             .map(|value| config::tree::Http::SCHANNEL_CHECK_REVOKE.enrich_error(value))
                             .transpose()
                             .with_leniency(lenient)
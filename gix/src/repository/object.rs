@@ -56,6 +56,24 @@ impl crate::Repository {
         self.objects.header(id)
     }
 
+    /// Obtain information about all objects identified by `ids` without fully decoding them, in order,
+    /// or fail if one of them doesn't exist.
+    ///
+    /// This is faster than calling [`Self::find_header()`] for each object, and useful for obtaining statistics about
+    /// many objects, like their size, at once.
+    pub fn find_headers(
+        &self,
+        ids: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<gix_odb::find::Header>, object::find::existing::Error> {
+        let ids: Vec<ObjectId> = ids.into_iter().map(Into::into).collect();
+        self.try_find_headers(ids.iter().copied())
+            .map_err(|err| object::find::existing::Error::Find(err.0))?
+            .into_iter()
+            .zip(ids)
+            .map(|(header, id)| header.ok_or(object::find::existing::Error::NotFound { oid: id }))
+            .collect()
+    }
+
     /// Return `true` if `id` exists in the object database.
     ///
     /// # Performance
@@ -93,6 +111,31 @@ impl crate::Repository {
         self.objects.try_header(&id).map_err(Into::into)
     }
 
+    /// Obtain information about all objects identified by `ids` without fully decoding them, in order,
+    /// with `None` for each object that doesn't exist.
+    ///
+    /// This is faster than calling [`Self::try_find_header()`] for each object.
+    pub fn try_find_headers(
+        &self,
+        ids: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<Option<gix_odb::find::Header>>, object::find::Error> {
+        let empty_tree = ObjectId::empty_tree(self.object_hash());
+        let ids: Vec<ObjectId> = ids.into_iter().map(Into::into).collect();
+        let mut headers = self
+            .objects
+            .try_headers(ids.iter().map(AsRef::as_ref))
+            .map_err(|err| object::find::Error(Box::new(err)))?;
+        for (header, id) in headers.iter_mut().zip(&ids) {
+            if header.is_none() && *id == empty_tree {
+                *header = Some(gix_odb::find::Header::Loose {
+                    kind: gix_object::Kind::Tree,
+                    size: 0,
+                });
+            }
+        }
+        Ok(headers)
+    }
+
     /// Try to find the object with `id` or return `None` if it wasn't found.
     #[momo]
     pub fn try_find_object(&self, id: impl Into<ObjectId>) -> Result<Option<Object<'_>>, object::find::Error> {
@@ -0,0 +1,109 @@
+use gix_hash::ObjectId;
+
+use crate::{revision, revision::Spec};
+
+/// The error returned by [`Spec::into_walk()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error("A commit could not be decoded")]
+    Decode(#[from] gix_object::decode::Error),
+}
+
+/// Conversion
+impl<'repo> Spec<'repo> {
+    /// Turn this specification into a revision walk which yields all commits it represents, with ranges translated into
+    /// tips to start at and hidden tips whose ancestry is excluded.
+    ///
+    /// For example, `a..b` walks `b` and hides `a`, `a...b` walks `a` and `b` and hides their merge-bases, and `a^@` walks all
+    /// parents of `a`. The returned platform can be configured further before the walk is started.
+    pub fn into_walk(self) -> Result<revision::walk::Platform<'repo>, Error> {
+        use gix_revision::Spec::*;
+        let repo = self.repo;
+        let (tips, hidden) = match self.inner {
+            Include(id) => (vec![peel_to_commit(repo, id)?], Vec::new()),
+            Exclude(id) => (Vec::new(), vec![peel_to_commit(repo, id)?]),
+            Range { from, to } => (vec![peel_to_commit(repo, to)?], vec![peel_to_commit(repo, from)?]),
+            Merge { theirs, ours } => {
+                let (theirs, ours) = (peel_to_commit(repo, theirs)?, peel_to_commit(repo, ours)?);
+                (vec![theirs, ours], merge_bases(repo, theirs, ours)?)
+            }
+            IncludeOnlyParents(id) => (parents(repo, id)?, Vec::new()),
+            ExcludeParents(id) => {
+                let id = peel_to_commit(repo, id)?;
+                (vec![id], parents(repo, id)?)
+            }
+        };
+        Ok(revision::walk::Platform::new(tips, repo).with_hidden(hidden))
+    }
+}
+
+fn peel_to_commit(repo: &crate::Repository, id: ObjectId) -> Result<ObjectId, Error> {
+    Ok(repo.find_object(id)?.peel_to_kind(gix_object::Kind::Commit)?.id)
+}
+
+fn parents(repo: &crate::Repository, id: ObjectId) -> Result<Vec<ObjectId>, Error> {
+    Ok(commit_info(repo, id)?.1)
+}
+
+fn commit_info(
+    repo: &crate::Repository,
+    id: ObjectId,
+) -> Result<(gix_date::SecondsSinceUnixEpoch, Vec<ObjectId>), Error> {
+    let commit = repo
+        .find_object(id)?
+        .peel_to_kind(gix_object::Kind::Commit)?
+        .into_commit();
+    let time = commit.committer()?.time.seconds;
+    let parents = commit.parent_ids().map(|id| id.detach()).collect();
+    Ok((time, parents))
+}
+
+/// Return the best common ancestors of `a` and `b`, akin to `git merge-base --all`, possibly along with a few redundant ones
+/// as they are not filtered. Hiding them hides all common ancestors of `a` and `b`.
+fn merge_bases(repo: &crate::Repository, a: ObjectId, b: ObjectId) -> Result<Vec<ObjectId>, Error> {
+    const A: u8 = 1 << 0;
+    const B: u8 = 1 << 1;
+    const STALE: u8 = 1 << 2;
+    const RESULT: u8 = 1 << 3;
+
+    if a == b {
+        return Ok(vec![a]);
+    }
+
+    let mut flags = gix_hashtable::HashMap::<ObjectId, u8>::default();
+    let mut queue = gix_revwalk::PriorityQueue::<gix_date::SecondsSinceUnixEpoch, ObjectId>::new();
+    for (id, flag) in [(a, A), (b, B)] {
+        flags.insert(id, flag);
+        queue.insert(commit_info(repo, id)?.0, id);
+    }
+
+    let mut bases = Vec::new();
+    while queue
+        .iter_unordered()
+        .any(|id| flags.get(id).map_or(false, |flags| flags & STALE == 0))
+    {
+        let Some(id) = queue.pop_value() else { break };
+        let mut commit_flags = flags[&id] & (A | B | STALE);
+        if commit_flags == (A | B) {
+            if flags[&id] & RESULT == 0 {
+                flags.insert(id, flags[&id] | RESULT);
+                bases.push(id);
+            }
+            commit_flags |= STALE;
+        }
+        for parent_id in commit_info(repo, id)?.1 {
+            let parent_flags = flags.entry(parent_id).or_default();
+            if *parent_flags & commit_flags == commit_flags {
+                continue;
+            }
+            *parent_flags |= commit_flags;
+            queue.insert(commit_info(repo, parent_id)?.0, parent_id);
+        }
+    }
+    Ok(bases)
+}
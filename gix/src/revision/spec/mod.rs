@@ -1,6 +1,8 @@
 use crate::bstr::BStr;
 use crate::{ext::ReferenceExt, revision::Spec, Id, Reference};
 
+///
+pub mod into_walk;
 ///
 pub mod parse;
 
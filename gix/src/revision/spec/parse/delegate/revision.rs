@@ -105,60 +105,120 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
 
     fn reflog(&mut self, query: ReflogLookup) -> Option<()> {
         self.unset_disambiguate_call();
+        let r = match &mut self.refs[self.idx] {
+            Some(r) => r.clone().attach(self.repo),
+            val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
+                Ok(Some(r)) => {
+                    *val = Some(r.clone().detach());
+                    r
+                }
+                Ok(None) => {
+                    self.err.push(Error::UnbornHeadsHaveNoRefLog);
+                    return None;
+                }
+                Err(err) => {
+                    self.err.push(err.into());
+                    return None;
+                }
+            },
+        };
+        let mut platform = r.log_iter();
         match query {
-            ReflogLookup::Date(_date) => {
-                // TODO: actually do this - this should be possible now despite incomplete date parsing
-                self.err.push(Error::Planned {
-                    dependency: "remote handling and ref-specs are fleshed out more",
-                });
-                None
-            }
-            ReflogLookup::Entry(no) => {
-                let r = match &mut self.refs[self.idx] {
-                    Some(r) => r.clone().attach(self.repo),
-                    val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
-                        Ok(Some(r)) => {
-                            *val = Some(r.clone().detach());
-                            r
-                        }
-                        Ok(None) => {
-                            self.err.push(Error::UnbornHeadsHaveNoRefLog);
-                            return None;
-                        }
-                        Err(err) => {
-                            self.err.push(err.into());
-                            return None;
-                        }
-                    },
-                };
-                let mut platform = r.log_iter();
-                match platform.rev().ok().flatten() {
-                    Some(mut it) => match it.nth(no).and_then(Result::ok) {
-                        Some(line) => {
-                            self.objs[self.idx]
-                                .get_or_insert_with(HashSet::default)
-                                .insert(line.new_oid);
-                            Some(())
-                        }
-                        None => {
-                            let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
-                            self.err.push(Error::RefLogEntryOutOfRange {
-                                reference: r.detach(),
-                                desired: no,
-                                available,
-                            });
-                            None
-                        }
-                    },
+            ReflogLookup::Date(date) => {
+                let it = match platform.rev().ok().flatten() {
+                    Some(it) => it,
                     None => {
                         self.err.push(Error::MissingRefLog {
                             reference: r.name().as_bstr().into(),
-                            action: "lookup entry",
+                            action: "lookup entry by date",
                         });
-                        None
+                        return None;
+                    }
+                };
+                // Like git, find the most recent entry at or before `date`, and warn if the log seems incomplete.
+                let mut newer: Option<gix_ref::log::Line> = None;
+                let mut found = None;
+                for line in it.filter_map(Result::ok) {
+                    if line.signature.time.seconds <= date.seconds {
+                        // An entry without previous id means the log was recreated, so it doesn't tell where it went next.
+                        found = Some(match newer.as_ref().filter(|newer| !newer.previous_oid.is_null()) {
+                            Some(newer) => {
+                                if newer.previous_oid != line.new_oid {
+                                    gix_trace::warn!(
+                                        "log for ref {} has gap after {}",
+                                        r.name().as_bstr(),
+                                        line.signature.time.format(gix_date::time::format::GIT_RFC2822)
+                                    );
+                                }
+                                line.new_oid
+                            }
+                            None if line.signature.time.seconds == date.seconds => line.new_oid,
+                            None => {
+                                let current = r.clone().into_fully_peeled_id().ok().map(crate::Id::detach);
+                                if current != Some(line.new_oid) {
+                                    gix_trace::warn!(
+                                        "log for ref {} unexpectedly ended on {}",
+                                        r.name().as_bstr(),
+                                        line.signature.time.format(gix_date::time::format::GIT_RFC2822)
+                                    );
+                                }
+                                current.unwrap_or(line.new_oid)
+                            }
+                        });
+                        break;
                     }
+                    newer = Some(line);
                 }
+                let id = match (found, newer) {
+                    (Some(id), _) => id,
+                    (None, Some(oldest)) => {
+                        gix_trace::warn!(
+                            "log for '{}' only goes back to {}",
+                            r.name().shorten(),
+                            oldest.signature.time.format(gix_date::time::format::GIT_RFC2822)
+                        );
+                        if oldest.previous_oid.is_null() {
+                            oldest.new_oid
+                        } else {
+                            oldest.previous_oid
+                        }
+                    }
+                    (None, None) => {
+                        self.err.push(Error::EmptyRefLog {
+                            reference: r.name().as_bstr().into(),
+                        });
+                        return None;
+                    }
+                };
+                self.objs[self.idx].get_or_insert_with(HashSet::default).insert(id);
+                Some(())
             }
+            ReflogLookup::Entry(no) => match platform.rev().ok().flatten() {
+                Some(mut it) => match it.nth(no).and_then(Result::ok) {
+                    Some(line) => {
+                        self.objs[self.idx]
+                            .get_or_insert_with(HashSet::default)
+                            .insert(line.new_oid);
+                        Some(())
+                    }
+                    None => {
+                        let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
+                        self.err.push(Error::RefLogEntryOutOfRange {
+                            reference: r.detach(),
+                            desired: no,
+                            available,
+                        });
+                        None
+                    }
+                },
+                None => {
+                    self.err.push(Error::MissingRefLog {
+                        reference: r.name().as_bstr().into(),
+                        action: "lookup entry",
+                    });
+                    None
+                }
+            },
         }
     }
 
@@ -236,11 +296,11 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
             },
             Some(r) => r.clone().attach(self.repo),
         };
-        let direction = match kind {
-            SiblingBranch::Upstream => remote::Direction::Fetch,
-            SiblingBranch::Push => remote::Direction::Push,
+        let (direction, tracking) = match kind {
+            SiblingBranch::Upstream => (remote::Direction::Fetch, reference.upstream()),
+            SiblingBranch::Push => (remote::Direction::Push, reference.push_tracking()),
         };
-        match reference.remote_tracking_ref_name(direction) {
+        match tracking {
             None => self.err.push(Error::NoTrackingBranch {
                 name: reference.inner.name,
                 direction,
@@ -250,17 +310,10 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                 direction,
                 source: Box::new(err),
             }),
-            Some(Ok(name)) => match self.repo.find_reference(name.as_ref()) {
-                Err(err) => self.err.push(Error::GetTrackingBranch {
-                    name: reference.inner.name,
-                    direction,
-                    source: Box::new(err),
-                }),
-                Ok(r) => {
-                    self.refs[self.idx] = r.inner.into();
-                    return Some(());
-                }
-            },
+            Some(Ok(r)) => {
+                self.refs[self.idx] = r.inner.into();
+                return Some(());
+            }
         };
         None
     }
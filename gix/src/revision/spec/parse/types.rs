@@ -76,6 +76,8 @@ pub enum Error {
     Planned { dependency: &'static str },
     #[error("Reference {reference:?} does not have a reference log, cannot {action}")]
     MissingRefLog { reference: BString, action: &'static str },
+    #[error("The reference log of {reference:?} is empty")]
+    EmptyRefLog { reference: BString },
     #[error("HEAD has {available} prior checkouts and checkout number {desired} is out of range")]
     PriorCheckoutOutOfRange { desired: usize, available: usize },
     #[error("Reference {:?} has {available} ref-log entries and entry number {desired} is out of range", reference.name.as_bstr())]
@@ -3,6 +3,10 @@ use gix_object::FindExt;
 
 use crate::{ext::ObjectIdExt, revision, Repository};
 
+///
+#[cfg(feature = "blob-diff")]
+pub mod simplify;
+
 /// The error returned by [`Platform::all()`].
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -13,6 +17,8 @@ pub enum Error {
     ShallowCommits(#[from] crate::shallow::open::Error),
     #[error(transparent)]
     ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error("Renames can only be followed for a pathspec with a single path, but it had {count}")]
+    FollowRenamesRequiresSinglePath { count: usize },
 }
 
 /// Information about a commit that we obtained naturally as part of the iteration.
@@ -91,10 +97,17 @@ impl<'repo> Info<'repo> {
 pub struct Platform<'repo> {
     pub(crate) repo: &'repo Repository,
     pub(crate) tips: Vec<ObjectId>,
+    pub(crate) hidden: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
     pub(crate) parents: gix_traverse::commit::Parents,
     pub(crate) use_commit_graph: Option<bool>,
     pub(crate) commit_graph: Option<gix_commitgraph::Graph>,
+    #[cfg(feature = "blob-diff")]
+    pub(crate) pathspec: Option<gix_pathspec::Search>,
+    #[cfg(feature = "blob-diff")]
+    pub(crate) full_history: bool,
+    #[cfg(feature = "blob-diff")]
+    pub(crate) follow_renames: bool,
 }
 
 impl<'repo> Platform<'repo> {
@@ -102,10 +115,17 @@ impl<'repo> Platform<'repo> {
         revision::walk::Platform {
             repo,
             tips: tips.into_iter().map(Into::into).collect(),
+            hidden: Vec::new(),
             sorting: Default::default(),
             parents: Default::default(),
             use_commit_graph: None,
             commit_graph: None,
+            #[cfg(feature = "blob-diff")]
+            pathspec: None,
+            #[cfg(feature = "blob-diff")]
+            full_history: false,
+            #[cfg(feature = "blob-diff")]
+            follow_renames: false,
         }
     }
 }
@@ -124,6 +144,48 @@ impl<'repo> Platform<'repo> {
         self
     }
 
+    /// Don't return any of the commits in `tips` or their ancestors, which is useful to exclude the commits of one branch
+    /// from those of another one, like `main..feature` would.
+    ///
+    /// This works with all sort modes, as the walk first determines which commits are hidden by traversing
+    /// the history of tips and hidden tips alike.
+    pub fn with_hidden(mut self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.hidden.extend(tips.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only return commits that changed paths matching `pathspec`, and simplify the history like `git log -- <pathspec>` does.
+    ///
+    /// By default, a commit that didn't change any matching path compared to one of its parents isn't returned, and only
+    /// this parent is traversed further, which hides merges and the side branches they brought in if these didn't contribute
+    /// to the final state of the matching paths. Use [`full_history()`](Self::full_history()) to see all commits that changed
+    /// matching paths instead.
+    ///
+    /// Note that pathspecs with attributes never match, and that bloom filters of the commit-graph are used if present.
+    #[cfg(feature = "blob-diff")]
+    pub fn with_pathspec(mut self, pathspec: gix_pathspec::Search) -> Self {
+        self.pathspec = Some(pathspec);
+        self
+    }
+
+    /// Traverse all parents of commits even if they didn't change paths matching the [pathspec](Self::with_pathspec()),
+    /// and return merges only if they changed matching paths compared to one of their parents, like `git log --full-history`.
+    #[cfg(feature = "blob-diff")]
+    pub fn full_history(mut self) -> Self {
+        self.full_history = true;
+        self
+    }
+
+    /// Follow renames and copies of the single path in the [pathspec](Self::with_pathspec()), like `git log --follow` does.
+    ///
+    /// Whenever a commit is encountered that renamed another path to the currently followed one, the source of the rename
+    /// is followed from then on. This works best with linear history, just like it does in `git`.
+    #[cfg(feature = "blob-diff")]
+    pub fn follow_renames(mut self) -> Self {
+        self.follow_renames = true;
+        self
+    }
+
     /// Allow using the commitgraph, if present, if `toggle` is `true`, or disallow it with `false`. Set it to `None` to leave
     /// control over this to the configuration of `core.commitGraph` (the default).
     ///
@@ -158,57 +220,88 @@ impl<'repo> Platform<'repo> {
         let Platform {
             repo,
             tips,
+            hidden,
             sorting,
             parents,
             use_commit_graph,
             commit_graph,
+            #[cfg(feature = "blob-diff")]
+            pathspec,
+            #[cfg(feature = "blob-diff")]
+            full_history,
+            #[cfg(feature = "blob-diff")]
+            follow_renames,
         } = self;
-        Ok(revision::Walk {
-            repo,
-            inner: Box::new(
-                gix_traverse::commit::Ancestors::filtered(
-                    tips,
-                    gix_traverse::commit::ancestors::State::default(),
-                    &repo.objects,
-                    {
-                        // Note that specific shallow handling for commit-graphs isn't needed as these contain
-                        // all information there is, and exclude shallow parents to be structurally consistent.
-                        let shallow_commits = repo.shallow_commits()?;
-                        let mut grafted_parents_to_skip = Vec::new();
-                        let mut buf = Vec::new();
-                        move |id| {
-                            if !filter(id) {
+        let use_commit_graph = use_commit_graph.map_or_else(|| repo.config.may_use_commit_graph(), Ok)?;
+        #[cfg(feature = "blob-diff")]
+        let simplify = match pathspec {
+            Some(pathspec) => {
+                if follow_renames && pathspec.patterns().len() != 1 {
+                    return Err(Error::FollowRenamesRequiresSinglePath {
+                        count: pathspec.patterns().len(),
+                    });
+                }
+                let graph = use_commit_graph.then(|| repo.commit_graph().ok()).flatten();
+                Some(simplify::Simplify::new(
+                    repo,
+                    pathspec,
+                    full_history,
+                    follow_renames,
+                    graph,
+                ))
+            }
+            None => None,
+        };
+        let ancestors = gix_traverse::commit::Ancestors::filtered(
+            tips,
+            gix_traverse::commit::ancestors::State::default(),
+            &repo.objects,
+            {
+                // Note that specific shallow handling for commit-graphs isn't needed as these contain
+                // all information there is, and exclude shallow parents to be structurally consistent.
+                let shallow_commits = repo.shallow_commits()?;
+                let mut grafted_parents_to_skip = Vec::new();
+                let mut buf = Vec::new();
+                move |id| {
+                    if !filter(id) {
+                        return false;
+                    }
+                    match shallow_commits.as_ref() {
+                        Some(commits) => {
+                            let id = id.to_owned();
+                            if let Ok(idx) = grafted_parents_to_skip.binary_search(&id) {
+                                grafted_parents_to_skip.remove(idx);
                                 return false;
-                            }
-                            match shallow_commits.as_ref() {
-                                Some(commits) => {
-                                    let id = id.to_owned();
-                                    if let Ok(idx) = grafted_parents_to_skip.binary_search(&id) {
-                                        grafted_parents_to_skip.remove(idx);
-                                        return false;
-                                    };
-                                    if commits.binary_search(&id).is_ok() {
-                                        if let Ok(commit) = repo.objects.find_commit_iter(&id, &mut buf) {
-                                            grafted_parents_to_skip.extend(commit.parent_ids());
-                                            grafted_parents_to_skip.sort();
-                                        }
-                                    };
-                                    true
+                            };
+                            if commits.binary_search(&id).is_ok() {
+                                if let Ok(commit) = repo.objects.find_commit_iter(&id, &mut buf) {
+                                    grafted_parents_to_skip.extend(commit.parent_ids());
+                                    grafted_parents_to_skip.sort();
                                 }
-                                None => true,
-                            }
+                            };
+                            true
                         }
-                    },
-                )
-                .sorting(sorting)?
-                .parents(parents)
-                .commit_graph(
-                    commit_graph.or(use_commit_graph
-                        .map_or_else(|| self.repo.config.may_use_commit_graph(), Ok)?
-                        .then(|| self.repo.commit_graph().ok())
-                        .flatten()),
+                        None => true,
+                    }
+                }
+            },
+        )
+        .sorting(sorting)?
+        .parents(parents)
+        .hide(hidden)
+        .commit_graph(commit_graph.or(use_commit_graph.then(|| repo.commit_graph().ok()).flatten()));
+        #[cfg(feature = "blob-diff")]
+        if let Some(mut simplify) = simplify {
+            return Ok(revision::Walk {
+                repo,
+                inner: Box::new(
+                    ancestors.simplify(move |id, parents| simplify.simplify(id, parents).map_err(Into::into)),
                 ),
-            ),
+            });
+        }
+        Ok(revision::Walk {
+            repo,
+            inner: Box::new(ancestors),
         })
     }
     /// Return an iterator to traverse all commits reachable as configured by the [Platform].
@@ -0,0 +1,266 @@
+use gix_hash::{oid, ObjectId};
+use gix_traverse::commit::ParentIds;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    object::tree::diff::{change::Event, Action},
+    Repository,
+};
+
+/// The error returned when simplifying the history of a [path-limited walk](super::Platform::with_pathspec()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    RenameConfiguration(#[from] crate::diff::new_rewrites::Error),
+    #[error(transparent)]
+    Diff(#[from] crate::object::tree::diff::for_each::Error),
+}
+
+/// How the tree of a commit compares to the one of its parent, as seen through the pathspec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    /// No path matching the pathspec was changed.
+    Same,
+    /// A matching path was changed.
+    Changed,
+    /// The path whose renames are followed didn't exist in the parent.
+    Added,
+}
+
+/// Simplify the history like `git log -- <pathspec>` does, by only returning commits that changed matching paths
+/// and only following parents that the commit didn't change in the default mode.
+pub(crate) struct Simplify<'repo> {
+    repo: &'repo Repository,
+    pathspec: gix_pathspec::Search,
+    full_history: bool,
+    /// The only path we are interested in if renames are followed, which changes to the source of each rename we encounter.
+    followed_path: Option<BString>,
+    graph: Option<gix_commitgraph::Graph>,
+    /// The paths to look up in bloom filters, or `None` if the pathspec can't be represented by plain paths.
+    bloom_paths: Option<Vec<BString>>,
+    /// The keys of all `bloom_paths` and their leading directories, along with the hash version they were created with.
+    bloom_keys: Option<(u32, Vec<Vec<gix_commitgraph::bloom::Key>>)>,
+}
+
+impl<'repo> Simplify<'repo> {
+    /// Create a new instance to simplify the history according to `pathspec`, and use `graph` for tree ids and bloom filters.
+    /// If `follow_renames` is `true`, the pathspec is expected to contain a single path whose renames are followed.
+    pub(crate) fn new(
+        repo: &'repo Repository,
+        pathspec: gix_pathspec::Search,
+        full_history: bool,
+        follow_renames: bool,
+        graph: Option<gix_commitgraph::Graph>,
+    ) -> Self {
+        let followed_path = follow_renames
+            .then(|| pathspec.patterns().next().map(|pattern| pattern.path().to_owned()))
+            .flatten();
+        let bloom_paths = match &followed_path {
+            Some(path) => Some(vec![path.clone()]),
+            None => pathspec
+                .patterns()
+                .map(|pattern| {
+                    let is_literal = pattern.search_mode == gix_pathspec::SearchMode::Literal
+                        || pattern.path().find_byteset(b"*?[\\").is_none();
+                    (is_literal
+                        && !pattern
+                            .signature
+                            .intersects(gix_pathspec::MagicSignature::ICASE | gix_pathspec::MagicSignature::EXCLUDE)
+                        && pattern.attributes.is_empty()
+                        && !pattern.path().is_empty())
+                    .then(|| pattern.path().to_owned())
+                })
+                .collect(),
+        };
+        Simplify {
+            repo,
+            pathspec,
+            full_history,
+            followed_path,
+            graph,
+            bloom_paths,
+            bloom_keys: None,
+        }
+    }
+
+    /// Remove the `parents` of the commit with `id` that shouldn't be traversed, and return `true` if the commit changed
+    /// matching paths and thus should be returned.
+    ///
+    /// In the default mode, a commit that didn't change matching paths compared to one of its parents isn't returned,
+    /// and only this parent is traversed. With full history, all parents are traversed, and the commit is returned
+    /// if it changed matching paths compared to any of its parents.
+    pub(crate) fn simplify(&mut self, id: &oid, parents: &mut ParentIds) -> Result<bool, Error> {
+        let tree = self.tree_id(id)?;
+        let Some(first_parent) = parents.first().copied() else {
+            let empty_tree = ObjectId::empty_tree(self.repo.object_hash());
+            return Ok(self.compare(empty_tree, tree)? != Comparison::Same);
+        };
+
+        let mut num_same = 0;
+        let mut first_parent_comparison = Comparison::Same;
+        for (parent_index, parent_id) in parents.iter().enumerate() {
+            let comparison = if parent_index == 0 && self.maybe_changed(id) == Some(false) {
+                Comparison::Same
+            } else {
+                let parent_tree = self.tree_id(parent_id)?;
+                self.compare(parent_tree, tree)?
+            };
+            if parent_index == 0 {
+                first_parent_comparison = comparison;
+            }
+            if comparison == Comparison::Same {
+                if !self.full_history {
+                    let parent_id = *parent_id;
+                    parents.clear();
+                    parents.push(parent_id);
+                    return Ok(false);
+                }
+                num_same += 1;
+            }
+        }
+        if num_same == parents.len() {
+            return Ok(false);
+        }
+
+        if first_parent_comparison == Comparison::Added {
+            let parent_tree = self.tree_id(&first_parent)?;
+            if let Some(source) = self.rename_source(parent_tree, tree)? {
+                self.bloom_paths = Some(vec![source.clone()]);
+                self.bloom_keys = None;
+                self.followed_path = Some(source);
+            }
+        }
+        Ok(true)
+    }
+
+    fn tree_id(&self, id: &oid) -> Result<ObjectId, Error> {
+        if let Some(commit) = self.graph.as_ref().and_then(|graph| graph.commit_by_id(id)) {
+            return Ok(commit.root_tree_id().to_owned());
+        }
+        Ok(self.repo.find_object(id)?.try_into_commit()?.tree_id()?.detach())
+    }
+
+    /// Return `Some(false)` if the bloom filter of the commit with `id` tells us that no matching path was changed compared
+    /// to its first parent, or `None` if there is no filter or it can't be used with our pathspec.
+    fn maybe_changed(&mut self, id: &oid) -> Option<bool> {
+        let paths = self.bloom_paths.as_ref()?;
+        let filter = self.graph.as_ref()?.commit_by_id(id)?.bloom_filter()?;
+        let hash_version = filter.settings().hash_version;
+        if self
+            .bloom_keys
+            .as_ref()
+            .map_or(true, |(keys_hash_version, _)| *keys_hash_version != hash_version)
+        {
+            let keys = paths
+                .iter()
+                .map(|path| gix_commitgraph::bloom::Key::new_with_leading_directories(path.as_ref(), hash_version))
+                .collect();
+            self.bloom_keys = Some((hash_version, keys));
+        }
+        let (_, keys) = self.bloom_keys.as_ref()?;
+        Some(
+            keys.iter()
+                .any(|path_keys| path_keys.iter().all(|key| filter.contains(key))),
+        )
+    }
+
+    fn compare(&mut self, parent_tree: ObjectId, tree: ObjectId) -> Result<Comparison, Error> {
+        if parent_tree == tree {
+            return Ok(Comparison::Same);
+        }
+        let parent_tree = self.repo.find_object(parent_tree)?.try_into_tree()?;
+        let tree = self.repo.find_object(tree)?.try_into_tree()?;
+        let mut comparison = Comparison::Same;
+        let res = parent_tree
+            .changes()?
+            .track_path()
+            .track_rewrites(None)
+            .for_each_to_obtain_tree(&tree, |change| {
+                let is_dir = change.event.entry_mode().is_tree();
+                if !is_interesting(
+                    &mut self.pathspec,
+                    self.followed_path.as_ref().map(AsRef::as_ref),
+                    change.location,
+                    is_dir,
+                ) {
+                    return Ok::<_, std::convert::Infallible>(Action::Continue);
+                }
+                comparison = match change.event {
+                    Event::Addition { .. }
+                        if self
+                            .followed_path
+                            .as_ref()
+                            .map_or(false, |path| path == change.location) =>
+                    {
+                        Comparison::Added
+                    }
+                    _ => Comparison::Changed,
+                };
+                Ok(Action::Cancel)
+            });
+        match res {
+            Ok(_)
+            | Err(crate::object::tree::diff::for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled)) => {
+                Ok(comparison)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Return the path that our followed path was renamed or copied from when turning `parent_tree` into `tree`.
+    fn rename_source(&self, parent_tree: ObjectId, tree: ObjectId) -> Result<Option<BString>, Error> {
+        let Some(path) = self.followed_path.as_ref() else {
+            return Ok(None);
+        };
+        let parent_tree = self.repo.find_object(parent_tree)?.try_into_tree()?;
+        let tree = self.repo.find_object(tree)?.try_into_tree()?;
+        let mut source = None;
+        let res = parent_tree
+            .changes()?
+            .track_path()
+            .for_each_to_obtain_tree(&tree, |change| {
+                if let Event::Rewrite { source_location, .. } = change.event {
+                    if change.location == path {
+                        source = Some(source_location.to_owned());
+                        return Ok::<_, std::convert::Infallible>(Action::Cancel);
+                    }
+                }
+                Ok(Action::Continue)
+            });
+        match res {
+            Ok(_)
+            | Err(crate::object::tree::diff::for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled)) => {
+                Ok(source)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Return `true` if a change at `location` is relevant, which is the case if it's the `followed_path` or inside of it,
+/// or if it's matched by `pathspec` if no path is followed.
+fn is_interesting(
+    pathspec: &mut gix_pathspec::Search,
+    followed_path: Option<&BStr>,
+    location: &BStr,
+    is_dir: bool,
+) -> bool {
+    match followed_path {
+        Some(path) => {
+            location == path
+                || location
+                    .strip_prefix(path.as_bytes())
+                    .map_or(false, |rest| rest.first() == Some(&b'/'))
+        }
+        None => pathspec
+            .pattern_matching_relative_path(location, Some(is_dir), &mut |_, _, _, _| false)
+            .map_or(false, |m| !m.is_excluded()),
+    }
+}
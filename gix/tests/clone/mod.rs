@@ -508,6 +508,145 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    fn fetch_and_checkout_from_bundle() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let bundle = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?.join("all.bundle");
+        let mut prepare = gix::prepare_clone(bundle, tmp.path())?;
+        let (mut checkout, out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        assert_eq!(
+            out.ref_map.handshake.server_protocol_version,
+            gix::protocol::transport::Protocol::V1,
+            "bundles are always served with V1"
+        );
+        assert_eq!(
+            repo.head_name()?.expect("not detached").as_bstr(),
+            "refs/heads/main",
+            "HEAD is derived from the branch it points to in the bundle"
+        );
+        for name in ["refs/remotes/origin/main", "refs/remotes/origin/old", "refs/tags/v2"] {
+            repo.find_reference(name)?;
+        }
+        let work_dir = repo.work_dir().expect("non-bare");
+        assert_eq!(std::fs::read(work_dir.join("dir").join("file"))?, b"3\n");
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_bundle_list_only_receives_what_is_missing() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let list = gix::path::realpath(fixture.join("creation-token.list"))?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            fixture.join("base"),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_bundle_uri(format!("file://{}", gix::path::into_bstr(&list)));
+        let (repo, out) = prepare.fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        for (bundle_ref, remote_ref) in [
+            ("refs/bundles/main", "refs/remotes/origin/main"),
+            ("refs/bundles/old", "refs/remotes/origin/old"),
+        ] {
+            assert_eq!(
+                repo.find_reference(bundle_ref)?.id(),
+                repo.find_reference(remote_ref)?.id(),
+                "the bundles were applied, in order of their creation token"
+            );
+        }
+        match out.status {
+            gix::remote::fetch::Status::Change { write_pack_bundle, .. } => {
+                assert_eq!(
+                    write_pack_bundle.index.num_objects, 1,
+                    "only the annotated tag wasn't bundled, everything else was negotiated as common"
+                );
+            }
+            _ => unreachable!("the tag is missing"),
+        }
+        repo.find_reference("refs/tags/v2")?;
+        assert_eq!(
+            repo.config_snapshot().string("fetch.bundleURI").expect("set").as_ref(),
+            format!("file://{}", gix::path::into_bstr(&list)),
+            "lists with a heuristic are remembered for later fetches"
+        );
+        let reopened = gix::open_opts(repo.path(), restricted())?;
+        let config = reopened.config_snapshot();
+        assert_eq!(
+            config.string("fetch.bundleURI"),
+            repo.config_snapshot().string("fetch.bundleURI"),
+            "it's written to the local configuration as well"
+        );
+        assert_eq!(
+            config.integer("fetch.bundleCreationToken"),
+            Some(2),
+            "the largest creation token of the applied bundles is stored"
+        );
+        let mut objects_dir_content = std::fs::read_dir(repo.objects.store_ref().path())?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect::<Result<Vec<_>, _>>()?;
+        objects_dir_content.sort();
+        assert_eq!(
+            objects_dir_content,
+            ["info", "pack"],
+            "no temporary download directory is left behind"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_bundle_list_in_any_mode_uses_the_first_bundle_that_works() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            fixture.join("base"),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_bundle_uri(gix::path::into_bstr(fixture.join("any.list")).into_owned());
+        let (repo, out) = prepare.fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        assert_eq!(
+            repo.find_reference("refs/bundles/main")?.id(),
+            repo.find_reference("refs/remotes/origin/main")?.id(),
+            "the missing bundle was skipped, and the next one was used"
+        );
+        assert!(
+            matches!(out.status, gix::remote::fetch::Status::NoPackReceived { .. }),
+            "the bundle contains everything"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_single_bundle_uri() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let fixture = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            fixture.join("base"),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_bundle_uri(gix::path::into_bstr(fixture.join("old.bundle")).into_owned());
+        let (repo, _) = prepare.fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        assert_eq!(
+            repo.find_reference("refs/bundles/old")?.id(),
+            repo.find_reference("refs/remotes/origin/old")?.id(),
+        );
+        assert!(repo.try_find_reference("refs/bundles/main")?.is_none());
+        Ok(())
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         for version in [
@@ -559,6 +698,215 @@ mod blocking_io {
         Ok(())
     }
 
+    /// Return a bare copy of the `base` remote repository which allows filtering objects, along with the directory that contains it.
+    fn filtering_remote() -> crate::Result<(gix_testtools::tempfile::TempDir, std::path::PathBuf)> {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let server = tmp.path().join("server.git");
+        let git = |args: &[&str]| -> crate::Result {
+            assert!(std::process::Command::new("git").args(args).status()?.success());
+            Ok(())
+        };
+        git(&[
+            "clone",
+            "--bare",
+            "--quiet",
+            remote::repo_path("base").to_str().expect("valid UTF-8"),
+            server.to_str().expect("valid UTF-8"),
+        ])?;
+        let server_path = server.to_str().expect("valid UTF-8");
+        git(&["-C", server_path, "config", "uploadpack.allowFilter", "true"])?;
+        git(&["-C", server_path, "config", "uploadpack.allowAnySHA1InWant", "true"])?;
+        Ok((tmp, server))
+    }
+
+    fn num_blobs(repo: &gix::Repository) -> crate::Result<usize> {
+        let mut count = 0;
+        for id in repo.objects.iter()? {
+            if repo.find_header(id?)?.kind() == gix::object::Kind::Blob {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn fetch_only_with_blob_filter_then_fetch_missing_blob_lazily() -> crate::Result {
+        let (_server_tmp, server) = filtering_remote()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, out) = gix::clone::PrepareFetch::new(
+            server.as_path(),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(gix::protocol::fetch::Filter::BlobNone)
+        .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let config = repo.config_snapshot();
+        assert_eq!(config.boolean("remote.origin.promisor"), Some(true));
+        assert_eq!(
+            config.string("remote.origin.partialCloneFilter").expect("set").as_ref(),
+            "blob:none"
+        );
+        assert_eq!(
+            config.string("extensions.partialClone").expect("set").as_ref(),
+            "origin"
+        );
+        assert_eq!(config.integer("core.repositoryFormatVersion"), Some(1));
+
+        let gix::remote::fetch::Status::Change { write_pack_bundle, .. } = out.status else {
+            unreachable!("a clone always carries a change")
+        };
+        let pack_path = write_pack_bundle.data_path.expect("pack was written");
+        assert!(
+            gix::odb::promisor::is_promisor_pack(&pack_path),
+            "packs received from a promisor remote are marked as such"
+        );
+        assert_eq!(num_blobs(&repo)?, 0, "the filter omitted all blobs");
+
+        let blob_id = repo
+            .head_commit()?
+            .tree()?
+            .find_entry("file")
+            .expect("present")
+            .object_id();
+        assert!(!repo.has_object(blob_id), "existence checks don't fetch");
+        let blob = repo.find_object(blob_id)?;
+        assert_eq!(
+            blob.kind,
+            gix::object::Kind::Blob,
+            "the missing blob is fetched on demand"
+        );
+        assert!(repo.has_object(blob_id));
+
+        let repo = gix::open_opts(repo.git_dir(), restricted())?;
+        assert_eq!(num_blobs(&repo)?, 1, "the blob is now present locally");
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_only_with_blob_filter_keeps_config_overrides() -> crate::Result {
+        let (_server_tmp, server) = filtering_remote()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, _out) = gix::prepare_clone_bare(server.as_path(), tmp.path())?
+            .with_filter(gix::protocol::fetch::Filter::BlobNone)
+            .with_in_memory_config_overrides(Some("my.marker=1"))
+            .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let config = repo.config_snapshot();
+        assert_eq!(
+            config.string("extensions.partialClone").expect("set").as_ref(),
+            "origin",
+            "the repository was re-opened to see the partial clone configuration"
+        );
+        assert_eq!(
+            config.boolean("my.marker"),
+            Some(true),
+            "configuration overrides survive re-opening the repository"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_fetching_can_be_disabled() -> crate::Result {
+        let (_server_tmp, server) = filtering_remote()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, _out) = gix::prepare_clone_bare(server.as_path(), tmp.path())?
+            .with_filter(gix::protocol::fetch::Filter::BlobNone)
+            .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let blob_id = repo
+            .head_commit()?
+            .tree()?
+            .find_entry("file")
+            .expect("present")
+            .object_id();
+
+        let repo = gix::open_opts(repo.git_dir(), restricted().lazy_fetch(false))?;
+        assert!(repo.try_find_object(blob_id)?.is_none(), "the blob remains missing");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "worktree-mutation")]
+    fn fetch_and_checkout_with_blob_filter() -> crate::Result {
+        let (_server_tmp, server) = filtering_remote()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            server.as_path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(gix::protocol::fetch::Filter::BlobNone);
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let work_dir = repo.work_dir().expect("non-bare");
+        assert_eq!(
+            std::fs::read(work_dir.join("file"))?.as_bstr(),
+            "g\nh\ni\nj\nd\ne\nf\nb\nc\na\n",
+            "missing blobs are fetched lazily during checkout"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "worktree-mutation")]
+    fn checkout_fetches_all_missing_blobs_at_once() -> crate::Result {
+        let server_tmp = gix_testtools::tempfile::TempDir::new()?;
+        let server = server_tmp.path().join("server");
+        let git = |args: &[&str]| -> crate::Result {
+            assert!(std::process::Command::new("git")
+                .args(["-c", "user.name=a", "-c", "user.email=a@example.com", "-C"])
+                .arg(&server)
+                .args(args)
+                .status()?
+                .success());
+            Ok(())
+        };
+        std::fs::create_dir(&server)?;
+        git(&["init", "--quiet"])?;
+        for name in ["a", "b", "c"] {
+            std::fs::write(server.join(name), name)?;
+        }
+        git(&["add", "."])?;
+        git(&["commit", "--quiet", "-m", "three files"])?;
+        git(&["config", "uploadpack.allowFilter", "true"])?;
+        git(&["config", "uploadpack.allowAnySHA1InWant", "true"])?;
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            server.as_path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(gix::protocol::fetch::Filter::BlobNone);
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let work_dir = repo.work_dir().expect("non-bare");
+        for name in ["a", "b", "c"] {
+            assert_eq!(std::fs::read(work_dir.join(name))?.as_bstr(), name);
+        }
+        let num_packs = std::fs::read_dir(repo.git_dir().join("objects").join("pack"))?
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .is_ok_and(|entry| entry.path().extension() == Some("pack".as_ref()))
+            })
+            .count();
+        assert_eq!(
+            num_packs, 2,
+            "the missing blobs are fetched all at once, in addition to the pack of the clone"
+        );
+        Ok(())
+    }
+
     #[test]
     fn fetch_only_without_configuration() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
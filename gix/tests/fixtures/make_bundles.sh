@@ -0,0 +1,44 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  echo 1 > file
+  git add file && git commit -q -m "c1"
+  git branch old
+  echo 2 > file
+  git add file && git commit -q -m "c2"
+  git tag -m "the tag" v2
+  mkdir dir && echo 3 > dir/file
+  git add dir && git commit -q -m "c3"
+)
+
+git -C base bundle create ../all.bundle --all
+git -C base bundle create ../old.bundle old
+git -C base bundle create ../incremental.bundle old..main
+
+# Bundle lists refer to bundles relative to their own location, and are listed in the wrong order on purpose.
+cat > creation-token.list <<EOT
+[bundle]
+	version = 1
+	mode = all
+	heuristic = creationToken
+[bundle "incremental"]
+	uri = incremental.bundle
+	creationToken = 2
+[bundle "old"]
+	uri = old.bundle
+	creationToken = 1
+EOT
+
+cat > any.list <<EOT
+[bundle]
+	version = 1
+	mode = any
+[bundle "missing"]
+	uri = does-not-exist.bundle
+[bundle "all"]
+	uri = all.bundle
+[bundle "old"]
+	uri = old.bundle
+EOT
@@ -175,3 +175,16 @@ git init ssl-no-verify-enabled
   git config http.sslVerify true
   git config gitoxide.http.sslNoVerify true
 )
+
+git init http-url-match
+(cd http-url-match
+  git config http.proxy http://global
+  git config http.extraHeader "ExtraHeader: global"
+  git config http.https://example.com.proxy http://example-host
+  git config http.https://example.com/repo.proxy http://example-repo
+  git config http.https://*.example.com.sslVerify false
+  git config http.https://*.example.com.extraHeader "ExtraHeader: subdomain"
+  git config http.https://user@other.example.com.proxy http://user-other
+  git config http.proxyAuthMethod basic
+  git config http.https://example.com.proxyAuthMethod negotiate
+)
@@ -0,0 +1,62 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function lines() {
+  for line in "$@"; do
+    echo "line-$line"
+  done
+}
+
+git init -q
+git checkout -q -b main
+
+mkdir a b
+echo content > a/other
+echo content > b/file
+git add . && git commit -q -m "identity-basename base"
+
+git rm -q a/other b/file
+mkdir c && echo content > c/file
+git add . && git commit -q -m "identity-basename"
+
+lines 01 02 03 04 05 06 07 08 09 10 > near
+lines 01 02 03 04 05 06 07 xx yy zz > far
+git add . && git commit -q -m "best-similarity base"
+
+git rm -q near far
+lines 01 02 03 04 05 06 07 08 09 00 > target
+git add . && git commit -q -m "best-similarity"
+
+lines 01 02 03 04 05 06 07 08 09 10 > s1
+lines aa bb cc 04 05 06 07 08 09 10 > s2
+git add . && git commit -q -m "best-candidates-first base"
+
+git rm -q s1 s2
+lines 01 02 03 04 05 06 07 08 xx yy > d1
+lines 01 02 03 04 05 06 07 08 09 zz > d2
+git add . && git commit -q -m "best-candidates-first"
+
+mkdir old
+lines 01 02 03 04 05 06 07 08 xx yy > old/file.txt
+lines 01 02 03 04 05 06 07 08 09 zz > other.txt
+git add . && git commit -q -m "unique-basename base"
+
+git rm -q old/file.txt other.txt
+mkdir new
+lines 01 02 03 04 05 06 07 08 09 10 > new/file.txt
+git add . && git commit -q -m "unique-basename"
+
+mkdir x y
+lines 01 02 xx 04 05 06 07 08 09 10 > x/file.md
+lines 01 02 03 04 05 06 yy 08 09 10 > y/other.md
+git add . && git commit -q -m "same-basename-on-tie base"
+
+git rm -q x/file.md y/other.md
+mkdir z w
+lines 01 02 03 04 05 06 07 08 09 10 > z/file.md
+lines aa bb cc dd ee ff gg hh ii jj > w/file.md
+git add . && git commit -q -m "same-basename-on-tie"
+
+for name in identity-basename best-similarity best-candidates-first unique-basename same-basename-on-tie; do
+  git -c diff.renames=1 show --format= --name-status ":/$name" > "baseline.$name"
+done
@@ -17,6 +17,7 @@ cat <<EOF >>.git/config
 [diff "all-but-binary"]
   command = command
   textconv = textconv
+  xfuncname = xfuncname
   algorithm = histogram
   binary = auto
 EOF
@@ -0,0 +1,57 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config merge.ff false
+
+function tick () {
+  if test -z "${tick+set}"
+  then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function commit() {
+  local message=${1:?first argument is the commit message}
+  tick
+  git add -A
+  git commit -q -m "$message"
+}
+
+function merge() {
+  local branch=${1:?first argument is the branch to merge}
+  local message=${2:?second argument is the commit message}
+  tick
+  git merge -q "$branch" -m "$message"
+}
+
+git checkout -q -b main
+echo a > a.txt && echo other > other.txt && commit c1
+echo other2 > other.txt && commit c2
+seq 10 > a.txt && commit c3
+
+git checkout -q -b side
+echo other3 > other.txt && commit s1
+seq 11 > a.txt && commit s2
+
+git checkout -q main
+echo c4 > unrelated.txt && commit c4
+merge side m1
+
+git checkout -q -b side2
+echo side2 > unrelated.txt && commit t1
+
+git checkout -q main
+echo c5 > other.txt && commit c5
+merge side2 m2
+
+git mv a.txt b.txt && commit "c6 rename"
+seq 12 > b.txt && commit c7
+
+git commit-graph write --no-progress --reachable --changed-paths
+git repack -adq
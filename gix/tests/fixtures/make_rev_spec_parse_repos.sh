@@ -357,6 +357,18 @@ EOF
   baseline ":file"      # index lookup, default stage 0
   baseline ":1:file"    # stage 1
   baseline ":foo"       # not found
+  # reflog by date
+  baseline "main@{2005-04-07 22:16:40 +0000}"  # between two entries, picks the older one
+  baseline "main@{2005-04-07 22:20:13 +0000}"  # exactly at an entry
+  baseline "main@{2005-04-07 22:00:00 +0000}"  # before the first entry, log only goes back to its creation
+  baseline "main@{2005-04-08 00:00:00 +0000}"  # after the last entry
+  baseline "@{2005-04-07 22:16:40 +0000}"      # the current branch is implied
+  baseline "HEAD@{2005-04-07 22:16:40 +0000}"  # HEAD has its own log, where the newer entry starts an orphan branch
+  baseline "HEAD@{2005-04-07 22:22:00 +0000}"
+  baseline "main@{1 week ago}"
+  baseline "main@{1.week.ago}"
+  baseline "main@{yesterday}"
+  baseline "main@{now}"
   # parents
   baseline "a"
   baseline "a^1"
@@ -417,3 +429,37 @@ git init new
 (cd new
   baseline '@{1}'
 )
+
+git init tracking
+(cd tracking
+  git checkout -b main
+  git commit --allow-empty -q -m c1
+  git branch other
+
+  git remote add origin .
+  git remote add fork .
+  git fetch -q origin
+  git commit --allow-empty -q -m c2
+  git fetch -q fork
+  git commit --allow-empty -q -m c3
+
+  git config branch.main.remote origin
+  git config branch.main.merge refs/heads/main
+  git config branch.main.pushRemote fork
+
+  git branch local
+  git config branch.local.remote .
+  git config branch.local.merge refs/heads/main
+)
+
+for mode in simple upstream current; do
+  cp -R tracking tracking-$mode
+  (cd tracking-$mode
+    git config push.default $mode
+
+    for branch in main local other; do
+      baseline "$branch@{upstream}"
+      baseline "$branch@{push}"
+    done
+  )
+done
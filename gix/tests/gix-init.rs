@@ -30,6 +30,7 @@ mod with_overrides {
             .set("NO_PROXY", "no-proxy")
             .set("GIT_PROTOCOL_FROM_USER", "file-allowed")
             .set("GIT_REPLACE_REF_BASE", "refs/replace-mine")
+            .set("GIT_QUARANTINE_PATH", "quarantine-env")
             .set("GIT_NO_REPLACE_OBJECTS", "no-replace")
             .set("GIT_COMMITTER_NAME", "committer name")
             .set("GIT_COMMITTER_EMAIL", "committer email")
@@ -242,6 +243,7 @@ mod with_overrides {
             #[cfg(feature = "blob-diff")]
             ("diff.external", "external-diff-env"),
             ("gitoxide.objects.replaceRefBase", "refs/replace-mine"),
+            ("gitoxide.objects.quarantinePath", "quarantine-env"),
             ("gitoxide.committer.nameFallback", "committer name"),
             ("gitoxide.committer.emailFallback", "committer email"),
             ("gitoxide.author.nameFallback", "author name"),
@@ -268,6 +270,10 @@ mod with_overrides {
                 "{key} == {expected}"
             );
         }
+        assert!(
+            repo.refs.quarantine,
+            "reference transactions are refused while objects are in quarantine"
+        );
         Ok(())
     }
 
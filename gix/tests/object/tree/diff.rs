@@ -115,7 +115,7 @@ mod track_rewrites {
     use gix_ref::bstr::BStr;
 
     use crate::{
-        object::tree::diff::{added, deleted, modified, store, tree_named},
+        object::tree::diff::{added, deleted, modified, shorthand, store, tree_named},
         util::named_repo,
     };
 
@@ -193,6 +193,55 @@ mod track_rewrites {
         Ok(())
     }
 
+    #[test]
+    fn heuristics_to_find_best_candidates_match_git() -> crate::Result {
+        let repo = named_repo("make_diff_for_rewrites_repo.sh")?;
+        for name in [
+            "identity-basename",
+            "best-similarity",
+            "best-candidates-first",
+            "unique-basename",
+            "same-basename-on-tie",
+        ] {
+            let from = tree_named(&repo, format!("@^{{/{name}}}~1"));
+            let to = tree_named(&repo, format!(":/{name}"));
+
+            let mut actual = Vec::new();
+            from.changes()?
+                .track_path()
+                .track_rewrites(Rewrites::default().into())
+                .for_each_to_obtain_tree(&to, |change| -> Result<_, Infallible> {
+                    if !change.event.entry_mode().is_tree() {
+                        actual.push(match change.event {
+                            Event::Rewrite { source_location, .. } => {
+                                format!("R\t{source_location}\t{}", change.location)
+                            }
+                            _ => format!("{}\t{}", shorthand(&change.event), change.location),
+                        });
+                    }
+                    Ok(Default::default())
+                })?;
+            actual.sort();
+
+            let baseline =
+                std::fs::read_to_string(repo.work_dir().expect("non-bare").join(format!("baseline.{name}")))?;
+            let mut expected: Vec<_> = baseline
+                .lines()
+                .map(|line| match line.strip_prefix('R') {
+                    // similarity scores are computed differently, so only compare the pairs.
+                    Some(rename) => format!("R\t{}", rename.split_once('\t').expect("score and paths").1),
+                    None => line.to_owned(),
+                })
+                .collect();
+            expected.sort();
+            assert_eq!(
+                actual, expected,
+                "{name}: renames should be the same as the ones found by git"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn rename_by_similarity() -> crate::Result {
         let repo = named_repo("make_diff_repo.sh")?;
@@ -536,7 +585,10 @@ mod track_rewrites {
             }),
             "by similarity there is a diff"
         );
-        assert_eq!(out.num_similarity_checks, 4);
+        assert_eq!(
+            out.num_similarity_checks, 22,
+            "both remaining destinations are compared with all sources to find the best candidate"
+        );
         assert_eq!(
             out.num_similarity_checks_skipped_for_rename_tracking_due_to_limit, 0,
             "no limit configured"
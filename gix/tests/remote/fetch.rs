@@ -109,6 +109,141 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_from_incremental_bundle() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let tmp = TempDir::new()?;
+        let (repo, _) = gix::prepare_clone_bare(dir.join("old.bundle"), tmp.path())?
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+        let old = repo.find_reference("refs/remotes/origin/old")?.id().detach();
+
+        let outcome = repo
+            .remote_at(dir.join("incremental.bundle"))?
+            .with_refspecs(Some("refs/heads/main:refs/remotes/bundle/main"), Fetch)?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        match outcome.status {
+            Status::Change { write_pack_bundle, .. } => {
+                assert_eq!(
+                    write_pack_bundle.index.num_objects, 7,
+                    "two commits with their new trees and blobs"
+                );
+            }
+            _ => unreachable!("the bundle has new objects"),
+        }
+        let main = repo.find_reference("refs/remotes/bundle/main")?.id();
+        assert_eq!(
+            main.ancestors().all()?.last().expect("root").expect("valid").id,
+            old,
+            "the received commits connect to the prerequisite we had"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_applies_the_newest_bundles_of_fetch_bundle_uri_first() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let tmp = TempDir::new()?;
+        let (mut repo, _) = gix::prepare_clone_bare(dir.join("old.bundle"), tmp.path())?
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+        repo.config_snapshot_mut().set_value(
+            &gix::config::tree::Fetch::BUNDLE_URI,
+            gix::path::into_bstr(dir.join("creation-token.list")).as_ref(),
+        )?;
+
+        let outcome = repo
+            .remote_at(dir.join("base"))?
+            .with_refspecs(Some("refs/heads/main:refs/remotes/base/main"), Fetch)?
+            .with_fetch_tags(fetch::Tags::None)
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(
+            matches!(outcome.status, Status::NoPackReceived { .. }),
+            "the bundles contain everything"
+        );
+        assert_eq!(
+            repo.find_reference("refs/bundles/main")?.id(),
+            repo.find_reference("refs/remotes/base/main")?.id()
+        );
+        assert!(
+            repo.try_find_reference("refs/bundles/old")?.is_none(),
+            "the newest bundle could be applied right away, so the older one wasn't used"
+        );
+        assert_eq!(
+            gix::open_opts(repo.path(), crate::restricted())?
+                .config_snapshot()
+                .integer("fetch.bundleCreationToken"),
+            Some(2),
+            "the creation token is stored for the next fetch"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn bundle_lists_with_creation_tokens_are_applied_down_to_the_stored_creation_token() -> crate::Result {
+        let dir = gix_testtools::scripted_fixture_read_only("make_bundles.sh")?;
+        let list = gix::path::into_bstr(dir.join("creation-token.list"));
+        let tmp = TempDir::new()?;
+        let repo = gix::init_bare(tmp.path())?;
+        let out = fetch::bundle_uri::apply(
+            &repo,
+            list.as_ref(),
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(
+            out.applied,
+            ["old", "incremental"],
+            "the newest bundle misses its prerequisites, so the older one is applied first"
+        );
+        assert!(out.skipped.is_empty());
+        assert_eq!(out.creation_token, Some(2));
+
+        let repo = gix::open_opts(repo.path(), crate::restricted())?;
+        let out = fetch::bundle_uri::apply(
+            &repo,
+            list.as_ref(),
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert!(
+            out.applied.is_empty() && out.skipped.is_empty(),
+            "no bundle is newer than the stored creation token"
+        );
+        assert_eq!(out.creation_token, None);
+
+        let tmp = TempDir::new()?;
+        let mut repo = gix::init_bare(tmp.path())?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Fetch::BUNDLE_CREATION_TOKEN, "1")?;
+        let out = fetch::bundle_uri::apply(
+            &repo,
+            list.as_ref(),
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert!(
+            out.applied.is_empty(),
+            "bundles at or below the stored creation token are assumed to be applied, so the history can't be completed"
+        );
+        assert_eq!(out.skipped.len(), 1);
+        assert_eq!(out.skipped[0].0, "incremental");
+        assert!(matches!(
+            out.skipped[0].1,
+            fetch::bundle_uri::Error::MissingPrerequisites { .. }
+        ));
+        assert_eq!(
+            out.creation_token, None,
+            "nothing is stored if the history is incomplete"
+        );
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "blocking-network-client")]
     fn fetch_with_alternates_adds_tips_from_alternates() -> crate::Result<()> {
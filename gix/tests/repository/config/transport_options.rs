@@ -336,4 +336,49 @@ mod http {
             "even with `http.sslVerify` enabled, `gitoxide.http.sslNoVerify` takes precedence`"
         );
     }
+
+    #[test]
+    fn url_specific_configuration() {
+        let repo = repo("http-url-match");
+
+        let opts = http_options(&repo, None, "https://unrelated.net/repo");
+        assert_eq!(opts.proxy.as_deref(), Some("http://global"));
+        assert!(opts.ssl_verify);
+        assert_eq!(opts.extra_headers, &["ExtraHeader: global"]);
+        assert_eq!(opts.proxy_auth_method, ProxyAuthMethod::Basic);
+
+        let opts = http_options(&repo, None, "https://EXAMPLE.com:443/other");
+        assert_eq!(opts.proxy.as_deref(), Some("http://example-host"));
+        assert_eq!(
+            opts.proxy_auth_method,
+            ProxyAuthMethod::Negotiate,
+            "`http.<url>.proxyAuthMethod` is matched like the proxy itself"
+        );
+
+        let opts = http_options(&repo, None, "https://example.com/repo/");
+        assert_eq!(
+            opts.proxy.as_deref(),
+            Some("http://example-repo"),
+            "longer paths are more specific"
+        );
+
+        let opts = http_options(&repo, None, "https://sub.example.com/repo");
+        assert!(!opts.ssl_verify);
+        assert_eq!(
+            opts.extra_headers,
+            &["ExtraHeader: global", "ExtraHeader: subdomain"],
+            "values of sections matching at least as specifically accumulate"
+        );
+        assert_eq!(opts.proxy.as_deref(), Some("http://global"));
+
+        let opts = http_options(&repo, None, "https://user@other.example.com/repo");
+        assert_eq!(opts.proxy.as_deref(), Some("http://user-other"));
+        assert!(!opts.ssl_verify);
+        let opts = http_options(&repo, None, "https://other.example.com/repo");
+        assert_eq!(
+            opts.proxy.as_deref(),
+            Some("http://global"),
+            "the user in the pattern must match"
+        );
+    }
 }
@@ -66,13 +66,20 @@ fn writes_avoid_io_using_duplicate_check() -> crate::Result {
         "there is packed objects as well"
     );
 
-    for id in repo.objects.iter()? {
-        let id = id?;
+    let ids = repo.objects.iter()?.collect::<Result<Vec<_>, _>>()?;
+    let headers = repo.find_headers(ids.iter().copied())?;
+    assert_eq!(
+        repo.try_find_headers(ids.iter().copied())?,
+        headers.iter().copied().map(Some).collect::<Vec<_>>(),
+        "both batched versions find all objects"
+    );
+    for (id, batched_header) in ids.into_iter().zip(headers) {
         assert!(repo.has_object(id));
         let obj = repo.find_object(id)?;
         let header = repo.find_header(id)?;
         assert_eq!(obj.kind, header.kind(), "header and object agree");
         assert_eq!(repo.try_find_header(id)?, Some(header));
+        assert_eq!(batched_header, header, "batched lookups yield the same result");
         use gix_object::Kind::*;
         match obj.kind {
             Commit => {
@@ -182,6 +189,21 @@ mod find {
             }),
             "empty tree is considered a loose object"
         );
+        assert_eq!(
+            repo.try_find_headers([empty_tree, gix::hash::Kind::Sha1.null()])?,
+            vec![
+                Some(gix_odb::find::Header::Loose {
+                    kind: gix_object::Kind::Tree,
+                    size: 0,
+                }),
+                None
+            ],
+            "the empty tree is special-cased in batches as well"
+        );
+        assert!(
+            repo.find_headers([empty_tree, gix::hash::Kind::Sha1.null()]).is_err(),
+            "missing objects are an error"
+        );
 
         let mut buf = Vec::new();
         assert!(
@@ -1 +1,3 @@
 mod spec;
+#[cfg(feature = "blob-diff")]
+mod walk;
@@ -35,6 +35,91 @@ mod sibling_branch {
         }
         Ok(())
     }
+
+    #[test]
+    fn push_and_upstream_by_push_default() -> crate::Result {
+        for (mode, expected) in [
+            (
+                "simple",
+                [
+                    Some("refs/remotes/origin/main"),
+                    None,
+                    Some("refs/heads/main"),
+                    None,
+                    None,
+                    None,
+                ],
+            ),
+            (
+                "upstream",
+                [
+                    Some("refs/remotes/origin/main"),
+                    Some("refs/remotes/origin/main"),
+                    Some("refs/heads/main"),
+                    Some("refs/heads/main"),
+                    None,
+                    None,
+                ],
+            ),
+            (
+                "current",
+                [
+                    Some("refs/remotes/origin/main"),
+                    Some("refs/remotes/fork/main"),
+                    Some("refs/heads/main"),
+                    None,
+                    None,
+                    Some("refs/remotes/origin/other"),
+                ],
+            ),
+        ] {
+            let repo = repo(&format!("tracking-{mode}"))?;
+            let specs = ["main", "local", "other"]
+                .into_iter()
+                .flat_map(|branch| ["upstream", "push"].map(|op| format!("{branch}@{{{op}}}")));
+            for (spec, expected) in specs.zip(expected) {
+                let actual = parse_spec(&spec, &repo);
+                assert_eq!(
+                    actual
+                        .ok()
+                        .map(|spec| spec.first_reference().expect("set").name.to_string()),
+                    expected.map(ToOwned::to_owned),
+                    "{mode}: {spec}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reference_upstream_and_push_tracking() -> crate::Result {
+        let repo = repo("tracking-current")?;
+        let main = repo.find_reference("main")?;
+        assert_eq!(
+            main.upstream().expect("configured")?.name().as_bstr(),
+            "refs/remotes/origin/main"
+        );
+        assert_eq!(
+            main.push_tracking().expect("configured")?.name().as_bstr(),
+            "refs/remotes/fork/main",
+            "branch.main.pushRemote is used to find the tracking branch"
+        );
+
+        let other = repo.find_reference("other")?;
+        assert!(other.upstream().is_none(), "no upstream is configured");
+        assert_eq!(
+            other.push_tracking().expect("origin is the default")?.name().as_bstr(),
+            "refs/remotes/origin/other"
+        );
+
+        let local = repo.find_reference("local")?;
+        assert_eq!(
+            local.upstream().expect("configured")?.name().as_bstr(),
+            "refs/heads/main",
+            "the remote `.` is this repository"
+        );
+        Ok(())
+    }
 }
 
 mod index {
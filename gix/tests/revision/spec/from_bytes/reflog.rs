@@ -78,10 +78,60 @@ fn by_index() {
 }
 
 #[test]
-fn by_date_is_planned_until_git_date_crate_is_implements_parsing() {
+fn by_date() {
+    let repo = repo("complex_graph").unwrap();
+    for (spec, expected) in [
+        (
+            "main@{2005-04-07 22:16:40 +0000}",
+            "a8fbd8e0c8753ef07ccdbfa93da030d53b68f0bc",
+        ),
+        (
+            "main@{2005-04-07 22:20:13 +0000}",
+            "5b3f9e24965d0b28780b7ce5daf2b5b7f7e0459f",
+        ),
+        (
+            "main@{2005-04-07 22:00:00 +0000}",
+            "9f9eac6bd1cd4b4cc6a494f044b28c985a22972b",
+        ),
+        (
+            "main@{2005-04-08 00:00:00 +0000}",
+            "55e825ebe8fd2ff78cad3826afb696b96b576a7e",
+        ),
+        (
+            "@{2005-04-07 22:16:40 +0000}",
+            "a8fbd8e0c8753ef07ccdbfa93da030d53b68f0bc",
+        ),
+        (
+            "HEAD@{2005-04-07 22:16:40 +0000}",
+            "55e825ebe8fd2ff78cad3826afb696b96b576a7e",
+        ),
+        (
+            "HEAD@{2005-04-07 22:22:00 +0000}",
+            "44ee37c98bfdd8de5936f0dbc525a679c8441e0b",
+        ),
+        ("main@{1 week ago}", "55e825ebe8fd2ff78cad3826afb696b96b576a7e"),
+        ("main@{1.week.ago}", "55e825ebe8fd2ff78cad3826afb696b96b576a7e"),
+        ("main@{yesterday}", "55e825ebe8fd2ff78cad3826afb696b96b576a7e"),
+        ("main@{now}", "55e825ebe8fd2ff78cad3826afb696b96b576a7e"),
+    ] {
+        let parsed = parse_spec(spec, &repo).unwrap_or_else(|err| panic!("{spec} to be parsed successfully: {err}"));
+        assert_eq!(parsed, Spec::from_id(hex_to_id(expected).attach(&repo)), "{spec}");
+        assert_eq!(
+            parsed.first_reference().expect("present").name.as_bstr(),
+            if spec.starts_with("HEAD") {
+                "HEAD"
+            } else {
+                "refs/heads/main"
+            }
+        );
+    }
+}
+
+#[test]
+fn by_date_without_reflog() {
     let repo = repo("complex_graph").unwrap();
     assert!(matches!(
-        parse_spec_no_baseline("main@{1979-02-26 18:30:00}", &repo).unwrap_err(),
-        Error::Planned { .. }
+        parse_spec_no_baseline("b-tag@{yesterday}", &repo).unwrap_err(),
+        Error::MissingRefLog { .. }
     ));
 }
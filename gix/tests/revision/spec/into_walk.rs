@@ -0,0 +1,78 @@
+use gix_hash::ObjectId;
+
+use crate::util::hex_to_id;
+
+fn walk(spec: &str) -> crate::Result<Vec<ObjectId>> {
+    let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+    let ids = repo
+        .rev_parse(spec)?
+        .into_walk()?
+        .sorting(gix::traverse::commit::Sorting::ByCommitTimeNewestFirst)
+        .all()?
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<_, _>>()?;
+    Ok(ids)
+}
+
+fn c1() -> ObjectId {
+    hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03")
+}
+fn c2() -> ObjectId {
+    hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7")
+}
+fn b1c1() -> ObjectId {
+    hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac")
+}
+fn m1b1() -> ObjectId {
+    hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170")
+}
+
+#[test]
+fn include() -> crate::Result {
+    assert_eq!(walk("main")?, [m1b1(), b1c1(), c2(), c1()]);
+    Ok(())
+}
+
+#[test]
+fn exclude() -> crate::Result {
+    assert_eq!(
+        walk("^main")?,
+        Vec::<ObjectId>::new(),
+        "there is nothing to walk, only things to hide"
+    );
+    Ok(())
+}
+
+#[test]
+fn range() -> crate::Result {
+    assert_eq!(walk("branch1..main")?, [m1b1(), c2()]);
+    assert_eq!(
+        walk("main..branch1")?,
+        Vec::<ObjectId>::new(),
+        "branch1 is fully merged into main"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge() -> crate::Result {
+    assert_eq!(
+        walk("main...branch1")?,
+        [m1b1(), c2()],
+        "the merge-base is branch1 itself, so only what's unique to main remains"
+    );
+    assert_eq!(walk("main~1...branch1")?, [b1c1(), c2()], "c1 is the merge-base");
+    Ok(())
+}
+
+#[test]
+fn include_only_parents() -> crate::Result {
+    assert_eq!(walk("main^@")?, [b1c1(), c2(), c1()]);
+    Ok(())
+}
+
+#[test]
+fn exclude_parents() -> crate::Result {
+    assert_eq!(walk("main^!")?, [m1b1()]);
+    Ok(())
+}
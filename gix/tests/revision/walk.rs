@@ -0,0 +1,138 @@
+mod with_pathspec {
+    use gix::traverse::commit::Sorting;
+
+    #[derive(Clone, Copy)]
+    enum Mode {
+        Default,
+        FullHistory,
+        FollowRenames,
+    }
+
+    fn walk_titles(specs: &[&str], mode: Mode) -> crate::Result<Vec<(Sorting, Vec<String>)>> {
+        let repo = crate::repo("make_path_history_repo.sh")?.to_thread_local();
+        let head = repo.head_id()?;
+        let mut out = Vec::new();
+        for use_commit_graph in [false, true] {
+            for sorting in [Sorting::ByCommitTimeNewestFirst, Sorting::TopoOrder] {
+                let patterns = specs
+                    .iter()
+                    .map(|spec| gix::pathspec::parse(spec.as_bytes(), Default::default()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let search = gix::pathspec::Search::from_specs(patterns, None, repo.work_dir().expect("non-bare"))?;
+                let walk = head
+                    .ancestors()
+                    .sorting(sorting)
+                    .use_commit_graph(use_commit_graph)
+                    .with_pathspec(search);
+                let walk = match mode {
+                    Mode::Default => walk,
+                    Mode::FullHistory => walk.full_history(),
+                    Mode::FollowRenames => walk.follow_renames(),
+                };
+                let titles = walk
+                    .all()?
+                    .map(|info| -> crate::Result<String> {
+                        Ok(info?.object()?.message()?.title.to_string().trim().to_owned())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                out.push((sorting, titles));
+            }
+        }
+        Ok(out)
+    }
+
+    fn assert_titles(actual: Vec<(Sorting, Vec<String>)>, expected: &[&str]) {
+        for (_sorting, titles) in actual {
+            assert_eq!(
+                titles, expected,
+                "every sorting and commit-graph setting yields the same commits"
+            );
+        }
+    }
+
+    #[test]
+    fn default_simplification_follows_treesame_parents() -> crate::Result {
+        assert_titles(
+            walk_titles(&["a.txt"], Mode::Default)?,
+            &["c6 rename", "s2", "c3", "c1"],
+        );
+        assert_titles(walk_titles(&["other.txt"], Mode::Default)?, &["c5", "s1", "c2", "c1"]);
+        assert_titles(walk_titles(&["unrelated.txt"], Mode::Default)?, &["t1", "c4"]);
+        assert_titles(walk_titles(&["b.txt"], Mode::Default)?, &["c7", "c6 rename"]);
+        Ok(())
+    }
+
+    #[test]
+    fn full_history_shows_merges_that_differ_from_some_parent() -> crate::Result {
+        assert_titles(
+            walk_titles(&["a.txt"], Mode::FullHistory)?,
+            &["c6 rename", "m1", "s2", "c3", "c1"],
+        );
+        assert_titles(
+            walk_titles(&["other.txt"], Mode::FullHistory)?,
+            &["m2", "c5", "m1", "s1", "c2", "c1"],
+        );
+        assert_titles(
+            walk_titles(&["unrelated.txt"], Mode::FullHistory)?,
+            &["m2", "t1", "m1", "c4"],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn glob_patterns_match_without_bloom_filters() -> crate::Result {
+        for (sorting, titles) in walk_titles(&["*.txt"], Mode::Default)? {
+            let expected: &[&str] = match sorting {
+                Sorting::TopoOrder => &[
+                    "c7",
+                    "c6 rename",
+                    "m2",
+                    "t1",
+                    "c5",
+                    "m1",
+                    "s2",
+                    "s1",
+                    "c4",
+                    "c3",
+                    "c2",
+                    "c1",
+                ],
+                _ => &[
+                    "c7",
+                    "c6 rename",
+                    "m2",
+                    "c5",
+                    "t1",
+                    "m1",
+                    "c4",
+                    "s2",
+                    "s1",
+                    "c3",
+                    "c2",
+                    "c1",
+                ],
+            };
+            assert_eq!(titles, expected, "{sorting:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn follow_renames() -> crate::Result {
+        assert_titles(
+            walk_titles(&["b.txt"], Mode::FollowRenames)?,
+            &["c7", "c6 rename", "s2", "c3", "c1"],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn follow_renames_requires_single_path() -> crate::Result {
+        let err = walk_titles(&["a.txt", "b.txt"], Mode::FollowRenames).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Renames can only be followed for a pathspec with a single path, but it had 2"
+        );
+        Ok(())
+    }
+}
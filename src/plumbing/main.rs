@@ -16,8 +16,8 @@ use gix::bstr::{io::BufReadExt, BString};
 
 use crate::plumbing::{
     options::{
-        attributes, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb, revision, tree,
-        Args, Subcommands,
+        attributes, bundle, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb,
+        revision, tree, Args, Subcommands,
     },
     show_progress,
 };
@@ -316,6 +316,91 @@ pub fn main() -> Result<()> {
                 },
             )
         }
+        Subcommands::Blame {
+            statistics,
+            range,
+            ignore_whitespace,
+            ignore_rev,
+            ignore_revs_file,
+            rev_spec,
+            file,
+        } => prepare_and_run(
+            "blame",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, err| {
+                core::repository::blame::blame_file(
+                    repository(Mode::Lenient)?,
+                    &file,
+                    rev_spec.as_deref(),
+                    out,
+                    err,
+                    core::repository::blame::Options {
+                        format,
+                        range,
+                        ignore_whitespace,
+                        ignore_revs: ignore_rev,
+                        ignore_revs_file,
+                        statistics,
+                    },
+                )
+            },
+        ),
+        Subcommands::Bundle(cmd) => match cmd {
+            bundle::Subcommands::Create {
+                all,
+                version,
+                path,
+                specs,
+            } => prepare_and_run(
+                "bundle-create",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |progress, _out, _err| {
+                    core::repository::bundle::create(
+                        repository(Mode::Lenient)?,
+                        &path,
+                        specs,
+                        progress,
+                        &should_interrupt,
+                        core::repository::bundle::Options {
+                            all,
+                            version: version.map(|version| match version {
+                                bundle::Version::V2 => core::repository::bundle::Version::V2,
+                                bundle::Version::V3 => core::repository::bundle::Version::V3,
+                            }),
+                            thread_limit,
+                        },
+                    )
+                },
+            ),
+            bundle::Subcommands::Verify { path } => prepare_and_run(
+                "bundle-verify",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::bundle::verify(repository(Mode::Lenient)?, &path, out, &should_interrupt)
+                },
+            ),
+            bundle::Subcommands::ListHeads { path } => prepare_and_run(
+                "bundle-list-heads",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bundle::list_heads(&path, out, format),
+            ),
+        },
         Subcommands::CommitGraph(cmd) => match cmd {
             commitgraph::Subcommands::List { spec } => prepare_and_run(
                 "commitgraph-list",
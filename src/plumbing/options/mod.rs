@@ -83,6 +83,32 @@ pub enum Subcommands {
     Archive(archive::Platform),
     #[cfg(feature = "gitoxide-core-tools-clean")]
     Clean(clean::Command),
+    /// Create and inspect bundles, which are files holding a pack along with references.
+    #[clap(subcommand)]
+    Bundle(bundle::Subcommands),
+    /// Show which commit introduced each line of a file, like `git blame`.
+    Blame {
+        /// Print additional statistics to help understanding performance.
+        #[clap(long, short = 's')]
+        statistics: bool,
+        /// Only blame the lines in the one-based and inclusive range `<start>,<end>`.
+        #[clap(short = 'L', value_parser = gitoxide::shared::AsLineRange)]
+        range: Option<(u32, u32)>,
+        /// Ignore whitespace when comparing lines.
+        #[clap(short = 'w')]
+        ignore_whitespace: bool,
+        /// A revision whose changes should be attributed to the commits that changed the same lines before.
+        #[clap(long)]
+        ignore_rev: Vec<String>,
+        /// A file with the full ids of revisions to ignore, one per line.
+        #[clap(long)]
+        ignore_revs_file: Option<PathBuf>,
+        /// The revision to blame the file at, or `HEAD` if unset.
+        #[clap(long = "rev")]
+        rev_spec: Option<String>,
+        /// The file to blame, relative to the current working directory.
+        file: std::ffi::OsString,
+    },
     /// Subcommands for interacting with commit-graphs
     #[clap(subcommand)]
     CommitGraph(commitgraph::Subcommands),
@@ -581,6 +607,48 @@ pub mod tree {
     }
 }
 
+pub mod bundle {
+    use std::path::PathBuf;
+
+    use gix::bstr::BString;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum Version {
+        /// The original format, which only supports SHA1.
+        V2,
+        /// The format that also supports capabilities.
+        V3,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Write a bundle with the given references and all objects reachable from them.
+        Create {
+            /// Include all references, along with `HEAD`.
+            #[clap(long)]
+            all: bool,
+            /// The version of the bundle to write, or the lowest version that can represent it if unset.
+            #[clap(long, value_enum)]
+            version: Option<Version>,
+            /// The path to write the bundle to.
+            path: PathBuf,
+            /// References to include, or revisions to exclude along with their ancestors like `^a`, or ranges like `a..main`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            specs: Vec<BString>,
+        },
+        /// Check that the bundle is valid and can be applied to the current repository.
+        Verify {
+            /// The path to the bundle to verify.
+            path: PathBuf,
+        },
+        /// List the references in the bundle.
+        ListHeads {
+            /// The path to the bundle to read.
+            path: PathBuf,
+        },
+    }
+}
+
 pub mod commit {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -386,5 +386,25 @@ mod clap {
                 .parse_ref(cmd, arg, value)
         }
     }
+
+    #[derive(Clone)]
+    pub struct AsLineRange;
+
+    impl TypedValueParser for AsLineRange {
+        type Value = (u32, u32);
+
+        fn parse_ref(&self, cmd: &Command, arg: Option<&Arg>, value: &OsStr) -> Result<Self::Value, Error> {
+            StringValueParser::new()
+                .try_map(|arg| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    let (start, end) = arg
+                        .split_once(',')
+                        .ok_or_else(|| format!("Expected a line range like '<start>,<end>', got '{arg}'"))?;
+                    Ok((start.parse()?, end.parse()?))
+                })
+                .parse_ref(cmd, arg, value)
+        }
+    }
 }
-pub use self::clap::{AsBString, AsHashKind, AsOutputFormat, AsPartialRefName, AsPathSpec, AsTime, CheckPathSpec};
+pub use self::clap::{
+    AsBString, AsHashKind, AsLineRange, AsOutputFormat, AsPartialRefName, AsPathSpec, AsTime, CheckPathSpec,
+};